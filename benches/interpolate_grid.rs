@@ -0,0 +1,51 @@
+//! Compares ``interpolate_grid`` with calling ``interpolate`` for every point of the same lattice and with ``interpolate_many`` on the list of its points.  
+//! The lattice is 200x200x50 plotting points on a grid of 100 nodes along each direction. Run with ``cargo bench``, add ``--features parallel`` for the multithreaded version.
+
+use interp3d::*;
+use std::hint::black_box;
+use std::time::Instant;
+
+fn time<F: FnMut() -> f64>(name: &str, n: usize, mut f: F) {
+    let start = Instant::now();
+    let mut sum = 0.0;
+    for _ in 0..5 {
+        sum += f();
+    }
+    let per_point = start.elapsed().as_secs_f64()/(5*n) as f64;
+    println!("{:<36} {:8.1} ns/point (checksum {:e})", name, 1e9*per_point, sum);
+}
+
+fn main() {
+    let f = |x: f64, y: f64, z: f64| (x*y).sin() + z.exp();
+    let config = DataGenConfBuilder::default()
+        .uniform_cube(0.0, 1.0, 100)
+        .build();
+    let mut ip = Interp3D::from_config(f, &config);
+
+    let lattice = |n: usize| -> Vec<f64> { (0..n).map(|i| i as f64/(n - 1) as f64).collect() };
+    let (xq, yq, zq) = (lattice(200), lattice(200), lattice(50));
+    let n = xq.len()*yq.len()*zq.len();
+    let mut points = Vec::with_capacity(n);
+    for &x in &xq {
+        for &y in &yq {
+            points.extend(zq.iter().map(|&z| (x, y, z)));
+        }
+    }
+
+    for scheme in [Type::Tricubic, Type::Trilinear] {
+        ip.set_type(scheme);
+        time(&format!("{:?} interpolate loop", scheme), n, || {
+            points.iter().map(|&(x, y, z)| ip.interpolate(black_box(x), y, z)).sum()
+        });
+        time(&format!("{:?} interpolate_many", scheme), n, || {
+            ip.interpolate_many(black_box(&points)).iter().sum()
+        });
+        time(&format!("{:?} interpolate_grid", scheme), n, || {
+            ip.interpolate_grid(black_box(&xq), &yq, &zq).iter().sum()
+        });
+        #[cfg(feature = "parallel")]
+        time(&format!("{:?} par_interpolate_grid", scheme), n, || {
+            ip.par_interpolate_grid(black_box(&xq), &yq, &zq).iter().sum()
+        });
+    }
+}
@@ -0,0 +1,55 @@
+//! Compares ``interpolate_many`` and ``interpolate_many_sorted`` with calling ``interpolate`` in a loop, for spatially coherent and for scattered query points.  
+//! The scattered points are also run on a grid too large for the CPU cache, which is where sorting them by cell pays off. Run with ``cargo bench``.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use interp3d::*;
+use std::hint::black_box;
+use std::time::Instant;
+
+fn time<F: FnMut() -> f64>(name: &str, n: usize, mut f: F) {
+    let start = Instant::now();
+    let mut sum = 0.0;
+    for _ in 0..10 {
+        sum += f();
+    }
+    let per_point = start.elapsed().as_secs_f64()/(10*n) as f64;
+    println!("{:<36} {:8.1} ns/point (checksum {:e})", name, 1e9*per_point, sum);
+}
+
+fn main() {
+    let f = |x: f64, y: f64, z: f64| (x*y).sin() + z.exp();
+    let config = DataGenConfBuilder::default()
+        .uniform_cube(0.0, 1.0, 100)
+        .build();
+    let ip = Interp3D::from_config(f, &config);
+    let large = Interp3D::from_config(f, &DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 300).build());
+
+    let n = 1_000_000;
+    let clustered: Vec<_> = (0..n).map(|i| {
+        let s = i as f64/n as f64;
+        (s, 0.5 + 0.4*(3.0*s).sin(), 0.5 + 0.4*(5.0*s).cos())
+    }).collect();
+    let mut next = common::uniform(1);
+    let scattered: Vec<_> = (0..n).map(|_| (next(), next(), next())).collect();
+
+    for (label, points) in [("clustered", &clustered), ("scattered", &scattered)] {
+        time(&format!("{} interpolate loop", label), n, || {
+            points.iter().map(|&(x, y, z)| ip.interpolate(black_box(x), y, z)).sum()
+        });
+        time(&format!("{} interpolate_many", label), n, || {
+            ip.interpolate_many(black_box(points)).iter().sum()
+        });
+        time(&format!("{} interpolate_many_sorted", label), n, || {
+            ip.interpolate_many_sorted(black_box(points)).iter().sum()
+        });
+    }
+
+    time("large scattered interpolate_many", n, || {
+        large.interpolate_many(black_box(&scattered)).iter().sum()
+    });
+    time("large scattered sorted", n, || {
+        large.interpolate_many_sorted(black_box(&scattered)).iter().sum()
+    });
+}
@@ -0,0 +1,41 @@
+//! Times the queries that are dominated by the 4x4x4 stencil sum, on a small grid that stays in the cache.  
+//! Run with ``cargo bench --bench stencil_sum`` and again with ``--features simd`` to compare the scalar with the vectorized sum.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use interp3d::*;
+use std::hint::black_box;
+use std::time::Instant;
+
+fn time<F: FnMut() -> f64>(name: &str, n: usize, mut f: F) {
+    let start = Instant::now();
+    let mut sum = 0.0;
+    for _ in 0..10 {
+        sum += f();
+    }
+    let per_point = start.elapsed().as_secs_f64()/(10*n) as f64;
+    println!("{:<30} {:8.1} ns/point (checksum {:e})", name, 1e9*per_point, sum);
+}
+
+fn main() {
+    let config = DataGenConfBuilder::default()
+        .uniform_cube(0.0, 1.0, 20)
+        .build();
+    let ip = Interp3D::from_config(|x, y, z| (x*y).sin() + z.exp(), &config);
+
+    let n = 1_000_000;
+    let mut next = common::uniform(1);
+    let points: Vec<_> = (0..n).map(|_| (next(), next(), next())).collect();
+
+    println!("simd feature: {}", cfg!(feature = "simd"));
+    time("interpolate", n, || {
+        points.iter().map(|&(x, y, z)| ip.interpolate(black_box(x), y, z)).sum()
+    });
+    time("interpolate_gradient", n, || {
+        points.iter().map(|&(x, y, z)| ip.interpolate_gradient(black_box(x), y, z).1[0]).sum()
+    });
+    time("interpolate_hessian", n, || {
+        points.iter().map(|&(x, y, z)| ip.interpolate_hessian(black_box(x), y, z).2[0][1]).sum()
+    });
+}
@@ -0,0 +1,97 @@
+//! Contains the data generation on grids that are refined until the interpolation meets a tolerance.
+
+use crate::{Interp3D, Interp3DError, DataGenConf, DataGenConfSingle};
+
+use std::collections::HashMap;
+
+impl Interp3D {
+    /// Generates the data on a grid that is refined until the interpolation error is below ``tol``, starting out from the grid of ``initial``.
+    /// The result has [`GridSpacing::Custom`](crate::GridSpacing::Custom) axes, so [`Self::config()`] tells the final node positions.
+    /// 
+    /// Every round checks the error at the midpoints of all edges of the grid, i.e. halfway between two neighbouring nodes along one direction and right at the nodes along the other two. There the interpolation only runs along that one direction, so the error tells which intervals of which axis are too coarse. Every interval with an error above ``tol`` is split in half (the worst ones first, as long as the axis has less than ``max_points_per_axis`` nodes) and the next round starts.
+    /// It stops as soon as no interval needs to be split or the axes that would need more nodes have reached ``max_points_per_axis``. As the checks are heuristic, the error elsewhere (e.g. in the middle of the cells) can be a bit larger than ``tol``, see [`Self::estimate_error_on_grid()`] to check the result.
+    /// 
+    /// ``f`` is called exactly once per point it is needed at: the values at the nodes and at the checked midpoints are all kept, so splitting an interval reuses the values at its midpoint and ``f`` is never called twice at the same point. On top of the nodes of the final grid, ``f`` is only called at the midpoints of intervals that turned out to be fine.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let f = |x: f64, _y: f64, _z: f64| (-10.0*x*x).exp();
+    /// let initial = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 5).build();
+    /// let ip = Interp3D::generate_adaptive(f, &initial, 1e-4, 100);
+    /// 
+    /// // the grid only got refined along x, where the data changes
+    /// let (nx, ny, nz) = ip.dims();
+    /// assert!(nx > 5 && ny == 5 && nz == 5);
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if ``initial`` is invalid, see [`Self::try_generate_adaptive()`] for a version that returns the error instead.
+    pub fn generate_adaptive<F>(f: F, initial: &DataGenConf, tol: f64, max_points_per_axis: usize) -> Interp3D
+    where F: FnMut(f64, f64, f64) -> f64 {
+        match Self::try_generate_adaptive(f, initial, tol, max_points_per_axis) {
+            Ok(ip) => ip,
+            Err(e) => panic!("{}", e)
+        }
+    }
+
+    /// Same as [`Self::generate_adaptive()`], but an invalid initial config is returned as an error instead of panicking.
+    pub fn try_generate_adaptive<F>(mut f: F, initial: &DataGenConf, tol: f64, max_points_per_axis: usize) -> Result<Interp3D, Interp3DError>
+    where F: FnMut(f64, f64, f64) -> f64 {
+        let mut grid = Interp3D::default();
+        grid.setup(initial)?;
+        let mut axes = [grid.grid_x().to_vec(), grid.grid_y().to_vec(), grid.grid_z().to_vec()];
+
+        let mut known: HashMap<[u64; 3], f64> = HashMap::new();
+        let mut value = |p: [f64; 3]| *known.entry(p.map(f64::to_bits)).or_insert_with(|| f(p[0], p[1], p[2]));
+
+        loop {
+            let conf = DataGenConf {
+                x: DataGenConfSingle::custom(axes[0].clone()),
+                y: DataGenConfSingle::custom(axes[1].clone()),
+                z: DataGenConfSingle::custom(axes[2].clone())
+            };
+            let ip = Interp3D::try_from_config(|x, y, z| value([x, y, z]), &conf)?;
+
+            // the checks all run on the current axes, the new nodes are only added afterwards
+            let mut refined = axes.clone();
+            for d in 0..3 {
+                let budget = max_points_per_axis.saturating_sub(axes[d].len());
+                if budget == 0 {
+                    continue;
+                }
+
+                // (worst error, midpoint) of every interval along d that is too coarse
+                let mut coarse = Vec::new();
+                for w in axes[d].windows(2) {
+                    let mid = 0.5*(w[0] + w[1]);
+                    let mut worst = 0.0f64;
+                    for &a in &axes[(d+1)%3] {
+                        for &b in &axes[(d+2)%3] {
+                            let mut p = [0.0; 3];
+                            p[d] = mid;
+                            p[(d+1)%3] = a;
+                            p[(d+2)%3] = b;
+                            worst = worst.max((ip.interpolate(p[0], p[1], p[2]) - value(p)).abs());
+                        }
+                    }
+                    if worst > tol {
+                        coarse.push((worst, mid));
+                    }
+                }
+
+                coarse.sort_by(|a, b| b.0.total_cmp(&a.0));
+                refined[d].extend(coarse.iter().take(budget).map(|&(_, mid)| mid));
+                refined[d].sort_by(f64::total_cmp);
+            }
+
+            if refined == axes {
+                return Ok(ip);
+            }
+            axes = refined;
+        }
+    }
+}
@@ -0,0 +1,542 @@
+//! Contains methods that look at the interpolant as a whole instead of single points, like integrals over it or statistics of its data.
+
+use crate::{Interp3D, Interp3DError, AxisTransform, Dir, Type, Storage, GridSpacing, GHOST_LO, GHOST_HI};
+
+use std::fmt;
+
+/// Positions of the 2-point Gauss–Legendre rule on ``[-1, 1]`` (both weights are 1)
+const GAUSS_2: f64 = 0.577_350_269_189_625_8;
+/// Real root of ``x^4 = x + 1``, the 3d generalization of the golden ratio. Its inverse powers give the low discrepancy sequence of [`Interp3D::estimate_error()`].
+const PLASTIC_3: f64 = 1.220_744_084_605_759_5;
+
+/// Result of comparing the interpolant with the function it was generated from, see [`Interp3D::estimate_error()`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ErrorReport {
+    /// largest absolute error of all points
+    pub max_abs: f64,
+    /// root mean square of the absolute errors
+    pub rms: f64,
+    /// point ``(x, y, z)`` with the largest error
+    pub worst: (f64, f64, f64),
+    /// number of points that were checked
+    pub samples: usize
+}
+
+/// How far the samples of two interpolators on the same grid are apart, see [`Interp3D::compare()`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DiffReport {
+    /// largest absolute difference of all nodes
+    pub max_abs: f64,
+    /// largest relative difference of all nodes, the smallest ``rel_tol`` for which [`Interp3D::approx_eq()`] holds with the ``abs_tol`` of the comparison
+    pub max_rel: f64,
+    /// root mean square of the absolute differences
+    pub rms: f64,
+    /// indices of the node with the largest absolute difference, as for [`Interp3D::value_at()`]
+    pub worst_node: (usize, usize, usize),
+    /// position ``(x, y, z)`` of that node
+    pub worst_coords: (f64, f64, f64)
+}
+
+/// Summary of the grid and the samples of an interpolator, see [`Interp3D::stats()`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DataStats {
+    /// number of nodes along each direction, see [`Interp3D::dims()`]
+    pub dims: (usize, usize, usize),
+    /// smallest finite sample (``NaN`` if there is none)
+    pub min: f64,
+    /// largest finite sample (``NaN`` if there is none)
+    pub max: f64,
+    /// indices of the node with ``min`` (as for [`Interp3D::value_at()`]), the first one in storage order if several share it (``(0, 0, 0)`` if there is none)
+    pub min_at: (usize, usize, usize),
+    /// indices of the node with ``max``, like ``min_at``
+    pub max_at: (usize, usize, usize),
+    /// mean of the finite samples (``NaN`` if there is none)
+    pub mean: f64,
+    /// number of ``NaN`` samples
+    pub nan_count: usize,
+    /// number of infinite samples (of either sign)
+    pub inf_count: usize,
+    /// memory taken up by the interpolator in bytes, i.e. the samples and the axes (ghost cells included), the supplied derivatives and the struct itself
+    pub memory: usize
+}
+
+/// A sample together with the indices of its node, as for [`Interp3D::value_at()`]
+type Extremum = (f64, (usize, usize, usize));
+
+/// SplitMix64, to turn a seed into well distributed bits
+pub(crate) fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+impl Interp3D {
+    /// Quadrature points ``(position, weight)`` along the axis ``d`` for the interval ``[a, b]``.  
+    /// The interval is split at every node (shifted by the period for periodic axes) and, for [`Type::Nearest`], at every cell midpoint, such that the interpolant is a polynomial of at most degree 3 on every piece. Then each piece gets the 2-point Gauss–Legendre rule, which is exact for that.
+    fn quadrature(&self, d: usize, (a, b): (f64, f64)) -> Vec<(f64, f64)> {
+        let v = [&self.x, &self.y, &self.z][d];
+        let nodes = &v[GHOST_LO..v.len()-GHOST_HI];
+        let (min, max) = Self::range(v);
+
+        let mut breaks = vec![a, b];
+        let shifts = if self.periodic[d] {
+            let period = max - min;
+            ((a - min)/period).floor() as i64..=((b - min)/period).ceil() as i64
+        } else {
+            0..=0
+        };
+        for shift in shifts {
+            let offset = shift as f64*(max - min);
+            breaks.extend(nodes.iter().map(|p| p + offset));
+            if self.scheme == Type::Nearest {
+                breaks.extend(nodes.windows(2).map(|w| 0.5*(w[0] + w[1]) + offset));
+            }
+        }
+        breaks.retain(|&p| a <= p && p <= b);
+        breaks.sort_by(f64::total_cmp);
+        breaks.dedup();
+
+        breaks.windows(2).flat_map(|w| {
+            let (mid, half) = (0.5*(w[0] + w[1]), 0.5*(w[1] - w[0]));
+            [(mid - half*GAUSS_2, half), (mid + half*GAUSS_2, half)]
+        }).collect()
+    }
+
+    /// Integrates the interpolant over the box ``[x0, x1] x [y0, y1] x [z0, z1]``, e.g. to get the total mass from a density table.
+    /// 
+    /// The box is split at the grid planes (and the boundaries of the box may cut through cells). On every piece the interpolant is a polynomial of at most degree 3 along each direction, which the 2-point Gauss–Legendre rule per direction integrates exactly. So the result is exact up to rounding for all schemes, it costs 8 evaluations of the interpolant per (partial) cell.
+    /// 
+    /// Parts of the box outside of the domain are integrated according to the out of bounds policy, i.e. over the clamped or extrapolated interpolant. With [`OutOfBounds::Error`](crate::OutOfBounds::Error) that's an error, with [`OutOfBounds::ReturnNan`](crate::OutOfBounds::ReturnNan) the result is ``NaN``. An empty or reversed range is returned as [`Interp3DError::InvalidRange`].
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+    /// let mut ip = Interp3D::from_config(|x, y, z| x*y*z, &config);
+    /// // reproduces x*y*z exactly
+    /// ip.set_type(Type::Trilinear);
+    /// 
+    /// let integral = ip.integrate((0.0, 1.0), (0.0, 1.0), (0.0, 0.5)).unwrap();
+    /// assert!((integral - 1.0/32.0).abs() < 1e-15);
+    /// ```
+    pub fn integrate(&self, xr: (f64, f64), yr: (f64, f64), zr: (f64, f64)) -> Result<f64, Interp3DError> {
+        if self.data.is_empty() {
+            return Err(Interp3DError::NotSetUp);
+        }
+        for (dir, (min, max)) in [(Dir::X, xr), (Dir::Y, yr), (Dir::Z, zr)] {
+            if !min.is_finite() || !max.is_finite() || min >= max {
+                return Err(Interp3DError::InvalidRange { dir, min, max });
+            }
+        }
+
+        let (qx, qy, qz) = (self.quadrature(0, xr), self.quadrature(1, yr), self.quadrature(2, zr));
+        let mut sum = 0.0;
+        for &(x, wx) in &qx {
+            let mut sum_y = 0.0;
+            for &(y, wy) in &qy {
+                let mut sum_z = 0.0;
+                for &(z, wz) in &qz {
+                    sum_z += wz*self.try_interpolate_grid_coords(x, y, z)?;
+                }
+                sum_y += wy*sum_z;
+            }
+            sum += wx*sum_y;
+        }
+
+        Ok(sum)
+    }
+
+    /// Compares the interpolant with ``f`` at all ``points``
+    fn error_report<F, I>(&self, mut f: F, points: I) -> ErrorReport
+    where F: FnMut(f64, f64, f64) -> f64, I: Iterator<Item = (f64, f64, f64)> {
+        let mut report = ErrorReport { max_abs: 0.0, rms: 0.0, worst: (f64::NAN, f64::NAN, f64::NAN), samples: 0 };
+        for (x, y, z) in points {
+            // the points are spread over the grid, f takes physical coordinates
+            let (px, py, pz) = self.to_physical(x, y, z);
+            let error = (self.interpolate_grid_coords(x, y, z) - f(px, py, pz)).abs();
+            if report.samples == 0 || error > report.max_abs {
+                report.max_abs = error;
+                report.worst = (px, py, pz);
+            }
+            report.rms += error*error;
+            report.samples += 1;
+        }
+        report.rms = (report.rms/report.samples as f64).sqrt();
+
+        report
+    }
+
+    /// Estimates how well the interpolant reproduces ``f`` (usually the closure the data was generated with), e.g. to tune the number of points and the spacing of a config.  
+    /// Both are evaluated at ``samples`` points spread evenly over the domain by a low discrepancy sequence (additive recurrence with the inverse powers of the 3d golden ratio), which is shifted randomly depending on ``seed``. So the same seed always checks the same points.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let f = |x: f64, y: f64, z: f64| (x*y).sin() + z;
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 21).build();
+    /// let ip = Interp3D::from_config(f, &config);
+    /// 
+    /// let report = ip.estimate_error(f, 1000, 42);
+    /// assert!(report.max_abs < 1e-2 && report.rms < 1e-3);
+    /// assert_eq!(report, ip.estimate_error(f, 1000, 42));
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet or if ``samples`` is 0.
+    pub fn estimate_error<F>(&self, f: F, samples: usize, seed: u64) -> ErrorReport
+    where F: FnMut(f64, f64, f64) -> f64 {
+        assert!(!self.data.is_empty(), "Interpolator has not been set up");
+        assert!(samples > 0, "Need at least one sample");
+
+        let mut state = seed;
+        let shift: [f64; 3] = std::array::from_fn(|_| (splitmix64(&mut state) >> 11) as f64/(1u64 << 53) as f64);
+        let alpha = [1.0/PLASTIC_3, 1.0/PLASTIC_3.powi(2), 1.0/PLASTIC_3.powi(3)];
+        let ranges = [Self::range(&self.x), Self::range(&self.y), Self::range(&self.z)];
+
+        let points = (0..samples).map(|n| {
+            let p: [f64; 3] = std::array::from_fn(|d| {
+                let u = (shift[d] + n as f64*alpha[d]).fract();
+                ranges[d].0 + u*(ranges[d].1 - ranges[d].0)
+            });
+            (p[0], p[1], p[2])
+        });
+        self.error_report(f, points)
+    }
+
+    /// Same as [`Self::estimate_error()`], but checks the centre of every cell, which is usually where the interpolation is worst (the nodes themselves are exact). That is one point per cell, so this can take a while for large grids.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet.
+    pub fn estimate_error_on_grid<F>(&self, f: F) -> ErrorReport
+    where F: FnMut(f64, f64, f64) -> f64 {
+        assert!(!self.data.is_empty(), "Interpolator has not been set up");
+
+        let centres = |v: &[f64]| -> Vec<f64> { Self::interior_axis(v).windows(2).map(|w| 0.5*(w[0] + w[1])).collect() };
+        let (cx, cy, cz) = (centres(&self.x), centres(&self.y), centres(&self.z));
+        let points = cx.iter().flat_map(|&x| {
+            let cz = &cz;
+            cy.iter().flat_map(move |&y| cz.iter().map(move |&z| (x, y, z)))
+        });
+        self.error_report(f, points)
+    }
+
+    /// Returns the dimensions, the memory footprint and statistics of the samples (only the interior nodes), e.g. to log them after generating a table.  
+    /// ``NaN`` and infinite samples are counted but left out of ``min``, ``max`` and ``mean``. A closure that silently returns ``NaN`` in some corner of the domain shows up in ``nan_count``. The ghost cells are left out as well, so the samples on the faces aren't counted more than once.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(-1.0, 1.0, 5).build();
+    /// let ip = Interp3D::from_config(|x, y, z| (x + y + z).sqrt(), &config);
+    /// 
+    /// let stats = ip.stats();
+    /// assert_eq!(stats.dims, (5, 5, 5));
+    /// assert_eq!((stats.min, stats.max), (0.0, 3f64.sqrt()));
+    /// // the square root of the negative sums
+    /// assert_eq!(stats.nan_count, 53);
+    /// ```
+    pub fn stats(&self) -> DataStats {
+        let [(min, min_at), (max, max_at)] = self.finite_extrema().unwrap_or([(f64::NAN, (0, 0, 0)); 2]);
+        let (sum, finite, nan_count, inf_count) = self.sample_counts();
+
+        let sample_size = match self.storage() {
+            Storage::F64 => std::mem::size_of::<f64>(),
+            Storage::F32 => std::mem::size_of::<f32>()
+        };
+        let memory = std::mem::size_of::<Interp3D>()
+            + self.data.len()*sample_size
+            + (self.x.len() + self.y.len() + self.z.len())*std::mem::size_of::<f64>()
+            + self.hermite.as_ref().map_or(0, |d| d.len()*std::mem::size_of::<[f64; 7]>());
+
+        DataStats {
+            dims: self.dims(),
+            min,
+            max,
+            min_at,
+            max_at,
+            mean: if finite == 0 { f64::NAN } else { sum/finite as f64 },
+            nan_count,
+            inf_count,
+            memory
+        }
+    }
+
+    /// Returns the smallest finite sample and the indices of its node (as for [`Self::value_at()`]), like [`Self::stats()`] but with a single comparison per sample. ``NaN`` and infinite samples are skipped, without any finite sample this is ``(NaN, (0, 0, 0))``.  
+    /// [`Self::data_extrema()`] and [`Self::argmax()`] give the same extrema, without the nodes and at the node positions respectively.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 5).build();
+    /// let mut ip = Interp3D::from_config(|x, y, z| x + y - z, &config);
+    /// ip.set_value(0, 0, 4, f64::NAN);
+    /// 
+    /// assert_eq!(ip.data_min(), (-0.75, (0, 0, 3)));
+    /// assert_eq!(ip.data_max(), (2.0, (4, 4, 0)));
+    /// ```
+    pub fn data_min(&self) -> (f64, (usize, usize, usize)) {
+        self.finite_extrema().map_or((f64::NAN, (0, 0, 0)), |[min, _]| min)
+    }
+
+    /// Returns the largest finite sample and the indices of its node, like [`Self::data_min()`].
+    pub fn data_max(&self) -> (f64, (usize, usize, usize)) {
+        self.finite_extrema().map_or((f64::NAN, (0, 0, 0)), |[_, max]| max)
+    }
+
+    /// Returns the mean of the finite samples (``NaN`` without any), see [`Self::stats()`].
+    pub fn data_mean(&self) -> f64 {
+        let (sum, finite, _, _) = self.sample_counts();
+        if finite == 0 { f64::NAN } else { sum/finite as f64 }
+    }
+
+    /// The smallest and the largest finite interior sample with the indices of their nodes (as for [`Self::value_at()`]), the first ones in storage order on ties, ``None`` without any finite sample.  
+    /// All the extremum queries ([`Self::stats()`], [`Self::data_min()`], [`Self::data_extrema()`], [`Self::argmax()`], ...) take them from here, so they agree on which samples count.
+    pub(crate) fn finite_extrema(&self) -> Option<[Extremum; 2]> {
+        let mut extrema: Option<[Extremum; 2]> = None;
+        for (i, j, k) in self.interior() {
+            let v = self.data.get(self.index(i, j, k));
+            if !v.is_finite() {
+                continue;
+            }
+            let node = (i - GHOST_LO, j - GHOST_LO, k - GHOST_LO);
+            match &mut extrema {
+                None => extrema = Some([(v, node); 2]),
+                Some([min, max]) => {
+                    if v < min.0 {
+                        *min = (v, node);
+                    }
+                    if v > max.0 {
+                        *max = (v, node);
+                    }
+                }
+            }
+        }
+
+        extrema
+    }
+
+    /// The sum and the number of the finite interior samples, followed by the numbers of ``NaN`` and of infinite ones
+    fn sample_counts(&self) -> (f64, usize, usize, usize) {
+        let (mut sum, mut finite, mut nan_count, mut inf_count) = (0.0, 0, 0, 0);
+        for (i, j, k) in self.interior() {
+            let v = self.data.get(self.index(i, j, k));
+            if v.is_nan() {
+                nan_count += 1;
+            } else if v.is_infinite() {
+                inf_count += 1;
+            } else {
+                sum += v;
+                finite += 1;
+            }
+        }
+
+        (sum, finite, nan_count, inf_count)
+    }
+
+    /// Checks whether ``other`` has the same grid as ``self``, i.e. the same number of nodes along each direction at positions that differ by at most ``tol`` times the extent of the axis (``tol = 0.0`` asks for identical grids).  
+    /// Only the actual grids are compared, the ghost cells are left out. Interpolators that have not been set up never have the same grid.
+    pub fn same_grid(&self, other: &Interp3D, tol: f64) -> bool {
+        self.check_grid_within(other, tol).is_ok()
+    }
+
+    /// Compares ``self`` with ``other`` (e.g. a freshly generated table with a stored reference), which needs the same grid within ``rel_tol`` (see [`Self::same_grid()`]) and samples that agree at every node:
+    /// ``|a - b| <= abs_tol + rel_tol*max(|a|, |b|)``. Two ``NaN`` samples count as equal, as do two infinities of the same sign.  
+    /// Like [`Self::same_grid()`] this only looks at the actual grid and ignores the ghost cells as well as all settings, so e.g. an export and import through a text file compares equal with a small tolerance.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 5).build();
+    /// let reference = Interp3D::from_config(|x, y, z| x*y + z, &config);
+    /// let mut ip = Interp3D::from_config(|x, y, z| x*y + z, &config);
+    /// ip.set_value(2, 2, 2, ip.value_at(2, 2, 2) + 1e-10);
+    /// 
+    /// assert!(ip.approx_eq(&reference, 1e-9, 0.0));
+    /// assert!(!ip.approx_eq(&reference, 1e-12, 0.0));
+    /// ```
+    pub fn approx_eq(&self, other: &Interp3D, rel_tol: f64, abs_tol: f64) -> bool {
+        if !self.same_grid(other, rel_tol) {
+            return false;
+        }
+
+        self.interior().all(|(i, j, k)| {
+            let index = self.index(i, j, k);
+            let (a, b) = (self.data.get(index), other.data.get(index));
+            a == b || (a.is_nan() && b.is_nan()) || (a.is_finite() && b.is_finite() && (a - b).abs() <= abs_tol + rel_tol*a.abs().max(b.abs()))
+        })
+    }
+
+    /// Returns the largest absolute difference between the samples of ``self`` and ``other`` together with the indices of its node (as for [`Self::value_at()`]), the first one in storage order if several share it.  
+    /// Nodes are compared by their indices, so only the number of nodes along each direction needs to match, use [`Self::same_grid()`] to check the positions as well. The ghost cells are ignored. Two ``NaN`` samples differ by 0, a ``NaN`` and a number by infinity.
+    /// 
+    /// Returns [`Interp3DError::NotSetUp`] if one of the interpolators has not been set up and [`Interp3DError::GridMismatch`] if the number of nodes differs.
+    pub fn max_abs_diff(&self, other: &Interp3D) -> Result<(f64, (usize, usize, usize)), Interp3DError> {
+        self.check_grid_within(other, f64::INFINITY)?;
+
+        let mut worst = (-1.0, (0, 0, 0));
+        for (i, j, k) in self.interior() {
+            let index = self.index(i, j, k);
+            let (a, b) = (self.data.get(index), other.data.get(index));
+            let diff = if a == b || (a.is_nan() && b.is_nan()) {
+                0.0
+            } else if a.is_nan() || b.is_nan() {
+                f64::INFINITY
+            } else {
+                (a - b).abs()
+            };
+            if diff > worst.0 {
+                worst = (diff, (i-GHOST_LO, j-GHOST_LO, k-GHOST_LO));
+            }
+        }
+
+        Ok(worst)
+    }
+
+    /// Compares the samples of ``self`` with the ones of a ``reference`` node by node, see [`DiffReport`] and [`Self::diff()`] for the difference itself. The grids need to match like for [`Self::add_assign_field()`], the ghost cells are ignored.  
+    /// The relative difference at a node is ``(|a - b| - abs_tol)/max(|a|, |b|)`` (but at least 0), like for [`Self::approx_eq()`], so nodes where both samples are close to 0 don't blow it up as long as ``abs_tol`` covers their difference. Two ``NaN`` samples differ by 0, a ``NaN`` and a number by infinity.  
+    /// A node where only one of the samples is infinite or ``NaN`` (or where they are infinities of opposite sign) makes ``max_rel`` infinite whatever ``abs_tol`` is, just as no tolerance lets [`Self::approx_eq()`] hold there.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 5).build();
+    /// let reference = Interp3D::from_config(|x, y, z| x*y + z, &config);
+    /// let mut ip = reference.clone();
+    /// ip.set_value(1, 2, 3, ip.value_at(1, 2, 3) + 1e-3);
+    /// 
+    /// let report = ip.compare(&reference, 1e-12).unwrap();
+    /// assert_eq!(report.worst_node, (1, 2, 3));
+    /// assert_eq!(report.worst_coords, (0.25, 0.5, 0.75));
+    /// assert!((report.max_abs - 1e-3).abs() < 1e-12);
+    /// assert!(ip.approx_eq(&reference, report.max_rel, 1e-12));
+    /// ```
+    pub fn compare(&self, reference: &Interp3D, abs_tol: f64) -> Result<DiffReport, Interp3DError> {
+        self.check_same_grid(reference)?;
+
+        let mut report = DiffReport { max_abs: -1.0, max_rel: 0.0, rms: 0.0, worst_node: (0, 0, 0), worst_coords: (0.0, 0.0, 0.0) };
+        let mut worst = (0, 0, 0);
+        for (i, j, k) in self.interior() {
+            let index = self.index(i, j, k);
+            let (a, b) = (self.data.get(index), reference.data.get(index));
+            let diff = if a == b || (a.is_nan() && b.is_nan()) {
+                0.0
+            } else if a.is_nan() || b.is_nan() {
+                f64::INFINITY
+            } else {
+                (a - b).abs()
+            };
+            if diff > report.max_abs {
+                report.max_abs = diff;
+                worst = (i, j, k);
+            }
+            if diff != 0.0 && !(a.is_finite() && b.is_finite()) {
+                // (inf - abs_tol)/inf would be NaN, which max() drops
+                report.max_rel = f64::INFINITY;
+            } else if diff > abs_tol {
+                report.max_rel = report.max_rel.max((diff - abs_tol)/a.abs().max(b.abs()));
+            }
+            report.rms += diff*diff;
+        }
+
+        let (nx, ny, nz) = self.dims();
+        report.rms = (report.rms/(nx*ny*nz) as f64).sqrt();
+        report.worst_node = (worst.0 - GHOST_LO, worst.1 - GHOST_LO, worst.2 - GHOST_LO);
+        report.worst_coords = self.physical(worst.0, worst.1, worst.2);
+
+        Ok(report)
+    }
+
+    /// Short description of the spacing along ``d`` for the summaries, the variant of the config or ``custom`` if there is none
+    fn spacing_name(&self, d: usize) -> String {
+        let Some(conf) = &self.conf else {
+            return String::from("custom");
+        };
+        match &[&conf.x, &conf.y, &conf.z][d].spacing {
+            GridSpacing::Custom(_) => String::from("custom"),
+            spacing => format!("{:?}", spacing)
+        }
+    }
+}
+
+/// Prints a compact summary instead of the samples, see [`Interp3D::stats()`]
+impl fmt::Debug for Interp3D {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let axis = |d: usize| {
+            let v = [&self.x, &self.y, &self.z][d];
+            if v.is_empty() { None } else { Some((Self::range(v), self.spacing_name(d))) }
+        };
+        f.debug_struct("Interp3D")
+            .field("x", &axis(0))
+            .field("y", &axis(1))
+            .field("z", &axis(2))
+            .field("scheme", &self.scheme)
+            .field("out_of_bounds", &self.out_of_bounds)
+            .field("tolerance", &self.tolerance)
+            .field("boundary", &self.boundary)
+            .field("periodic", &self.periodic)
+            .field("storage", &self.storage())
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+/// Prints the grid and the statistics of the samples in a few lines, e.g.
+/// 
+/// ```text
+/// Interp3D with 50x200x40 nodes (f64, 3.05 MiB)
+///   x: [0, 15] Exponential(8.0)
+///   y: [-1, 1] Linear
+///   z: [0, 3.141592653589793] Linear
+///   data: min -0.5, max 2, mean 0.75, 3 NaN
+/// ```
+impl fmt::Display for Interp3D {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.data.is_empty() {
+            return write!(f, "Interp3D (not set up)");
+        }
+
+        let stats = self.stats();
+        let (nx, ny, nz) = stats.dims;
+        let storage = match self.storage() {
+            Storage::F64 => "f64",
+            Storage::F32 => "f32"
+        };
+        writeln!(f, "Interp3D with {}x{}x{} nodes ({}, {:.2} MiB)", nx, ny, nz, storage, stats.memory as f64/(1u64 << 20) as f64)?;
+        for (d, (name, v)) in [("x", &self.x), ("y", &self.y), ("z", &self.z)].into_iter().enumerate() {
+            let (min, max) = Self::range(v);
+            let periodic = if self.periodic[d] { ", periodic" } else { "" };
+            let transform = match self.transform[d] {
+                AxisTransform::Identity => "",
+                AxisTransform::Log10 => ", log10 transform",
+                AxisTransform::Ln => ", ln transform",
+                AxisTransform::Custom(..) => ", custom transform"
+            };
+            writeln!(f, "  {}: [{}, {}] {}{}{}", name, min, max, self.spacing_name(d), periodic, transform)?;
+        }
+        write!(f, "  data: min {}, max {}, mean {}", stats.min, stats.max, stats.mean)?;
+        if stats.nan_count > 0 {
+            write!(f, ", {} NaN", stats.nan_count)?;
+        }
+        if stats.inf_count > 0 {
+            write!(f, ", {} inf", stats.inf_count)?;
+        }
+
+        Ok(())
+    }
+}
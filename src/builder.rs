@@ -0,0 +1,160 @@
+//! Contains the builder that sets up an interpolator with all of its settings at once.
+
+use crate::{Interp3D, Interp3DError, DataGenConf, DataGenConfSingle, GridSpacing, BoundaryCondition, OutOfBounds, AxisTransform, Type, Storage, Dir, SanitizeReport};
+use crate::sanitize::{sanitize, Sanitized};
+
+/// Collects the grid and the settings of an interpolator and then sets it up in one go, with the data from a closure, a file or a slice.  
+/// Every setting starts out at the default of [`Interp3D`] and the grid at [`DataGenConf::default()`], so only what differs needs to be given. The terminal methods return the finished interpolator or the error, they never panic.
+/// 
+/// # Example
+/// 
+/// ```
+/// use crate::interp3d::*;
+/// 
+/// let ip = Interp3DBuilder::default()
+///     .x_axis(0.0, 10.0, 50, GridSpacing::Exponential(4.0))
+///     .y_axis(-1.0, 1.0, 20, GridSpacing::Linear)
+///     .z_axis(0.0, 1.0, 10, GridSpacing::Linear)
+///     .interpolation(Type::Tricubic)
+///     .out_of_bounds(OutOfBounds::ClampToEdge)
+///     .boundary(BoundaryCondition::LinearExtrapolate)
+///     .generate(|x, y, z| x*y + z)
+///     .unwrap();
+/// 
+/// assert_eq!(ip.dims(), (50, 20, 10));
+/// assert!((ip.interpolate(2.0, 0.5, 0.5) - 1.5).abs() < 1e-12);
+/// // clamped onto x = 10
+/// assert!((ip.interpolate(12.0, 0.5, 0.5) - 5.5).abs() < 1e-12);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Interp3DBuilder {
+    conf: DataGenConf,
+    scheme: Type,
+    out_of_bounds: OutOfBounds,
+    tolerance: (f64, f64),
+    boundary: [BoundaryCondition; 3],
+    periodic: [bool; 3],
+    transform: [AxisTransform; 3],
+    storage: Storage
+}
+
+impl Interp3DBuilder {
+    /// Sets the grid along X, like [`DataGenConfBuilder::x()`](crate::DataGenConfBuilder::x())
+    pub fn x_axis(mut self, min: f64, max: f64, n: usize, spacing: GridSpacing) -> Self {
+        self.conf.x = DataGenConfSingle { n, min, max, spacing };
+        self
+    }
+
+    /// Sets the grid along Y
+    pub fn y_axis(mut self, min: f64, max: f64, n: usize, spacing: GridSpacing) -> Self {
+        self.conf.y = DataGenConfSingle { n, min, max, spacing };
+        self
+    }
+
+    /// Sets the grid along Z
+    pub fn z_axis(mut self, min: f64, max: f64, n: usize, spacing: GridSpacing) -> Self {
+        self.conf.z = DataGenConfSingle { n, min, max, spacing };
+        self
+    }
+
+    /// Sets the grid along all directions at once, e.g. from a [`DataGenConfBuilder`](crate::DataGenConfBuilder)
+    pub fn config(mut self, conf: DataGenConf) -> Self {
+        self.conf = conf;
+        self
+    }
+
+    /// Sets the interpolation scheme, see [`Interp3D::set_type()`]
+    pub fn interpolation(mut self, scheme: Type) -> Self {
+        self.scheme = scheme;
+        self
+    }
+
+    /// Sets the out of bounds policy, see [`Interp3D::set_out_of_bounds()`]
+    pub fn out_of_bounds(mut self, policy: OutOfBounds) -> Self {
+        self.out_of_bounds = policy;
+        self
+    }
+
+    /// Sets the tolerance for points just outside of the domain, see [`Interp3D::set_out_of_bounds_tolerance()`]
+    pub fn out_of_bounds_tolerance(mut self, rel: f64, abs: f64) -> Self {
+        self.tolerance = (rel, abs);
+        self
+    }
+
+    /// Sets the boundary condition along all directions, see [`Interp3D::set_boundary_condition()`]
+    pub fn boundary(mut self, boundary: BoundaryCondition) -> Self {
+        self.boundary = [boundary; 3];
+        self
+    }
+
+    /// Sets the boundary condition along ``dir`` only, see [`Interp3D::set_boundary_condition_along()`]
+    pub fn boundary_along(mut self, dir: Dir, boundary: BoundaryCondition) -> Self {
+        self.boundary[dir as usize] = boundary;
+        self
+    }
+
+    /// Makes the axis along ``dir`` periodic, see [`Interp3D::set_periodic()`]
+    pub fn periodic(mut self, dir: Dir) -> Self {
+        self.periodic[dir as usize] = true;
+        self
+    }
+
+    /// Sets the transform between the physical coordinate along ``dir`` and the grid, see [`Interp3D::set_axis_transform()`]
+    pub fn axis_transform(mut self, dir: Dir, transform: AxisTransform) -> Self {
+        self.transform[dir as usize] = transform;
+        self
+    }
+
+    /// Sets the precision at which the samples are stored, see [`Interp3D::set_storage()`]
+    pub fn storage(mut self, storage: Storage) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    /// An interpolator without data that has all of the settings
+    fn settings(&self) -> Interp3D {
+        let mut ip = Interp3D::default();
+        ip.set_type(self.scheme);
+        ip.set_out_of_bounds(self.out_of_bounds);
+        ip.set_out_of_bounds_tolerance(self.tolerance.0, self.tolerance.1);
+        for (d, dir) in [Dir::X, Dir::Y, Dir::Z].into_iter().enumerate() {
+            ip.set_boundary_condition_along(dir, self.boundary[d]);
+            ip.set_periodic(dir, self.periodic[d]);
+            ip.set_axis_transform(dir, self.transform[d]);
+        }
+        ip.set_storage(self.storage);
+        ip
+    }
+
+    /// Generates the data on the grid with ``f``, see [`Interp3D::generate_data()`]. An invalid grid is returned as an error before ``f`` is called.
+    pub fn generate<F>(self, f: F) -> Result<Interp3D, Interp3DError>
+    where F: FnMut(f64, f64, f64) -> f64 {
+        let mut ip = self.settings();
+        ip.try_generate_data(f, &self.conf)?;
+        Ok(ip)
+    }
+
+    /// Reads the grid and the data from a file, see [`Interp3D::import_data()`]. The grid set on the builder is ignored.
+    pub fn build_from_file(self, file: &str) -> Result<Interp3D, Interp3DError> {
+        let mut ip = self.settings();
+        ip.import_data(file)?;
+        Ok(ip)
+    }
+
+    /// Takes the grid and the data from slices, see [`Interp3D::from_grid_and_data()`] for the layout. The grid set on the builder is ignored.
+    pub fn build_from_grid_and_data(self, x: &[f64], y: &[f64], z: &[f64], data: &[f64]) -> Result<Interp3D, Interp3DError> {
+        let expected = x.len()*y.len()*z.len();
+        if data.len() != expected {
+            return Err(Interp3DError::DataLength { expected, found: data.len() });
+        }
+
+        let mut data = data.iter();
+        Interp3D::from_parts(x, y, z, &self.settings(), || Ok(*data.next().unwrap()))
+    }
+
+    /// Same as [`Self::build_from_grid_and_data()`], but sorts the axes and merges nodes closer than ``merge_tol``, see [`Interp3D::from_grid_and_data_lenient()`].
+    pub fn build_from_grid_and_data_lenient(self, x: &[f64], y: &[f64], z: &[f64], data: &[f64], merge_tol: f64) -> Result<(Interp3D, SanitizeReport), Interp3DError> {
+        let Sanitized { axes: [x, y, z], data, report } = sanitize(x, y, z, data, merge_tol)?;
+        Ok((self.build_from_grid_and_data(&x, &y, &z, &data)?, report))
+    }
+}
@@ -0,0 +1,168 @@
+//! Contains the compact form of an interpolator, whose samples are quantized to ``u16`` for large tables that are only read.
+
+use crate::{Interp3D, Interp3DError, ErrorReport, QuantizeSpec, Storage, GHOST_LO, GHOST_HI};
+use crate::samples::{Samples, Quantized};
+
+use std::fmt;
+
+/// An interpolator whose samples are stored as ``u16`` with a linear map back to ``f64``, see [`Interp3D::quantize()`].  
+/// That's a quarter of the memory of ``f64`` samples, in memory and in files (see [`Self::export_data()`]). The axes stay ``f64`` and the samples of the stencil are mapped back on the fly, so the queries work like the ones of [`Interp3D`] and take about as long.
+/// 
+/// The compact interpolator can't be changed anymore, to change settings or data change the original one and quantize it again. Like [`Interp3D`] it is ``Send + Sync``.
+#[derive(Clone)]
+pub struct CompactInterp3D {
+    /// the grid, the settings and the quantized samples
+    pub(crate) ip: Interp3D,
+    pub(crate) spec: QuantizeSpec,
+    pub(crate) report: ErrorReport
+}
+
+impl Interp3D {
+    /// Quantizes the samples to ``u16`` for memory-constrained deployments that can live with a relative accuracy of about ``1e-5`` of the range of the data, see [`QuantizeSpec`] and [`CompactInterp3D`].  
+    /// The error this introduces at the samples is in [`CompactInterp3D::error_report()`]. The schemes other than [`Type::TricubicMonotone`](crate::Type::TricubicMonotone) are linear in the samples, so inside of the domain the interpolated values differ from the ones of ``self`` by at most ``max_abs`` times the sum of the absolute weights: 1 for [`Type::Trilinear`](crate::Type::Trilinear) and [`Type::Nearest`](crate::Type::Nearest) and below 2 for [`Type::Tricubic`](crate::Type::Tricubic) on evenly spaced grids. Supplied derivatives (see [`Self::generate_data_hermite()`]) aren't quantized and are dropped.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 21).build();
+    /// let ip = Interp3D::from_config(|x, y, z| (x*y).sin() + z, &config);
+    /// let small = ip.quantize(QuantizeSpec::U16PerSlab);
+    /// 
+    /// let bound = 2.0*small.error_report().max_abs;
+    /// assert!(bound < 1e-4);
+    /// assert!((small.interpolate(0.3, 0.6, 0.9) - ip.interpolate(0.3, 0.6, 0.9)).abs() <= bound);
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet or if a sample isn't finite, see [`Self::try_quantize()`] for a version that returns the error instead.
+    pub fn quantize(&self, spec: QuantizeSpec) -> CompactInterp3D {
+        match self.try_quantize(spec) {
+            Ok(compact) => compact,
+            Err(e) => panic!("{}", e)
+        }
+    }
+
+    /// Same as [`Self::quantize()`], but returns an error instead of panicking: [`Interp3DError::NotSetUp`] without data and [`Interp3DError::NonFiniteSample`] for the first sample that is ``NaN`` or infinite (replace those first, e.g. with [`Self::map_data()`] or through [`Self::generate_data_masked()`]).
+    pub fn try_quantize(&self, spec: QuantizeSpec) -> Result<CompactInterp3D, Interp3DError> {
+        if self.data.is_empty() {
+            return Err(Interp3DError::NotSetUp);
+        }
+
+        // the ghost cells are quantized as well, with fresh values if they are stale
+        let samples: Vec<f64> = (0..self.data.len()).map(|index| self.sample(&self.data, index)).collect();
+        if let Some(index) = samples.iter().position(|v| !v.is_finite()) {
+            let interior = |i: usize, n: usize| i.clamp(GHOST_LO, n-1-GHOST_HI) - GHOST_LO;
+            let (i, j, k) = (index/(self.ny*self.nz), index/self.nz % self.ny, index % self.nz);
+            return Err(Interp3DError::NonFiniteSample { i: interior(i, self.nx), j: interior(j, self.ny), k: interior(k, self.nz) });
+        }
+
+        let slab_len = match spec {
+            QuantizeSpec::U16 => samples.len(),
+            QuantizeSpec::U16PerSlab => self.ny*self.nz
+        };
+        let mut ip = self.clone();
+        ip.data = Samples::U16(Quantized::new(&samples, slab_len));
+        ip.stale_ghosts = false;
+        ip.hermite = None;
+
+        let report = ip.quantization_report(&samples);
+        Ok(CompactInterp3D { ip, spec, report })
+    }
+
+    /// Compares the (quantized) samples with ``samples``, at every node including the ghost cells
+    fn quantization_report(&self, samples: &[f64]) -> ErrorReport {
+        let mut report = ErrorReport { max_abs: 0.0, rms: 0.0, worst: (f64::NAN, f64::NAN, f64::NAN), samples: samples.len() };
+        let mut worst = 0;
+        for (index, v) in samples.iter().enumerate() {
+            let error = (self.data.get(index) - v).abs();
+            if error > report.max_abs {
+                (report.max_abs, worst) = (error, index);
+            }
+            report.rms += error*error;
+        }
+        report.rms = (report.rms/samples.len() as f64).sqrt();
+        let (i, j, k) = (worst/(self.ny*self.nz), worst/self.nz % self.ny, worst % self.nz);
+        report.worst = self.to_physical(self.x[i], self.y[j], self.z[k]);
+
+        report
+    }
+}
+
+impl CompactInterp3D {
+    /// Interpolates at ``(x, y, z)``, see [`Interp3D::interpolate()`].
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the point is out of bounds with [`OutOfBounds::Error`](crate::OutOfBounds::Error).
+    pub fn interpolate(&self, x: f64, y: f64, z: f64) -> f64 {
+        self.ip.interpolate(x, y, z)
+    }
+
+    /// Same as [`Self::interpolate()`], but returns an error instead of panicking.
+    pub fn try_interpolate(&self, x: f64, y: f64, z: f64) -> Result<f64, Interp3DError> {
+        self.ip.try_interpolate(x, y, z)
+    }
+
+    /// Interpolates at all ``points``, see [`Interp3D::interpolate_many()`].
+    pub fn interpolate_many(&self, points: &[(f64, f64, f64)]) -> Vec<f64> {
+        self.ip.interpolate_many(points)
+    }
+
+    /// Interpolates the value and the gradient at ``(x, y, z)``, see [`Interp3D::interpolate_gradient()`].
+    pub fn interpolate_gradient(&self, x: f64, y: f64, z: f64) -> (f64, [f64; 3]) {
+        self.ip.interpolate_gradient(x, y, z)
+    }
+
+    /// Returns the sampled domain, see [`Interp3D::domain()`]
+    pub fn domain(&self) -> ((f64, f64), (f64, f64), (f64, f64)) {
+        self.ip.domain()
+    }
+
+    /// Returns the number of nodes along each direction, see [`Interp3D::dims()`]
+    pub fn dims(&self) -> (usize, usize, usize) {
+        self.ip.dims()
+    }
+
+    /// Returns how the samples were quantized
+    pub fn spec(&self) -> QuantizeSpec {
+        self.spec
+    }
+
+    /// Returns the error the quantization introduced at the samples: the largest and the root mean square absolute difference to the original samples over all nodes (including the ghost cells beyond the boundary, see [`BoundaryCondition`](crate::BoundaryCondition)), the position of the node with the largest one and the number of nodes.  
+    /// See [`Interp3D::quantize()`] for what that means for the interpolated values.
+    pub fn error_report(&self) -> ErrorReport {
+        self.report
+    }
+
+    /// Returns the approximate memory footprint in bytes, like [`DataStats::memory`](crate::DataStats::memory)
+    pub fn memory(&self) -> usize {
+        let Samples::U16(q) = &self.ip.data else {
+            unreachable!("a compact interpolator holds quantized samples")
+        };
+        std::mem::size_of::<Self>()
+            + q.values.len()*std::mem::size_of::<u16>()
+            + q.scales.len()*std::mem::size_of::<(f64, f64)>()
+            + (self.ip.x.len() + self.ip.y.len() + self.ip.z.len())*std::mem::size_of::<f64>()
+    }
+
+    /// Returns an [`Interp3D`] with the quantized samples as ``f64``, with the settings of the original one, e.g. to change them or to write it to a file in one of the other formats
+    pub fn dequantize(&self) -> Interp3D {
+        let mut ip = self.ip.clone();
+        ip.data = std::mem::take(&mut ip.data).converted(Storage::F64);
+        ip
+    }
+}
+
+impl fmt::Debug for CompactInterp3D {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompactInterp3D")
+            .field("dims", &self.dims())
+            .field("domain", &self.domain())
+            .field("spec", &self.spec)
+            .field("error", &self.report)
+            .finish()
+    }
+}
@@ -0,0 +1,184 @@
+//! Contains the compiled form of an interpolator, which stores the polynomial of every cell instead of recomputing it from the samples on every query.
+
+use crate::{Interp3D, Interp3DError, QueryCache, Type, Dir, GHOST_LO, GHOST};
+use crate::kernel::{self, Kernel};
+
+/// Coefficients of the cubic Hermite basis functions ``[h00, h10, h01, h11]`` in powers ``[1, t, t^2, t^3]``
+const HERMITE_MONOMIALS: [[f64; 4]; 4] = [
+    [1.0, 0.0, -3.0, 2.0],
+    [0.0, 1.0, -2.0, 1.0],
+    [0.0, 0.0, 3.0, -2.0],
+    [0.0, 0.0, -1.0, 1.0]
+];
+
+/// Coefficients of the linear stencil weights in powers of ``t``, see [`Interp3D::weight_monomials()`]
+const LINEAR_MONOMIALS: [[f64; 4]; 4] = [[0.0, 1.0, 0.0, 0.0], [0.0, -1.0, 1.0, 0.0], [0.0; 4], [0.0; 4]];
+
+/// An interpolator that stores the 64 coefficients of the tricubic polynomial ``sum c[p][q][r] tx^p ty^q tz^r`` of every cell, see [`Interp3D::compile()`].  
+/// A query then only locates the cell and evaluates that polynomial, instead of computing the weights along each direction and summing up the 4x4x4 stencil. That's 64 times the memory of the samples (as ``f64``), in exchange for the fastest queries.
+/// 
+/// The compiled interpolator can't be changed anymore, to change settings or data change the original one and compile it again. Like [`Interp3D`] it is ``Send + Sync``.
+pub struct CompiledInterp3D {
+    /// the original interpolator, for the grid, the settings and the cell lookup
+    ip: Interp3D,
+    /// number of cells along Y and Z
+    cells: (usize, usize),
+    /// the coefficients ``c[p][q][r]`` of every cell at ``16*p + 4*q + r``, in storage order of the cells
+    coefficients: Vec<[f64; 64]>
+}
+
+impl Interp3D {
+    /// Coefficients of the stencil weights along ``dir`` in the cell ``i`` in powers of ``t``, i.e. the weight of the stencil node ``i-1+a`` is ``sum m[p][a] t^p``. ``None`` for the nearest node, which isn't a polynomial.
+    fn weight_monomials(&self, dir: Dir, i: usize) -> Option<[[f64; 4]; 4]> {
+        match self.kernel(dir) {
+            Kernel::Cubic => {
+                let v = self.axis(dir);
+                Some(std::array::from_fn(|p| kernel::hermite_weights(v, i, HERMITE_MONOMIALS.map(|h| h[p]))))
+            },
+            Kernel::Linear => Some(LINEAR_MONOMIALS),
+            Kernel::Nearest => None
+        }
+    }
+
+    /// Precomputes the polynomial of every cell for query-heavy workloads, see [`CompiledInterp3D`].  
+    /// The results agree with the ones of ``self`` up to rounding (the polynomial is evaluated in another order than the stencil sum), i.e. to about ``1e-15`` relative to the largest magnitude of the samples inside of the domain. The outermost cells continue their polynomial beyond the domain like the stencil sum does, but the difference grows with the distance there.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+    /// let ip = Interp3D::from_config(|x, y, z| (x*y).sin() + z, &config);
+    /// let fast = ip.compile();
+    /// 
+    /// assert!((fast.interpolate(0.3, 0.6, 0.9) - ip.interpolate(0.3, 0.6, 0.9)).abs() < 1e-14);
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet or if its scheme can't be compiled, see [`Self::try_compile()`] for a version that returns the error instead.
+    pub fn compile(&self) -> CompiledInterp3D {
+        match self.try_compile() {
+            Ok(compiled) => compiled,
+            Err(e) => panic!("{}", e)
+        }
+    }
+
+    /// Same as [`Self::compile()`], but returns an error instead of panicking.  
+    /// All schemes that are polynomials on every cell can be compiled, i.e. all except [`Type::TricubicMonotone`] (whose slopes depend on the data along the way) and [`Type::Nearest`]. Those give [`Interp3DError::UnsupportedScheme`].  
+    /// Supplied derivatives (see [`Self::generate_data_hermite()`]) are compiled into the polynomials as well.
+    pub fn try_compile(&self) -> Result<CompiledInterp3D, Interp3DError> {
+        if self.data.is_empty() {
+            return Err(Interp3DError::NotSetUp);
+        }
+        if self.scheme == Type::TricubicMonotone {
+            return Err(Interp3DError::UnsupportedScheme { scheme: self.scheme });
+        }
+
+        let monomials = |dir: Dir, cells: std::ops::Range<usize>| -> Result<Vec<[[f64; 4]; 4]>, Interp3DError> {
+            cells.map(|i| self.weight_monomials(dir, i).ok_or(Interp3DError::UnsupportedScheme { scheme: self.scheme })).collect()
+        };
+        // the cells start at every interior node but the last one
+        let (cx, cy, cz) = (self.nx - GHOST - 1, self.ny - GHOST - 1, self.nz - GHOST - 1);
+        let mx = monomials(Dir::X, GHOST_LO..GHOST_LO+cx)?;
+        let my = monomials(Dir::Y, GHOST_LO..GHOST_LO+cy)?;
+        let mz = monomials(Dir::Z, GHOST_LO..GHOST_LO+cz)?;
+
+        // the Hermite weights of the corners in powers of t, for supplied derivatives
+        let hermite = self.hermite().map(|derivatives| {
+            let corners = |dir: Dir, cells: std::ops::Range<usize>| -> Vec<[[[f64; 2]; 2]; 4]> {
+                cells.map(|i| std::array::from_fn(|p| kernel::hermite_corners(self.axis(dir), i, HERMITE_MONOMIALS.map(|h| h[p])))).collect()
+            };
+            (derivatives, corners(Dir::X, GHOST_LO..GHOST_LO+cx), corners(Dir::Y, GHOST_LO..GHOST_LO+cy), corners(Dir::Z, GHOST_LO..GHOST_LO+cz))
+        });
+
+        // the share of the trilinear part of a blend, whose polynomial is added to the cubic one
+        let linear = match self.scheme {
+            Type::Blend(alpha) => 1.0 - alpha,
+            _ => 0.0
+        };
+
+        let mut coefficients = Vec::with_capacity(cx*cy*cz);
+        for (ci, mx) in mx.iter().enumerate() {
+            for (cj, my) in my.iter().enumerate() {
+                for (ck, mz) in mz.iter().enumerate() {
+                    let cell = (ci+GHOST_LO, cj+GHOST_LO, ck+GHOST_LO);
+                    let mut c = [0.0; 64];
+                    for p in 0..4 {
+                        for q in 0..4 {
+                            for r in 0..4 {
+                                c[16*p + 4*q + r] = match &hermite {
+                                    Some((derivatives, hx, hy, hz)) => self.hermite_sum(derivatives, cell, &hx[ci][p], &hy[cj][q], &hz[ck][r]),
+                                    None if linear != 0.0 => {
+                                        let cubic = self.stencil_sum(&self.data, cell, &mx[p], &my[q], &mz[r]);
+                                        (1.0 - linear)*cubic + linear*self.stencil_sum(&self.data, cell, &LINEAR_MONOMIALS[p], &LINEAR_MONOMIALS[q], &LINEAR_MONOMIALS[r])
+                                    },
+                                    None => self.stencil_sum(&self.data, cell, &mx[p], &my[q], &mz[r])
+                                };
+                            }
+                        }
+                    }
+                    coefficients.push(c);
+                }
+            }
+        }
+
+        Ok(CompiledInterp3D { ip: self.with_samples(self.data.clone()), cells: (cy, cz), coefficients })
+    }
+}
+
+impl CompiledInterp3D {
+    /// Evaluates the polynomial of the cells found by [`Interp3D::locate()`]
+    fn evaluate(&self, ((i, tx), (j, ty), (k, tz)): ((usize, f64), (usize, f64), (usize, f64))) -> f64 {
+        let c = &self.coefficients[(i-GHOST_LO)*self.cells.0*self.cells.1 + (j-GHOST_LO)*self.cells.1 + (k-GHOST_LO)];
+        let mut sum = 0.0;
+        for p in (0..4).rev() {
+            let mut sum_y = 0.0;
+            for q in (0..4).rev() {
+                let r = 16*p + 4*q;
+                sum_y = sum_y*ty + ((c[r+3]*tz + c[r+2])*tz + c[r+1])*tz + c[r];
+            }
+            sum = sum*tx + sum_y;
+        }
+
+        sum
+    }
+
+    /// Interpolates at ``(x, y, z)``, see [`Interp3D::interpolate()`].
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the point is out of bounds with [`OutOfBounds::Error`](crate::OutOfBounds::Error).
+    pub fn interpolate(&self, x: f64, y: f64, z: f64) -> f64 {
+        let (x, y, z) = self.ip.to_grid(x, y, z);
+        match self.ip.bounded(x, y, z) {
+            Some((x, y, z)) => self.evaluate(self.ip.locate(x, y, z)),
+            None => f64::NAN
+        }
+    }
+
+    /// Same as [`Self::interpolate()`], but returns an error instead of panicking.
+    pub fn try_interpolate(&self, x: f64, y: f64, z: f64) -> Result<f64, Interp3DError> {
+        let (x, y, z) = self.ip.to_grid(x, y, z);
+        match self.ip.try_bounded(x, y, z)? {
+            Some((x, y, z)) => Ok(self.evaluate(self.ip.locate(x, y, z))),
+            None => Ok(f64::NAN)
+        }
+    }
+
+    /// Interpolates at ``(x, y, z)`` and checks the cell of the previous query first, see [`Interp3D::interpolate_cached()`].
+    pub fn interpolate_cached(&self, cache: &mut QueryCache, x: f64, y: f64, z: f64) -> f64 {
+        let (x, y, z) = self.ip.to_grid(x, y, z);
+        let Some((x, y, z)) = self.ip.bounded(x, y, z) else {
+            return f64::NAN;
+        };
+        let located = self.ip.locate_cached(cache, x, y, z);
+        self.evaluate(located)
+    }
+
+    /// Returns the interpolator this was compiled from
+    pub fn interpolator(&self) -> &Interp3D {
+        &self.ip
+    }
+}
@@ -0,0 +1,130 @@
+//! Contains the error type used throughout the crate.
+
+use crate::{Dir, Type};
+
+use std::fmt;
+
+/// Everything that can go wrong when setting up, querying or saving an [`Interp3D`](crate::Interp3D).
+#[derive(Debug)]
+pub enum Interp3DError {
+    /// Reading from or writing to a file failed
+    Io(std::io::Error),
+    /// A file could not be understood, ``line`` is where the problem was noticed
+    Parse { line: usize, message: String },
+    /// Less than the 2 required points along ``dir``
+    TooFewPoints { dir: Dir, n: usize },
+    /// The range along ``dir`` is empty, reversed or not finite, or it contains 0 with [`GridSpacing::Logarithmic`](crate::GridSpacing::Logarithmic)
+    InvalidRange { dir: Dir, min: f64, max: f64 },
+    /// The ``parameter`` of the exponential spacing along ``dir`` is not finite
+    InvalidSpacing { dir: Dir, parameter: f64 },
+    /// ``n``, ``min`` or ``max`` along ``dir`` don't match the nodes of a [`GridSpacing::Custom`](crate::GridSpacing::Custom) axis
+    CustomAxisMismatch { dir: Dir },
+    /// The node positions along ``dir`` are not finite and strictly increasing, ``index`` is the first offending (interior) node
+    NonMonotonicAxis { dir: Dir, index: usize },
+    /// The number of samples doesn't match the grid
+    DataLength { expected: usize, found: usize },
+    /// The interpolator holds no data yet
+    NotSetUp,
+    /// A binary file was written in a format ``version`` this build can't read, it only understands up to ``supported``
+    UnsupportedVersion { version: u8, supported: u8 },
+    /// A query point lies outside of the sampled ``range`` along ``axis`` (only with [`OutOfBounds::Error`](crate::OutOfBounds::Error))
+    OutOfBounds { axis: Dir, value: f64, range: (f64, f64) },
+    /// Two interpolators that are combined node by node have different node positions along ``dir``
+    GridMismatch { dir: Dir },
+    /// A line segment leaves the domain (only with [`OutOfBounds::Error`](crate::OutOfBounds::Error)), ``inside`` is the range of the parameter ``t`` (from 0 at the start to 1 at the end) that lies inside, ``None`` if the segment misses the domain completely
+    SegmentOutOfBounds { inside: Option<(f64, f64)> },
+    /// The operation isn't available for the interpolation ``scheme``
+    UnsupportedScheme { scheme: Type },
+    /// The interpolant doesn't reach ``target`` along ``dir`` (within the searched range and the tolerance), see [`Interp3D::invert_along()`](crate::Interp3D::invert_along())
+    NoSolution { dir: Dir, target: f64 },
+    /// The interpolant reaches the target at several ``roots`` along ``dir`` while a unique one was asked for, see [`Interp3D::invert_along_all()`](crate::Interp3D::invert_along_all())
+    MultipleSolutions { dir: Dir, roots: Vec<f64> },
+    /// The [`AxisTransform::Custom`](crate::AxisTransform::Custom) along ``dir`` can't be written to a file
+    UnsupportedTransform { dir: Dir },
+    /// The ``delimiter`` of [`ExportOptions`](crate::ExportOptions) isn't one the text format can be read with
+    UnsupportedDelimiter { delimiter: char },
+    /// A query time lies outside of the ``range`` covered by an [`Interp3DSeries`](crate::Interp3DSeries) (only with [`TimeBounds::Error`](crate::TimeBounds::Error))
+    TimeOutOfBounds { t: f64, range: (f64, f64) },
+    /// The times of the snapshots of an [`Interp3DSeries`](crate::Interp3DSeries) are not finite and strictly increasing, ``index`` is the first offending snapshot
+    NonMonotonicTimes { index: usize },
+    /// The ``tolerance`` for merging nodes of [`Interp3D::from_grid_and_data_lenient()`](crate::Interp3D::from_grid_and_data_lenient()) is negative or not finite
+    InvalidTolerance { tolerance: f64 },
+    /// The sample at the node ``(i, j, k)`` (as for [`Interp3D::value_at()`](crate::Interp3D::value_at())) is ``NaN`` or infinite, which [`Interp3D::quantize()`](crate::Interp3D::quantize()) can't represent
+    NonFiniteSample { i: usize, j: usize, k: usize },
+    /// The samples can't be taken as a probability density by [`Interp3D::sample_points()`](crate::Interp3D::sample_points()): the one at ``node`` (as for [`Interp3D::value_at()`](crate::Interp3D::value_at())) is negative or not finite, or, with ``None``, all of them are 0
+    InvalidDensity { node: Option<(usize, usize, usize)> }
+}
+
+impl fmt::Display for Interp3DError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Interp3DError::Io(e) => write!(f, "I/O error: {}", e),
+            Interp3DError::Parse { line, message } => write!(f, "Parse error in line {}: {}", line, message),
+            Interp3DError::TooFewPoints { dir, n } => write!(f, "Number of points along {:?} too low ({}, at least 2 required)", dir, n),
+            Interp3DError::InvalidRange { dir, min, max } => write!(f, "Invalid range along {:?} (min: {}, max: {})", dir, min, max),
+            Interp3DError::InvalidSpacing { dir, parameter } => write!(f, "Invalid spacing parameter along {:?} ({})", dir, parameter),
+            Interp3DError::CustomAxisMismatch { dir } => write!(f, "n, min and max along {:?} don't match the custom node positions", dir),
+            Interp3DError::NonMonotonicAxis { dir, index } => write!(f, "Grid points along {:?} are not finite and strictly increasing (first offending index: {})", dir, index),
+            Interp3DError::DataLength { expected, found } => write!(f, "Got {} samples, but the grid has {} nodes", found, expected),
+            Interp3DError::NotSetUp => write!(f, "Interpolator has not been set up"),
+            Interp3DError::UnsupportedVersion { version, supported } => write!(f, "File is format version {}, this build reads up to version {}", version, supported),
+            Interp3DError::OutOfBounds { axis, value, range } => write!(f, "Query {} along {:?} is outside of the sampled range [{}, {}]", value, axis, range.0, range.1),
+            Interp3DError::GridMismatch { dir } => write!(f, "The grids differ along {:?}", dir),
+            Interp3DError::SegmentOutOfBounds { inside: Some((t0, t1)) } => write!(f, "Line segment leaves the sampled domain, only the part {} <= t <= {} is inside", t0, t1),
+            Interp3DError::SegmentOutOfBounds { inside: None } => write!(f, "Line segment lies outside of the sampled domain"),
+            Interp3DError::UnsupportedScheme { scheme } => write!(f, "Not supported for the interpolation scheme {:?}", scheme),
+            Interp3DError::NoSolution { dir, target } => write!(f, "The interpolant doesn't reach {} along {:?}", target, dir),
+            Interp3DError::MultipleSolutions { dir, roots } => write!(f, "The interpolant reaches the target {} times along {:?} (at {:?})", roots.len(), dir, roots),
+            Interp3DError::UnsupportedTransform { dir } => write!(f, "The custom transform along {:?} can't be written to a file", dir),
+            Interp3DError::UnsupportedDelimiter { delimiter } => write!(f, "The delimiter {:?} can't be read back, use one of ' ', '\\t', ',' and ';'", delimiter),
+            Interp3DError::TimeOutOfBounds { t, range } => write!(f, "Query time {} is outside of the covered range [{}, {}]", t, range.0, range.1),
+            Interp3DError::NonMonotonicTimes { index } => write!(f, "Snapshot times are not finite and strictly increasing (first offending index: {})", index),
+            Interp3DError::InvalidTolerance { tolerance } => write!(f, "Invalid merge tolerance {} (needs to be non-negative and finite)", tolerance),
+            Interp3DError::NonFiniteSample { i, j, k } => write!(f, "The sample at the node ({}, {}, {}) is not finite and can't be quantized", i, j, k),
+            Interp3DError::InvalidDensity { node: Some((i, j, k)) } => write!(f, "The sample at the node ({}, {}, {}) is negative or not finite, which isn't a density", i, j, k),
+            Interp3DError::InvalidDensity { node: None } => write!(f, "All samples are 0, which isn't a density")
+        }
+    }
+}
+
+impl std::error::Error for Interp3DError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Interp3DError::Io(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<std::io::Error> for Interp3DError {
+    fn from(e: std::io::Error) -> Self {
+        Interp3DError::Io(e)
+    }
+}
+
+/// Error of the data generation with a closure that can fail, see [`Interp3D::generate_data_fallible()`](crate::Interp3D::generate_data_fallible()).
+#[derive(Debug)]
+pub enum GenerateError<E> {
+    /// The config is invalid, the closure has not been called
+    Config(Interp3DError),
+    /// The closure returned ``error`` at ``(x, y, z)``
+    Closure { x: f64, y: f64, z: f64, error: E }
+}
+
+impl<E: fmt::Display> fmt::Display for GenerateError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerateError::Config(e) => e.fmt(f),
+            GenerateError::Closure { x, y, z, error } => write!(f, "Data generation failed at ({}, {}, {}): {}", x, y, z, error)
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for GenerateError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GenerateError::Config(e) => Some(e),
+            GenerateError::Closure { error, .. } => Some(error)
+        }
+    }
+}
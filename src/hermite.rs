@@ -0,0 +1,151 @@
+//! Contains the data generation from a closure that also supplies the gradient, which the tricubic interpolation then uses instead of estimating the slopes from the neighbouring nodes.
+
+use crate::{Interp3D, Interp3DError, DataGenConf, Type, Dir, GHOST_LO};
+use crate::kernel;
+
+/// Orders of the derivatives stored per node along ``[x, y, z]``: ``fx, fy, fz, fxy, fxz, fyz, fxyz``
+pub(crate) const ORDERS: [[usize; 3]; 7] = [[1, 0, 0], [0, 1, 0], [0, 0, 1], [1, 1, 0], [1, 0, 1], [0, 1, 1], [1, 1, 1]];
+
+/// Nodes and weights of the slope at the node ``m`` of ``v`` from the parabola through it and its two neighbours (one-sided at the ends, the secant if there are only 2 nodes)
+fn slope_weights(v: &[f64], m: usize) -> [(usize, f64); 3] {
+    let n = v.len();
+    if n == 2 {
+        let h = v[1] - v[0];
+        return [(0, -1.0/h), (1, 1.0/h), (1, 0.0)];
+    }
+
+    let first = m.clamp(1, n-2) - 1;
+    let (h0, h1) = (v[first+1] - v[first], v[first+2] - v[first+1]);
+    let w = if m == 0 {
+        [-(2.0*h0 + h1)/(h0*(h0 + h1)), (h0 + h1)/(h0*h1), -h0/(h1*(h0 + h1))]
+    } else if m == n-1 {
+        [h1/(h0*(h0 + h1)), -(h0 + h1)/(h0*h1), (h0 + 2.0*h1)/(h1*(h0 + h1))]
+    } else {
+        [-h1/(h0*(h0 + h1)), (h1 - h0)/(h0*h1), h0/(h1*(h0 + h1))]
+    };
+
+    [(first, w[0]), (first+1, w[1]), (first+2, w[2])]
+}
+
+impl Interp3D {
+    /// Generates the data with a closure ``f`` that returns the value and the gradient ``[df/dx, df/dy, df/dz]`` at each node. The tricubic interpolation then uses the supplied slopes instead of estimating them from the neighbouring nodes, which is noticeably more accurate (e.g. all cubics along an axis are reproduced, not only the quadratics).  
+    /// The mixed derivatives ``fxy, fxz, fyz`` and ``fxyz`` that a tricubic Hermite cell needs as well are estimated from differences of the supplied gradients along the grid. Besides the samples, 7 ``f64`` are stored per node for the derivatives (regardless of [`Storage`](crate::Storage)), and [`Self::export_data()`] and [`Self::export_data_binary()`] write them along.
+    /// 
+    /// The derivatives are only used by [`Type::Tricubic`], the other schemes work on the samples alone. Changing the samples afterwards (e.g. with [`Self::set_value()`] or [`Self::map_data()`]) drops the derivatives, after that the slopes are estimated again like for data without them. Only [`Self::add_assign_field()`], [`Self::sub_assign_field()`] and [`Self::blend()`] keep them, if both interpolators have derivatives.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 3.0, 8).build();
+    /// let mut ip = Interp3D::default();
+    /// ip.generate_data_hermite(|x, y, z| (x.sin()*z, [x.cos()*z, 0.0, x.sin()]), &config);
+    /// assert!(ip.has_hermite_data());
+    /// 
+    /// let plain = Interp3D::from_config(|x, _, z| x.sin()*z, &config);
+    /// let error = |ip: &Interp3D| (ip.interpolate(0.2, 1.0, 1.0) - 0.2f64.sin()).abs();
+    /// assert!(error(&ip) < 0.1*error(&plain));
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the config is invalid, see [`Self::try_generate_data_hermite()`] for a version that returns the error instead.
+    pub fn generate_data_hermite<F>(&mut self, f: F, conf: &DataGenConf)
+    where F: FnMut(f64, f64, f64) -> (f64, [f64; 3]) {
+        if let Err(e) = self.try_generate_data_hermite(f, conf) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Same as [`Self::generate_data_hermite()`], but an invalid config is returned as an error instead of panicking. In that case the closure is never called and the interpolator is left unchanged.
+    pub fn try_generate_data_hermite<F>(&mut self, mut f: F, conf: &DataGenConf) -> Result<(), Interp3DError>
+    where F: FnMut(f64, f64, f64) -> (f64, [f64; 3]) {
+        self.setup(conf)?;
+
+        let mut gradients = Vec::with_capacity(self.iter().len());
+        let mut data = std::mem::take(&mut self.data);
+        let mut samples = data.writer();
+        for (i, j, k) in self.interior() {
+            let (x, y, z) = self.physical(i, j, k);
+            let (value, mut gradient) = f(x, y, z);
+            // the slopes with respect to the coordinates of the grid
+            for (d, (g, p)) in gradient.iter_mut().zip([x, y, z]).enumerate() {
+                *g /= self.transform[d].derivatives(p).0;
+            }
+            samples.set(self.index(i, j, k), value);
+            gradients.push(gradient);
+        }
+        self.data = data;
+        self.set_data_outermost();
+
+        let component = |c: usize| -> Vec<f64> { gradients.iter().map(|g| g[c]).collect() };
+        let (fx, fy, fz) = (component(0), component(1), component(2));
+        let mean = |a: Vec<f64>, b: Vec<f64>| -> Vec<f64> { a.iter().zip(&b).map(|(a, b)| 0.5*(a + b)).collect() };
+        let fxy = mean(self.differentiate(&fx, Dir::Y), self.differentiate(&fy, Dir::X));
+        let fxz = mean(self.differentiate(&fx, Dir::Z), self.differentiate(&fz, Dir::X));
+        let fyz = mean(self.differentiate(&fy, Dir::Z), self.differentiate(&fz, Dir::Y));
+        let fxyz: Vec<f64> = self.differentiate(&fxy, Dir::Z).iter()
+            .zip(self.differentiate(&fxz, Dir::Y))
+            .zip(self.differentiate(&fyz, Dir::X))
+            .map(|((a, b), c)| (a + b + c)/3.0)
+            .collect();
+
+        let fields = [fx, fy, fz, fxy, fxz, fyz, fxyz];
+        self.hermite = Some((0..fields[0].len()).map(|n| fields.each_ref().map(|field| field[n])).collect());
+        Ok(())
+    }
+
+    /// Returns whether the interpolator holds derivatives supplied by [`Self::generate_data_hermite()`] (or read from a file written with them).
+    pub fn has_hermite_data(&self) -> bool {
+        self.hermite.is_some()
+    }
+
+    /// Slopes along ``dir`` of a field given on the interior nodes in storage order, see [`slope_weights()`]
+    fn differentiate(&self, field: &[f64], dir: Dir) -> Vec<f64> {
+        let (nx, ny, nz) = self.dims();
+        let v = &self.axis(dir)[GHOST_LO..GHOST_LO + [nx, ny, nz][dir as usize]];
+        let stride = [ny*nz, nz, 1][dir as usize];
+
+        (0..field.len()).map(|n| {
+            let m = [n/(ny*nz), n/nz % ny, n % nz][dir as usize];
+            slope_weights(v, m).iter()
+                .map(|&(node, w)| w*field[n + node*stride - m*stride])
+                .sum()
+        }).collect()
+    }
+
+    /// The supplied derivatives if they are used for the interpolation, i.e. with [`Type::Tricubic`]
+    pub(crate) fn hermite(&self) -> Option<&[[f64; 7]]> {
+        match self.scheme {
+            Type::Tricubic => self.hermite.as_deref(),
+            _ => None
+        }
+    }
+
+    /// Sums up the 8 corners of ``cell`` with the Hermite weights ``[[value, value], [slope, slope]]`` along each direction (see [`kernel::hermite_corners()`])
+    pub(crate) fn hermite_sum(&self, derivatives: &[[f64; 7]], (i, j, k): (usize, usize, usize), wx: &[[f64; 2]; 2], wy: &[[f64; 2]; 2], wz: &[[f64; 2]; 2]) -> f64 {
+        let (_, ny, nz) = self.dims();
+        let mut sum = 0.0;
+        for corner in 0..8 {
+            let (a, b, c) = (corner >> 2, (corner >> 1) & 1, corner & 1);
+            let (ci, cj, ck) = (i+a, j+b, k+c);
+            let node = &derivatives[(ci-GHOST_LO)*ny*nz + (cj-GHOST_LO)*nz + (ck-GHOST_LO)];
+            sum += wx[0][a]*wy[0][b]*wz[0][c]*self.sample(&self.data, self.index(ci, cj, ck));
+            for (&[p, q, r], d) in ORDERS.iter().zip(node) {
+                sum += wx[p][a]*wy[q][b]*wz[r][c]*d;
+            }
+        }
+
+        sum
+    }
+
+    /// The derivative of the Hermite interpolant of the orders ``[p, q, r]`` (up to 2 each) along ``[x, y, z]`` inside the cells found by [`Self::locate()`]
+    pub(crate) fn hermite_derivative(&self, derivatives: &[[f64; 7]], ((i, tx), (j, ty), (k, tz)): ((usize, f64), (usize, f64), (usize, f64)), [p, q, r]: [usize; 3]) -> f64 {
+        let wx = kernel::hermite(&self.x, i, tx);
+        let wy = kernel::hermite(&self.y, j, ty);
+        let wz = kernel::hermite(&self.z, k, tz);
+
+        self.hermite_sum(derivatives, (i, j, k), &wx[p], &wy[q], &wz[r])
+    }
+}
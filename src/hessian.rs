@@ -0,0 +1,134 @@
+//! Contains the evaluation of the second derivatives of the interpolant.
+
+use crate::{Interp3D, Type, Dir};
+use crate::kernel::{self, Kernel};
+
+impl Interp3D {
+    /// Interpolates the sampled data at ``(x, y, z)`` and returns the value, the gradient and the Hessian ``h[a][b] = d^2f/(da db)`` of the interpolant, e.g. for a Newton solver.  
+    /// Like for [`Self::interpolate_gradient()`] these are the exact derivatives of the piecewise polynomial, so the value and the gradient are the same as the ones of [`Self::interpolate_gradient()`]. The Hessian is symmetric by construction, every mixed partial is only computed once.
+    /// 
+    /// The interpolant is only C1, so unlike the gradient the second derivatives are generally discontinuous across cell faces. Right on a face they are the ones of the upper cell (the one [`Self::interpolate()`] evaluates there). The second derivative along a direction with linear or nearest interpolation is 0, with [`OutOfBounds::ClampToEdge`](crate::OutOfBounds::ClampToEdge) all derivatives along the clamped directions are 0.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+    /// let ip = Interp3D::from_config(|x, y, z| x*x*y + y*z*z, &config);
+    /// 
+    /// let (_, _, h) = ip.interpolate_hessian(0.45, 0.55, 0.35);
+    /// assert!((h[0][0] - 2.0*0.55).abs() < 1e-10);
+    /// assert!((h[0][1] - 2.0*0.45).abs() < 1e-10);
+    /// assert_eq!(h[0][1], h[1][0]);
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet or if the point is out of bounds with [`OutOfBounds::Error`](crate::OutOfBounds::Error).
+    pub fn interpolate_hessian(&self, x: f64, y: f64, z: f64) -> (f64, [f64; 3], [[f64; 3]; 3]) {
+        let physical = (x, y, z);
+        let Some(((x, y, z), clamped)) = self.bounded_derivatives(x, y, z) else {
+            return (f64::NAN, [f64::NAN; 3], [[f64::NAN; 3]; 3]);
+        };
+
+        let ((i, tx), (j, ty), (k, tz)) = self.locate(x, y, z);
+        let (value, mut gradient, mut hessian) = if let Some(derivatives) = self.hermite() {
+            let d = |order: [usize; 3]| self.hermite_derivative(derivatives, ((i, tx), (j, ty), (k, tz)), order);
+            let (xy, xz, yz) = (d([1, 1, 0]), d([1, 0, 1]), d([0, 1, 1]));
+            (
+                d([0, 0, 0]),
+                [d([1, 0, 0]), d([0, 1, 0]), d([0, 0, 1])],
+                [[d([2, 0, 0]), xy, xz], [xy, d([0, 2, 0]), yz], [xz, yz, d([0, 0, 2])]]
+            )
+        } else if self.scheme == Type::TricubicMonotone {
+            self.monotone_hessian((i, j, k), tx, ty, tz)
+        } else {
+            // the weights of the value, the first and the second derivative along each direction
+            let [wx, wy, wz] = [(Dir::X, i, tx), (Dir::Y, j, ty), (Dir::Z, k, tz)].map(|(dir, i, t)| {
+                [self.weights(dir, i, t), self.weights_derivative(dir, i, t), self.weights_second(dir, i, t)]
+            });
+            // the sum with the derivatives of the given orders along each direction
+            let sum = |a: usize, b: usize, c: usize| self.stencil_sum(&self.data, (i, j, k), &wx[a], &wy[b], &wz[c]);
+
+            let (xy, xz, yz) = (sum(1, 1, 0), sum(1, 0, 1), sum(0, 1, 1));
+            let (mut value, mut gradient, mut hessian) = (
+                sum(0, 0, 0),
+                [sum(1, 0, 0), sum(0, 1, 0), sum(0, 0, 1)],
+                [[sum(2, 0, 0), xy, xz], [xy, sum(0, 2, 0), yz], [xz, yz, sum(0, 0, 2)]]
+            );
+
+            // the trilinear part has no second derivatives along a single direction, only the mixed ones
+            if let Some((share, [lx, ly, lz])) = self.blend_linear((i, j, k), tx, ty, tz) {
+                let linear = |a: usize, b: usize, c: usize| self.stencil_sum(&self.data, (i, j, k), &lx[a], &ly[b], &lz[c]);
+                let (xy, xz, yz) = (linear(1, 1, 0), linear(1, 0, 1), linear(0, 1, 1));
+                let blend = |cubic: &mut f64, linear: f64| *cubic = (1.0 - share)*(*cubic) + share*linear;
+
+                blend(&mut value, linear(0, 0, 0));
+                for (d, linear) in gradient.iter_mut().zip([linear(1, 0, 0), linear(0, 1, 0), linear(0, 0, 1)]) {
+                    blend(d, linear);
+                }
+                for (d, linear) in hessian.iter_mut().flatten().zip([0.0, xy, xz, xy, 0.0, yz, xz, yz, 0.0]) {
+                    blend(d, linear);
+                }
+            }
+            (value, gradient, hessian)
+        };
+
+        for (d, clamped) in clamped.into_iter().enumerate() {
+            if clamped {
+                gradient[d] = 0.0;
+                hessian[d] = [0.0; 3];
+                for row in hessian.iter_mut() {
+                    row[d] = 0.0;
+                }
+            }
+        }
+        self.to_physical_derivatives(physical, &mut gradient, Some(&mut hessian));
+
+        (value, gradient, hessian)
+    }
+
+    /// Second derivative of [`Self::weights()`] with respect to the coordinate along ``dir``
+    fn weights_second(&self, dir: Dir, i: usize, t: f64) -> [f64; 4] {
+        match self.kernel(dir) {
+            Kernel::Cubic => kernel::cubic_second(self.axis(dir), i, t),
+            Kernel::Linear | Kernel::Nearest => [0.0; 4]
+        }
+    }
+
+    /// Same as [`Self::monotone()`], but carries the second derivatives along as well
+    fn monotone_hessian(&self, (i, j, k): (usize, usize, usize), tx: f64, ty: f64, tz: f64) -> (f64, [f64; 3], [[f64; 3]; 3]) {
+        let dot = |w: &[f64; 4], f: &[f64; 4]| w.iter().zip(f).map(|(w, f)| w*f).sum::<f64>();
+
+        // f, d/dy, d^2/dy^2, d/dz, d^2/dydz and d^2/dz^2 of every line along X
+        let lines: [[f64; 6]; 4] = std::array::from_fn(|a| {
+            // f, d/dz and d^2/dz^2 of every column along Z
+            let columns: [[f64; 3]; 4] = std::array::from_fn(|b| {
+                let f = self.column(&self.data, self.index(i-1+a, j-1+b, k-1));
+                [
+                    dot(&kernel::monotone(&self.z, k, tz, &f), &f),
+                    dot(&kernel::monotone_derivative(&self.z, k, tz, &f), &f),
+                    dot(&kernel::monotone_second(&self.z, k, tz, &f), &f)
+                ]
+            });
+            let [values, d_dz, d_dzz] = [0, 1, 2].map(|n| columns.map(|c| c[n]));
+
+            let wy = kernel::monotone(&self.y, j, ty, &values);
+            let dy = kernel::monotone_derivative(&self.y, j, ty, &values);
+            let sy = kernel::monotone_second(&self.y, j, ty, &values);
+            [dot(&wy, &values), dot(&dy, &values), dot(&sy, &values), dot(&wy, &d_dz), dot(&dy, &d_dz), dot(&wy, &d_dzz)]
+        });
+        let [f, fy, fyy, fz, fyz, fzz] = [0, 1, 2, 3, 4, 5].map(|n| lines.map(|l| l[n]));
+
+        let wx = kernel::monotone(&self.x, i, tx, &f);
+        let dx = kernel::monotone_derivative(&self.x, i, tx, &f);
+        let sx = kernel::monotone_second(&self.x, i, tx, &f);
+        let (xy, xz, yz) = (dot(&dx, &fy), dot(&dx, &fz), dot(&wx, &fyz));
+        (
+            dot(&wx, &f),
+            [dot(&dx, &f), dot(&wx, &fy), dot(&wx, &fz)],
+            [[dot(&sx, &f), xy, xz], [xy, dot(&wx, &fyy), yz], [xz, yz, dot(&wx, &fzz)]]
+        )
+    }
+}
@@ -0,0 +1,51 @@
+//! Contains the indexing of the interpolator by node indices, i.e. ``ip[(i, j, k)]``.
+
+use crate::Interp3D;
+
+use std::ops::{Index, IndexMut};
+
+/// Reads the sample at the node ``(i, j, k)``, same as [`Interp3D::value_at()`].
+/// 
+/// # Panics
+/// 
+/// Panics if any index is out of range or if the samples are stored as [`Storage::F32`](crate::Storage::F32), which has no ``f64`` to reference (use [`Interp3D::value_at()`] then).
+impl Index<(usize, usize, usize)> for Interp3D {
+    type Output = f64;
+
+    fn index(&self, node: (usize, usize, usize)) -> &f64 {
+        let index = self.node_index(node);
+        self.data.get_ref(index).expect("Samples stored as f32 can't be indexed, use value_at() instead")
+    }
+}
+
+/// Gives write access to the sample at the node ``(i, j, k)``, like [`Interp3D::set_value()`] but in place.  
+/// Like any change of the samples, this drops supplied derivatives (see [`Interp3D::generate_data_hermite()`]).  
+/// The ghost cells can't be updated during the write, so they are marked as stale instead: until the next method that refreshes them (e.g. [`Interp3D::set_boundary_condition()`] or [`Interp3D::map_data()`]), queries compute the ghost values from the interior nodes on the fly. The results are the same, the queries close to the boundary are just a bit slower until then.
+/// 
+/// # Example
+/// 
+/// ```
+/// use crate::interp3d::*;
+/// 
+/// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 5).build();
+/// let mut ip = Interp3D::from_config(|x, y, z| x + y + z, &config);
+/// 
+/// ip[(0, 0, 0)] = 1.0;
+/// ip[(4, 4, 4)] += 1.0;
+/// 
+/// assert_eq!(ip[(0, 0, 0)], 1.0);
+/// assert_eq!(ip.value_at(4, 4, 4), 4.0);
+/// assert_eq!(ip.interpolate(0.0, 0.0, 0.0), 1.0);
+/// ```
+/// 
+/// # Panics
+/// 
+/// Panics if any index is out of range or if the samples are stored as [`Storage::F32`](crate::Storage::F32) (use [`Interp3D::set_value()`] then).
+impl IndexMut<(usize, usize, usize)> for Interp3D {
+    fn index_mut(&mut self, node: (usize, usize, usize)) -> &mut f64 {
+        let index = self.node_index(node);
+        self.stale_ghosts = true;
+        self.hermite = None;
+        self.data.get_mut(index).expect("Samples stored as f32 can't be indexed, use set_value() instead")
+    }
+}
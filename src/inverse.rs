@@ -0,0 +1,162 @@
+//! Contains the inverse lookup, i.e. solving ``f = target`` along one direction with the other two coordinates fixed.
+
+use crate::{Interp3D, Interp3DError, Dir};
+
+/// Number of parts every interval between two nodes is split into when scanning for sign changes
+const SUBDIVISIONS: usize = 4;
+/// Upper limit of iterations of [`solve()`], far beyond what any bracket of ``f64`` needs
+const MAX_ITERATIONS: usize = 500;
+
+/// Finds a point in ``[a, b]`` with ``|g| <= tol``, given that ``g`` changes its sign between ``a`` and ``b``.  
+/// This is regula falsi with the Illinois modification, falling back to bisection whenever the secant leaves the bracket, so it converges superlinearly on smooth functions and never leaves the bracket. Returns ``None`` if the bracket shrinks down to a jump of ``g`` (e.g. with [`Type::Nearest`](crate::Type::Nearest)) or ``g`` turns ``NaN``.
+fn solve<G>(mut g: G, (mut a, mut ga): (f64, f64), (mut b, mut gb): (f64, f64), tol: f64) -> Result<Option<f64>, Interp3DError>
+where G: FnMut(f64) -> Result<f64, Interp3DError> {
+    // which end was kept the last time: -1 for a, 1 for b
+    let mut kept = 0;
+    for _ in 0..MAX_ITERATIONS {
+        let mut c = (a*gb - b*ga)/(gb - ga);
+        if !(c > a && c < b) {
+            c = 0.5*(a + b);
+            if c <= a || c >= b {
+                return Ok(None);
+            }
+        }
+
+        let gc = g(c)?;
+        if gc.abs() <= tol {
+            return Ok(Some(c));
+        }
+        if gc.is_nan() {
+            return Ok(None);
+        }
+        if (gc > 0.0) == (gb > 0.0) {
+            (b, gb) = (c, gc);
+            if kept == -1 {
+                ga *= 0.5;
+            }
+            kept = -1;
+        } else {
+            (a, ga) = (c, gc);
+            if kept == 1 {
+                gb *= 0.5;
+            }
+            kept = 1;
+        }
+    }
+
+    Ok(None)
+}
+
+impl Interp3D {
+    /// ``interpolate() - target`` along ``dir`` at the position ``p``, with the other two coordinates ``fixed`` (in the order X, Y, Z)
+    fn offset_along(&self, dir: Dir, p: f64, fixed: (f64, f64), target: f64) -> Result<f64, Interp3DError> {
+        let (x, y, z) = match dir {
+            Dir::X => (p, fixed.0, fixed.1),
+            Dir::Y => (fixed.0, p, fixed.1),
+            Dir::Z => (fixed.0, fixed.1, p)
+        };
+
+        Ok(self.try_interpolate_grid_coords(x, y, z)? - target)
+    }
+
+    /// Finds the coordinate along ``dir`` where the interpolant reaches ``target``, with the other two coordinates ``fixed`` (in the order X, Y, Z, e.g. ``(x, z)`` for [`Dir::Y`]). E.g. the density that gives a pressure at a fixed temperature and composition from a table of the pressure.  
+    /// The result satisfies ``|f - target| <= tol`` for the interpolated ``f``. The root is found on the interpolant itself with regula falsi (Illinois) and bisection, so it converges quickly and never leaves the bracket.
+    /// 
+    /// With a ``bracket``, the interpolant needs to reach ``target`` at one of its ends or cross it in between (if it crosses several times, one of the solutions is returned). Without, the whole domain along ``dir`` is searched like in [`Self::invert_along_all()`], and a unique solution is required: [`Interp3DError::MultipleSolutions`] is returned if the interpolant isn't monotone and reaches ``target`` several times.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// // pressure as a function of density, temperature and composition
+    /// let config = DataGenConfBuilder::default().uniform_cube(1.0, 2.0, 11).build();
+    /// let pressure = Interp3D::from_config(|rho, t, c| rho*rho*t*(1.0 + c), &config);
+    /// 
+    /// let rho = pressure.invert_along(Dir::X, 6.0, (1.5, 1.0), None, 1e-12).unwrap();
+    /// assert!((pressure.interpolate(rho, 1.5, 1.0) - 6.0).abs() <= 1e-12);
+    /// assert!((rho - 2f64.sqrt()).abs() < 1e-4);
+    /// 
+    /// assert!(pressure.invert_along(Dir::X, 100.0, (1.5, 1.0), None, 1e-12).is_err());
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// [`Interp3DError::NoSolution`] if the target isn't reached (or the bracket doesn't enclose it), [`Interp3DError::MultipleSolutions`] as described above and the errors of [`Self::try_interpolate()`], e.g. for fixed coordinates outside of the domain with [`OutOfBounds::Error`](crate::OutOfBounds::Error).
+    pub fn invert_along(&self, dir: Dir, target: f64, fixed: (f64, f64), bracket: Option<(f64, f64)>, tol: f64) -> Result<f64, Interp3DError> {
+        let Some((a, b)) = bracket else {
+            let mut roots = self.invert_along_all(dir, target, fixed, tol)?;
+            return match roots.len() {
+                0 => Err(Interp3DError::NoSolution { dir, target }),
+                1 => Ok(roots.remove(0)),
+                _ => Err(Interp3DError::MultipleSolutions { dir, roots })
+            };
+        };
+
+        let (a, b) = (a.min(b), a.max(b));
+        let g = |p: f64| self.offset_along(dir, p, fixed, target);
+        let (ga, gb) = (g(a)?, g(b)?);
+        if ga.abs() <= tol {
+            return Ok(a);
+        }
+        if gb.abs() <= tol {
+            return Ok(b);
+        }
+        if ga*gb >= 0.0 || ga.is_nan() || gb.is_nan() {
+            return Err(Interp3DError::NoSolution { dir, target });
+        }
+
+        solve(g, (a, ga), (b, gb), tol)?.ok_or(Interp3DError::NoSolution { dir, target })
+    }
+
+    /// Finds all coordinates along ``dir`` inside of the domain where the interpolant reaches ``target``, with the other two coordinates ``fixed`` (see [`Self::invert_along()`]), in increasing order. An empty result means that ``target`` isn't reached.  
+    /// Every interval between two nodes is scanned at 4 equidistant points for sign changes of ``f - target``, which are then solved to ``|f - target| <= tol``. Two solutions closer together than a quarter of an interval (where the interpolant just touches the target or crosses it back and forth) can be missed.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(-2.0, 2.0, 21).build();
+    /// let ip = Interp3D::from_config(|x, y, z| x*x + y + z, &config);
+    /// 
+    /// let roots = ip.invert_along_all(Dir::X, 1.0, (0.0, 0.0), 1e-12).unwrap();
+    /// assert_eq!(roots.len(), 2);
+    /// assert!((roots[0] + 1.0).abs() < 1e-10 && (roots[1] - 1.0).abs() < 1e-10);
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// The errors of [`Self::try_interpolate()`], see [`Self::invert_along()`].
+    pub fn invert_along_all(&self, dir: Dir, target: f64, fixed: (f64, f64), tol: f64) -> Result<Vec<f64>, Interp3DError> {
+        if self.data.is_empty() {
+            return Err(Interp3DError::NotSetUp);
+        }
+
+        let g = |p: f64| self.offset_along(dir, p, fixed, target);
+        let nodes = Self::interior_axis(self.axis(dir));
+        let mut samples = Vec::with_capacity(SUBDIVISIONS*nodes.len());
+        for w in nodes.windows(2) {
+            samples.extend((0..SUBDIVISIONS).map(|s| w[0] + (w[1] - w[0])*s as f64/SUBDIVISIONS as f64));
+        }
+        samples.push(nodes[nodes.len()-1]);
+
+        let mut roots = Vec::new();
+        let mut previous = (samples[0], g(samples[0])?);
+        for &p in &samples[1..] {
+            let (a, ga) = previous;
+            let gb = g(p)?;
+            if ga == 0.0 {
+                roots.push(a);
+            } else if ga*gb < 0.0 {
+                roots.extend(solve(g, (a, ga), (p, gb), tol)?);
+            }
+            previous = (p, gb);
+        }
+        if previous.1 == 0.0 {
+            roots.push(previous.0);
+        }
+
+        Ok(roots)
+    }
+}
@@ -0,0 +1,1388 @@
+//! Contains reading and writing of data sets from and to files.
+
+use crate::{Interp3D, Interp3DVec, Interp3DSeries, CompactInterp3D, Interp3DError, ErrorReport, QuantizeSpec, DataGenConf, DataGenConfSingle, GridSpacing, AxisTransform, Dir, Storage, ExportOptions, FloatFormat, LineEnding, FileFormat, GHOST, GHOST_LO, GHOST_HI};
+use crate::samples::{Samples, Quantized};
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Start of every binary data file
+const MAGIC: &[u8; 8] = b"IP3DBIN\0";
+/// Start of a binary data file that is still being written by [`Interp3D::generate_data_to_file()`], replaced by [`MAGIC`] once it is complete
+const MAGIC_INCOMPLETE: &[u8; 8] = b"IP3DPAR\0";
+/// Latest version of the binary format. Version 2 added the storage byte, version 3 the flags byte (for the derivatives of [`Interp3D::generate_data_hermite()`], the axis transforms, the config and the checksum). All older versions can still be read.
+const VERSION: u8 = 3;
+/// Start of gzip compressed files, which can't be read (yet)
+const GZIP_MAGIC: &[u8; 2] = b"\x1f\x8b";
+/// Start of zip archives, i.e. of npz files
+const ZIP_MAGIC: &[u8; 2] = b"PK";
+/// Start of every file of an [`Interp3DSeries`]
+const SERIES_MAGIC: &[u8; 8] = b"IP3DSER\0";
+/// Latest version of the series format
+const SERIES_VERSION: u8 = 1;
+/// Start of every file of a [`CompactInterp3D`]
+const COMPACT_MAGIC: &[u8; 8] = b"IP3DQNT\0";
+/// Latest version of the compact format
+const COMPACT_VERSION: u8 = 1;
+/// Flag of binary files that hold the supplied derivatives after the samples
+const FLAG_HERMITE: u8 = 1;
+/// Flag of binary files that hold the axis transforms, one byte per axis after the flags byte
+const FLAG_TRANSFORM: u8 = 2;
+/// The axis transforms that can be written to a file, by their name in the text format. The index is their byte in the binary format.
+pub(crate) const TRANSFORMS: [(&str, AxisTransform); 3] = [("identity", AxisTransform::Identity), ("log10", AxisTransform::Log10), ("ln", AxisTransform::Ln)];
+/// Flag of binary files that hold the config the grid was generated with, [`CONFIG_AXIS_LEN`] bytes per axis after the transforms
+const FLAG_CONFIG: u8 = 4;
+/// Flag of binary files that end with the CRC-32 of everything after the magic bytes, as ``u32``
+const FLAG_CHECKSUM: u8 = 8;
+/// Bytes of the config of one axis in the binary format: ``n`` as ``u64``, ``min`` and ``max`` as ``f64``, the spacing byte (see [`SPACINGS`]) and its parameter as ``f64`` (0 if it has none)
+const CONFIG_AXIS_LEN: usize = 8 + 8 + 8 + 1 + 8;
+/// The grid spacings by their name in the text format. The index is their byte in the binary format.
+const SPACINGS: [&str; 6] = ["linear", "exponential", "two-sided-exponential", "logarithmic", "chebyshev", "custom"];
+/// The characters that separate the numbers of a text file besides any whitespace
+const DELIMITERS: [char; 2] = [',', ';'];
+
+/// The index of ``spacing`` in [`SPACINGS`] and its parameter, ``None`` if it has none
+fn spacing_code(spacing: &GridSpacing) -> (u8, Option<f64>) {
+    match *spacing {
+        GridSpacing::Linear => (0, None),
+        GridSpacing::Exponential(k) => (1, Some(k)),
+        GridSpacing::TwoSidedExponential(k) => (2, Some(k)),
+        GridSpacing::Logarithmic => (3, None),
+        GridSpacing::Chebyshev => (4, None),
+        GridSpacing::Custom(_) => (5, None)
+    }
+}
+
+/// The inverse of [`spacing_code()`], where ``parameter`` gives the parameter if the spacing has one. ``Custom`` comes without its nodes, those are the node positions of the file (see [`complete_config()`]).
+fn spacing_from_code<F>(code: u8, parameter: F) -> Result<GridSpacing, Interp3DError>
+where F: FnOnce() -> Result<f64, Interp3DError> {
+    Ok(match code {
+        0 => GridSpacing::Linear,
+        1 => GridSpacing::Exponential(parameter()?),
+        2 => GridSpacing::TwoSidedExponential(parameter()?),
+        3 => GridSpacing::Logarithmic,
+        4 => GridSpacing::Chebyshev,
+        5 => GridSpacing::Custom(Vec::new()),
+        _ => return Err(Interp3DError::Parse { line: 0, message: format!("unknown grid spacing {} in binary file", code) })
+    })
+}
+
+/// Decodes the config section of a binary file, see [`CONFIG_AXIS_LEN`]
+fn read_config(bytes: &[u8; 3*CONFIG_AXIS_LEN]) -> Result<DataGenConf, Interp3DError> {
+    let axis = |b: &[u8]| -> Result<DataGenConfSingle, Interp3DError> {
+        let f64_at = |at: usize| f64::from_le_bytes(b[at..at+8].try_into().unwrap());
+        let spacing = spacing_from_code(b[24], || Ok(f64_at(25)))?;
+        Ok(DataGenConfSingle { n: u64::from_le_bytes(b[..8].try_into().unwrap()) as usize, min: f64_at(8), max: f64_at(16), spacing })
+    };
+    let mut axes = bytes.chunks_exact(CONFIG_AXIS_LEN);
+    let mut next = || axis(axes.next().unwrap());
+
+    Ok(DataGenConf { x: next()?, y: next()?, z: next()? })
+}
+
+/// Encodes ``conf`` as the config section of a binary file, see [`CONFIG_AXIS_LEN`]
+fn write_config<W: Write>(w: &mut W, conf: &DataGenConf) -> Result<(), Interp3DError> {
+    for single in [&conf.x, &conf.y, &conf.z] {
+        let (code, parameter) = spacing_code(&single.spacing);
+        w.write_all(&(single.n as u64).to_le_bytes())?;
+        w.write_all(&single.min.to_le_bytes())?;
+        w.write_all(&single.max.to_le_bytes())?;
+        w.write_all(&[code])?;
+        w.write_all(&parameter.unwrap_or(0.0).to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Writes everything between the magic bytes and the dimensions of a binary file: the version, the storage type, the flags and the sections they announce. The checksum is always announced, the caller appends it (see [`Crc32`]).
+fn write_binary_head<W: Write>(w: &mut W, storage: Storage, hermite: bool, transforms: Option<[u8; 3]>, conf: Option<&DataGenConf>) -> Result<(), Interp3DError> {
+    let flags = FLAG_CHECKSUM | if hermite { FLAG_HERMITE } else { 0 } | if transforms.is_some() { FLAG_TRANSFORM } else { 0 } | if conf.is_some() { FLAG_CONFIG } else { 0 };
+    w.write_all(&[VERSION, (storage == Storage::F32) as u8, flags])?;
+    if let Some(codes) = transforms {
+        w.write_all(&codes)?;
+    }
+    if let Some(conf) = conf {
+        write_config(w, conf)?;
+    }
+
+    Ok(())
+}
+
+/// Checks the config read from a file against the node positions of the file and fills in the nodes of ``Custom`` axes. Returns the first axis that doesn't match.
+fn complete_config(conf: &mut DataGenConf, axes: [&[f64]; 3]) -> Result<(), Dir> {
+    for ((dir, single), v) in [(Dir::X, &mut conf.x), (Dir::Y, &mut conf.y), (Dir::Z, &mut conf.z)].into_iter().zip(axes) {
+        if single.n != v.len() {
+            return Err(dir);
+        }
+        if let GridSpacing::Custom(nodes) = &mut single.spacing {
+            *nodes = v.to_vec();
+        }
+    }
+
+    Ok(())
+}
+
+/// Everything in front of the samples of a text file, see [`Interp3D::read_text_head()`]
+struct TextHead {
+    /// an interpolator without data with the settings of the template and the storage type of the file
+    template: Interp3D,
+    components: usize,
+    /// whether the derivatives follow the samples
+    hermite: bool,
+    /// the config the grid was generated with, if the file has it
+    conf: Option<DataGenConf>,
+    axes: [Vec<f64>; 3]
+}
+
+/// The CRC-32 (IEEE, as used by zip and PNG) of every byte value
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            c = if c & 1 != 0 { 0xEDB88320 ^ (c >> 1) } else { c >> 1 };
+            bit += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+/// The CRC-32 (IEEE) of ``bytes``, e.g. of the members of a zip file
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = Crc32::new(());
+    crc.update(bytes);
+    crc.value()
+}
+
+/// Passes the bytes read from or written to ``inner`` through and keeps the CRC-32 of all of them
+struct Crc32<T> {
+    inner: T,
+    /// the running register, i.e. the inverted CRC of the bytes so far
+    register: u32
+}
+
+impl<T> Crc32<T> {
+    fn new(inner: T) -> Self {
+        Crc32 { inner, register: !0 }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.register = CRC_TABLE[((self.register ^ b as u32) & 0xff) as usize] ^ (self.register >> 8);
+        }
+    }
+
+    /// The CRC-32 of all bytes so far
+    fn value(&self) -> u32 {
+        !self.register
+    }
+}
+
+impl<R: Read> Read for Crc32<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<W: Write> Write for Crc32<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Writes ``v`` for a text file
+fn format_float(v: f64, format: FloatFormat) -> String {
+    match format {
+        FloatFormat::Shortest => format!("{:e}", v),
+        // sign, leading digit, point, the digits and the exponent of up to 3 digits with its sign
+        FloatFormat::Scientific { digits } => format!("{:>width$.digits$e}", v, width = digits + 8),
+        FloatFormat::RoundTrip17 => format!("{:.16e}", v)
+    }
+}
+
+/// Recognizes the format of a file by its first bytes ``start``, anything without a known header is taken for text.  
+/// Files that can't be read as a data set give what they look like and what to do about it instead.
+fn detect_format(start: &[u8]) -> Result<FileFormat, (&'static str, &'static str)> {
+    if start.starts_with(MAGIC) {
+        Ok(FileFormat::Binary)
+    } else if start.starts_with(ZIP_MAGIC) {
+        Ok(FileFormat::Npz)
+    } else if start.starts_with(GZIP_MAGIC) {
+        Err(("gzip compressed data", "decompress it first, this build only reads text, binary and npz files"))
+    } else if start.starts_with(MAGIC_INCOMPLETE) {
+        Err(("an incomplete binary file", "writing it was interrupted"))
+    } else if start.starts_with(SERIES_MAGIC) {
+        Err(("a series file", "read it with Interp3DSeries::from_file()"))
+    } else if start.starts_with(COMPACT_MAGIC) {
+        Err(("a compact file of quantized samples", "read it with CompactInterp3D::from_file()"))
+    } else {
+        Ok(FileFormat::Text)
+    }
+}
+
+/// The format the extension of ``file`` suggests, only used to explain errors since the content decides
+fn extension_format(file: &str) -> Option<FileFormat> {
+    match Path::new(file).extension()?.to_str()? {
+        "npz" => Some(FileFormat::Npz),
+        "bin" => Some(FileFormat::Binary),
+        _ => None
+    }
+}
+
+/// How ``format`` is called in errors
+fn format_name(format: FileFormat) -> &'static str {
+    match format {
+        FileFormat::Text => "text",
+        FileFormat::Binary => "a binary data file",
+        FileFormat::Npz => "a npz archive"
+    }
+}
+
+/// Checks whether the file behind ``reader`` is a binary file. One that was never completed is an error, instead of being taken for a text file.
+fn is_binary<R: BufRead>(reader: &mut R) -> Result<bool, Interp3DError> {
+    let start = reader.fill_buf()?;
+    if start.starts_with(MAGIC_INCOMPLETE) {
+        return Err(Interp3DError::Parse { line: 0, message: String::from("incomplete binary file, writing it was interrupted") });
+    }
+
+    Ok(start.starts_with(MAGIC))
+}
+
+/// Reads ``n`` little-endian ``f64``
+fn read_f64s<R: Read>(reader: &mut R, n: usize) -> Result<Vec<f64>, Interp3DError> {
+    let mut bytes = vec![0u8; 8*n];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(bytes.chunks_exact(8).map(|b| f64::from_le_bytes(b.try_into().unwrap())).collect())
+}
+
+/// Reads ``n`` little-endian ``f32`` (widened to ``f64``, which is exact)
+fn read_f32s<R: Read>(reader: &mut R, n: usize) -> Result<Vec<f64>, Interp3DError> {
+    let mut bytes = vec![0u8; 4*n];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(bytes.chunks_exact(4).map(|b| f32::from_le_bytes(b.try_into().unwrap()) as f64).collect())
+}
+
+/// Splits a text file into tokens separated by whitespace or any of the [`DELIMITERS`] while keeping track of the line they came from.
+/// Everything after a ``#`` is a comment.
+struct Tokens<R: BufRead> {
+    reader: R,
+    line: usize,
+    pending: VecDeque<String>
+}
+
+impl<R: BufRead> Tokens<R> {
+    fn new(reader: R) -> Self {
+        Tokens { reader, line: 0, pending: VecDeque::new() }
+    }
+
+    fn next(&mut self) -> Result<Option<String>, Interp3DError> {
+        self.peek()?;
+        Ok(self.pending.pop_front())
+    }
+
+    /// Returns the next token without consuming it
+    fn peek(&mut self) -> Result<Option<&str>, Interp3DError> {
+        while self.pending.is_empty() {
+            let mut buf = String::new();
+            if self.reader.read_line(&mut buf)? == 0 {
+                return Ok(None);
+            }
+            self.line += 1;
+
+            let content = buf.split('#').next().unwrap_or("");
+            let tokens = content.split(|c: char| c.is_whitespace() || DELIMITERS.contains(&c)).filter(|t| !t.is_empty());
+            self.pending.extend(tokens.map(String::from));
+        }
+
+        Ok(self.pending.front().map(|t| t.as_str()))
+    }
+
+    fn error(&self, message: String) -> Interp3DError {
+        Interp3DError::Parse { line: self.line, message }
+    }
+
+    fn parse<T: FromStr>(&mut self, what: &str) -> Result<T, Interp3DError> {
+        match self.next()? {
+            Some(token) => token.parse().map_err(|_| self.error(format!("'{}' is not a valid {}", token, what))),
+            None => Err(self.error(format!("unexpected end of file, expected {}", what)))
+        }
+    }
+
+    fn parse_n(&mut self, n: usize, what: &str) -> Result<Vec<f64>, Interp3DError> {
+        // n comes from the file, so a corrupted count must end in the parse error at the end of the file rather than in a huge allocation
+        let mut v = Vec::with_capacity(n.min(4096));
+        for _ in 0..n {
+            v.push(self.parse(what)?);
+        }
+
+        Ok(v)
+    }
+}
+
+impl Interp3D {
+    /// This will construct the interpolator with data read from a file directly.  
+    /// The data in the file can stem from either a previous export after data generation or you can format your own existing data for use with this interpolator. See [`Self::import_data()`] for the format.
+    /// 
+    /// # Example
+    /// ```no_run
+    /// use crate::interp3d::*;
+    /// 
+    /// let file = String::from("some/file.ip3d"); // file extension can be whatever (also nothing)
+    /// let ip = Interp3D::from_file(&file);
+    /// // ip is now set up for use
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the file can't be read or is malformed, see [`Self::try_from_file()`] for a version that returns the error instead.
+    pub fn from_file(file: &str) -> Self {
+        match Self::try_from_file(file) {
+            Ok(ip) => ip,
+            Err(e) => panic!("Could not import {}: {}", file, e)
+        }
+    }
+
+    /// Same as [`Self::from_file()`], but a file that can't be read or is malformed is returned as an error instead of panicking.
+    pub fn try_from_file(file: &str) -> Result<Self, Interp3DError> {
+        let mut ip: Interp3D = Interp3D::default();
+        ip.import_data(file)?;
+
+        Ok(ip)
+    }
+
+    /// Reads a data set from file and sets the interpolator up with it. On error the interpolator is left unchanged.  
+    /// 
+    /// The file is plain text made up of numbers separated by whitespace, ``,`` or ``;`` (line breaks don't matter), everything after a ``#`` is ignored:
+    /// 0. optionally the storage type ``f64`` or ``f32`` (see [`Storage`](crate::Storage)), then optionally ``components`` followed by the number of components per node (only for [`Interp3DVec`], 1 otherwise), then optionally ``hermite`` if the derivatives follow the samples, then optionally ``transform`` followed by the transforms along X, Y and Z (``identity``, ``log10`` or ``ln``, see [`Self::set_axis_transform()`]), then optionally ``config`` followed by the config the grid was generated with (see [`Self::config()`]) as ``n min max spacing`` along X, Y and Z. The spacing is one of ``linear``, ``exponential k``, ``two-sided-exponential k``, ``logarithmic``, ``chebyshev`` and ``custom`` (whose nodes are the node positions below).
+    /// 1. the number of nodes along X, Y and Z (``nx ny nz``, at least 2 each)
+    /// 2. the ``nx`` node positions along X, strictly increasing
+    /// 3. the ``ny`` node positions along Y
+    /// 4. the ``nz`` node positions along Z
+    /// 5. the ``nx*ny*nz`` samples, where the sample at ``(x[i], y[j], z[k])`` is number ``i*ny*nz + j*nz + k`` (i.e. ``z`` changes fastest). With several components, the components of a node follow each other.
+    /// 6. with ``hermite``, the derivatives ``fx fy fz fxy fxz fyz fxyz`` of every node in the same order (see [`Self::generate_data_hermite()`])
+    /// 
+    /// Only the actual grid is stored, the ghost cells are rebuilt on import.  
+    /// Files stored as ``f32`` are imported with [`Storage::F32`](crate::Storage::F32), all others keep the current storage of the interpolator. The axis transforms are always taken from the file, the identity if it has none. The same goes for the config, files without it give ``None``.  
+    /// The format is recognized by the content of the file, whatever its name: files written by [`Self::export_data_binary()`] and NumPy ``.npz`` archives (see [`Self::from_npz()`]) by their header, everything else is read as text. Gzip compressed files and series files (see [`Interp3DSeries`]) are recognized, but rejected with an error saying so. See [`Self::import_data_format()`] to pick the format yourself.
+    /// 
+    /// ```text
+    /// # a 2x2x3 grid
+    /// 2 2 3
+    /// 0.0 1.0
+    /// 0.0 0.5
+    /// -1.0 0.0 1.0
+    /// 1 2 3  4 5 6
+    /// 7 8 9  10 11 12
+    /// ```
+    pub fn import_data(&mut self, file: &str) -> Result<(), Interp3DError> {
+        *self = self.read_file(file, None)?;
+        Ok(())
+    }
+
+    /// Same as [`Self::import_data()`], but reads the file as ``format`` whatever its content looks like. This is the way out if the detection is wrong, e.g. for a text file that starts with ``PK``.  
+    /// If reading it fails, the error also says what the file looks like.
+    pub fn import_data_format(&mut self, file: &str, format: FileFormat) -> Result<(), Interp3DError> {
+        *self = self.read_file(file, Some(format))?;
+        Ok(())
+    }
+
+    /// Constructs the interpolator from a file read as ``format``, see [`Self::import_data_format()`].
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the file can't be read as ``format``, see [`Self::try_from_file_format()`] for a version that returns the error instead.
+    pub fn from_file_format(file: &str, format: FileFormat) -> Self {
+        match Self::try_from_file_format(file, format) {
+            Ok(ip) => ip,
+            Err(e) => panic!("Could not import {}: {}", file, e)
+        }
+    }
+
+    /// Same as [`Self::from_file_format()`], but a file that can't be read is returned as an error instead of panicking.
+    pub fn try_from_file_format(file: &str, format: FileFormat) -> Result<Self, Interp3DError> {
+        let mut ip: Interp3D = Interp3D::default();
+        ip.import_data_format(file, format)?;
+
+        Ok(ip)
+    }
+
+    /// Reads ``file`` with the settings of ``self``, as ``format`` or in the format detected from its content
+    fn read_file(&self, file: &str, format: Option<FileFormat>) -> Result<Interp3D, Interp3DError> {
+        let extension = extension_format(file);
+        let file = File::open(file)?;
+        let len = file.metadata()?.len();
+        let mut reader = BufReader::new(file);
+
+        let detected = detect_format(reader.fill_buf()?);
+        let explicit = format.is_some();
+        let format = match (format, detected) {
+            (Some(format), _) | (None, Ok(format)) => format,
+            (None, Err((what, advice))) => return Err(Interp3DError::Parse { line: 0, message: format!("file looks like {}, {}", what, advice) })
+        };
+        let result = match format {
+            FileFormat::Text => Self::read_text(reader, self),
+            FileFormat::Binary if detected != Ok(FileFormat::Binary) => Err(Interp3DError::Parse { line: 0, message: String::from("not a binary data file") }),
+            FileFormat::Binary => Self::read_binary(reader, len, self),
+            FileFormat::Npz => {
+                let mut zip = Vec::new();
+                reader.read_to_end(&mut zip)?;
+                Self::read_npz(&zip, self)
+            }
+        };
+
+        // name what the file looks like if it wasn't read as that, or what its extension suggests
+        result.map_err(|e| {
+            let note = match detected {
+                Ok(looks_like) if looks_like == format => match extension {
+                    Some(suggested) if suggested != format => format!("read as {} by its content, although the extension suggests {}", format_name(format), format_name(suggested)),
+                    _ if !explicit && format != FileFormat::Text => format!("read as {} by its content, see Interp3D::import_data_format() to read it as another format", format_name(format)),
+                    _ => return e
+                },
+                Ok(looks_like) => format!("read as {}, but it looks like {}", format_name(format), format_name(looks_like)),
+                Err((what, _)) => format!("read as {}, but it looks like {}", format_name(format), what)
+            };
+            match e {
+                Interp3DError::Parse { line, message } => Interp3DError::Parse { line, message: format!("{} ({})", message, note) },
+                e => e
+            }
+        })
+    }
+
+    fn read_text<R: BufRead>(reader: R, template: &Interp3D) -> Result<Interp3D, Interp3DError> {
+        let mut tokens = Tokens::new(reader);
+        let TextHead { template, components, hermite, conf, axes: [x, y, z] } = Self::read_text_head(&mut tokens, template)?;
+        if components != 1 {
+            return Err(tokens.error(format!("file holds {} components per node, use Interp3DVec to read it", components)));
+        }
+
+        let mut ip = Self::from_parts(&x, &y, &z, &template, || tokens.parse("sample"))?;
+        ip.conf = conf;
+        if hermite {
+            let derivatives = tokens.parse_n(7*x.len()*y.len()*z.len(), "derivative")?;
+            ip.hermite = Some(derivatives.chunks_exact(7).map(|d| d.try_into().unwrap()).collect());
+        }
+        if let Some(token) = tokens.next()? {
+            return Err(tokens.error(format!("unexpected '{}' after the last sample", token)));
+        }
+
+        Ok(ip)
+    }
+
+    /// Reads everything in front of the samples of a text file: the optional storage type, number of components, ``hermite`` flag, axis transforms and config and the axes.
+    fn read_text_head<R: BufRead>(tokens: &mut Tokens<R>, template: &Interp3D) -> Result<TextHead, Interp3DError> {
+        let mut template = template.settings_only();
+        if let Some(storage @ ("f64" | "f32")) = tokens.peek()? {
+            if storage == "f32" {
+                template.set_storage(Storage::F32);
+            }
+            tokens.next()?;
+        }
+        let mut components = 1;
+        if let Some("components") = tokens.peek()? {
+            tokens.next()?;
+            components = tokens.parse("number of components")?;
+            if components == 0 {
+                return Err(tokens.error(String::from("a file needs at least 1 component")));
+            }
+        }
+        let hermite = tokens.peek()? == Some("hermite");
+        if hermite {
+            tokens.next()?;
+        }
+        template.transform = [AxisTransform::Identity; 3];
+        if let Some("transform") = tokens.peek()? {
+            tokens.next()?;
+            for transform in template.transform.iter_mut() {
+                let name = tokens.next()?.unwrap_or_default();
+                *transform = match TRANSFORMS.iter().find(|(n, _)| *n == name) {
+                    Some(&(_, t)) => t,
+                    None => return Err(tokens.error(format!("unknown axis transform '{}'", name)))
+                };
+            }
+        }
+        let mut conf = None;
+        if let Some("config") = tokens.peek()? {
+            tokens.next()?;
+            let mut axes = Vec::with_capacity(3);
+            for dir in [Dir::X, Dir::Y, Dir::Z] {
+                let n = tokens.parse(&format!("number of points along {:?} in the config", dir))?;
+                let min = tokens.parse(&format!("min along {:?} in the config", dir))?;
+                let max = tokens.parse(&format!("max along {:?} in the config", dir))?;
+                let name = tokens.next()?.unwrap_or_default();
+                let Some(code) = SPACINGS.iter().position(|&s| s == name) else {
+                    return Err(tokens.error(format!("unknown grid spacing '{}'", name)));
+                };
+                let spacing = spacing_from_code(code as u8, || tokens.parse("parameter of the grid spacing"))?;
+                axes.push(DataGenConfSingle { n, min, max, spacing });
+            }
+            let [x, y, z]: [DataGenConfSingle; 3] = axes.try_into().unwrap();
+            conf = Some(DataGenConf { x, y, z });
+        }
+
+        let nx: usize = tokens.parse("number of points along X")?;
+        let ny: usize = tokens.parse("number of points along Y")?;
+        let nz: usize = tokens.parse("number of points along Z")?;
+        for (dir, n) in [(Dir::X, nx), (Dir::Y, ny), (Dir::Z, nz)] {
+            if n < 2 {
+                return Err(Interp3DError::TooFewPoints { dir, n });
+            }
+        }
+
+        let x = tokens.parse_n(nx, "node position along X")?;
+        let y = tokens.parse_n(ny, "node position along Y")?;
+        let z = tokens.parse_n(nz, "node position along Z")?;
+        if let Some(conf) = &mut conf {
+            if let Err(dir) = complete_config(conf, [&x, &y, &z]) {
+                return Err(tokens.error(format!("the config along {:?} doesn't match the nodes", dir)));
+            }
+        }
+
+        Ok(TextHead { template, components, hermite, conf, axes: [x, y, z] })
+    }
+
+    fn read_binary<R: Read>(mut reader: R, len: u64, template: &Interp3D) -> Result<Interp3D, Interp3DError> {
+        let mut magic = [0u8; MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        // the checksum covers everything after the magic bytes
+        let mut reader = Crc32::new(reader);
+        let mut version = [0u8];
+        reader.read_exact(&mut version)?;
+
+        let version = version[0];
+        if !(1..=VERSION).contains(&version) {
+            return Err(Interp3DError::UnsupportedVersion { version, supported: VERSION });
+        }
+
+        let mut template = template.settings_only();
+        let mut storage = [0u8];
+        if version >= 2 {
+            reader.read_exact(&mut storage)?;
+        }
+        let mut flags = [0u8];
+        if version >= 3 {
+            reader.read_exact(&mut flags)?;
+        }
+        if flags[0] & !(FLAG_HERMITE | FLAG_TRANSFORM | FLAG_CONFIG | FLAG_CHECKSUM) != 0 {
+            return Err(Interp3DError::Parse { line: 0, message: format!("unknown flags {} in binary file", flags[0]) });
+        }
+        let hermite = flags[0] & FLAG_HERMITE != 0;
+        let checksum = flags[0] & FLAG_CHECKSUM != 0;
+        let mut transforms = [0u8; 3];
+        let transformed = flags[0] & FLAG_TRANSFORM != 0;
+        if transformed {
+            reader.read_exact(&mut transforms)?;
+        }
+        for (t, &code) in template.transform.iter_mut().zip(&transforms) {
+            *t = match TRANSFORMS.get(code as usize) {
+                Some(&(_, transform)) => transform,
+                None => return Err(Interp3DError::Parse { line: 0, message: format!("unknown axis transform {} in binary file", code) })
+            };
+        }
+        let mut config = [0u8; 3*CONFIG_AXIS_LEN];
+        let configured = flags[0] & FLAG_CONFIG != 0;
+        if configured {
+            reader.read_exact(&mut config)?;
+        }
+        let sample_size = match storage[0] {
+            0 => 8,
+            1 => {
+                template.set_storage(Storage::F32);
+                4
+            },
+            other => return Err(Interp3DError::Parse { line: 0, message: format!("unknown storage type {} in binary file", other) })
+        };
+
+        let mut header = [0u8; 3*8];
+        reader.read_exact(&mut header)?;
+        let header_len = MAGIC.len() + 1 + (version >= 2) as usize + (version >= 3) as usize + if transformed { transforms.len() } else { 0 } + if configured { config.len() } else { 0 } + header.len() + if checksum { 4 } else { 0 };
+
+        let mut n = [0usize; 3];
+        for (d, n) in n.iter_mut().enumerate() {
+            let start = 8*d;
+            *n = u64::from_le_bytes(header[start..start+8].try_into().unwrap()) as usize;
+        }
+        for (dir, n) in [(Dir::X, n[0]), (Dir::Y, n[1]), (Dir::Z, n[2])] {
+            if n < 2 {
+                return Err(Interp3DError::TooFewPoints { dir, n });
+            }
+        }
+
+        // check the size up front, such that a corrupted header can't make us allocate absurd amounts of memory
+        let expected = n[0].checked_mul(n[1])
+            .and_then(|n01| n01.checked_mul(n[2]))
+            .and_then(|samples| samples.checked_mul(sample_size + if hermite { 7*8 } else { 0 }))
+            .and_then(|bytes| bytes.checked_add(8*(n[0] + n[1] + n[2]) + header_len))
+            .map(|bytes| bytes as u64);
+        if expected != Some(len) {
+            return Err(Interp3DError::Parse { line: 0, message: format!("binary file has {} bytes, which doesn't match the dimensions {}x{}x{}", len, n[0], n[1], n[2]) });
+        }
+
+        let x = read_f64s(&mut reader, n[0])?;
+        let y = read_f64s(&mut reader, n[1])?;
+        let z = read_f64s(&mut reader, n[2])?;
+        let mut data = if sample_size == 4 {
+            read_f32s(&mut reader, n[0]*n[1]*n[2])?
+        } else {
+            read_f64s(&mut reader, n[0]*n[1]*n[2])?
+        }.into_iter();
+
+        let mut ip = Self::from_parts(&x, &y, &z, &template, || Ok(data.next().unwrap()))?;
+        if configured {
+            let mut conf = read_config(&config)?;
+            if let Err(dir) = complete_config(&mut conf, [&x, &y, &z]) {
+                return Err(Interp3DError::Parse { line: 0, message: format!("the config along {:?} doesn't match the nodes", dir) });
+            }
+            ip.conf = Some(conf);
+        }
+        if hermite {
+            let derivatives = read_f64s(&mut reader, 7*n[0]*n[1]*n[2])?;
+            ip.hermite = Some(derivatives.chunks_exact(7).map(|d| d.try_into().unwrap()).collect());
+        }
+        if checksum {
+            let computed = reader.value();
+            let mut stored = [0u8; 4];
+            reader.inner.read_exact(&mut stored)?;
+            if u32::from_le_bytes(stored) != computed {
+                return Err(Interp3DError::Parse { line: 0, message: format!("checksum mismatch in binary file (stored {:08x}, computed {:08x}), the file is corrupted", u32::from_le_bytes(stored), computed) });
+            }
+        }
+
+        Ok(ip)
+    }
+
+    /// Constructs the interpolator from a table with one ``x y z value`` row per node, as most tabulated data comes in.  
+    /// The columns can be separated by whitespace or commas, everything after a ``#`` is ignored and a header line (like the one written by [`Self::export_csv()`]) is skipped. The rows can come in any order.
+    /// 
+    /// The grid is detected from the coordinates: values along an axis that differ by less than ``1e-9`` times the extent of the axis count as the same node (to allow for rounding in the file). The rows then have to cover every combination of the node positions exactly once, otherwise an error names the duplicate row or the missing point.
+    /// 
+    /// # Example
+    /// ```no_run
+    /// use crate::interp3d::*;
+    /// 
+    /// let ip = Interp3D::from_table("some/table.txt").unwrap();
+    /// ```
+    pub fn from_table(file: &str) -> Result<Self, Interp3DError> {
+        let reader = BufReader::new(File::open(file)?);
+
+        // (line, [x, y, z, value])
+        let mut rows: Vec<(usize, [f64; 4])> = Vec::new();
+        for (line, content) in reader.lines().enumerate() {
+            let line = line + 1;
+            let content = content?;
+            let content = content.split('#').next().unwrap_or("");
+            let fields: Vec<&str> = content.split(|c: char| c == ',' || c.is_whitespace()).filter(|f| !f.is_empty()).collect();
+            if fields.is_empty() {
+                continue;
+            }
+
+            let parsed: Result<Vec<f64>, _> = fields.iter().map(|f| f.parse::<f64>()).collect();
+            match parsed {
+                Ok(values) if values.len() == 4 => rows.push((line, [values[0], values[1], values[2], values[3]])),
+                Ok(values) => return Err(Interp3DError::Parse { line, message: format!("expected 4 columns (x, y, z, value), found {}", values.len()) }),
+                // a header in front of the data
+                Err(_) if rows.is_empty() && fields.iter().all(|f| f.parse::<f64>().is_err()) => continue,
+                Err(_) => {
+                    let token = fields.iter().find(|f| f.parse::<f64>().is_err()).unwrap();
+                    return Err(Interp3DError::Parse { line, message: format!("'{}' is not a valid number", token) });
+                }
+            }
+        }
+
+        // node positions along each axis and the index of every row along it
+        let mut axes = Vec::with_capacity(3);
+        let mut indices = vec![[0usize; 3]; rows.len()];
+        for (d, dir) in [Dir::X, Dir::Y, Dir::Z].into_iter().enumerate() {
+            let mut order: Vec<usize> = (0..rows.len()).collect();
+            if let Some(&(line, _)) = rows.iter().find(|(_, r)| !r[d].is_finite()) {
+                return Err(Interp3DError::Parse { line, message: format!("coordinate along {:?} is not finite", dir) });
+            }
+            order.sort_by(|&a, &b| rows[a].1[d].total_cmp(&rows[b].1[d]));
+
+            let extent = order.last().map_or(0.0, |&l| rows[l].1[d]) - order.first().map_or(0.0, |&f| rows[f].1[d]);
+            let tolerance = 1e-9*extent;
+            let mut nodes: Vec<f64> = Vec::new();
+            for &r in &order {
+                let p = rows[r].1[d];
+                match nodes.last() {
+                    Some(&last) if p - last <= tolerance => (),
+                    _ => nodes.push(p)
+                }
+                indices[r][d] = nodes.len() - 1;
+            }
+            axes.push(nodes);
+        }
+
+        let (nx, ny, nz) = (axes[0].len(), axes[1].len(), axes[2].len());
+        let mut data = vec![f64::NAN; nx*ny*nz];
+        let mut source = vec![0usize; nx*ny*nz];
+        for (&(line, row), [i, j, k]) in rows.iter().zip(indices) {
+            let index = i*ny*nz + j*nz + k;
+            if source[index] != 0 {
+                return Err(Interp3DError::Parse { line, message: format!("the point ({}, {}, {}) already appeared in line {}", row[0], row[1], row[2], source[index]) });
+            }
+            source[index] = line;
+            data[index] = row[3];
+        }
+        if let Some(index) = source.iter().position(|&line| line == 0) {
+            let (i, j, k) = (index/(ny*nz), (index/nz)%ny, index%nz);
+            return Err(Interp3DError::Parse { line: 0, message: format!("the table has no value for the point ({}, {}, {}) of the detected {}x{}x{} grid", axes[0][i], axes[1][j], axes[2][k], nx, ny, nz) });
+        }
+
+        Self::from_grid_and_data(&axes[0], &axes[1], &axes[2], &data)
+    }
+
+    /// This will export a loaded data set and grid to file, in the format described in [`Self::import_data()`].  
+    /// The numbers are written in the shortest representation that parses back to exactly the same ``f64``, so nothing is lost on the way. See [`Self::export_data_with()`] to format the file differently.
+    /// 
+    /// Returns [`Interp3DError::NotSetUp`] if there is no data to export yet and [`Interp3DError::UnsupportedTransform`] for an [`AxisTransform::Custom`], and doesn't touch the file in both cases.
+    pub fn export_data(&self, file: &str) -> Result<(), Interp3DError> {
+        self.export_data_with(file, &ExportOptions::default())
+    }
+
+    /// Same as [`Self::export_data()`], but with the delimiter, number format, header comments and line breaks of ``options``. [`Self::import_data()`] reads the file with any options, only [`FloatFormat::Scientific`] with less than 16 digits rounds the numbers on the way.
+    /// 
+    /// Returns the same errors as [`Self::export_data()`] and [`Interp3DError::UnsupportedDelimiter`] for a delimiter the file can't be read with, and doesn't touch the file in all cases.
+    /// 
+    /// # Example
+    /// 
+    /// ```no_run
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 5).build();
+    /// let ip = Interp3D::from_config(|x, y, z| x + y + z, &config);
+    /// 
+    /// // for a reader that wants fixed-width columns
+    /// let options = ExportOptions { float_format: FloatFormat::Scientific { digits: 16 }, ..ExportOptions::default() };
+    /// ip.export_data_with("some/file.ip3d", &options).unwrap();
+    /// ```
+    pub fn export_data_with(&self, file: &str, options: &ExportOptions) -> Result<(), Interp3DError> {
+        self.write_text(file, &[&self.data], options)
+    }
+
+    /// The bytes of the axis transforms in the binary format (the index in [`TRANSFORMS`]), ``None`` if all of them are the identity
+    pub(crate) fn transform_codes(&self) -> Result<Option<[u8; 3]>, Interp3DError> {
+        if self.transform == [AxisTransform::Identity; 3] {
+            return Ok(None);
+        }
+
+        let mut codes = [0u8; 3];
+        for (dir, code) in [Dir::X, Dir::Y, Dir::Z].into_iter().zip(codes.iter_mut()) {
+            *code = TRANSFORMS.iter().position(|&(_, t)| t == self.transform[dir as usize]).ok_or(Interp3DError::UnsupportedTransform { dir })? as u8;
+        }
+
+        Ok(Some(codes))
+    }
+
+    /// Writes the text format of [`Self::import_data()`] with the samples of all ``components``, which have the layout of ``self.data``
+    pub(crate) fn write_text(&self, file: &str, components: &[&Samples], options: &ExportOptions) -> Result<(), Interp3DError> {
+        if self.data.is_empty() {
+            return Err(Interp3DError::NotSetUp);
+        }
+        let transforms = self.transform_codes()?;
+        if !matches!(options.delimiter, ' ' | '\t') && !DELIMITERS.contains(&options.delimiter) {
+            return Err(Interp3DError::UnsupportedDelimiter { delimiter: options.delimiter });
+        }
+        let delimiter = options.delimiter.to_string();
+        let eol = match options.line_ending {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n"
+        };
+        let float = |v: f64| format_float(v, options.float_format);
+
+        let (nx, ny, nz) = (self.nx-GHOST, self.ny-GHOST, self.nz-GHOST);
+        let mut w = BufWriter::new(File::create(file)?);
+        if options.header {
+            write!(w, "# interp3d data: nx ny nz, the node positions along X, Y and Z, then the samples with z changing fastest{}", eol)?;
+        }
+        if self.storage() == Storage::F32 {
+            write!(w, "f32{}", eol)?;
+        }
+        if components.len() != 1 {
+            write!(w, "components{}{}{}", delimiter, components.len(), eol)?;
+        }
+        if self.hermite.is_some() {
+            write!(w, "hermite{}", eol)?;
+        }
+        if let Some(codes) = transforms {
+            let names: Vec<&str> = codes.iter().map(|&c| TRANSFORMS[c as usize].0).collect();
+            write!(w, "transform{}{}{}", delimiter, names.join(&delimiter), eol)?;
+        }
+        if let Some(conf) = &self.conf {
+            let mut line = vec![String::from("config")];
+            for single in [&conf.x, &conf.y, &conf.z] {
+                let (code, parameter) = spacing_code(&single.spacing);
+                line.extend([single.n.to_string(), float(single.min), float(single.max), String::from(SPACINGS[code as usize])]);
+                line.extend(parameter.map(float));
+            }
+            write!(w, "{}{}", line.join(&delimiter), eol)?;
+        }
+        write!(w, "{}{}{}{}{}{}", nx, delimiter, ny, delimiter, nz, eol)?;
+        for v in [&self.x, &self.y, &self.z] {
+            let interior = &v[GHOST_LO..v.len()-GHOST_HI];
+            let line: Vec<String> = interior.iter().map(|&p| float(p)).collect();
+            write!(w, "{}{}", line.join(&delimiter), eol)?;
+        }
+
+        let mut line = Vec::with_capacity(nz*components.len());
+        for (i, j, k) in self.interior() {
+            let index = self.index(i, j, k);
+            for samples in components {
+                let v = samples.get(index);
+                line.push(match (self.storage(), options.float_format) {
+                    // the shortest representation of the f32 is enough, it's rounded to the same f32 again on import
+                    (Storage::F32, FloatFormat::Shortest) => format!("{:e}", v as f32),
+                    _ => float(v)
+                });
+            }
+            if line.len() == nz*components.len() {
+                write!(w, "{}{}", line.join(&delimiter), eol)?;
+                line.clear();
+            }
+        }
+        if let Some(derivatives) = &self.hermite {
+            if options.header {
+                write!(w, "# derivatives fx fy fz fxy fxz fyz fxyz of every node{}", eol)?;
+            }
+            for d in derivatives.iter() {
+                let line: Vec<String> = d.iter().map(|&d| float(d)).collect();
+                write!(w, "{}{}", line.join(&delimiter), eol)?;
+            }
+        }
+        w.flush()?;
+
+        Ok(())
+    }
+
+    /// Exports the data set to a compact binary file, which is much faster to read than the text format of [`Self::export_data()`]. [`Self::import_data()`] recognizes these files by their header.
+    /// 
+    /// The layout is: the 8 magic bytes ``IP3DBIN\0``, the format version byte 3, the storage type byte (0 for ``f64``, 1 for ``f32``, see [`Storage`](crate::Storage)), a flags byte (+1 if derivatives follow, +2 for the transforms, +4 for the config, +8 for the checksum), with transforms one byte per axis (0 for the identity, 1 for ``log10``, 2 for ``ln``, see [`Self::set_axis_transform()`]), with a config (see [`Self::config()`]) ``n`` as ``u64``, ``min`` and ``max`` as ``f64``, a spacing byte (0 linear, 1 exponential, 2 two-sided exponential, 3 logarithmic, 4 Chebyshev, 5 custom with the node positions as nodes) and the parameter of the spacing as ``f64`` (0 if it has none) per axis, ``nx``, ``ny`` and ``nz`` as ``u64``, the node positions along X, Y and Z as ``f64``, the samples in the same order as in the text format, as ``f64`` or ``f32`` depending on the storage type, and finally the derivatives of every node (see [`Self::generate_data_hermite()`]) as 7 ``f64`` each if there are any, followed by the CRC-32 (IEEE) of everything after the magic bytes as ``u32``. Everything is little-endian whatever the byte order of the machine, so files can be shared between machines of any architecture. [`Self::import_data()`] checks the size of the file and the checksum, so truncated or corrupted files are rejected instead of being read wrongly.  
+    /// Older files are read as well: version 2 files end after the derivatives (there are none) and have no flags byte, version 1 files also lack the storage type byte.
+    /// 
+    /// Returns [`Interp3DError::NotSetUp`] if there is no data to export yet and [`Interp3DError::UnsupportedTransform`] for an [`AxisTransform::Custom`], and doesn't touch the file in both cases.
+    pub fn export_data_binary(&self, file: &str) -> Result<(), Interp3DError> {
+        if self.data.is_empty() {
+            return Err(Interp3DError::NotSetUp);
+        }
+        let transforms = self.transform_codes()?;
+
+        let mut w = BufWriter::new(File::create(file)?);
+        self.write_binary(&mut w, transforms)?;
+        w.flush()?;
+
+        Ok(())
+    }
+
+    /// Writes the binary format of [`Self::export_data_binary()`] with the given [`Self::transform_codes()`]
+    fn write_binary<W: Write>(&self, mut w: W, transforms: Option<[u8; 3]>) -> Result<(), Interp3DError> {
+        w.write_all(MAGIC)?;
+        let mut w = Crc32::new(w);
+        write_binary_head(&mut w, self.storage(), self.hermite.is_some(), transforms, self.conf.as_ref())?;
+        for n in [self.nx, self.ny, self.nz] {
+            w.write_all(&((n-GHOST) as u64).to_le_bytes())?;
+        }
+        for v in [&self.x, &self.y, &self.z] {
+            for p in &v[GHOST_LO..v.len()-GHOST_HI] {
+                w.write_all(&p.to_le_bytes())?;
+            }
+        }
+        for (i, j, k) in self.interior() {
+            let v = self.data.get(self.index(i, j, k));
+            match self.storage() {
+                Storage::F32 => w.write_all(&(v as f32).to_le_bytes())?,
+                Storage::F64 => w.write_all(&v.to_le_bytes())?
+            }
+        }
+        for d in self.hermite.iter().flat_map(|h| h.iter()).flatten() {
+            w.write_all(&d.to_le_bytes())?;
+        }
+        let checksum = w.value();
+        w.inner.write_all(&checksum.to_le_bytes())?;
+
+        Ok(())
+    }
+
+    /// Generates the data like [`Self::generate_data()`], but streams it straight into a binary file (see [`Self::export_data_binary()`]) instead of keeping it in memory, for tables that don't fit into memory (next to whatever else needs it).  
+    /// The closure is evaluated ``chunk_planes`` planes of constant ``x`` at a time (at least 1), and every finished slab is appended to the file. So the peak memory is one slab plus the axes. The samples are written as ``f64``.
+    /// 
+    /// The file starts out with a marker instead of the magic bytes, which is only replaced once all samples are written. So a file whose generation was interrupted (by a panic in the closure, a crash or a full disk) is rejected by [`Self::import_data()`] instead of being read partially. The finished file is the same as the one of [`Self::export_data_binary()`] after generating the data.
+    /// 
+    /// # Example
+    /// ```no_run
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default()
+    ///     .x(0.0, 1.0, 1200, GridSpacing::Linear)
+    ///     .y(0.0, 1.0, 1200, GridSpacing::Linear)
+    ///     .z(0.0, 1.0, 200, GridSpacing::Linear)
+    ///     .build();
+    /// Interp3D::generate_data_to_file(|x, y, z| x*y*z, &config, "table.bin", 8).unwrap();
+    /// 
+    /// let mut ip = Interp3D::default();
+    /// ip.import_data("table.bin").unwrap();
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// An invalid config is returned before the closure is called or the file is created, like for [`Self::try_generate_data()`]. Errors writing the file are returned as [`Interp3DError::Io`] and leave an incomplete file behind.
+    pub fn generate_data_to_file<F>(mut f: F, conf: &DataGenConf, file: &str, chunk_planes: usize) -> Result<(), Interp3DError>
+    where F: FnMut(f64, f64, f64) -> f64 {
+        conf.validate()?;
+        let axis = |dir: Dir, n: usize| -> Result<Vec<f64>, Interp3DError> {
+            let v: Vec<f64> = (0..n).map(|i| Self::grid_point_pos(dir, i, conf)).collect();
+            Self::check_axis(dir, &v)?;
+            Ok(v)
+        };
+        let (x, y, z) = (axis(Dir::X, conf.x.n)?, axis(Dir::Y, conf.y.n)?, axis(Dir::Z, conf.z.n)?);
+
+        let mut w = BufWriter::new(File::create(file)?);
+        w.write_all(MAGIC_INCOMPLETE)?;
+        let mut w = Crc32::new(w);
+        write_binary_head(&mut w, Storage::F64, false, None, Some(conf))?;
+        for n in [x.len(), y.len(), z.len()] {
+            w.write_all(&(n as u64).to_le_bytes())?;
+        }
+        for p in x.iter().chain(&y).chain(&z) {
+            w.write_all(&p.to_le_bytes())?;
+        }
+
+        let mut slab = Vec::with_capacity(8*chunk_planes.max(1)*y.len()*z.len());
+        for planes in x.chunks(chunk_planes.max(1)) {
+            slab.clear();
+            for &px in planes {
+                for &py in &y {
+                    for &pz in &z {
+                        slab.extend_from_slice(&f(px, py, pz).to_le_bytes());
+                    }
+                }
+            }
+            w.write_all(&slab)?;
+        }
+
+        let checksum = w.value();
+        let mut w = w.inner;
+        w.write_all(&checksum.to_le_bytes())?;
+        let mut file = w.into_inner().map_err(|e| e.into_error())?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(MAGIC)?;
+        file.sync_all()?;
+
+        Ok(())
+    }
+
+    /// Writes the data set to a legacy ASCII VTK file (``DATASET RECTILINEAR_GRID``), e.g. to look at it in ParaView or VisIt.  
+    /// The node positions are written as they are, so non-uniform grids show up correctly, and the samples become point scalars named ``value``. Like VTK expects, the points are ordered with ``x`` changing fastest (the other way round than in [`Self::export_data()`]). The ghost cells are not exported.
+    /// 
+    /// Returns [`Interp3DError::NotSetUp`] (and doesn't touch the file) if there is no data to export yet.
+    pub fn export_vtk(&self, file: &str) -> Result<(), Interp3DError> {
+        if self.data.is_empty() {
+            return Err(Interp3DError::NotSetUp);
+        }
+
+        let (nx, ny, nz) = self.dims();
+        let mut w = BufWriter::new(File::create(file)?);
+        writeln!(w, "# vtk DataFile Version 3.0")?;
+        writeln!(w, "interp3d data")?;
+        writeln!(w, "ASCII")?;
+        writeln!(w, "DATASET RECTILINEAR_GRID")?;
+        writeln!(w, "DIMENSIONS {} {} {}", nx, ny, nz)?;
+        for (name, v) in [("X", self.grid_x()), ("Y", self.grid_y()), ("Z", self.grid_z())] {
+            writeln!(w, "{}_COORDINATES {} double", name, v.len())?;
+            let line: Vec<String> = v.iter().map(|p| format!("{:e}", p)).collect();
+            writeln!(w, "{}", line.join(" "))?;
+        }
+
+        writeln!(w, "POINT_DATA {}", nx*ny*nz)?;
+        writeln!(w, "SCALARS value double 1")?;
+        writeln!(w, "LOOKUP_TABLE default")?;
+        for k in 0..nz {
+            for j in 0..ny {
+                let line: Vec<String> = (0..nx).map(|i| format!("{:e}", self.value_at(i, j, k))).collect();
+                writeln!(w, "{}", line.join(" "))?;
+            }
+        }
+        w.flush()?;
+
+        Ok(())
+    }
+
+    /// Writes the interior nodes to a human readable csv file, one ``x,y,z,value`` row per node in the order of [`Self::iter_points()`], preceded by a header line.  
+    /// This is meant for quick inspection or for spreadsheets and gnuplot. Use [`Self::export_data()`] if you want to load the data again later.
+    /// 
+    /// Returns [`Interp3DError::NotSetUp`] (and doesn't touch the file) if there is no data to export yet.
+    pub fn export_csv(&self, file: &str) -> Result<(), Interp3DError> {
+        if self.data.is_empty() {
+            return Err(Interp3DError::NotSetUp);
+        }
+
+        let mut w = BufWriter::new(File::create(file)?);
+        writeln!(w, "x,y,z,value")?;
+        for (x, y, z, v) in self.iter_points() {
+            writeln!(w, "{},{},{},{}", x, y, z, v)?;
+        }
+        w.flush()?;
+
+        Ok(())
+    }
+}
+
+impl<const N: usize> Interp3DVec<N> {
+    /// Constructs the interpolator with data read from a file, see [`Self::import_data()`].
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the file can't be read or is malformed, see [`Self::try_from_file()`] for a version that returns the error instead.
+    pub fn from_file(file: &str) -> Self {
+        match Self::try_from_file(file) {
+            Ok(ip) => ip,
+            Err(e) => panic!("Could not import {}: {}", file, e)
+        }
+    }
+
+    /// Same as [`Self::from_file()`], but a file that can't be read or is malformed is returned as an error instead of panicking.
+    pub fn try_from_file(file: &str) -> Result<Self, Interp3DError> {
+        let mut ip = Self::default();
+        ip.import_data(file)?;
+
+        Ok(ip)
+    }
+
+    /// Reads a data set with ``N`` components from file and sets the interpolator up with it. On error the interpolator is left unchanged.  
+    /// The format is the text format of [`Interp3D::import_data()`], with the number of components given in the file. That needs to be ``N``, files without it hold 1 component. Binary files hold a single component, so they can only be read with ``N = 1``.
+    pub fn import_data(&mut self, file: &str) -> Result<(), Interp3DError> {
+        let file = File::open(file)?;
+        let len = file.metadata()?.len();
+        let mut reader = BufReader::new(file);
+
+        match detect_format(reader.fill_buf()?) {
+            Ok(FileFormat::Text) => (),
+            Ok(FileFormat::Npz) => return Err(Interp3DError::Parse { line: 0, message: String::from("file looks like a npz archive, which holds 1 component, use Interp3D to read it") }),
+            Err((what, advice)) => return Err(Interp3DError::Parse { line: 0, message: format!("file looks like {}, {}", what, advice) }),
+            Ok(FileFormat::Binary) => {
+                if N != 1 {
+                    return Err(Interp3DError::Parse { line: 0, message: format!("binary files hold 1 component, expected {}", N) });
+                }
+                let grid = Interp3D::read_binary(reader, len, &self.grid)?;
+                if grid.hermite.is_some() {
+                    return Err(Interp3DError::Parse { line: 0, message: String::from("file holds derivatives, use Interp3D to read it") });
+                }
+                *self = Interp3DVec { grid, rest: Vec::new() };
+                return Ok(());
+            }
+        }
+
+        let mut tokens = Tokens::new(reader);
+        let TextHead { template, components, hermite, conf, axes: [x, y, z] } = Interp3D::read_text_head(&mut tokens, &self.grid)?;
+        if components != N {
+            return Err(tokens.error(format!("file holds {} components per node, expected {}", components, N)));
+        }
+        if hermite {
+            return Err(tokens.error(String::from("file holds derivatives, use Interp3D to read it")));
+        }
+
+        let mut ip = Interp3DVec { grid: template, rest: Vec::new() };
+        let samples = std::iter::repeat_with(|| -> Result<[f64; N], Interp3DError> {
+            let mut values = [0.0; N];
+            for v in values.iter_mut() {
+                *v = tokens.parse("sample")?;
+            }
+            Ok(values)
+        });
+        ip.fill_from_parts(&x, &y, &z, samples)?;
+        ip.grid.conf = conf;
+        if let Some(token) = tokens.next()? {
+            return Err(tokens.error(format!("unexpected '{}' after the last sample", token)));
+        }
+
+        *self = ip;
+        Ok(())
+    }
+
+    /// Exports the data set of all components to file, in the text format described in [`Self::import_data()`].
+    /// 
+    /// Returns [`Interp3DError::NotSetUp`] if there is no data to export yet and [`Interp3DError::UnsupportedTransform`] for an [`AxisTransform::Custom`], and doesn't touch the file in both cases.
+    pub fn export_data(&self, file: &str) -> Result<(), Interp3DError> {
+        self.export_data_with(file, &ExportOptions::default())
+    }
+
+    /// Same as [`Self::export_data()`] with the formatting of ``options``, see [`Interp3D::export_data_with()`].
+    pub fn export_data_with(&self, file: &str, options: &ExportOptions) -> Result<(), Interp3DError> {
+        if self.grid.data.is_empty() {
+            return Err(Interp3DError::NotSetUp);
+        }
+
+        self.grid.write_text(file, &self.components(), options)
+    }
+}
+
+impl Interp3DSeries {
+    /// Constructs the series with the snapshots read from a file written by [`Self::export_data()`].
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the file can't be read or is malformed, see [`Self::try_from_file()`] for a version that returns the error instead.
+    pub fn from_file(file: &str) -> Self {
+        match Self::try_from_file(file) {
+            Ok(series) => series,
+            Err(e) => panic!("Could not import {}: {}", file, e)
+        }
+    }
+
+    /// Same as [`Self::from_file()`], but a file that can't be read or is malformed is returned as an error instead of panicking.
+    pub fn try_from_file(file: &str) -> Result<Self, Interp3DError> {
+        let file = File::open(file)?;
+        let mut remaining = file.metadata()?.len();
+        let mut reader = BufReader::new(file);
+
+        let mut head = [0u8; SERIES_MAGIC.len() + 1 + 8];
+        reader.read_exact(&mut head)?;
+        if &head[..SERIES_MAGIC.len()] != SERIES_MAGIC {
+            return Err(Interp3DError::Parse { line: 0, message: String::from("not a series file") });
+        }
+        let version = head[SERIES_MAGIC.len()];
+        if version != SERIES_VERSION {
+            return Err(Interp3DError::UnsupportedVersion { version, supported: SERIES_VERSION });
+        }
+        let count = u64::from_le_bytes(head[SERIES_MAGIC.len()+1..].try_into().unwrap());
+        remaining -= head.len() as u64;
+        // every snapshot takes at least its time and length, such that a corrupted count can't make us allocate absurd amounts of memory
+        if count.checked_mul(16).is_none_or(|bytes| bytes > remaining) {
+            return Err(Interp3DError::Parse { line: 0, message: format!("series file is too short for {} snapshots", count) });
+        }
+
+        let mut snapshots = Vec::with_capacity(count as usize);
+        for n in 0..count {
+            let mut entry = [0u8; 16];
+            reader.read_exact(&mut entry)?;
+            let (t, len) = (f64::from_le_bytes(entry[..8].try_into().unwrap()), u64::from_le_bytes(entry[8..].try_into().unwrap()));
+            remaining -= 16;
+            if len > remaining {
+                return Err(Interp3DError::Parse { line: 0, message: format!("snapshot {} of the series file is truncated", n) });
+            }
+            remaining -= len;
+
+            let mut snapshot = (&mut reader).take(len);
+            if !is_binary(&mut snapshot)? {
+                return Err(Interp3DError::Parse { line: 0, message: format!("snapshot {} of the series file is not a binary data set", n) });
+            }
+            snapshots.push((t, Interp3D::read_binary(snapshot, len, &Interp3D::default())?));
+        }
+        if remaining != 0 {
+            return Err(Interp3DError::Parse { line: 0, message: format!("{} unexpected bytes after the last snapshot of the series file", remaining) });
+        }
+
+        Self::try_new(snapshots)
+    }
+
+    /// Exports all snapshots with their times to a single binary file, which [`Self::try_from_file()`] reads again.  
+    /// The layout is: the 8 magic bytes ``IP3DSER\0``, the format version byte 1, the number of snapshots as ``u64``, and then for every snapshot its time as ``f64``, the length of its data as ``u64`` and the data itself in the format of [`Interp3D::export_data_binary()`] (with its own checksum). Everything is little-endian.  
+    /// Like for the single files, only the data, the storage type, the axis transforms and the config are stored, the snapshots are read with the default settings of [`Interp3D`].
+    /// 
+    /// Returns [`Interp3DError::UnsupportedTransform`] for an [`AxisTransform::Custom`] and doesn't touch the file in that case.
+    pub fn export_data(&self, file: &str) -> Result<(), Interp3DError> {
+        let transforms = self.snapshots.iter().map(|s| s.transform_codes()).collect::<Result<Vec<_>, _>>()?;
+
+        let mut w = BufWriter::new(File::create(file)?);
+        w.write_all(SERIES_MAGIC)?;
+        w.write_all(&[SERIES_VERSION])?;
+        w.write_all(&(self.snapshots.len() as u64).to_le_bytes())?;
+        let mut bytes = Vec::new();
+        for ((t, snapshot), transforms) in self.times.iter().zip(&self.snapshots).zip(transforms) {
+            bytes.clear();
+            snapshot.write_binary(&mut bytes, transforms)?;
+            w.write_all(&t.to_le_bytes())?;
+            w.write_all(&(bytes.len() as u64).to_le_bytes())?;
+            w.write_all(&bytes)?;
+        }
+        w.flush()?;
+
+        Ok(())
+    }
+}
+
+impl CompactInterp3D {
+    /// Constructs the compact interpolator from a file written by [`Self::export_data()`].
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the file can't be read or is malformed, see [`Self::try_from_file()`] for a version that returns the error instead.
+    pub fn from_file(file: &str) -> Self {
+        match Self::try_from_file(file) {
+            Ok(compact) => compact,
+            Err(e) => panic!("Could not import {}: {}", file, e)
+        }
+    }
+
+    /// Same as [`Self::from_file()`], but a file that can't be read or is malformed is returned as an error instead of panicking.
+    pub fn try_from_file(file: &str) -> Result<Self, Interp3DError> {
+        let file = File::open(file)?;
+        let len = file.metadata()?.len();
+        let mut reader = BufReader::new(file);
+
+        let mut magic = [0u8; COMPACT_MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if &magic != COMPACT_MAGIC {
+            return Err(Interp3DError::Parse { line: 0, message: String::from("not a compact file") });
+        }
+        let mut reader = Crc32::new(reader);
+        let mut head = [0u8; 4];
+        reader.read_exact(&mut head)?;
+        let [version, flags, spec, periodic] = head;
+        if version != COMPACT_VERSION {
+            return Err(Interp3DError::UnsupportedVersion { version, supported: COMPACT_VERSION });
+        }
+        if flags & !(FLAG_TRANSFORM | FLAG_CONFIG | FLAG_CHECKSUM) != 0 {
+            return Err(Interp3DError::Parse { line: 0, message: format!("unknown flags {} in compact file", flags) });
+        }
+        let spec = match spec {
+            0 => QuantizeSpec::U16,
+            1 => QuantizeSpec::U16PerSlab,
+            other => return Err(Interp3DError::Parse { line: 0, message: format!("unknown quantization {} in compact file", other) })
+        };
+
+        let mut ip = Interp3D::default();
+        for (d, dir) in [Dir::X, Dir::Y, Dir::Z].into_iter().enumerate() {
+            ip.set_periodic(dir, periodic & (1 << d) != 0);
+        }
+        let mut transforms = [0u8; 3];
+        if flags & FLAG_TRANSFORM != 0 {
+            reader.read_exact(&mut transforms)?;
+        }
+        for (t, &code) in ip.transform.iter_mut().zip(&transforms) {
+            *t = match TRANSFORMS.get(code as usize) {
+                Some(&(_, transform)) => transform,
+                None => return Err(Interp3DError::Parse { line: 0, message: format!("unknown axis transform {} in compact file", code) })
+            };
+        }
+        let mut config = [0u8; 3*CONFIG_AXIS_LEN];
+        let configured = flags & FLAG_CONFIG != 0;
+        if configured {
+            reader.read_exact(&mut config)?;
+        }
+
+        let mut header = [0u8; 3*8];
+        reader.read_exact(&mut header)?;
+        let n: [usize; 3] = std::array::from_fn(|d| u64::from_le_bytes(header[8*d..8*d+8].try_into().unwrap()) as usize);
+        for (dir, n) in [(Dir::X, n[0]), (Dir::Y, n[1]), (Dir::Z, n[2])] {
+            if n < 2 {
+                return Err(Interp3DError::TooFewPoints { dir, n });
+            }
+        }
+        let header_len = COMPACT_MAGIC.len() + head.len() + if flags & FLAG_TRANSFORM != 0 { transforms.len() } else { 0 } + if configured { config.len() } else { 0 } + header.len() + 6*8 + if flags & FLAG_CHECKSUM != 0 { 4 } else { 0 };
+
+        // check the size up front, such that a corrupted header can't make us allocate absurd amounts of memory
+        // every term is checked, as the dimensions of a corrupted header can be anything up to u64::MAX
+        let slabs = match spec {
+            QuantizeSpec::U16 => 1,
+            QuantizeSpec::U16PerSlab => n[0].saturating_add(GHOST)
+        };
+        let expected = (|| {
+            let samples = n[0].checked_add(GHOST)?.checked_mul(n[1].checked_add(GHOST)?)?.checked_mul(n[2].checked_add(GHOST)?)?;
+            let nodes = n[0].checked_add(n[1])?.checked_add(n[2])?;
+            let bytes = samples.checked_mul(2)?.checked_add(nodes.checked_mul(8)?)?.checked_add(slabs.checked_mul(16)?)?.checked_add(header_len)?;
+            u64::try_from(bytes).ok()
+        })();
+        if expected != Some(len) {
+            return Err(Interp3DError::Parse { line: 0, message: format!("compact file has {} bytes, which doesn't match the dimensions {}x{}x{}", len, n[0], n[1], n[2]) });
+        }
+
+        let x = read_f64s(&mut reader, n[0])?;
+        let y = read_f64s(&mut reader, n[1])?;
+        let z = read_f64s(&mut reader, n[2])?;
+        ip.pad_axes(&x, &y, &z)?;
+        if configured {
+            let mut conf = read_config(&config)?;
+            if let Err(dir) = complete_config(&mut conf, [&x, &y, &z]) {
+                return Err(Interp3DError::Parse { line: 0, message: format!("the config along {:?} doesn't match the nodes", dir) });
+            }
+            ip.conf = Some(conf);
+        }
+
+        let report = read_f64s(&mut reader, 6)?;
+        let report = ErrorReport { max_abs: report[0], rms: report[1], worst: (report[2], report[3], report[4]), samples: report[5].to_bits() as usize };
+        let scales = read_f64s(&mut reader, 2*slabs)?;
+        let mut bytes = vec![0u8; 2*ip.nx*ip.ny*ip.nz];
+        reader.read_exact(&mut bytes)?;
+        let values = bytes.chunks_exact(2).map(|b| u16::from_le_bytes([b[0], b[1]])).collect();
+        let slab_len = if slabs == 1 { ip.nx*ip.ny*ip.nz } else { ip.ny*ip.nz };
+        ip.data = Samples::U16(Quantized { values, slab_len, scales: scales.chunks_exact(2).map(|s| (s[0], s[1])).collect() });
+
+        if flags & FLAG_CHECKSUM != 0 {
+            let computed = reader.value();
+            let mut stored = [0u8; 4];
+            reader.inner.read_exact(&mut stored)?;
+            if u32::from_le_bytes(stored) != computed {
+                return Err(Interp3DError::Parse { line: 0, message: format!("checksum mismatch in compact file (stored {:08x}, computed {:08x}), the file is corrupted", u32::from_le_bytes(stored), computed) });
+            }
+        }
+
+        Ok(CompactInterp3D { ip, spec, report })
+    }
+
+    /// Exports the quantized samples to a binary file, which [`Self::try_from_file()`] reads again. The samples take 2 bytes each like in memory.  
+    /// The layout is: the 8 magic bytes ``IP3DQNT\0``, the format version byte 1, a flags byte (+2 for the transforms, +4 for the config, +8 for the checksum, like for [`Interp3D::export_data_binary()`]), the quantization byte (0 for [`QuantizeSpec::U16`], 1 for [`QuantizeSpec::U16PerSlab`]), a byte with the periodic axes (+1 for X, +2 for Y, +4 for Z), the transforms and the config in the format of [`Interp3D::export_data_binary()`], ``nx``, ``ny`` and ``nz`` as ``u64``, the node positions along X, Y and Z as ``f64``, the [`Self::error_report()`] (``max_abs``, ``rms``, the 3 coordinates of ``worst`` as ``f64`` and ``samples`` as ``u64``), the ``(offset, step)`` of the whole table or of every slab (``nx + 3``) as ``f64``, the quantized samples (``offset + step*sample``) as ``u16`` including 1 ghost cell below and 2 above the nodes along every axis, in storage order, and finally the CRC-32 (IEEE) of everything after the magic bytes as ``u32``. Everything is little-endian.  
+    /// The ghost cells are stored, so the boundary conditions the table was quantized with are kept. Of the other settings only the axis transforms and the periodic axes are stored, the file is read with the defaults of [`Interp3D`] otherwise.
+    /// 
+    /// Returns [`Interp3DError::UnsupportedTransform`] for an [`AxisTransform::Custom`] and doesn't touch the file in that case.
+    pub fn export_data(&self, file: &str) -> Result<(), Interp3DError> {
+        let ip = &self.ip;
+        let transforms = ip.transform_codes()?;
+        let Samples::U16(q) = &ip.data else {
+            unreachable!("a compact interpolator holds quantized samples")
+        };
+
+        let mut w = BufWriter::new(File::create(file)?);
+        w.write_all(COMPACT_MAGIC)?;
+        let mut w = Crc32::new(w);
+        let flags = FLAG_CHECKSUM | if transforms.is_some() { FLAG_TRANSFORM } else { 0 } | if ip.conf.is_some() { FLAG_CONFIG } else { 0 };
+        let periodic = ip.periodic.iter().enumerate().map(|(d, &p)| (p as u8) << d).sum::<u8>();
+        w.write_all(&[COMPACT_VERSION, flags, (self.spec == QuantizeSpec::U16PerSlab) as u8, periodic])?;
+        if let Some(codes) = transforms {
+            w.write_all(&codes)?;
+        }
+        if let Some(conf) = &ip.conf {
+            write_config(&mut w, conf)?;
+        }
+        for n in [ip.nx, ip.ny, ip.nz] {
+            w.write_all(&((n-GHOST) as u64).to_le_bytes())?;
+        }
+        for v in [&ip.x, &ip.y, &ip.z] {
+            for p in &v[GHOST_LO..v.len()-GHOST_HI] {
+                w.write_all(&p.to_le_bytes())?;
+            }
+        }
+        let report = &self.report;
+        for v in [report.max_abs, report.rms, report.worst.0, report.worst.1, report.worst.2] {
+            w.write_all(&v.to_le_bytes())?;
+        }
+        w.write_all(&(report.samples as u64).to_le_bytes())?;
+        for &(offset, step) in q.scales.iter() {
+            w.write_all(&offset.to_le_bytes())?;
+            w.write_all(&step.to_le_bytes())?;
+        }
+        for v in q.values.iter() {
+            w.write_all(&v.to_le_bytes())?;
+        }
+        let checksum = w.value();
+        w.inner.write_all(&checksum.to_le_bytes())?;
+        w.inner.flush()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+//! Contains the 1d building blocks of the interpolation, i.e. locating a query point on an axis and computing the stencil weights along it.
+//! The 3d schemes are tensor products of these.
+
+use crate::{GHOST_LO, GHOST_HI};
+
+/// 1d interpolation along one direction
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Kernel {
+    Cubic,
+    Linear,
+    Nearest
+}
+
+/// Finds the cell ``[v[i], v[i+1]]`` that contains ``p`` and returns ``i`` together with the relative position ``t`` of ``p`` inside that cell.
+/// Only interior cells are returned. Points outside of the sampled range end up in the outermost cell with ``t < 0`` or ``t > 1``.
+pub(crate) fn locate(v: &[f64], p: f64) -> (usize, f64) {
+    let i = v.partition_point(|&node| node <= p)
+        .saturating_sub(1)
+        .clamp(GHOST_LO, v.len()-2-GHOST_HI);
+
+    (i, (p - v[i])/(v[i+1] - v[i]))
+}
+
+/// Same as [`locate()`], but checks the cell ``hint`` (usually the result of the previous query) and its two neighbours first, which saves the binary search for spatially coherent queries.
+/// The result is always identical to the one of [`locate()`], whatever the hint is.
+pub(crate) fn locate_hinted(v: &[f64], p: f64, hint: usize) -> (usize, f64) {
+    // an interior cell that contains p is exactly what the binary search would find
+    for i in [hint, hint.wrapping_add(1), hint.wrapping_sub(1)] {
+        if (GHOST_LO..v.len()-1-GHOST_HI).contains(&i) && v[i] <= p && p < v[i+1] {
+            return (i, (p - v[i])/(v[i+1] - v[i]));
+        }
+    }
+
+    locate(v, p)
+}
+
+/// Cubic Hermite basis functions ``[h00, h10, h01, h11]`` at ``t``
+fn hermite_basis(t: f64) -> [f64; 4] {
+    let t2 = t*t;
+    let t3 = t2*t;
+    [2.0*t3 - 3.0*t2 + 1.0, t3 - 2.0*t2 + t, -2.0*t3 + 3.0*t2, t3 - t2]
+}
+
+/// Derivatives of the cubic Hermite basis functions with respect to ``t``
+fn hermite_basis_dt(t: f64) -> [f64; 4] {
+    let t2 = t*t;
+    [6.0*t2 - 6.0*t, 3.0*t2 - 4.0*t + 1.0, -6.0*t2 + 6.0*t, 3.0*t2 - 2.0*t]
+}
+
+/// Second derivatives of the cubic Hermite basis functions with respect to ``t``
+fn hermite_basis_dt2(t: f64) -> [f64; 4] {
+    [12.0*t - 6.0, 6.0*t - 4.0, -12.0*t + 6.0, 6.0*t - 2.0]
+}
+
+/// Combines the Hermite basis ``h`` on the cell ``[v[i], v[i+1]]`` with the derivative estimates at both cell nodes into weights for the 4 stencil nodes ``i-1..=i+2``.
+/// The derivatives are the ones of the parabola through the node and its two neighbours, which is exact for quadratics on arbitrarily spaced grids.
+pub(crate) fn hermite_weights(v: &[f64], i: usize, h: [f64; 4]) -> [f64; 4] {
+    let h0 = v[i] - v[i-1];
+    let h1 = v[i+1] - v[i];
+    let h2 = v[i+2] - v[i+1];
+
+    // derivative at v[i] in terms of the values at i-1, i, i+1
+    let d1 = [-h1/(h0*(h0 + h1)), (h1 - h0)/(h0*h1), h0/(h1*(h0 + h1))];
+    // derivative at v[i+1] in terms of the values at i, i+1, i+2
+    let d2 = [-h2/(h1*(h1 + h2)), (h2 - h1)/(h1*h2), h1/(h2*(h1 + h2))];
+
+    [
+        h1*h[1]*d1[0],
+        h[0] + h1*(h[1]*d1[1] + h[3]*d2[0]),
+        h[2] + h1*(h[1]*d1[2] + h[3]*d2[1]),
+        h1*h[3]*d2[2]
+    ]
+}
+
+/// Weights of the cubic interpolation on the cell ``[v[i], v[i+1]]`` at the relative position ``t``, for the stencil nodes ``i-1..=i+2``
+pub(crate) fn cubic(v: &[f64], i: usize, t: f64) -> [f64; 4] {
+    hermite_weights(v, i, hermite_basis(t))
+}
+
+/// Weights of the first derivative of the cubic interpolation (with respect to the coordinate, not ``t``), see [`cubic()`]
+pub(crate) fn cubic_derivative(v: &[f64], i: usize, t: f64) -> [f64; 4] {
+    let h = v[i+1] - v[i];
+    hermite_weights(v, i, hermite_basis_dt(t).map(|b| b/h))
+}
+
+/// Weights of the second derivative of the cubic interpolation (with respect to the coordinate), see [`cubic()`]
+pub(crate) fn cubic_second(v: &[f64], i: usize, t: f64) -> [f64; 4] {
+    let h = v[i+1] - v[i];
+    hermite_weights(v, i, hermite_basis_dt2(t).map(|b| b/(h*h)))
+}
+
+/// Weights of the cubic Hermite interpolation with the basis ``h`` on the cell ``[v[i], v[i+1]]`` for given values and slopes at both cell nodes, as ``[[value at v[i], value at v[i+1]], [slope at v[i], slope at v[i+1]]]``
+pub(crate) fn hermite_corners(v: &[f64], i: usize, h: [f64; 4]) -> [[f64; 2]; 2] {
+    let w = v[i+1] - v[i];
+    [[h[0], h[2]], [w*h[1], w*h[3]]]
+}
+
+/// Weights of [`hermite_corners()`] at the relative position ``t`` for the value, the first and the second derivative (with respect to the coordinate)
+pub(crate) fn hermite(v: &[f64], i: usize, t: f64) -> [[[f64; 2]; 2]; 3] {
+    let w = v[i+1] - v[i];
+    [
+        hermite_corners(v, i, hermite_basis(t)),
+        hermite_corners(v, i, hermite_basis_dt(t).map(|b| b/w)),
+        hermite_corners(v, i, hermite_basis_dt2(t).map(|b| b/(w*w)))
+    ]
+}
+
+/// Weights of the linear interpolation on the cell ``[v[i], v[i+1]]`` at the relative position ``t``, using the same stencil as [`cubic()`]
+pub(crate) fn linear(t: f64) -> [f64; 4] {
+    [0.0, 1.0 - t, t, 0.0]
+}
+
+/// Weights of the first derivative of the linear interpolation, see [`linear()`]
+pub(crate) fn linear_derivative(v: &[f64], i: usize) -> [f64; 4] {
+    let h = v[i+1] - v[i];
+    [0.0, -1.0/h, 1.0/h, 0.0]
+}
+
+/// Weights of the nearest node on the cell ``[v[i], v[i+1]]`` at the relative position ``t``, using the same stencil as [`cubic()`]. Ties go to the lower node.
+pub(crate) fn nearest(t: f64) -> [f64; 4] {
+    if t <= 0.5 { [0.0, 1.0, 0.0, 0.0] } else { [0.0, 0.0, 1.0, 0.0] }
+}
+
+/// Coefficients (for the stencil nodes ``i-1..=i+2``) of the slopes at ``v[i]`` and ``v[i+1]`` for the samples ``f``, limited such that the cubic on the cell is monotone.  
+/// Each slope is the one of [`hermite_weights()`], but set to 0 at local extrema and limited to 3 times the adjacent secants otherwise (Fritsch–Carlson). As this only picks one of a few linear candidates, the result is again linear in the samples.
+fn limited_slopes(v: &[f64], i: usize, f: &[f64; 4]) -> [[f64; 4]; 2] {
+    let h = [v[i] - v[i-1], v[i+1] - v[i], v[i+2] - v[i+1]];
+    let dot = |c: &[f64; 4]| c.iter().zip(f).map(|(c, f)| c*f).sum::<f64>();
+    let secant = |s: usize| {
+        let mut c = [0.0; 4];
+        c[s] = -1.0/h[s];
+        c[s+1] = 1.0/h[s];
+        c
+    };
+
+    // slope at the node between the secants s and s+1
+    let slope = |s: usize| {
+        let (left, right) = (secant(s), secant(s+1));
+        if dot(&left)*dot(&right) <= 0.0 {
+            return [0.0; 4];
+        }
+
+        let w = h[s+1]/(h[s] + h[s+1]);
+        let parabola: [f64; 4] = std::array::from_fn(|n| w*left[n] + (1.0 - w)*right[n]);
+        [parabola, left.map(|c| 3.0*c), right.map(|c| 3.0*c)].into_iter()
+            .min_by(|a, b| dot(a).abs().total_cmp(&dot(b).abs()))
+            .unwrap()
+    };
+
+    [slope(0), slope(1)]
+}
+
+/// Same as [`hermite_weights()`], but with the slopes of [`limited_slopes()`]
+fn monotone_weights(v: &[f64], i: usize, h: [f64; 4], f: &[f64; 4]) -> [f64; 4] {
+    let h1 = v[i+1] - v[i];
+    let [d1, d2] = limited_slopes(v, i, f);
+
+    let mut w: [f64; 4] = std::array::from_fn(|n| h1*(h[1]*d1[n] + h[3]*d2[n]));
+    w[1] += h[0];
+    w[2] += h[2];
+    w
+}
+
+/// Weights of the monotone cubic interpolation of the samples ``f`` on the cell ``[v[i], v[i+1]]`` at the relative position ``t``, see [`cubic()`].  
+/// Unlike the other weights these depend on the samples, but only piecewise constantly.
+pub(crate) fn monotone(v: &[f64], i: usize, t: f64, f: &[f64; 4]) -> [f64; 4] {
+    monotone_weights(v, i, hermite_basis(t), f)
+}
+
+/// Weights of the first derivative of [`monotone()`]
+pub(crate) fn monotone_derivative(v: &[f64], i: usize, t: f64, f: &[f64; 4]) -> [f64; 4] {
+    let h = v[i+1] - v[i];
+    monotone_weights(v, i, hermite_basis_dt(t).map(|b| b/h), f)
+}
+
+/// Weights of the second derivative of [`monotone()`]
+pub(crate) fn monotone_second(v: &[f64], i: usize, t: f64, f: &[f64; 4]) -> [f64; 4] {
+    let h = v[i+1] - v[i];
+    monotone_weights(v, i, hermite_basis_dt2(t).map(|b| b/(h*h)), f)
+}
@@ -0,0 +1,110 @@
+//! Contains the evaluation on the tensor product of 1d query vectors, where the cells and the weights along each direction are found once per query coordinate instead of once per point.
+
+use crate::{Interp3D, Interp3DError, AxisBound, Dir, OutOfBounds, Type};
+use crate::kernel;
+
+use std::sync::atomic::Ordering;
+
+/// One query coordinate of [`Interp3D::interpolate_grid()`], located on its axis once for all points that share it
+pub(crate) struct AxisQuery {
+    /// cell and relative position inside of it, as from [`Interp3D::locate()`]
+    located: (usize, f64),
+    /// weights of the scheme along the axis, see [`Interp3D::weights()`]
+    weights: [f64; 4],
+    bound: AxisBound
+}
+
+impl Interp3D {
+    /// Interpolates at every combination of the coordinates ``xq``, ``yq`` and ``zq``, e.g. to put the field onto a regular lattice for plotting. The values come in row-major order (``x`` slowest, ``z`` fastest), the one at ``(xq[a], yq[b], zq[c])`` is at ``(a*yq.len() + b)*zq.len() + c``.  
+    /// The cell, the weights and the out of bounds handling along each direction only depend on the coordinate along it, so they are done once per entry of the query vectors instead of once per point, which leaves the sum over the stencil for every point. The results are identical to calling [`Self::interpolate()`] for every point (see the ``interpolate_grid`` benchmark for how much faster this is), and every point counts as one query for [`Self::query_stats()`].
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+    /// let ip = Interp3D::from_config(|x, y, z| x + y*z, &config);
+    /// 
+    /// let (xq, yq, zq) = ([0.1, 0.5, 0.9], [0.0, 1.0], [0.25, 0.5, 0.75, 1.0]);
+    /// let values = ip.interpolate_grid(&xq, &yq, &zq);
+    /// assert_eq!(values.len(), 24);
+    /// assert_eq!(values[(2*2 + 1)*4 + 3], ip.interpolate(0.9, 1.0, 1.0));
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet or if a point is out of bounds with [`OutOfBounds::Error`], which reports the first one in the order of the values.
+    pub fn interpolate_grid(&self, xq: &[f64], yq: &[f64], zq: &[f64]) -> Vec<f64> {
+        let queries = match self.locate_grid(xq, yq, zq) {
+            Ok(queries) => queries,
+            Err(e) => panic!("{}", e)
+        };
+
+        let mut values = vec![0.0; xq.len()*yq.len()*zq.len()];
+        if !values.is_empty() {
+            for (a, slab) in values.chunks_mut(yq.len()*zq.len()).enumerate() {
+                self.fill_grid_slab(&queries, a, slab);
+            }
+        }
+
+        values
+    }
+
+    /// Locates every query coordinate of [`Self::interpolate_grid()`] on its axis and counts the queries for [`Self::query_stats()`], or returns the error of the first point out of bounds with [`OutOfBounds::Error`]
+    pub(crate) fn locate_grid(&self, xq: &[f64], yq: &[f64], zq: &[f64]) -> Result<[Vec<AxisQuery>; 3], Interp3DError> {
+        assert!(!self.data.is_empty(), "Interpolator has not been set up");
+
+        let queries = [(Dir::X, xq), (Dir::Y, yq), (Dir::Z, zq)].map(|(dir, q)| {
+            q.iter().map(|&p| {
+                let (p, bound) = self.bound_axis(dir, self.transform[dir as usize].forward(p));
+                let range = Self::range(self.axis(dir));
+                let p = if bound == AxisBound::Beyond && self.out_of_bounds == OutOfBounds::ClampToEdge { p.clamp(range.0, range.1) } else { p };
+                let located = kernel::locate(self.axis(dir), p);
+                AxisQuery { located, weights: self.weights(dir, located.0, located.1), bound }
+            }).collect::<Vec<_>>()
+        });
+
+        // a point is beyond if one of its coordinates is, and within the tolerance if none is but one was moved
+        let count = |bounds: &[AxisBound]| queries.iter().map(|q| q.iter().filter(|q| bounds.contains(&q.bound)).count() as u64).product::<u64>();
+        let total = count(&[AxisBound::Inside, AxisBound::WithinTolerance, AxisBound::Beyond]);
+        let (not_beyond, inside) = (count(&[AxisBound::Inside, AxisBound::WithinTolerance]), count(&[AxisBound::Inside]));
+
+        if self.out_of_bounds == OutOfBounds::Error && not_beyond < total {
+            // the first point in row-major order with a coordinate beyond the domain
+            let [bx, by, bz] = queries.each_ref().map(|q| q.iter().position(|q| q.bound == AxisBound::Beyond));
+            let (a, b, c) = match (bx, by, bz) {
+                (Some(0), _, _) | (_, Some(0), _) => (0, 0, 0),
+                (_, _, Some(c)) => (0, 0, c),
+                (_, Some(b), None) => (0, b, 0),
+                (a, None, None) => (a.unwrap_or(0), 0, 0)
+            };
+            let (x, y, z) = self.to_grid(xq[a], yq[b], zq[c]);
+            self.try_bounded(x, y, z)?;
+        }
+        self.counters.out_of_range.fetch_add(total - not_beyond, Ordering::Relaxed);
+        self.counters.within_tolerance.fetch_add(not_beyond - inside, Ordering::Relaxed);
+
+        Ok(queries)
+    }
+
+    /// Evaluates the slab ``a`` of [`Self::interpolate_grid()`], i.e. all points with the coordinate ``xq[a]``, into ``slab``
+    pub(crate) fn fill_grid_slab(&self, [qx, qy, qz]: &[Vec<AxisQuery>; 3], a: usize, slab: &mut [f64]) {
+        let x = &qx[a];
+        // the schemes whose weights are the product of the 1d ones take the precomputed weights, the others their usual path
+        let separable = self.hermite().is_none() && matches!(self.scheme, Type::Tricubic | Type::BicubicUnilinear(_));
+        let nan = self.out_of_bounds == OutOfBounds::ReturnNan;
+
+        for (y, row) in qy.iter().zip(slab.chunks_mut(qz.len())) {
+            for (z, value) in qz.iter().zip(row) {
+                *value = if nan && [x.bound, y.bound, z.bound].contains(&AxisBound::Beyond) {
+                    f64::NAN
+                } else if separable {
+                    self.stencil_sum(&self.data, (x.located.0, y.located.0, z.located.0), &x.weights, &y.weights, &z.weights)
+                } else {
+                    self.evaluate((x.located, y.located, z.located))
+                };
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Contains the interpolator that only evaluates the closure at the nodes the queries actually need.
+
+use crate::{Interp3D, Interp3DError, DataGenConf, Type, OutOfBounds, QueryStats, GHOST_LO};
+
+/// An interpolator whose samples are generated on demand, for exploring a function interactively when only a small part of the domain will ever be looked at.  
+/// The grid is set up right away, but the closure is only evaluated at the nodes a query depends on (the stencil of its cell, see [`Interp3D::stencil_nodes()`]) the first time they are needed, and the samples are kept for later queries. So the cost scales with the region that is visited instead of the whole domain.
+/// 
+/// The results are identical to the ones of an [`Interp3D`] generated from the same closure and config with the same settings. [`Self::materialize()`] fills the remaining nodes and returns that interpolator, e.g. to export it.  
+/// Since queries change the interpolator, they take ``&mut self``. To share it between threads, put it behind a ``Mutex`` (or materialize it).
+/// 
+/// # Example
+/// 
+/// ```
+/// use crate::interp3d::*;
+/// 
+/// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 101).build();
+/// let mut lazy = LazyInterp3D::new(|x, y, z| (x*y).sin() + z, &config);
+/// 
+/// let value = lazy.interpolate(0.123, 0.456, 0.789);
+/// assert_eq!(lazy.evaluated_nodes(), 64);
+/// 
+/// let ip = lazy.materialize();
+/// assert_eq!(ip.interpolate(0.123, 0.456, 0.789), value);
+/// ```
+pub struct LazyInterp3D<F> {
+    ip: Interp3D,
+    f: F,
+    /// whether the sample of each interior node has been generated, in storage order
+    known: Vec<bool>,
+    evaluated: usize
+}
+
+impl<F> LazyInterp3D<F>
+where F: FnMut(f64, f64, f64) -> f64 {
+    /// Sets up the grid described by ``conf`` without evaluating ``f`` anywhere yet. The interpolator starts out with the default settings of [`Interp3D`].
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the config is invalid, see [`Self::try_new()`] for a version that returns the error instead.
+    pub fn new(f: F, conf: &DataGenConf) -> Self {
+        match Self::try_new(f, conf) {
+            Ok(lazy) => lazy,
+            Err(e) => panic!("{}", e)
+        }
+    }
+
+    /// Same as [`Self::new()`], but an invalid config is returned as an error instead of panicking.
+    pub fn try_new(f: F, conf: &DataGenConf) -> Result<Self, Interp3DError> {
+        let mut ip = Interp3D::default();
+        ip.setup(conf)?;
+        // the ghost cells are computed from the interior nodes on every query, since those keep changing
+        ip.stale_ghosts = true;
+        let known = vec![false; ip.iter().len()];
+
+        Ok(LazyInterp3D { ip, f, known, evaluated: 0 })
+    }
+
+    /// Sets the interpolation scheme, see [`Interp3D::set_type()`]
+    pub fn set_type(&mut self, scheme: Type) {
+        self.ip.set_type(scheme);
+    }
+
+    /// Sets the out of bounds policy, see [`Interp3D::set_out_of_bounds()`]
+    pub fn set_out_of_bounds(&mut self, policy: OutOfBounds) {
+        self.ip.set_out_of_bounds(policy);
+    }
+
+    /// Sets the tolerance for points just outside of the domain, see [`Interp3D::set_out_of_bounds_tolerance()`]
+    pub fn set_out_of_bounds_tolerance(&mut self, rel: f64, abs: f64) {
+        self.ip.set_out_of_bounds_tolerance(rel, abs);
+    }
+
+    /// Returns how many queries were outside of the domain, see [`Interp3D::query_stats()`]
+    pub fn query_stats(&self) -> QueryStats {
+        self.ip.query_stats()
+    }
+
+    /// Returns the number of nodes the closure has been evaluated at so far
+    pub fn evaluated_nodes(&self) -> usize {
+        self.evaluated
+    }
+
+    /// Generates the samples of the nodes in the stencil of the cells found by [`Interp3D::locate()`] that are still missing
+    fn fill_stencil(&mut self, located: ((usize, f64), (usize, f64), (usize, f64))) {
+        let (_, ny, nz) = self.ip.dims();
+        let [xs, ys, zs] = self.ip.stencil_sources(located);
+        for &i in &xs {
+            for &j in &ys {
+                for &k in &zs {
+                    self.fill(i*ny*nz + j*nz + k, (i, j, k));
+                }
+            }
+        }
+    }
+
+    /// Generates the sample of the node ``(i, j, k)`` (as for [`Interp3D::value_at()`]), which is the ``n``-th interior node, if it is still missing
+    fn fill(&mut self, n: usize, (i, j, k): (usize, usize, usize)) {
+        if self.known[n] {
+            return;
+        }
+
+        let (i, j, k) = (i+GHOST_LO, j+GHOST_LO, k+GHOST_LO);
+        let (x, y, z) = self.ip.physical(i, j, k);
+        let value = (self.f)(x, y, z);
+        let index = self.ip.index(i, j, k);
+        self.ip.data.set(index, value);
+        self.known[n] = true;
+        self.evaluated += 1;
+    }
+
+    /// Interpolates at ``(x, y, z)``, generating the samples it needs first, see [`Interp3D::interpolate()`].
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the point is out of bounds with [`OutOfBounds::Error`].
+    pub fn interpolate(&mut self, x: f64, y: f64, z: f64) -> f64 {
+        match self.try_interpolate(x, y, z) {
+            Ok(value) => value,
+            Err(e) => panic!("{}", e)
+        }
+    }
+
+    /// Same as [`Self::interpolate()`], but returns an error instead of panicking.
+    pub fn try_interpolate(&mut self, x: f64, y: f64, z: f64) -> Result<f64, Interp3DError> {
+        let (x, y, z) = self.ip.to_grid(x, y, z);
+        let Some((x, y, z)) = self.ip.try_bounded(x, y, z)? else {
+            return Ok(f64::NAN);
+        };
+
+        let located = self.ip.locate(x, y, z);
+        self.fill_stencil(located);
+        Ok(self.ip.evaluate(located))
+    }
+
+    /// Generates the samples of all nodes that are still missing and returns the finished interpolator, which is the same as the one of [`Interp3D::from_config()`] with the settings of ``self``.
+    pub fn materialize(mut self) -> Interp3D {
+        let (_, ny, nz) = self.ip.dims();
+        for n in 0..self.known.len() {
+            self.fill(n, (n/(ny*nz), n/nz % ny, n % nz));
+        }
+        self.ip.set_data_outermost();
+
+        self.ip
+    }
+}
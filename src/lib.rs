@@ -1,34 +1,188 @@
 //! # interp3d
 //! 
 //! This crate introduces a struct that can interpolate a 3d arbitrarily spaced data set.
+//! 
+//! The easiest way to set one up is the [`Interp3DBuilder`], which takes the grid and all settings and then generates the data with a closure (or reads it from a file):
+//! 
+//! ```
+//! use interp3d::*;
+//! 
+//! let ip = Interp3DBuilder::default()
+//!     .x_axis(0.0, 1.0, 20, GridSpacing::Linear)
+//!     .y_axis(0.0, 1.0, 20, GridSpacing::Linear)
+//!     .z_axis(1.0, 100.0, 30, GridSpacing::Logarithmic)
+//!     .out_of_bounds(OutOfBounds::Error)
+//!     .generate(|x, y, z| x + y*z.ln())
+//!     .unwrap();
+//! 
+//! assert!((ip.interpolate(0.5, 0.5, 10.0) - (0.5 + 0.5*10f64.ln())).abs() < 1e-4);
+//! assert!(ip.try_interpolate(0.5, 0.5, 200.0).is_err());
+//! ```
 
 mod utils;
-
-use crate::utils::Dir;
+mod error;
+mod kernel;
+mod samples;
+mod io;
+mod npz;
+mod vector;
+mod slice;
+mod analysis;
+mod adaptive;
+mod compiled;
+mod builder;
+mod index;
+mod nodes;
+mod hessian;
+mod hermite;
+mod inverse;
+mod lazy;
+mod smooth;
+mod masked;
+mod series;
+mod sanitize;
+mod compact;
+mod sampling;
+mod lattice;
+#[cfg(feature = "simd")]
+mod simd;
+#[cfg(feature = "parallel")]
+mod parallel;
 
 pub use crate::utils::{
+    Dir,
     GridSpacing,
     DataGenConfSingle,
     DataGenConf,
-    Type
+    DataGenConfBuilder,
+    BoundaryCondition,
+    OutOfBounds,
+    TimeBounds,
+    AxisTransform,
+    SmoothingKernel,
+    FillStrategy,
+    Type,
+    Storage,
+    QuantizeSpec,
+    FloatFormat,
+    LineEnding,
+    ExportOptions,
+    FileFormat
 };
 
-use std::f64::consts::LN_2;
+pub use crate::error::{Interp3DError, GenerateError};
+pub use crate::vector::Interp3DVec;
+pub use crate::slice::Slice2D;
+pub use crate::analysis::{ErrorReport, DataStats, DiffReport};
+pub use crate::compiled::CompiledInterp3D;
+pub use crate::builder::Interp3DBuilder;
+pub use crate::nodes::{GridNode, QueryDetail};
+pub use crate::lazy::LazyInterp3D;
+pub use crate::series::Interp3DSeries;
+pub use crate::sanitize::SanitizeReport;
+pub use crate::compact::CompactInterp3D;
+
+use crate::kernel::Kernel;
+use crate::samples::Samples;
+
+use std::f64::consts::{LN_2, PI};
+use std::ops::Range;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Number of ghost cells in front of the first interior node of every axis.
+const GHOST_LO: usize = 1;
+/// Number of ghost cells behind the last interior node of every axis.
+const GHOST_HI: usize = 2;
+/// Total number of ghost cells per axis. The cubic stencil needs one neighbour on either side of a cell, the additional upper cell keeps queries right at ``max`` inside the padded grid.
+const GHOST: usize = GHOST_LO + GHOST_HI;
 
 /// This is the main interpolator struct.  
 /// Will need to be set up before use. Either generate data (see function [`Self::generate_data()`]) or load from file (see function [`Self::import_data()`]).
-#[derive(Default)]
+/// 
+/// All queries take ``&self`` and keep their intermediate results on the stack, so an interpolator is ``Send + Sync`` and can be queried from many threads at once (e.g. shared by reference across a thread pool). The only state that carries over between queries is an explicit [`QueryCache`], which each thread owns itself, and the atomic counters of [`Self::query_stats()`].  
+/// Cloning is cheap, the clones share the grid and the samples until one of them changes them, which then copies them first. So a clone per worker thread costs no more memory than sharing one interpolator by reference.
+#[derive(Default, Clone)]
 pub struct Interp3D {
     nx: usize,
     ny: usize,
     nz: usize,
-    x: Vec<f64>,
-    y: Vec<f64>,
-    z: Vec<f64>,
-    tx: (f64, f64),
-    ty: (f64, f64),
-    tz: (f64, f64),
-    data: Vec<f64>
+    // the axes, the samples, the derivatives and the mask are shared between clones until one of them changes them
+    x: Arc<[f64]>,
+    y: Arc<[f64]>,
+    z: Arc<[f64]>,
+    data: Samples,
+    conf: Option<DataGenConf>,
+    boundary: [BoundaryCondition; 3],
+    out_of_bounds: OutOfBounds,
+    /// ``(rel, abs)`` of [`Self::set_out_of_bounds_tolerance()`]
+    tolerance: (f64, f64),
+    /// the queries outside of the domain, see [`Self::query_stats()`]
+    counters: QueryCounters,
+    periodic: [bool; 3],
+    transform: [AxisTransform; 3],
+    scheme: Type,
+    /// set by writes through [`IndexMut`](std::ops::IndexMut), which can't refresh the ghost cells themselves. Until the next refresh, the reads of the queries compute the ghost cells on the fly.
+    stale_ghosts: bool,
+    /// the derivatives of every interior node (in storage order) supplied by [`Self::generate_data_hermite()`], see [`hermite::ORDERS`] for their order
+    hermite: Option<Arc<[[f64; 7]]>>,
+    /// whether the closure of [`Self::generate_data_masked()`] had a value at every interior node (in storage order), ``None`` if all nodes are valid
+    mask: Option<Arc<[bool]>>
+}
+
+/// Remembers the cell of the previous query along each axis, see [`Interp3D::interpolate_cached()`].  
+/// Successive queries usually move slowly through space, so the next point is most likely in the same cell or a neighbouring one. Those are checked first, the binary search over the axis is only needed if that fails.
+/// 
+/// The cache is owned by the caller and only holds indices, so the interpolator itself stays immutable during queries and can be shared between threads. Use one cache per thread (or per trajectory). A cache that was used with another interpolator is harmless, the hint is just checked and discarded.
+#[derive(Copy, Clone, Debug)]
+pub struct QueryCache {
+    cell: (usize, usize, usize)
+}
+
+impl Default for QueryCache {
+    fn default() -> Self {
+        QueryCache { cell: (GHOST_LO, GHOST_LO, GHOST_LO) }
+    }
+}
+
+/// Where a query coordinate lies with respect to its axis, see [`Interp3D::bound_axis()`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum AxisBound {
+    /// inside of the sampled range (or on a periodic axis)
+    Inside,
+    /// outside, but close enough to be moved onto the edge, see [`Interp3D::set_out_of_bounds_tolerance()`]
+    WithinTolerance,
+    /// outside, the out of bounds policy decides
+    Beyond
+}
+
+/// How many queries of an interpolator fell outside of its domain, see [`Interp3D::query_stats()`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct QueryStats {
+    /// queries within the tolerance of the domain (see [`Interp3D::set_out_of_bounds_tolerance()`]), which were moved onto its boundary
+    pub within_tolerance: u64,
+    /// queries beyond the tolerance, which the out of bounds policy took care of
+    pub out_of_range: u64
+}
+
+/// The atomic counters behind [`QueryStats`]. Clones start with the counts of the original and count on their own from then on.
+#[derive(Default, Debug)]
+struct QueryCounters {
+    within_tolerance: AtomicU64,
+    out_of_range: AtomicU64
+}
+
+impl Clone for QueryCounters {
+    fn clone(&self) -> Self {
+        let stats = self.get();
+        QueryCounters { within_tolerance: AtomicU64::new(stats.within_tolerance), out_of_range: AtomicU64::new(stats.out_of_range) }
+    }
+}
+
+impl QueryCounters {
+    fn get(&self) -> QueryStats {
+        QueryStats { within_tolerance: self.within_tolerance.load(Ordering::Relaxed), out_of_range: self.out_of_range.load(Ordering::Relaxed) }
+    }
 }
 
 impl Interp3D {
@@ -36,91 +190,271 @@ impl Interp3D {
         i*self.ny*self.nz + j*self.nz + k
     }
 
-    fn grid_point_pos(dir: Dir, i: isize, conf: &DataGenConf) -> f64 {
-        let conf: DataGenConfSingle = match dir {
-            Dir::X => conf.x,
-            Dir::Y => conf.y,
-            Dir::Z => conf.z
+    /// Storage index of the interior node ``(i, j, k)`` as counted by [`Self::value_at()`], panics with the dims if it's out of range
+    fn node_index(&self, (i, j, k): (usize, usize, usize)) -> usize {
+        let (nx, ny, nz) = self.dims();
+        assert!(i < nx && j < ny && k < nz, "Node ({}, {}, {}) is out of range, the grid has {}x{}x{} nodes", i, j, k, nx, ny, nz);
+
+        self.index(i+GHOST_LO, j+GHOST_LO, k+GHOST_LO)
+    }
+
+    /// Iterates over the indices of all interior (non-ghost) nodes in storage order.
+    fn interior(&self) -> impl Iterator<Item = (usize, usize, usize)> {
+        let (nx, ny, nz) = (self.nx, self.ny, self.nz);
+        (GHOST_LO..nx.saturating_sub(GHOST_HI)).flat_map(move |i| {
+            (GHOST_LO..ny.saturating_sub(GHOST_HI)).flat_map(move |j| {
+                (GHOST_LO..nz.saturating_sub(GHOST_HI)).map(move |k| (i, j, k))
+            })
+        })
+    }
+
+    /// Position of the interior node ``i`` (``0..n``) along ``dir``. The nodes run from ``min`` to ``max`` with the relative coordinate ``t = i/(n - 1)``, the ghost cells are added by [`Self::pad_axes()`].
+    fn grid_point_pos(dir: Dir, i: usize, conf: &DataGenConf) -> f64 {
+        let conf: &DataGenConfSingle = match dir {
+            Dir::X => &conf.x,
+            Dir::Y => &conf.y,
+            Dir::Z => &conf.z
         };
 
+        let t = (i as f64)/((conf.n - 1) as f64);
         match conf.spacing {
-            GridSpacing::Exponential(k) if k != 0.0 => conf.min + (conf.max-conf.min) * ( (LN_2*(i as f64)/((conf.n as isize - 4) as f64)*k).exp() ) / (2.0f64.powf(k)-1.0),
-            _ => conf.min+(conf.max-conf.min)*(i as f64)/((conf.n as isize - 4) as f64)
+            GridSpacing::Custom(ref nodes) => nodes[i],
+            GridSpacing::Logarithmic => conf.min*(conf.max/conf.min).powf(t),
+            // (1 - cos(pi*t))/2 written as sin^2, which is more precise close to min
+            GridSpacing::Chebyshev => conf.min + (conf.max-conf.min)*(0.5*PI*t).sin().powi(2),
+            GridSpacing::Exponential(k) => Self::exponential_pos(k, t, conf.min, conf.max),
+            GridSpacing::TwoSidedExponential(k) => {
+                // both halves are exponential grids with their dense end at min and max respectively,
+                // the upper one being the mirror image of the lower one
+                let mid = 0.5*(conf.min + conf.max);
+                if t <= 0.5 {
+                    Self::exponential_pos(k, 2.0*t, conf.min, mid)
+                } else {
+                    Self::exponential_pos(-k, 2.0*t - 1.0, mid, conf.max)
+                }
+            },
+            GridSpacing::Linear => conf.min+(conf.max-conf.min)*t
+        }
+    }
+
+    /// Position at the relative coordinate ``t`` of an exponential grid with parameter ``k`` (see [`DataGenConfSingle`]) between ``min`` and ``max``
+    fn exponential_pos(k: f64, t: f64, min: f64, max: f64) -> f64 {
+        // (2^(k*t) - 1)/(2^k - 1), rearranged such that nothing overflows for large |k|
+        // and measured from the dense end of the range, where the precision is needed
+        let a = LN_2*k;
+        if a > 0.0 {
+            min + (max-min) * ((a*(t - 1.0)).exp() - (-a).exp()) / -(-a).exp_m1()
+        } else if a < 0.0 {
+            max - (max-min) * ((a*t).exp() - a.exp()) / -a.exp_m1()
+        } else {
+            min+(max-min)*t
         }
     }
 
-    fn setup(&mut self, conf: &DataGenConf) {
-        self.nx = conf.x.n+3;
-        self.ny = conf.y.n+3;
-        self.nz = conf.z.n+3;
+    /// Returns an interpolator without any data, but with the same settings (boundary condition, out of bounds policy, periodicity, axis transforms, scheme and storage) as ``self``.
+    fn settings_only(&self) -> Interp3D {
+        Interp3D { boundary: self.boundary, out_of_bounds: self.out_of_bounds, tolerance: self.tolerance, periodic: self.periodic, transform: self.transform, scheme: self.scheme, data: Samples::zeros(self.data.storage(), 0), ..Default::default() }
+    }
+
+    /// Returns a copy of the grid, the config and the settings of ``self`` that holds ``data`` (with the layout of ``self.data``) as its samples.
+    fn with_samples(&self, data: Samples) -> Interp3D {
+        Interp3D {
+            nx: self.nx,
+            ny: self.ny,
+            nz: self.nz,
+            x: self.x.clone(),
+            y: self.y.clone(),
+            z: self.z.clone(),
+            data,
+            conf: self.conf.clone(),
+            ..self.settings_only()
+        }
+    }
 
-        if self.nx < 2+3 || self.ny < 2+3 || self.nz < 2+3 {
-            panic!("Number of points too low (at least 2 per direction required)");
+    /// Fills the ghost cells of an axis, by wrapping around for periodic axes and by linear extension otherwise.
+    fn fill_axis(v: &mut [f64], periodic: bool) {
+        if periodic {
+            Self::wrap_axis(v);
+        } else {
+            Self::extend_axis(v);
         }
+    }
 
-        self.x = Vec::with_capacity(self.nx);
-        self.y = Vec::with_capacity(self.ny);
-        self.z = Vec::with_capacity(self.nz);
-        for i in 0..self.nx {
-            self.x.push(Self::grid_point_pos(Dir::X, i as isize - 1, &conf));
+    /// Continues the interior node positions of an axis periodically into its ghost cells.  
+    /// The last interior node is the same as the first one shifted by one period, so the ghost cell behind it mirrors the second node and so on.
+    fn wrap_axis(v: &mut [f64]) {
+        let n = v.len();
+        let (first, last) = (GHOST_LO, n-1-GHOST_HI);
+        let (cells, period) = ((last - first) as isize, v[last] - v[first]);
+        for g in (0..GHOST_LO).chain(n-GHOST_HI..n) {
+            let offset = g as isize - first as isize;
+            v[g] = v[first + offset.rem_euclid(cells) as usize] + offset.div_euclid(cells) as f64*period;
+        }
+    }
+
+    /// Extends the interior node positions of an axis linearly into its ghost cells.
+    fn extend_axis(v: &mut [f64]) {
+        let n = v.len();
+        for g in (0..GHOST_LO).rev() {
+            v[g] = 2.0*v[g+1] - v[g+2];
+        }
+        for g in n-GHOST_HI..n {
+            v[g] = 2.0*v[g-1] - v[g-2];
+        }
+    }
+
+    /// Checks that an axis has at least 2 nodes which are finite and strictly increasing.
+    fn check_axis(dir: Dir, v: &[f64]) -> Result<(), Interp3DError> {
+        if v.len() < 2 {
+            return Err(Interp3DError::TooFewPoints { dir, n: v.len() });
+        }
+
+        let not_finite = v.iter().position(|p| !p.is_finite());
+        let not_increasing = v.windows(2).position(|w| w[0] >= w[1]);
+        match not_finite.into_iter().chain(not_increasing).min() {
+            Some(index) => Err(Interp3DError::NonMonotonicAxis { dir, index }),
+            None => Ok(())
+        }
+    }
+
+    /// Sets up the axes from the interior node positions, including the ghost cells, and allocates the data.  
+    /// The data is left at 0 and no config is attached.
+    fn setup_axes(&mut self, x: &[f64], y: &[f64], z: &[f64]) -> Result<(), Interp3DError> {
+        self.pad_axes(x, y, z)?;
+
+        self.data = Samples::zeros(self.data.storage(), self.nx*self.ny*self.nz);
+        self.conf = None;
+        self.hermite = None;
+        self.mask = None;
+
+        Ok(())
+    }
+
+    /// The part of [`Self::setup_axes()`] that only sets the axes and the dimensions, the data is left as it is
+    fn pad_axes(&mut self, x: &[f64], y: &[f64], z: &[f64]) -> Result<(), Interp3DError> {
+        Self::check_axis(Dir::X, x)?;
+        Self::check_axis(Dir::Y, y)?;
+        Self::check_axis(Dir::Z, z)?;
+
+        let pad = |v: &[f64], periodic: bool| {
+            let mut padded = vec![0.0; v.len()+GHOST];
+            padded[GHOST_LO..GHOST_LO+v.len()].copy_from_slice(v);
+            Self::fill_axis(&mut padded, periodic);
+            padded
+        };
+        self.x = pad(x, self.periodic[0]).into();
+        self.y = pad(y, self.periodic[1]).into();
+        self.z = pad(z, self.periodic[2]).into();
+        self.nx = self.x.len();
+        self.ny = self.y.len();
+        self.nz = self.z.len();
+
+        Ok(())
+    }
+
+    /// Sets up a new interpolator with the given axes whose data is then filled in one sample after the other (in storage order) by ``next``.  
+    /// The settings are taken over from ``template``, see [`Self::settings_only()`].
+    fn from_parts<F>(x: &[f64], y: &[f64], z: &[f64], template: &Interp3D, mut next: F) -> Result<Interp3D, Interp3DError>
+    where F: FnMut() -> Result<f64, Interp3DError> {
+        let mut ip = template.settings_only();
+        ip.setup_axes(x, y, z)?;
+        let mut data = std::mem::take(&mut ip.data);
+        let mut samples = data.writer();
+        for (i, j, k) in ip.interior() {
+            samples.set(ip.index(i, j, k), next()?);
         }
-        for i in 0..self.ny {
-            self.y.push(Self::grid_point_pos(Dir::Y, i as isize - 1, &conf));
+        ip.data = data;
+        ip.set_data_outermost();
+
+        Ok(ip)
+    }
+
+    /// Sets up the grid described by ``conf``. On error the interpolator is left unchanged.
+    fn setup(&mut self, conf: &DataGenConf) -> Result<(), Interp3DError> {
+        conf.validate()?;
+
+        let axis = |dir: Dir, n: usize| -> Vec<f64> {
+            (0..n).map(|i| Self::grid_point_pos(dir, i, conf)).collect()
+        };
+        self.setup_axes(&axis(Dir::X, conf.x.n), &axis(Dir::Y, conf.y.n), &axis(Dir::Z, conf.z.n))?;
+
+        self.conf = Some(conf.clone());
+        Ok(())
+    }
+
+    /// Maps an index along the axis ``d`` (ghost cells included) onto the interior nodes its value is computed from, as ``(node, weight)`` pairs.  
+    /// Interior indices map onto themselves. All conditions but [`BoundaryCondition::LinearExtrapolate`] only need one node, the second weight is 0 then.
+    fn ghost_sources(&self, d: usize, i: usize) -> [(usize, f64); 2] {
+        let v = [&self.x, &self.y, &self.z][d];
+        let (first, last) = (GHOST_LO, v.len()-1-GHOST_HI);
+        let single = |node: usize, factor: f64| [(node, factor), (node, 0.0)];
+        if (first..=last).contains(&i) {
+            return single(i, 1.0);
         }
-        for i in 0..self.nz {
-            self.z.push(Self::grid_point_pos(Dir::Z, i as isize - 1, &conf));
+        if self.periodic[d] {
+            let offset = i as isize - first as isize;
+            return single(first + offset.rem_euclid((last - first) as isize) as usize, 1.0);
         }
-        
-        self.data = Vec::with_capacity(self.nx*self.ny*self.nz);
-        for _ in 0..self.nx*self.ny*self.nz {
-            self.data.push(0.0);
+
+        match self.boundary[d] {
+            BoundaryCondition::Copy => single(i.clamp(first, last), 1.0),
+            BoundaryCondition::Reflect { even } => {
+                let mirrored = if i < first { 2*first - i } else { (2*last).saturating_sub(i) };
+                single(mirrored.clamp(first, last), if even { 1.0 } else { -1.0 })
+            },
+            BoundaryCondition::LinearExtrapolate => {
+                // the line through the outermost node and its neighbour, evaluated at the ghost node
+                let (near, other) = if i < first { (first, first+1) } else { (last, last-1) };
+                let s = (v[i] - v[near])/(v[other] - v[near]);
+                [(near, 1.0 - s), (other, s)]
+            }
         }
+    }
 
-        self.x[0] = 2.0*self.x[1] - self.x[2];
-        self.x[self.nx-2] = 2.0*self.x[self.nx-3] - self.x[self.nx-4];
-        self.x[self.nx-1] = 2.0*self.x[self.nx-2] - self.x[self.nx-3];
-        
-        self.y[0] = 2.0*self.y[1] - self.y[2];
-        self.y[self.ny-2] = 2.0*self.y[self.ny-3] - self.y[self.ny-4];
-        self.y[self.ny-1] = 2.0*self.y[self.ny-2] - self.y[self.ny-3];
+    /// Value of the node ``(i, j, k)`` of ``data`` (which has the layout of ``self.data``) computed from its sources along each direction, see [`Self::ghost_sources()`]
+    fn ghost_value(&self, data: &Samples, i: usize, j: usize, k: usize) -> f64 {
+        let (si, sj, sk) = (self.ghost_sources(0, i), self.ghost_sources(1, j), self.ghost_sources(2, k));
+        let mut sum = 0.0;
+        for &(i, wi) in si.iter().filter(|s| s.1 != 0.0) {
+            for &(j, wj) in sj.iter().filter(|s| s.1 != 0.0) {
+                for &(k, wk) in sk.iter().filter(|s| s.1 != 0.0) {
+                    sum += wi*wj*wk*data.get(self.index(i, j, k));
+                }
+            }
+        }
 
-        self.z[0] = 2.0*self.z[1] - self.z[2];
-        self.z[self.nz-2] = 2.0*self.z[self.nz-3] - self.z[self.nz-4];
-        self.z[self.nz-1] = 2.0*self.z[self.nz-2] - self.z[self.nz-3];
+        sum
     }
 
+    /// Fills the ghost cells according to the boundary condition along each direction (see [`BoundaryCondition`]).  
+    /// Every ghost cell is computed from interior nodes only and only the padding nodes are written, so this also works for the minimal grid (``n = 2`` per direction) where the lower and upper ghost cells lie right next to each other.
     fn set_data_outermost(&mut self) {
+        let mut data = std::mem::take(&mut self.data);
+        self.fill_ghosts(&mut data);
+        self.data = data;
+        self.stale_ghosts = false;
+    }
+
+    /// Fills the ghost cells of ``data``, which has the layout of ``self.data`` (see [`Self::set_data_outermost()`])
+    fn fill_ghosts(&self, data: &mut Samples) {
+        debug_assert_eq!(data.len(), self.nx*self.ny*self.nz);
+
+        let interior = |i: usize, n: usize| (GHOST_LO..n-GHOST_HI).contains(&i);
+        let mut ghosts = Vec::with_capacity(data.len() - self.iter().len());
         for i in 0..self.nx {
             for j in 0..self.ny {
                 for k in 0..self.nz {
-                    let mut i_temp = i;
-                    if i_temp == 0 {
-                        i_temp = 1;
-                    } else if i_temp > self.nx-3 {
-                        i_temp = self.nx-3;
-                    }
-
-                    let mut j_temp = j;
-                    if j_temp == 0 {
-                        j_temp = 1;
-                    } else if j_temp > self.nx-3 {
-                        j_temp = self.ny-3;
-                    }
-
-                    let mut k_temp = k;
-                    if k_temp == 0 {
-                        k_temp = 1;
-                    } else if k_temp > self.nz-3 {
-                        k_temp = self.nz-3;
-                    }
-
-                    if i != i_temp || j != j_temp || k != k_temp {
-                        let index = self.index(i, j, k);
-                        self.data[index] = self.data[self.index(i_temp, j_temp, k_temp)];
+                    if !interior(i, self.nx) || !interior(j, self.ny) || !interior(k, self.nz) {
+                        ghosts.push((self.index(i, j, k), self.ghost_value(data, i, j, k)));
                     }
                 }
             }
         }
+
+        let mut samples = data.writer();
+        for (index, value) in ghosts {
+            samples.set(index, value);
+        }
     }
 
     /// Use this to generate new data for the interpolator.
@@ -142,8 +476,8 @@ impl Interp3D {
     /// };
     /// // using the same config for all 3 directions
     /// let config = DataGenConf {
-    ///     x: config,
-    ///     y: config,
+    ///     x: config.clone(),
+    ///     y: config.clone(),
     ///     z: config
     /// };
     /// 
@@ -155,20 +489,107 @@ impl Interp3D {
     /// ip.generate_data(f, &config);
     /// // ip is now set up for use
     /// ```
-    pub fn generate_data<F>(&mut self, mut f: F, conf: &DataGenConf/*, monitor_progress: bool*/)
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the config is invalid, see [`Self::try_generate_data()`] for a version that returns the error instead.
+    pub fn generate_data<F>(&mut self, f: F, conf: &DataGenConf)
     where F: FnMut(f64, f64, f64) -> f64 {
-        self.setup(&conf);
+        if let Err(e) = self.try_generate_data(f, conf) {
+            panic!("{}", e);
+        }
+    }
 
-        //MARK: -add multithreading
-        for i in 1..self.nx-2 {
-            for j in 1..self.ny-2 {
-                for k in 1..self.nz-2 {
-                    let index = self.index(i, j, k);
-                    self.data[index] = f(self.x[i], self.y[j], self.z[k]);
-                }
+    /// Same as [`Self::generate_data()`], but an invalid config is returned as an error (e.g. [`Interp3DError::TooFewPoints`] naming the offending direction) instead of panicking.  
+    /// In that case the closure is never called and the interpolator is left unchanged.
+    pub fn try_generate_data<F>(&mut self, f: F, conf: &DataGenConf) -> Result<(), Interp3DError>
+    where F: FnMut(f64, f64, f64) -> f64 {
+        self.try_generate_data_with_progress(f, conf, |_, _| ())
+    }
+
+    /// Same as [`Self::generate_data()`], but ``progress`` is called with the number of finished points and the total number of points (the actual grid, not counting the ghost cells) whenever a column of nodes along Z is done.  
+    /// This is meant to drive a progress bar or an ETA estimate while generating with expensive closures. The last call always reports ``(total, total)``.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 10).build();
+    /// let mut ip = Interp3D::default();
+    /// 
+    /// ip.generate_data_with_progress(|x, y, z| x*y*z, &config, |done, total| {
+    ///     println!("{:.1}%", 100.0*done as f64/total as f64);
+    /// });
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the config is invalid, like [`Self::generate_data()`].
+    pub fn generate_data_with_progress<F, P>(&mut self, f: F, conf: &DataGenConf, progress: P)
+    where F: FnMut(f64, f64, f64) -> f64, P: FnMut(usize, usize) {
+        if let Err(e) = self.try_generate_data_with_progress(f, conf, progress) {
+            panic!("{}", e);
+        }
+    }
+
+    fn try_generate_data_with_progress<F, P>(&mut self, mut f: F, conf: &DataGenConf, mut progress: P) -> Result<(), Interp3DError>
+    where F: FnMut(f64, f64, f64) -> f64, P: FnMut(usize, usize) {
+        self.setup(conf)?;
+
+        let total = conf.x.n*conf.y.n*conf.z.n;
+        let mut done = 0;
+        let mut data = std::mem::take(&mut self.data);
+        let mut samples = data.writer();
+        for (i, j, k) in self.interior() {
+            let (x, y, z) = self.physical(i, j, k);
+            samples.set(self.index(i, j, k), f(x, y, z));
+
+            done += 1;
+            if k == self.nz-1-GHOST_HI {
+                progress(done, total);
             }
         }
+        self.data = data;
         self.set_data_outermost();
+
+        Ok(())
+    }
+
+    /// Same as [`Self::try_generate_data()`], but for closures that can fail, e.g. because they call a solver that doesn't always converge.  
+    /// Generation stops at the first error, which is returned together with the coordinates it occurred at. In that case the interpolator keeps its previous state, it never holds half generated data.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 5).build();
+    /// let mut ip = Interp3D::default();
+    /// 
+    /// let result = ip.generate_data_fallible(|x, y, z| if x + y + z < 2.9 { Ok(x*y*z) } else { Err("no convergence") }, &config);
+    /// match result {
+    ///     Err(GenerateError::Closure { x, y, z, error }) => println!("failed at ({}, {}, {}): {}", x, y, z, error),
+    ///     _ => unreachable!()
+    /// }
+    /// assert!(ip.config().is_none());
+    /// ```
+    pub fn generate_data_fallible<F, E>(&mut self, mut f: F, conf: &DataGenConf) -> Result<(), GenerateError<E>>
+    where F: FnMut(f64, f64, f64) -> Result<f64, E> {
+        let mut ip = self.settings_only();
+        ip.setup(conf).map_err(GenerateError::Config)?;
+
+        let mut data = std::mem::take(&mut ip.data);
+        let mut samples = data.writer();
+        for (i, j, k) in ip.interior() {
+            let (x, y, z) = ip.physical(i, j, k);
+            samples.set(ip.index(i, j, k), f(x, y, z).map_err(|error| GenerateError::Closure { x, y, z, error })?);
+        }
+        ip.data = data;
+        ip.set_data_outermost();
+
+        *self = ip;
+        Ok(())
     }
 
     /// This allows a construction, similar to the example for [`Self::generate_data()`], but here we construct and set up the object directly using the passed config.
@@ -186,8 +607,8 @@ impl Interp3D {
     /// };
     /// // using the same config for all 3 directions
     /// let config = DataGenConf {
-    ///     x: config,
-    ///     y: config,
+    ///     x: config.clone(),
+    ///     y: config.clone(),
     ///     z: config
     /// };
     /// 
@@ -199,6 +620,10 @@ impl Interp3D {
     /// let ip: Interp3D = Interp3D::from_config(f, &config); 
     /// // ip is now set up for use
     /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the config is invalid, see [`Self::try_from_config()`] for a version that returns the error instead.
     pub fn from_config<F>(f: F, conf: &DataGenConf) -> Self
     where F: FnMut(f64, f64, f64) -> f64 {
         let mut ip: Interp3D = Interp3D::default();
@@ -207,27 +632,1483 @@ impl Interp3D {
         ip
     }
 
-    /// This will construct the interpolator with data read from a file directly.  
-    /// The data in the file can stem from either a previous export after data generation or you can format your own existing data for use with this interpolator.
-    /// Information on the data format can be found on [the github page](https://github.com/y-hoffmann/interp3d) and [the crates.io page](https://crates.io/crates/interp3d) for this crate.
+    /// Same as [`Self::from_config()`], but an invalid config is returned as an error instead of panicking.
+    pub fn try_from_config<F>(f: F, conf: &DataGenConf) -> Result<Self, Interp3DError>
+    where F: FnMut(f64, f64, f64) -> f64 {
+        let mut ip: Interp3D = Interp3D::default();
+        ip.try_generate_data(f, conf)?;
+
+        Ok(ip)
+    }
+
+    /// Constructs the interpolator from existing data on an arbitrary (rectilinear) grid.  
+    /// ``x``, ``y`` and ``z`` are the node positions along each direction, they need at least 2 entries each and have to be strictly increasing. The sample at ``(x[i], y[j], z[k])`` is ``data[i*y.len()*z.len() + j*z.len() + k]``, i.e. ``z`` changes fastest.  
+    /// The ghost cells the interpolation needs beyond the edges are created here, you only pass the actual grid.
     /// 
     /// # Example
+    /// 
     /// ```
     /// use crate::interp3d::*;
     /// 
-    /// let file = String::from("some/file.ip3d"); // file extension can be whatever (also nothing)
-    /// let ip = Interp3D::from_file(&file);
-    /// // ip is now set up for use
+    /// let x = [0.0, 1.0, 3.0];
+    /// let y = [0.0, 0.5];
+    /// let z = [-1.0, 1.0];
+    /// let data = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0];
+    /// 
+    /// let ip = Interp3D::from_grid_and_data(&x, &y, &z, &data).unwrap();
+    /// assert_eq!(ip.interpolate(1.0, 0.5, -1.0), 7.0);
     /// ```
-    pub fn from_file(file: &str) -> Self {
-        let ip: Interp3D = Interp3D::default();
-        //ip.import_data(file);
+    pub fn from_grid_and_data(x: &[f64], y: &[f64], z: &[f64], data: &[f64]) -> Result<Self, Interp3DError> {
+        Interp3DBuilder::default().build_from_grid_and_data(x, y, z, data)
+    }
 
-        ip
+    /// Same as [`Self::from_config()`], but for closures that can fail, see [`Self::generate_data_fallible()`].
+    pub fn from_config_fallible<F, E>(f: F, conf: &DataGenConf) -> Result<Self, GenerateError<E>>
+    where F: FnMut(f64, f64, f64) -> Result<f64, E> {
+        let mut ip: Interp3D = Interp3D::default();
+        ip.generate_data_fallible(f, conf)?;
+
+        Ok(ip)
+    }
+
+    /// Interpolates the sampled data at ``(x, y, z)``.  
+    /// With the default [`Type::Tricubic`] the cubic through the 4 surrounding nodes is used along each direction, with the slopes at the nodes estimated from their neighbours. This reproduces the samples at the nodes exactly and quadratics everywhere away from the boundary cells. See [`Self::set_type()`] for the other schemes.  
+    /// Points outside of the sampled range are handled according to [`Self::set_out_of_bounds()`], by default they are extrapolated with the polynomial of the outermost cell.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let f = |x: f64, y: f64, z: f64| ((-x*x - y*y - z*z)/5.0).exp();
+    /// let config = DataGenConfBuilder::default()
+    ///     .uniform_cube(0.0, 3.0, 31)
+    ///     .build();
+    /// let ip = Interp3D::from_config(f, &config);
+    /// 
+    /// for &(x, y, z) in &[(0.55, 1.23, 2.01), (2.9, 0.05, 1.5), (1.0, 1.0, 1.0)] {
+    ///     assert!((ip.interpolate(x, y, z) - f(x, y, z)).abs() < 1e-4);
+    /// }
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet or if the point is out of bounds with [`OutOfBounds::Error`].
+    pub fn interpolate(&self, x: f64, y: f64, z: f64) -> f64 {
+        let (x, y, z) = self.to_grid(x, y, z);
+        self.interpolate_grid_coords(x, y, z)
+    }
+
+    /// Same as [`Self::interpolate()`], but at a point in the coordinates of the grid (see [`Self::set_axis_transform()`])
+    fn interpolate_grid_coords(&self, x: f64, y: f64, z: f64) -> f64 {
+        match self.bounded(x, y, z) {
+            Some((x, y, z)) => self.evaluate(self.locate(x, y, z)),
+            None => f64::NAN
+        }
+    }
+
+    /// Same as [`Self::interpolate()`], but returns an error instead of panicking if the interpolator has not been set up or if the point is out of bounds with [`OutOfBounds::Error`].
+    pub fn try_interpolate(&self, x: f64, y: f64, z: f64) -> Result<f64, Interp3DError> {
+        let (x, y, z) = self.to_grid(x, y, z);
+        self.try_interpolate_grid_coords(x, y, z)
+    }
+
+    /// Same as [`Self::try_interpolate()`], but at a point in the coordinates of the grid (see [`Self::set_axis_transform()`])
+    fn try_interpolate_grid_coords(&self, x: f64, y: f64, z: f64) -> Result<f64, Interp3DError> {
+        if self.data.is_empty() {
+            return Err(Interp3DError::NotSetUp);
+        }
+
+        match self.try_bounded(x, y, z)? {
+            Some((x, y, z)) => Ok(self.evaluate(self.locate(x, y, z))),
+            None => Ok(f64::NAN)
+        }
+    }
+
+    /// Sets what happens to query points outside of the sampled domain (see [`OutOfBounds`]). The default is [`OutOfBounds::LinearExtrapolate`].  
+    /// This applies to all query methods. Points exactly on the boundary are always inside.
+    pub fn set_out_of_bounds(&mut self, policy: OutOfBounds) {
+        self.out_of_bounds = policy;
+    }
+
+    /// Moves query points that are just a hair outside of the domain onto its boundary, whatever the out of bounds policy (see [`Self::set_out_of_bounds()`]). E.g. an integrator that steps to ``max + 1e-12`` through rounding then gets the value at ``max`` even with [`OutOfBounds::Error`], while points further out still follow the policy.  
+    /// A coordinate beyond the edge ``e`` of its axis is within the tolerance if it is at most ``abs + rel*|e|`` away from it, in the coordinates of the grid (see [`Self::set_axis_transform()`]). The default is no tolerance at all, periodic axes are never out of bounds. See [`Self::query_stats()`] for how many queries needed the tolerance.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+    /// let mut ip = Interp3D::from_config(|x, y, z| x + y + z, &config);
+    /// ip.set_out_of_bounds(OutOfBounds::Error);
+    /// ip.set_out_of_bounds_tolerance(1e-9, 0.0);
+    /// 
+    /// assert_eq!(ip.try_interpolate(1.0 + 1e-12, 0.5, 0.5).unwrap(), ip.interpolate(1.0, 0.5, 0.5));
+    /// assert!(ip.try_interpolate(1.0 + 1e-6, 0.5, 0.5).is_err());
+    /// assert_eq!(ip.query_stats(), QueryStats { within_tolerance: 1, out_of_range: 1 });
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if a tolerance is negative or not finite.
+    pub fn set_out_of_bounds_tolerance(&mut self, rel: f64, abs: f64) {
+        assert!(rel >= 0.0 && rel.is_finite() && abs >= 0.0 && abs.is_finite(), "The tolerances need to be non-negative and finite, got rel = {} and abs = {}", rel, abs);
+        self.tolerance = (rel, abs);
+    }
+
+    /// Returns the tolerance ``(rel, abs)`` for points outside of the domain, see [`Self::set_out_of_bounds_tolerance()`]
+    pub fn out_of_bounds_tolerance(&self) -> (f64, f64) {
+        self.tolerance
+    }
+
+    /// Returns how many queries so far were within the tolerance of the domain (see [`Self::set_out_of_bounds_tolerance()`]) and how many were truly out of range, e.g. to audit a run afterwards. Queries inside of the domain aren't counted. A point counts once, even if it is outside along several directions.  
+    /// The counters are atomic and only touched by queries outside of the domain, so they cost nothing inside and work with queries from many threads at once.
+    pub fn query_stats(&self) -> QueryStats {
+        self.counters.get()
+    }
+
+    /// Sets both counters of [`Self::query_stats()`] back to 0
+    pub fn reset_query_stats(&self) {
+        self.counters.within_tolerance.store(0, Ordering::Relaxed);
+        self.counters.out_of_range.store(0, Ordering::Relaxed);
+    }
+
+    /// Makes the axis along ``dir`` periodic (or bounded again with ``periodic: false``), e.g. for angles.  
+    /// The period is ``max - min``, i.e. the last node is the first one shifted by one period and the samples there should agree (like ``0`` and ``2*PI`` for an angle). The ghost cells along that axis are then filled by wrapping around, such that the interpolation is smooth across the seam, and queries are reduced into ``[min, max)`` before the lookup. The out of bounds policy and the boundary condition don't apply to periodic axes.  
+    /// Can be called before or after the data has been generated, the setting is kept when generating new data or importing a file.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// use std::f64::consts::PI;
+    /// 
+    /// let config = DataGenConfBuilder::default()
+    ///     .uniform_cube(0.0, 1.0, 5)
+    ///     .z(0.0, 2.0*PI, 33, GridSpacing::Linear)
+    ///     .build();
+    /// let mut ip = Interp3D::default();
+    /// ip.set_periodic(Dir::Z, true);
+    /// ip.generate_data(|_, _, z| z.cos(), &config);
+    /// 
+    /// assert!((ip.interpolate(0.5, 0.5, -0.1) - ip.interpolate(0.5, 0.5, 2.0*PI - 0.1)).abs() < 1e-12);
+    /// ```
+    pub fn set_periodic(&mut self, dir: Dir, periodic: bool) {
+        let (d, v) = match dir {
+            Dir::X => (0, &mut self.x),
+            Dir::Y => (1, &mut self.y),
+            Dir::Z => (2, &mut self.z)
+        };
+        self.periodic[d] = periodic;
+        if !self.data.is_empty() {
+            Self::fill_axis(Arc::make_mut(v), periodic);
+            self.set_data_outermost();
+        }
+    }
+
+    /// Sets the transform between the physical coordinate along ``dir`` and the coordinate the grid is laid out in (see [`AxisTransform`]), e.g. to tabulate against ``log10(x)`` for data that spans several orders of magnitude in ``x``. The default is [`AxisTransform::Identity`].  
+    /// The transform applies to the point queries ([`Self::interpolate()`], [`Self::try_interpolate()`], [`Self::interpolate_many()`], [`Self::interpolate_cached()`], [`Self::interpolate_gradient()`] and [`Self::interpolate_hessian()`], as well as those of [`CompiledInterp3D`] and [`Interp3DVec`]), which take physical coordinates and return the derivatives with respect to them, and to the closures of the data generation, which get called with physical coordinates. Everything else works in the coordinates of the grid: the config, [`Self::grid_x()`], [`Self::domain()`] and the nodes, as well as the whole-grid operations like [`Self::slice()`], [`Self::integrate()`] and [`Self::invert_along()`] and the boxes of [`Self::crop()`] and [`Self::regenerate_region()`].  
+    /// Set the transform before generating the data, changing it afterwards reinterprets the existing grid. It is written to files by [`Self::export_data()`], [`Self::export_data_binary()`] and [`Self::export_npz()`] (except for [`AxisTransform::Custom`], which they reject) and taken from the file on import.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// // 40 nodes per decade of x from 1e-3 to 1e5
+    /// let config = DataGenConfBuilder::default()
+    ///     .x(-3.0, 5.0, 321, GridSpacing::Linear)
+    ///     .y(0.0, 1.0, 5, GridSpacing::Linear)
+    ///     .z(0.0, 1.0, 5, GridSpacing::Linear)
+    ///     .build();
+    /// let mut ip = Interp3D::default();
+    /// ip.set_axis_transform(Dir::X, AxisTransform::Log10);
+    /// ip.generate_data(|x, y, z| x.sqrt() + y + z, &config);
+    /// 
+    /// let exact = 2.5e3f64.sqrt() + 1.0;
+    /// assert!((ip.interpolate(2.5e3, 0.5, 0.5) - exact).abs() < 1e-6*exact);
+    /// assert_eq!(ip.domain().0, (-3.0, 5.0));
+    /// ```
+    pub fn set_axis_transform(&mut self, dir: Dir, transform: AxisTransform) {
+        self.transform[dir as usize] = transform;
+    }
+
+    /// Returns the transform along ``dir``, see [`Self::set_axis_transform()`]
+    pub fn axis_transform(&self, dir: Dir) -> AxisTransform {
+        self.transform[dir as usize]
+    }
+
+    /// Maps a query point from physical coordinates onto the grid, see [`Self::set_axis_transform()`]
+    #[inline]
+    fn to_grid(&self, x: f64, y: f64, z: f64) -> (f64, f64, f64) {
+        (self.transform[0].forward(x), self.transform[1].forward(y), self.transform[2].forward(z))
+    }
+
+    /// Maps a point of the grid back onto physical coordinates, the inverse of [`Self::to_grid()`]
+    fn to_physical(&self, x: f64, y: f64, z: f64) -> (f64, f64, f64) {
+        (self.transform[0].inverse(x), self.transform[1].inverse(y), self.transform[2].inverse(z))
+    }
+
+    /// Physical coordinates of the node ``(i, j, k)`` (ghost cells included), as passed to the closures of the data generation
+    fn physical(&self, i: usize, j: usize, k: usize) -> (f64, f64, f64) {
+        self.to_physical(self.x[i], self.y[j], self.z[k])
+    }
+
+    /// Turns the gradient and the Hessian with respect to the coordinates of the grid into the ones with respect to the physical coordinates ``(x, y, z)`` (chain rule)
+    fn to_physical_derivatives(&self, (x, y, z): (f64, f64, f64), gradient: &mut [f64; 3], hessian: Option<&mut [[f64; 3]; 3]>) {
+        if self.transform == [AxisTransform::Identity; 3] {
+            return;
+        }
+
+        let [(d1x, d2x), (d1y, d2y), (d1z, d2z)] = [(0, x), (1, y), (2, z)].map(|(d, p)| self.transform[d].derivatives(p));
+        let (d1, d2) = ([d1x, d1y, d1z], [d2x, d2y, d2z]);
+        if let Some(hessian) = hessian {
+            for (a, row) in hessian.iter_mut().enumerate() {
+                for (b, h) in row.iter_mut().enumerate() {
+                    *h *= d1[a]*d1[b];
+                }
+                row[a] += gradient[a]*d2[a];
+            }
+        }
+        for (g, d1) in gradient.iter_mut().zip(d1) {
+            *g *= d1;
+        }
+    }
+
+    /// Sampled range ``(min, max)`` of one axis, without the ghost cells
+    fn range(v: &[f64]) -> (f64, f64) {
+        (v[GHOST_LO], v[v.len()-1-GHOST_HI])
+    }
+
+    /// Applies the out of bounds policy to a query point. Returns the point to evaluate, ``None`` if the result is ``NaN`` or the error for [`OutOfBounds::Error`].  
+    /// A ``NaN`` coordinate counts as out of bounds. Points within the tolerance are moved onto the boundary first, see [`Self::set_out_of_bounds_tolerance()`].
+    fn try_bounded(&self, x: f64, y: f64, z: f64) -> Result<Option<(f64, f64, f64)>, Interp3DError> {
+        self.apply_bounds(x, y, z, true)
+    }
+
+    /// [`Self::try_bounded()`], which only adds to [`Self::query_stats()`] if ``count`` is set
+    pub(crate) fn apply_bounds(&self, x: f64, y: f64, z: f64, count: bool) -> Result<Option<(f64, f64, f64)>, Interp3DError> {
+        if self.data.is_empty() {
+            return Ok(Some((x, y, z)));
+        }
+
+        let mut p = [x, y, z];
+        let mut within_tolerance = false;
+        let mut beyond = None;
+        for (axis, p) in [Dir::X, Dir::Y, Dir::Z].into_iter().zip(p.iter_mut()) {
+            match self.bound_axis(axis, *p) {
+                (_, AxisBound::Beyond) => if beyond.is_none() {
+                    beyond = Some((axis, *p, Self::range(self.axis(axis))));
+                },
+                (bounded, state) => {
+                    *p = bounded;
+                    within_tolerance |= state == AxisBound::WithinTolerance;
+                }
+            }
+        }
+
+        let Some((axis, value, range)) = beyond else {
+            if within_tolerance && count {
+                self.counters.within_tolerance.fetch_add(1, Ordering::Relaxed);
+            }
+            return Ok(Some((p[0], p[1], p[2])));
+        };
+        if count {
+            self.counters.out_of_range.fetch_add(1, Ordering::Relaxed);
+        }
+        match self.out_of_bounds {
+            OutOfBounds::LinearExtrapolate => Ok(Some((p[0], p[1], p[2]))),
+            OutOfBounds::ClampToEdge => {
+                for (p, v) in p.iter_mut().zip([&self.x, &self.y, &self.z]) {
+                    let range = Self::range(v);
+                    *p = p.clamp(range.0, range.1);
+                }
+                Ok(Some((p[0], p[1], p[2])))
+            },
+            OutOfBounds::ReturnNan => Ok(None),
+            OutOfBounds::Error => Err(Interp3DError::OutOfBounds { axis, value, range })
+        }
+    }
+
+    /// The part of [`Self::apply_bounds()`] along one direction: wraps ``p`` around on periodic axes and moves it onto the edge if it's within the tolerance. Points beyond that are returned as they are.
+    pub(crate) fn bound_axis(&self, dir: Dir, p: f64) -> (f64, AxisBound) {
+        let range = Self::range(self.axis(dir));
+        if self.periodic[dir as usize] {
+            return (range.0 + (p - range.0).rem_euclid(range.1 - range.0), AxisBound::Inside);
+        }
+        if (range.0..=range.1).contains(&p) {
+            return (p, AxisBound::Inside);
+        }
+
+        let (rel, abs) = self.tolerance;
+        let edge = if p < range.0 { range.0 } else { range.1 };
+        if (p - edge).abs() <= abs + rel*edge.abs() {
+            (edge, AxisBound::WithinTolerance)
+        } else {
+            (p, AxisBound::Beyond)
+        }
     }
 
-    /// This will export a loaded data set and grid to file.
-    pub fn export_data(file: &str) {
+    /// Panicking version of [`Self::try_bounded()`], for the query methods that can't return an error
+    fn bounded(&self, x: f64, y: f64, z: f64) -> Option<(f64, f64, f64)> {
+        match self.try_bounded(x, y, z) {
+            Ok(p) => p,
+            Err(e) => panic!("{}", e)
+        }
+    }
+
+    /// Interpolates the sampled data at all ``points``, with the results in the same order.  
+    /// The results are identical to calling [`Self::interpolate()`] for every point, but the cell of the previous point (and its neighbours) is checked first before searching the grid, like with [`Self::interpolate_cached()`]. That pays off when consecutive points are close to each other (e.g. particles sorted along a trajectory or by cell), for scattered points the extra check costs a little on top of the loop.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default()
+    ///     .uniform_cube(0.0, 1.0, 11)
+    ///     .build();
+    /// let ip = Interp3D::from_config(|x, y, z| x + y*z, &config);
+    /// 
+    /// let points: Vec<_> = (0..100).map(|i| (0.01*i as f64, 0.5, 0.25)).collect();
+    /// let values = ip.interpolate_many(&points);
+    /// assert_eq!(values[42], ip.interpolate(0.42, 0.5, 0.25));
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet or if a point is out of bounds with [`OutOfBounds::Error`].
+    pub fn interpolate_many(&self, points: &[(f64, f64, f64)]) -> Vec<f64> {
+        assert!(!self.data.is_empty(), "Interpolator has not been set up");
 
+        let mut cache = QueryCache::default();
+        points.iter().map(|&(x, y, z)| self.interpolate_cached(&mut cache, x, y, z)).collect()
     }
-}
\ No newline at end of file
+
+    /// Same as [`Self::interpolate_many()`], but for points in random order, e.g. an unsorted list of particles. All points are located first and then evaluated grouped by cell, so the samples around a cell are still in the cache for the next point, and the results are put back into the order of ``points``.  
+    /// The results are identical to the ones of [`Self::interpolate_many()`]. Sorting costs time and memory of its own, which only pays off for large grids (whose samples don't fit into the CPU cache) and many points, for points that are already close to each other [`Self::interpolate_many()`] is faster (see the ``interpolate_many`` benchmark).
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+    /// let ip = Interp3D::from_config(|x, y, z| x + y*z, &config);
+    /// 
+    /// let points: Vec<_> = (0..100).map(|i| ((0.37*i as f64).fract(), (0.61*i as f64).fract(), 0.25)).collect();
+    /// assert_eq!(ip.interpolate_many_sorted(&points), ip.interpolate_many(&points));
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics like [`Self::interpolate_many()`]. With [`OutOfBounds::Error`] the first point out of bounds (in the order of ``points``) is reported, before anything is evaluated.
+    pub fn interpolate_many_sorted(&self, points: &[(f64, f64, f64)]) -> Vec<f64> {
+        assert!(!self.data.is_empty(), "Interpolator has not been set up");
+
+        let located: Vec<_> = points.iter().map(|&(x, y, z)| {
+            let (x, y, z) = self.to_grid(x, y, z);
+            self.bounded(x, y, z).map(|(x, y, z)| self.locate(x, y, z))
+        }).collect();
+
+        // points without a cell are NaN and never evaluated
+        let mut order: Vec<(usize, usize)> = located.iter().enumerate()
+            .filter_map(|(n, located)| located.map(|((i, _), (j, _), (k, _))| (self.index(i, j, k), n)))
+            .collect();
+        order.sort_unstable();
+
+        let mut values = vec![f64::NAN; points.len()];
+        for (_, n) in order {
+            if let Some(located) = located[n] {
+                values[n] = self.evaluate(located);
+            }
+        }
+        values
+    }
+
+    /// Same as [`Self::interpolate_many()`], but at points in the coordinates of the grid (see [`Self::set_axis_transform()`])
+    fn interpolate_many_grid(&self, points: &[(f64, f64, f64)]) -> Vec<f64> {
+        assert!(!self.data.is_empty(), "Interpolator has not been set up");
+
+        let mut cache = QueryCache::default();
+        points.iter().map(|&(x, y, z)| self.interpolate_cached_grid(&mut cache, x, y, z)).collect()
+    }
+
+    /// Same as [`Self::interpolate()`], but checks the cell of the previous query stored in ``cache`` (and its neighbours) first, before searching the grid.  
+    /// The results are identical to the ones of [`Self::interpolate()`]. This pays off for queries that move slowly through space, e.g. along a trajectory, and on large grids where the binary search over the axes is noticeable.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default()
+    ///     .uniform_cube(0.0, 1.0, 300)
+    ///     .build();
+    /// let ip = Interp3D::from_config(|x, y, z| x + y*z, &config);
+    /// 
+    /// let mut cache = QueryCache::default();
+    /// for i in 0..1000 {
+    ///     let x = 0.001*i as f64;
+    ///     assert_eq!(ip.interpolate_cached(&mut cache, x, 0.5, 0.25), ip.interpolate(x, 0.5, 0.25));
+    /// }
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet or if the point is out of bounds with [`OutOfBounds::Error`].
+    pub fn interpolate_cached(&self, cache: &mut QueryCache, x: f64, y: f64, z: f64) -> f64 {
+        let (x, y, z) = self.to_grid(x, y, z);
+        self.interpolate_cached_grid(cache, x, y, z)
+    }
+
+    /// Same as [`Self::interpolate_cached()`], but at a point in the coordinates of the grid (see [`Self::set_axis_transform()`])
+    fn interpolate_cached_grid(&self, cache: &mut QueryCache, x: f64, y: f64, z: f64) -> f64 {
+        assert!(!self.data.is_empty(), "Interpolator has not been set up");
+
+        let Some((x, y, z)) = self.bounded(x, y, z) else {
+            return f64::NAN;
+        };
+        let located = self.locate_cached(cache, x, y, z);
+        self.evaluate(located)
+    }
+
+    /// Same as [`Self::locate()`], but starts with the cells stored in ``cache`` and stores the new ones there.
+    fn locate_cached(&self, cache: &mut QueryCache, x: f64, y: f64, z: f64) -> ((usize, f64), (usize, f64), (usize, f64)) {
+        let located = (
+            kernel::locate_hinted(&self.x, x, cache.cell.0),
+            kernel::locate_hinted(&self.y, y, cache.cell.1),
+            kernel::locate_hinted(&self.z, z, cache.cell.2)
+        );
+        cache.cell = (located.0.0, located.1.0, located.2.0);
+        located
+    }
+
+    /// Evaluates the interpolant inside the cells found by [`Self::locate()`].
+    fn evaluate(&self, located: ((usize, f64), (usize, f64), (usize, f64))) -> f64 {
+        if let Some(derivatives) = self.hermite() {
+            return self.hermite_derivative(derivatives, located, [0, 0, 0]);
+        }
+
+        let [value] = self.evaluate_components([&self.data], located);
+        value
+    }
+
+    /// Evaluates the interpolants of several sample sets with the layout of ``self.data`` at once, such that the weights are only computed once.
+    fn evaluate_components<const N: usize>(&self, data: [&Samples; N], ((i, tx), (j, ty), (k, tz)): ((usize, f64), (usize, f64), (usize, f64))) -> [f64; N] {
+        match self.scheme {
+            Type::Trilinear => return data.map(|data| self.trilinear(data, (i, j, k), tx, ty, tz)),
+            Type::TricubicMonotone => return data.map(|data| self.monotone(data, (i, j, k), tx, ty, tz).0),
+            Type::Blend(alpha) => {
+                let [wx, wy, wz] = [(Dir::X, i, tx), (Dir::Y, j, ty), (Dir::Z, k, tz)].map(|(dir, i, t)| self.weights(dir, i, t));
+                return data.map(|data| alpha*self.stencil_sum(data, (i, j, k), &wx, &wy, &wz) + (1.0 - alpha)*self.trilinear(data, (i, j, k), tx, ty, tz));
+            },
+            Type::Nearest => {
+                let nearest = |i: usize, t: f64| if t <= 0.5 { i } else { i+1 };
+                let index = self.index(nearest(i, tx), nearest(j, ty), nearest(k, tz));
+                return data.map(|data| self.sample(data, index));
+            },
+            _ => ()
+        }
+
+        let wx = self.weights(Dir::X, i, tx);
+        let wy = self.weights(Dir::Y, j, ty);
+        let wz = self.weights(Dir::Z, k, tz);
+
+        data.map(|data| self.stencil_sum(data, (i, j, k), &wx, &wy, &wz))
+    }
+
+    /// Sets the interpolation scheme used by all query methods (see [`Type`]). The default is [`Type::Tricubic`].  
+    /// Only the weights of the nodes around a query change, the grid, the cell lookup and the out of bounds handling are the same for all schemes. So this can be switched at any time, also after the data has been generated.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 5).build();
+    /// let mut ip = Interp3D::from_config(|x, y, z| 2.0*x - 3.0*y + z, &config);
+    /// ip.set_type(Type::Trilinear);
+    /// 
+    /// assert!((ip.interpolate(0.1, 0.2, 0.3) - (0.2 - 0.6 + 0.3)).abs() < 1e-14);
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if ``alpha`` of [`Type::Blend`] isn't finite.
+    pub fn set_type(&mut self, scheme: Type) {
+        if let Type::Blend(alpha) = scheme {
+            assert!(alpha.is_finite(), "alpha of Type::Blend must be finite, got {}", alpha);
+        }
+        self.scheme = scheme;
+    }
+
+    /// Sets the precision at which the samples are stored (see [`Storage`]). The default is [`Storage::F64`].  
+    /// Call this before generating or importing data to have the samples rounded once as they are stored. Already present samples are converted right away, going back to ``F64`` doesn't bring the lost digits back of course.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 5).build();
+    /// let mut ip = Interp3D::default();
+    /// ip.set_storage(Storage::F32);
+    /// ip.generate_data(|x, y, z| 1.0 + x*y*z, &config);
+    /// 
+    /// assert_eq!(ip.storage(), Storage::F32);
+    /// assert!((ip.interpolate(0.5, 0.5, 0.5) - 1.125).abs() < 1e-6);
+    /// ```
+    pub fn set_storage(&mut self, storage: Storage) {
+        self.data = std::mem::take(&mut self.data).converted(storage);
+    }
+
+    /// Returns the precision at which the samples are stored, see [`Self::set_storage()`]
+    pub fn storage(&self) -> Storage {
+        self.data.storage()
+    }
+
+    /// 1d interpolation of the current scheme along ``dir``
+    fn kernel(&self, dir: Dir) -> Kernel {
+        match self.scheme {
+            Type::Tricubic | Type::TricubicMonotone | Type::Blend(_) => Kernel::Cubic,
+            Type::BicubicUnilinear(linear) if dir != linear => Kernel::Cubic,
+            Type::BicubicUnilinear(_) | Type::Trilinear => Kernel::Linear,
+            Type::Nearest => Kernel::Nearest
+        }
+    }
+
+    /// Returns the axis along ``dir``
+    fn axis(&self, dir: Dir) -> &[f64] {
+        match dir {
+            Dir::X => &self.x,
+            Dir::Y => &self.y,
+            Dir::Z => &self.z
+        }
+    }
+
+    /// Stencil weights along ``dir`` for the cell ``i`` at the relative position ``t``, according to the scheme
+    fn weights(&self, dir: Dir, i: usize, t: f64) -> [f64; 4] {
+        match self.kernel(dir) {
+            Kernel::Cubic => kernel::cubic(self.axis(dir), i, t),
+            Kernel::Linear => kernel::linear(t),
+            Kernel::Nearest => kernel::nearest(t)
+        }
+    }
+
+    /// Derivative of [`Self::weights()`] with respect to the coordinate along ``dir``
+    fn weights_derivative(&self, dir: Dir, i: usize, t: f64) -> [f64; 4] {
+        match self.kernel(dir) {
+            Kernel::Cubic => kernel::cubic_derivative(self.axis(dir), i, t),
+            Kernel::Linear => kernel::linear_derivative(self.axis(dir), i),
+            Kernel::Nearest => [0.0; 4]
+        }
+    }
+
+    /// The trilinear part of [`Type::Blend`] in the cell ``(i, j, k)``: its share ``1 - alpha`` and the weights and their derivatives along X, Y and Z. The weights of the scheme (see [`Self::weights()`]) are the ones of the cubic part. ``None`` for the other schemes.
+    fn blend_linear(&self, (i, j, k): (usize, usize, usize), tx: f64, ty: f64, tz: f64) -> Option<(f64, [[[f64; 4]; 2]; 3])> {
+        let Type::Blend(alpha) = self.scheme else {
+            return None;
+        };
+        Some((1.0 - alpha, [
+            [kernel::linear(tx), kernel::linear_derivative(&self.x, i)],
+            [kernel::linear(ty), kernel::linear_derivative(&self.y, j)],
+            [kernel::linear(tz), kernel::linear_derivative(&self.z, k)]
+        ]))
+    }
+
+    /// Monotone tricubic interpolation inside of ``cell`` (see [`Type::TricubicMonotone`]), returns the value and the gradient.  
+    /// As the limited weights depend on the data, this can't be written as one sum over the stencil. Instead the columns along Z are interpolated first, then the resulting lines along Y and finally X, carrying the derivatives along.
+    fn monotone(&self, data: &Samples, (i, j, k): (usize, usize, usize), tx: f64, ty: f64, tz: f64) -> (f64, [f64; 3]) {
+        let dot = |w: &[f64; 4], f: &[f64; 4]| w.iter().zip(f).map(|(w, f)| w*f).sum::<f64>();
+
+        // value, d/dy and d/dz of every line along X
+        let lines: [[f64; 3]; 4] = std::array::from_fn(|a| {
+            // value and d/dz of every column along Z
+            let columns: [[f64; 2]; 4] = std::array::from_fn(|b| {
+                let index = self.index(i-1+a, j-1+b, k-1);
+                let f = self.column(data, index);
+                [dot(&kernel::monotone(&self.z, k, tz, &f), &f), dot(&kernel::monotone_derivative(&self.z, k, tz, &f), &f)]
+            });
+            let (values, d_dz) = (columns.map(|c| c[0]), columns.map(|c| c[1]));
+
+            let wy = kernel::monotone(&self.y, j, ty, &values);
+            [dot(&wy, &values), dot(&kernel::monotone_derivative(&self.y, j, ty, &values), &values), dot(&wy, &d_dz)]
+        });
+        let lines = [lines.map(|l| l[0]), lines.map(|l| l[1]), lines.map(|l| l[2])];
+
+        let wx = kernel::monotone(&self.x, i, tx, &lines[0]);
+        (
+            dot(&wx, &lines[0]),
+            [dot(&kernel::monotone_derivative(&self.x, i, tx, &lines[0]), &lines[0]), dot(&wx, &lines[1]), dot(&wx, &lines[2])]
+        )
+    }
+
+    /// Trilinear blend of the 8 corners of the cell, see [`Type::Trilinear`]
+    fn trilinear(&self, data: &Samples, (i, j, k): (usize, usize, usize), tx: f64, ty: f64, tz: f64) -> f64 {
+        let mut sum = 0.0;
+        for (a, wx) in [1.0 - tx, tx].into_iter().enumerate() {
+            for (b, wy) in [1.0 - ty, ty].into_iter().enumerate() {
+                let index = self.index(i+a, j+b, k);
+                sum += wx*wy*((1.0 - tz)*self.sample(data, index) + tz*self.sample(data, index+1));
+            }
+        }
+
+        sum
+    }
+
+    /// Interpolates the sampled data at ``(x, y, z)`` and returns the value together with the gradient ``[df/dx, df/dy, df/dz]`` of the interpolant.  
+    /// The derivatives are the exact derivatives of the same piecewise polynomial [`Self::interpolate()`] evaluates (same cell, same stencil), not finite differences. Since the scheme is C1, the gradient is continuous across cell faces.  
+    /// With [`OutOfBounds::ClampToEdge`] the interpolant is constant outside of the domain, so the derivatives along the clamped directions are 0 there.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet or if the point is out of bounds with [`OutOfBounds::Error`].
+    pub fn interpolate_gradient(&self, x: f64, y: f64, z: f64) -> (f64, [f64; 3]) {
+        let [result] = self.gradient_components([&self.data], x, y, z);
+        result
+    }
+
+    /// Maps the physical query point onto the grid and applies the out of bounds policy like [`Self::bounded()`], and also tells along which directions the point got clamped onto the edge, where the interpolant is constant and so all derivatives are 0.
+    fn bounded_derivatives(&self, x: f64, y: f64, z: f64) -> Option<((f64, f64, f64), [bool; 3])> {
+        let (x, y, z) = self.to_grid(x, y, z);
+        let (cx, cy, cz) = self.bounded(x, y, z)?;
+        let clamp = self.out_of_bounds == OutOfBounds::ClampToEdge;
+        let clamped = [
+            clamp && !self.periodic[0] && cx != x,
+            clamp && !self.periodic[1] && cy != y,
+            clamp && !self.periodic[2] && cz != z
+        ];
+
+        Some(((cx, cy, cz), clamped))
+    }
+
+    /// Value and gradient of the interpolants of several sample sets with the layout of ``self.data`` at once, see [`Self::interpolate_gradient()`]
+    fn gradient_components<const N: usize>(&self, data: [&Samples; N], x: f64, y: f64, z: f64) -> [(f64, [f64; 3]); N] {
+        let physical = (x, y, z);
+        let Some(((x, y, z), clamped)) = self.bounded_derivatives(x, y, z) else {
+            return [(f64::NAN, [f64::NAN; 3]); N];
+        };
+
+        let ((i, tx), (j, ty), (k, tz)) = self.locate(x, y, z);
+        let cell = (i, j, k);
+        let mut results = if let Some(derivatives) = self.hermite() {
+            // only the single set of samples of an Interp3D can come with derivatives
+            let d = |order: [usize; 3]| self.hermite_derivative(derivatives, ((i, tx), (j, ty), (k, tz)), order);
+            data.map(|_| (d([0, 0, 0]), [d([1, 0, 0]), d([0, 1, 0]), d([0, 0, 1])]))
+        } else if self.scheme == Type::TricubicMonotone {
+            data.map(|data| self.monotone(data, cell, tx, ty, tz))
+        } else {
+            let wx = self.weights(Dir::X, i, tx);
+            let wy = self.weights(Dir::Y, j, ty);
+            let wz = self.weights(Dir::Z, k, tz);
+            let dx = self.weights_derivative(Dir::X, i, tx);
+            let dy = self.weights_derivative(Dir::Y, j, ty);
+            let dz = self.weights_derivative(Dir::Z, k, tz);
+
+            let sums = |data: &Samples, [wx, wy, wz]: [&[f64; 4]; 3], [dx, dy, dz]: [&[f64; 4]; 3]| (
+                self.stencil_sum(data, cell, wx, wy, wz),
+                [self.stencil_sum(data, cell, dx, wy, wz), self.stencil_sum(data, cell, wx, dy, wz), self.stencil_sum(data, cell, wx, wy, dz)]
+            );
+            let linear = self.blend_linear(cell, tx, ty, tz);
+
+            data.map(|data| {
+                let (value, gradient) = sums(data, [&wx, &wy, &wz], [&dx, &dy, &dz]);
+                let Some((share, [[lx, ex], [ly, ey], [lz, ez]])) = linear else {
+                    return (value, gradient);
+                };
+                let (linear, linear_gradient) = sums(data, [&lx, &ly, &lz], [&ex, &ey, &ez]);
+                let blend = |cubic: f64, linear: f64| (1.0 - share)*cubic + share*linear;
+                (blend(value, linear), std::array::from_fn(|d| blend(gradient[d], linear_gradient[d])))
+            })
+        };
+        for (_, gradient) in results.iter_mut() {
+            for (d, clamped) in gradient.iter_mut().zip(clamped) {
+                if clamped {
+                    *d = 0.0;
+                }
+            }
+            self.to_physical_derivatives(physical, gradient, None);
+        }
+
+        results
+    }
+
+    /// Finds the cell containing ``(x, y, z)`` along each direction together with the relative position inside of it (see [`kernel::locate()`]).
+    fn locate(&self, x: f64, y: f64, z: f64) -> ((usize, f64), (usize, f64), (usize, f64)) {
+        assert!(!self.data.is_empty(), "Interpolator has not been set up");
+
+        (kernel::locate(&self.x, x), kernel::locate(&self.y, y), kernel::locate(&self.z, z))
+    }
+
+    /// Reads the sample at ``index`` of ``data`` (which has the layout of ``self.data``) for a query. If the ghost cells are stale (after a write through ``IndexMut``), the value is computed from the interior nodes instead.
+    #[inline]
+    fn sample(&self, data: &Samples, index: usize) -> f64 {
+        if !self.stale_ghosts {
+            return data.get(index);
+        }
+
+        let (i, j, k) = (index/(self.ny*self.nz), index/self.nz % self.ny, index % self.nz);
+        self.ghost_value(data, i, j, k)
+    }
+
+    /// Same as [`Self::sample()`] for the 4 consecutive samples starting at ``index``, see [`Samples::column()`]
+    #[inline]
+    fn column(&self, data: &Samples, index: usize) -> [f64; 4] {
+        if !self.stale_ghosts {
+            return data.column(index);
+        }
+
+        std::array::from_fn(|n| self.sample(data, index+n))
+    }
+
+    /// Sums up the 4x4x4 stencil around ``cell`` with the given weights along each direction.  
+    /// With the ``simd`` feature ``F64`` samples are summed up with vector instructions where the CPU supports them (see ``simd.rs``).
+    fn stencil_sum(&self, data: &Samples, (i, j, k): (usize, usize, usize), wx: &[f64; 4], wy: &[f64; 4], wz: &[f64; 4]) -> f64 {
+        #[cfg(feature = "simd")]
+        if let (Samples::F64(v), false) = (data, self.stale_ghosts) {
+            if let Some(sum) = self.stencil_sum_simd(v, (i, j, k), wx, wy, wz) {
+                return sum;
+            }
+        }
+
+        let mut sum = 0.0;
+        for (a, wx) in wx.iter().enumerate() {
+            let mut sum_y = 0.0;
+            for (b, wy) in wy.iter().enumerate() {
+                let index = self.index(i-1+a, j-1+b, k-1);
+                let column = self.column(data, index);
+                sum_y += wy*(wz[0]*column[0] + wz[1]*column[1] + wz[2]*column[2] + wz[3]*column[3]);
+            }
+            sum += wx*sum_y;
+        }
+
+        sum
+    }
+
+    /// Sets how the ghost cells around the sampled region are filled along all directions (see [`BoundaryCondition`]).  
+    /// Can be called before or after the data has been generated, the ghost cells of an already set up interpolator are refilled right away.
+    pub fn set_boundary_condition(&mut self, boundary: BoundaryCondition) {
+        self.boundary = [boundary; 3];
+        if !self.data.is_empty() {
+            self.set_data_outermost();
+        }
+    }
+
+    /// Same as [`Self::set_boundary_condition()`], but only for the two faces normal to ``dir``.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 6).build();
+    /// let mut ip = Interp3D::from_config(|x, y, z| x*x + y + z, &config);
+    /// // symmetric about x = 0, linear along y and z
+    /// ip.set_boundary_condition(BoundaryCondition::LinearExtrapolate);
+    /// ip.set_boundary_condition_along(Dir::X, BoundaryCondition::Reflect { even: true });
+    /// 
+    /// assert_eq!(ip.boundary_condition(Dir::Y), BoundaryCondition::LinearExtrapolate);
+    /// assert!((ip.interpolate(0.1, 0.05, 0.9) - (0.01 + 0.05 + 0.9)).abs() < 1e-12);
+    /// ```
+    pub fn set_boundary_condition_along(&mut self, dir: Dir, boundary: BoundaryCondition) {
+        self.boundary[dir as usize] = boundary;
+        if !self.data.is_empty() {
+            self.set_data_outermost();
+        }
+    }
+
+    /// Returns the boundary condition for the faces normal to ``dir``
+    pub fn boundary_condition(&self, dir: Dir) -> BoundaryCondition {
+        self.boundary[dir as usize]
+    }
+
+    /// Returns the config the grid was generated with.  
+    /// This is ``None`` if the interpolator has not been set up or if the grid did not stem from a [`DataGenConf`] (e.g. because it was built from its node positions with [`Self::from_grid_and_data()`]). After [`Self::remap_domain()`] the config reports the new range.  
+    /// [`Self::export_data()`] and [`Self::export_data_binary()`] write the config along, so it's still known after [`Self::import_data()`]. E.g. to regenerate a table with more nodes, or to find out which spacing an old table used.
+    pub fn config(&self) -> Option<&DataGenConf> {
+        self.conf.as_ref()
+    }
+
+    /// Returns a copy of the config the grid was generated with, see [`Self::config()`] for when there is one.  
+    /// This is meant to start a new generation from, e.g. a refined table that keeps the spacing of an imported one.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 5).build();
+    /// let ip = Interp3D::from_config(|x, y, z| x*y*z, &config);
+    /// 
+    /// let mut finer = ip.generation_config().unwrap();
+    /// finer.x.n = 9;
+    /// let refined = Interp3D::from_config(|x, y, z| x*y*z, &finer);
+    /// assert_eq!(refined.dims(), (9, 5, 5));
+    /// ```
+    pub fn generation_config(&self) -> Option<DataGenConf> {
+        self.conf.clone()
+    }
+
+    /// Returns the number of nodes ``(nx, ny, nz)`` along each direction.  
+    /// This is the actual grid (e.g. ``conf.x.n`` points along X), the ghost cells the interpolation uses internally are not counted. All indices taken by the accessors run from ``0`` to these sizes (exclusive). It is ``(0, 0, 0)`` as long as the interpolator has not been set up.
+    pub fn dims(&self) -> (usize, usize, usize) {
+        (self.nx.saturating_sub(GHOST), self.ny.saturating_sub(GHOST), self.nz.saturating_sub(GHOST))
+    }
+
+    /// Returns the interior part of an axis, empty if not set up
+    fn interior_axis(v: &[f64]) -> &[f64] {
+        if v.is_empty() { v } else { &v[GHOST_LO..v.len()-GHOST_HI] }
+    }
+
+    /// Returns the node positions along X, without the ghost cells. Empty if the interpolator has not been set up.
+    pub fn grid_x(&self) -> &[f64] {
+        Self::interior_axis(&self.x)
+    }
+
+    /// Returns the node positions along Y, without the ghost cells. Empty if the interpolator has not been set up.
+    pub fn grid_y(&self) -> &[f64] {
+        Self::interior_axis(&self.y)
+    }
+
+    /// Returns the node positions along Z, without the ghost cells. Empty if the interpolator has not been set up.
+    pub fn grid_z(&self) -> &[f64] {
+        Self::interior_axis(&self.z)
+    }
+
+    /// Returns the sample stored at the node ``(grid_x()[i], grid_y()[j], grid_z()[k])``.  
+    /// The indices count the actual grid only, i.e. ``(0, 0, 0)`` is the node at the minimum of every direction (see [`Self::dims()`]).
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 3).build();
+    /// let ip = Interp3D::from_config(|x, y, z| x + 10.0*y + 100.0*z, &config);
+    /// 
+    /// assert_eq!(ip.dims(), (3, 3, 3));
+    /// assert_eq!(ip.grid_y(), &[0.0, 0.5, 1.0]);
+    /// assert_eq!(ip.value_at(2, 1, 0), 1.0 + 5.0);
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if any index is out of range.
+    pub fn value_at(&self, i: usize, j: usize, k: usize) -> f64 {
+        self.data.get(self.node_index((i, j, k)))
+    }
+
+    /// Returns the sampled domain ``((x_min, x_max), (y_min, y_max), (z_min, z_max))``, i.e. the first and last node along each direction.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet.
+    pub fn domain(&self) -> ((f64, f64), (f64, f64), (f64, f64)) {
+        assert!(!self.data.is_empty(), "Interpolator has not been set up");
+
+        (Self::range(&self.x), Self::range(&self.y), Self::range(&self.z))
+    }
+
+    /// Returns whether ``(x, y, z)`` lies inside of [`Self::domain()`], the boundary included. A ``NaN`` coordinate is never contained.  
+    /// This only looks at the sampled range, not at the out of bounds policy or periodic axes, i.e. it tells whether a query would need any of them.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 5).build();
+    /// let ip = Interp3D::from_config(|x, y, z| x + y + z, &config);
+    /// 
+    /// assert!(ip.contains(1.0, 0.0, 0.5));
+    /// assert!(!ip.contains(1.0, 0.0, 1.1));
+    /// assert_eq!(ip.clamp_to_domain(1.0, -3.0, 1.1), (1.0, 0.0, 1.0));
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet.
+    pub fn contains(&self, x: f64, y: f64, z: f64) -> bool {
+        let (rx, ry, rz) = self.domain();
+        (rx.0..=rx.1).contains(&x) && (ry.0..=ry.1).contains(&y) && (rz.0..=rz.1).contains(&z)
+    }
+
+    /// Moves ``(x, y, z)`` onto the closest point of [`Self::domain()`], i.e. clamps each coordinate to its range. That's what [`OutOfBounds::ClampToEdge`] does with a query, whatever the policy of ``self`` is. ``NaN`` coordinates stay ``NaN``.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet.
+    pub fn clamp_to_domain(&self, x: f64, y: f64, z: f64) -> (f64, f64, f64) {
+        let (rx, ry, rz) = self.domain();
+        (x.clamp(rx.0, rx.1), y.clamp(ry.0, ry.1), z.clamp(rz.0, rz.1))
+    }
+
+    /// Iterates over all interior nodes (the ghost cells are skipped) and yields ``(x, y, z, value)`` for each of them.  
+    /// The nodes come in storage order, i.e. ``z`` changes fastest and ``x`` slowest.
+    pub fn iter_points(&self) -> impl Iterator<Item = (f64, f64, f64, f64)> + '_ {
+        self.iter().map(|node| (node.x, node.y, node.z, node.value))
+    }
+
+    /// Applies ``f`` to every interior sample, e.g. to take the log of the data or to normalize it, without regenerating anything. The ghost cells are refreshed afterwards.  
+    /// ``f`` only gets the sampled value, not the coordinates of the node, see [`Self::update_data()`] for that.
+    pub fn map_data<F>(&mut self, mut f: F)
+    where F: FnMut(f64) -> f64 {
+        self.update_data(|_, _, _, v| f(v));
+    }
+
+    /// Replaces every interior sample by ``f(x, y, z, old_value)``, e.g. to apply a unit conversion that depends on the position. The ghost cells are refreshed afterwards.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(1.0, 2.0, 5).build();
+    /// let mut ip = Interp3D::from_config(|x, y, z| x*y*z, &config);
+    /// ip.update_data(|x, _, _, v| v/x);
+    /// 
+    /// assert!((ip.interpolate(1.5, 1.5, 1.5) - 2.25).abs() < 1e-12);
+    /// ```
+    pub fn update_data<F>(&mut self, mut f: F)
+    where F: FnMut(f64, f64, f64, f64) -> f64 {
+        let mut data = std::mem::take(&mut self.data);
+        let mut samples = data.writer();
+        for (i, j, k) in self.interior() {
+            let index = self.index(i, j, k);
+            let (x, y, z) = self.physical(i, j, k);
+            samples.set(index, f(x, y, z, samples.get(index)));
+        }
+        self.data = data;
+        self.set_data_outermost();
+        self.hermite = None;
+    }
+
+    /// Replaces every interior sample by ``f(x, y, z)`` at the existing nodes, e.g. to fix a bug in the sampled function while keeping the grid node for node. The ghost cells are refreshed afterwards.  
+    /// Unlike [`Self::generate_data()`] this needs no config, so it also works for interpolators read from a file or built with [`Self::from_grid_and_data()`]. The axes, the config and the settings are kept, supplied derivatives (see [`Self::generate_data_hermite()`]) and the record of missing nodes (see [`Self::generate_data_masked()`]) are dropped.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let mut ip = Interp3D::from_grid_and_data(&[0.0, 0.5, 2.0], &[0.0, 1.0], &[-1.0, 1.0], &[0.0; 12]).unwrap();
+    /// ip.regenerate_data(|x, y, z| x + y*z);
+    /// 
+    /// assert_eq!(ip.grid_x(), &[0.0, 0.5, 2.0]);
+    /// assert_eq!(ip.value_at(2, 1, 0), 1.0);
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet.
+    pub fn regenerate_data<F>(&mut self, mut f: F)
+    where F: FnMut(f64, f64, f64) -> f64 {
+        assert!(!self.data.is_empty(), "Interpolator has not been set up");
+        self.update_data(|x, y, z, _| f(x, y, z));
+        self.mask = None;
+    }
+
+    /// Replaces the samples of the interior nodes within the box ``xr`` x ``yr`` x ``zr`` (given as ``(min, max)`` in the coordinates of the grid like for [`Self::crop()`], boundaries included) by ``f(x, y, z)``, e.g. when the sampled function only changed in part of the domain. All other samples stay exactly as they are, and ``f`` is only called for the nodes inside. The ghost cells are refreshed afterwards.  
+    /// ``f`` gets called with physical coordinates like for the data generation (see [`Self::set_axis_transform()`]). Like any change of the samples this drops supplied derivatives (see [`Self::generate_data_hermite()`]). The regenerated nodes count as valid for [`Self::is_valid_region()`]. A box without any node changes nothing. With the ``parallel`` feature, ``regenerate_region_par()`` spreads the nodes over all cores.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+    /// let mut ip = Interp3D::from_config(|x, y, z| x + y + z, &config);
+    /// ip.regenerate_region(|_, _, _| 0.0, (0.45, 1.0), (0.0, 1.0), (0.0, 0.25));
+    /// 
+    /// assert_eq!(ip.value_at(5, 3, 2), 0.0);
+    /// assert_ne!(ip.value_at(5, 3, 3), 0.0);
+    /// assert_ne!(ip.value_at(4, 3, 2), 0.0);
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet.
+    pub fn regenerate_region<F>(&mut self, mut f: F, xr: (f64, f64), yr: (f64, f64), zr: (f64, f64))
+    where F: FnMut(f64, f64, f64) -> f64 {
+        assert!(!self.data.is_empty(), "Interpolator has not been set up");
+        let [is, js, ks] = self.region([xr, yr, zr]);
+
+        let mut data = std::mem::take(&mut self.data);
+        let mut samples = data.writer();
+        for i in is.clone() {
+            for j in js.clone() {
+                for k in ks.clone() {
+                    let (x, y, z) = self.physical(i, j, k);
+                    samples.set(self.index(i, j, k), f(x, y, z));
+                }
+            }
+        }
+        self.data = data;
+        self.region_regenerated([is, js, ks]);
+    }
+
+    /// The (padded) indices of the interior nodes whose grid coordinates lie within ``ranges`` along X, Y and Z, boundaries included. The axes are increasing, so these are contiguous.
+    fn region(&self, ranges: [(f64, f64); 3]) -> [Range<usize>; 3] {
+        let axes = [&self.x, &self.y, &self.z];
+        std::array::from_fn(|d| {
+            let (min, max) = ranges[d];
+            let inside = |&i: &usize| (min..=max).contains(&axes[d][i]);
+            let mut interior = GHOST_LO..axes[d].len()-GHOST_HI;
+            match (interior.find(inside), interior.rfind(inside)) {
+                (Some(start), Some(last)) => start..last+1,
+                (Some(start), None) => start..start+1,
+                _ => 0..0
+            }
+        })
+    }
+
+    /// Refreshes everything that depends on the samples after the nodes in ``region`` were regenerated, see [`Self::regenerate_region()`]
+    fn region_regenerated(&mut self, [is, js, ks]: [Range<usize>; 3]) {
+        self.set_data_outermost();
+        self.hermite = None;
+        let (_, ny, nz) = self.dims();
+        if let Some(mask) = &mut self.mask {
+            let mask = Arc::make_mut(mask);
+            for i in is {
+                for j in js.clone() {
+                    for k in ks.clone() {
+                        mask[(i-GHOST_LO)*ny*nz + (j-GHOST_LO)*nz + k-GHOST_LO] = true;
+                    }
+                }
+            }
+            if !mask.contains(&false) {
+                self.mask = None;
+            }
+        }
+    }
+
+    /// Returns a new interpolator on the same grid (an exact copy, see [`Self::same_grid()`]) and with the same settings, whose samples are ``f(x, y, z, value)`` of the samples of ``self``. E.g. ``1/f``, a unit conversion or a specific intensity times ``r^2``. ``self`` is left unchanged, see [`Self::update_data()`] (or [`Self::map_data()`] for a function of the value alone) to transform it in place.  
+    /// Non-finite results (like the ``ln`` of a zero sample) are stored as they are. They are counted in ``nan_count`` and ``inf_count`` of [`Self::stats()`], which the [`Display`](std::fmt::Display) output shows as well. Supplied derivatives (see [`Self::generate_data_hermite()`]) aren't carried over.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 5).build();
+    /// let ip = Interp3D::from_config(|x, y, z| x*y*z, &config);
+    /// 
+    /// let log = ip.derive(|_, _, _, v| v.ln());
+    /// assert!(log.same_grid(&ip, 0.0));
+    /// // ln(0) on the three faces through the origin
+    /// assert_eq!(log.stats().inf_count, 125 - 64);
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet.
+    pub fn derive<F>(&self, f: F) -> Interp3D
+    where F: FnMut(f64, f64, f64, f64) -> f64 {
+        assert!(!self.data.is_empty(), "Interpolator has not been set up");
+
+        let mut ip = self.with_samples(self.data.clone());
+        ip.update_data(f);
+        ip
+    }
+
+    /// Overwrites the sample at the node ``(i, j, k)`` (same indices as [`Self::value_at()`]), e.g. to patch a known bad entry of an imported table.  
+    /// The ghost cells that are filled from this node are updated as well, so queries close to the boundary see the new value right away.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if any index is out of range.
+    pub fn set_value(&mut self, i: usize, j: usize, k: usize, v: f64) {
+        let index = self.node_index((i, j, k));
+        self.data.set(index, v);
+        self.hermite = None;
+
+        // all nodes along each axis (the node itself included) whose value depends on the patched one
+        let dependent = |d: usize, n: usize, node: usize| -> Vec<usize> {
+            (0..n).filter(|&g| self.ghost_sources(d, g).iter().any(|&(source, w)| source == node && w != 0.0)).collect()
+        };
+        let di = dependent(0, self.nx, i+GHOST_LO);
+        let dj = dependent(1, self.ny, j+GHOST_LO);
+        let dk = dependent(2, self.nz, k+GHOST_LO);
+
+        for &gi in &di {
+            for &gj in &dj {
+                for &gk in &dk {
+                    let ghost = self.index(gi, gj, gk);
+                    if ghost != index {
+                        let value = self.ghost_value(&self.data, gi, gj, gk);
+                        self.data.set(ghost, value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns a new interpolator on the grid described by ``conf`` whose samples are the values of ``self`` at the new nodes, e.g. to ship a coarser version of a finely generated table. The settings of ``self`` are taken over.  
+    /// The new grid is built exactly like in [`Self::generate_data()`], so resampling onto the config of ``self`` reproduces the data.
+    /// 
+    /// Nodes outside of the domain of ``self`` are handled by its out of bounds policy (see [`Self::set_out_of_bounds()`]): with [`OutOfBounds::Error`] that's an error, with [`OutOfBounds::ReturnNan`] the samples there are ``NaN`` and otherwise they are clamped or extrapolated. Set the policy to ``Error`` if you want to be sure the new domain lies inside of the old one.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let fine = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 41).build();
+    /// let coarse = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+    /// let ip = Interp3D::from_config(|x, y, z| x*y + z, &fine);
+    /// 
+    /// let resampled = ip.resample(&coarse).unwrap();
+    /// assert_eq!(resampled.dims(), (11, 11, 11));
+    /// assert!((resampled.interpolate(0.35, 0.5, 0.2) - 0.375).abs() < 1e-12);
+    /// ```
+    pub fn resample(&self, conf: &DataGenConf) -> Result<Interp3D, Interp3DError> {
+        if self.data.is_empty() {
+            return Err(Interp3DError::NotSetUp);
+        }
+
+        let mut ip = self.settings_only();
+        ip.setup(conf)?;
+        let mut data = std::mem::take(&mut ip.data);
+        let mut samples = data.writer();
+        for (i, j, k) in ip.interior() {
+            samples.set(ip.index(i, j, k), self.try_interpolate_grid_coords(ip.x[i], ip.y[j], ip.z[k])?);
+        }
+        ip.data = data;
+        ip.set_data_outermost();
+
+        Ok(ip)
+    }
+
+    /// Returns a new interpolator that only holds the block of nodes around the box ``[x0, x1] x [y0, y1] x [z0, z1]``, e.g. to ship just the part of a large table a run will query. The settings and the supplied derivatives (see [`Self::generate_data_hermite()`]) are taken over.  
+    /// The ranges are snapped outward onto the existing nodes, so nothing is interpolated. Since the cubic schemes also look at the neighbours of a cell, the block is one node larger on each side (where ``self`` has one), so every query inside of the box gives exactly the same result as with ``self``. Beyond that, the new ghost cells follow the boundary condition. A periodic axis is always kept as a whole.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 16.0, 17).build();
+    /// let ip = Interp3D::from_config(|x, y, z| (x*y).sin() + z, &config);
+    /// 
+    /// let cropped = ip.crop((2.5, 5.0), (0.0, 1.0), (15.5, 16.0)).unwrap();
+    /// assert_eq!(cropped.dims(), (6, 3, 3));
+    /// assert_eq!(cropped.domain(), ((1.0, 6.0), (0.0, 2.0), (14.0, 16.0)));
+    /// assert_eq!(cropped.interpolate(3.7, 0.2, 15.9), ip.interpolate(3.7, 0.2, 15.9));
+    /// ```
+    /// 
+    /// # Errors
+    /// 
+    /// [`Interp3DError::NotSetUp`], [`Interp3DError::InvalidRange`] for an empty or reversed range and [`Interp3DError::OutOfBounds`] for a range that reaches beyond the domain of ``self``.
+    pub fn crop(&self, xr: (f64, f64), yr: (f64, f64), zr: (f64, f64)) -> Result<Interp3D, Interp3DError> {
+        if self.data.is_empty() {
+            return Err(Interp3DError::NotSetUp);
+        }
+
+        // the first and the last interior node of the block along each direction
+        let mut block = [(0, 0); 3];
+        for (d, (dir, (min, max))) in [(Dir::X, xr), (Dir::Y, yr), (Dir::Z, zr)].into_iter().enumerate() {
+            if !min.is_finite() || !max.is_finite() || min >= max {
+                return Err(Interp3DError::InvalidRange { dir, min, max });
+            }
+            let v = Self::interior_axis(self.axis(dir));
+            let range = (v[0], v[v.len()-1]);
+            for value in [min, max] {
+                if !(range.0..=range.1).contains(&value) {
+                    return Err(Interp3DError::OutOfBounds { axis: dir, value, range });
+                }
+            }
+
+            block[d] = if self.periodic[d] {
+                (0, v.len()-1)
+            } else {
+                // the last node at or below min and the first one at or above max, plus the neighbours of both
+                let first = v.partition_point(|&p| p <= min) - 1;
+                let last = v.partition_point(|&p| p < max);
+                (first.saturating_sub(1), (last+1).min(v.len()-1))
+            };
+        }
+
+        let [(i0, i1), (j0, j1), (k0, k1)] = block;
+        let nodes = || (i0..=i1).flat_map(move |i| (j0..=j1).flat_map(move |j| (k0..=k1).map(move |k| (i, j, k))));
+        let mut samples = nodes().map(|(i, j, k)| self.data.get(self.index(i+GHOST_LO, j+GHOST_LO, k+GHOST_LO)));
+        let mut ip = Self::from_parts(
+            &self.grid_x()[i0..=i1], &self.grid_y()[j0..=j1], &self.grid_z()[k0..=k1],
+            self,
+            || Ok(samples.next().unwrap())
+        )?;
+
+        if let Some(derivatives) = &self.hermite {
+            let (_, ny, nz) = self.dims();
+            ip.hermite = Some(nodes().map(|(i, j, k)| derivatives[i*ny*nz + j*nz + k]).collect());
+        }
+
+        Ok(ip)
+    }
+
+    /// Checks that both interpolators are set up and have exactly the same node positions
+    fn check_same_grid(&self, other: &Interp3D) -> Result<(), Interp3DError> {
+        self.check_grid_within(other, 0.0)
+    }
+
+    /// Checks that both interpolators are set up and have the same number of nodes along each direction, at positions that differ by at most ``tol`` times the extent of the axis
+    fn check_grid_within(&self, other: &Interp3D, tol: f64) -> Result<(), Interp3DError> {
+        if self.data.is_empty() || other.data.is_empty() {
+            return Err(Interp3DError::NotSetUp);
+        }
+
+        for (dir, a, b) in [(Dir::X, self.grid_x(), other.grid_x()), (Dir::Y, self.grid_y(), other.grid_y()), (Dir::Z, self.grid_z(), other.grid_z())] {
+            let extent = a[a.len()-1] - a[0];
+            if a.len() != b.len() || a.iter().zip(b).any(|(a, b)| (a - b).abs() > tol*extent) {
+                return Err(Interp3DError::GridMismatch { dir });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replaces every interior sample ``v`` of ``self`` by ``f(v, w)``, with ``w`` the sample of ``other`` at the same node, and refreshes the ghost cells.  
+    /// ``f`` needs to be linear, as the supplied derivatives are combined the same way if both have them (and dropped otherwise).
+    fn combine<F>(&mut self, other: &Interp3D, f: F) -> Result<(), Interp3DError>
+    where F: Fn(f64, f64) -> f64 {
+        self.check_same_grid(other)?;
+
+        let mut data = std::mem::take(&mut self.data);
+        let mut samples = data.writer();
+        for (i, j, k) in self.interior() {
+            let index = self.index(i, j, k);
+            samples.set(index, f(samples.get(index), other.data.get(index)));
+        }
+        self.data = data;
+        self.set_data_outermost();
+        self.hermite = match (self.hermite.take(), &other.hermite) {
+            (Some(mut derivatives), Some(others)) => {
+                for (d, o) in Arc::make_mut(&mut derivatives).iter_mut().zip(others.iter()) {
+                    *d = std::array::from_fn(|n| f(d[n], o[n]));
+                }
+                Some(derivatives)
+            },
+            _ => None
+        };
+
+        Ok(())
+    }
+
+    /// Adds the samples of ``other`` to the ones of ``self`` node by node, e.g. to sum up two contributions that were generated separately.  
+    /// Both need to have exactly the same node positions (no tolerance is applied, grids generated from the same config or read from the same file always match), otherwise [`Interp3DError::GridMismatch`] is returned and ``self`` is left unchanged. The settings of ``self`` are kept.
+    pub fn add_assign_field(&mut self, other: &Interp3D) -> Result<(), Interp3DError> {
+        self.combine(other, |a, b| a + b)
+    }
+
+    /// Subtracts the samples of ``other`` from the ones of ``self`` node by node, see [`Self::add_assign_field()`].
+    pub fn sub_assign_field(&mut self, other: &Interp3D) -> Result<(), Interp3DError> {
+        self.combine(other, |a, b| a - b)
+    }
+
+    /// Multiplies all samples by ``c``.
+    pub fn scale(&mut self, c: f64) {
+        self.map_data(|v| c*v);
+    }
+
+    /// Returns the node by node blend ``(1 - w)*self + w*other``, e.g. to go from one time snapshot of a field to the next one (for a whole series of snapshots see [`Interp3DSeries`]). The result has the settings of ``self``.  
+    /// The grids need to match like for [`Self::add_assign_field()`]. As the interpolation is linear in the samples, interpolating the blend is the same as blending the interpolated values (up to rounding), except for [`Type::TricubicMonotone`].
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+    /// let before = Interp3D::from_config(|x, y, z| x + y + z, &config);
+    /// let after = Interp3D::from_config(|x, y, z| 2.0*(x + y + z), &config);
+    /// 
+    /// let halfway = before.blend(&after, 0.5).unwrap();
+    /// assert!((halfway.interpolate(0.1, 0.2, 0.3) - 0.9).abs() < 1e-14);
+    /// ```
+    pub fn blend(&self, other: &Interp3D, w: f64) -> Result<Interp3D, Interp3DError> {
+        let mut blended = self.with_samples(self.data.clone());
+        blended.hermite.clone_from(&self.hermite);
+        blended.combine(other, |a, b| (1.0 - w)*a + w*b)?;
+
+        Ok(blended)
+    }
+
+    /// Returns the node by node difference ``self - other`` as a new interpolator with the grid and the settings of ``self``, e.g. to look at where two versions of a table disagree. It's a complete interpolator, so it can be queried and written to any of the formats (e.g. [`Self::export_vtk()`] to have a look at it).  
+    /// The grids need to match like for [`Self::add_assign_field()`]. See [`Self::compare()`] for a summary of the difference.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+    /// let new = Interp3D::from_config(|x, y, z| x*y + z, &config);
+    /// let old = Interp3D::from_config(|x, y, z| x*y, &config);
+    /// 
+    /// let diff = new.diff(&old).unwrap();
+    /// assert!((diff.interpolate(0.1, 0.2, 0.3) - 0.3).abs() < 1e-14);
+    /// ```
+    pub fn diff(&self, other: &Interp3D) -> Result<Interp3D, Interp3DError> {
+        let mut diff = self.with_samples(self.data.clone());
+        diff.hermite.clone_from(&self.hermite);
+        diff.combine(other, |a, b| a - b)?;
+
+        Ok(diff)
+    }
+
+    /// Returns ``(min, max)`` of the sampled data, i.e. the values of [`Self::data_min()`] and [`Self::data_max()`] without their nodes.  
+    /// Only the interior nodes are taken into account, the ghost cells are merely padding for the interpolation. Only finite samples count: ``NaN`` and infinite samples are skipped (see [`Self::stats()`] for how many there are), and without any finite sample this is ``(NaN, NaN)``.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet, like [`Self::argmax()`].
+    pub fn data_extrema(&self) -> (f64, f64) {
+        assert!(!self.data.is_empty(), "Interpolator has not been set up");
+
+        self.finite_extrema().map_or((f64::NAN, f64::NAN), |[min, max]| (min.0, max.0))
+    }
+
+    /// Returns the grid coordinates ``(x, y, z)`` of the node holding the largest finite sample, i.e. the position of the node of [`Self::data_max()`].  
+    /// Like [`Self::data_extrema()`] this only looks at the interior nodes and skips ``NaN`` and infinite samples. If several nodes share the maximum, the first one in storage order wins.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet or if none of its samples is finite.
+    pub fn argmax(&self) -> (f64, f64, f64) {
+        assert!(!self.data.is_empty(), "Interpolator has not been set up");
+
+        let [_, (_, (i, j, k))] = self.finite_extrema().expect("Interpolator has no finite samples");
+        (self.x[i+GHOST_LO], self.y[j+GHOST_LO], self.z[k+GHOST_LO])
+    }
+
+    /// Maps the sampled data onto a different range along one direction without regenerating it.  
+    /// The stored node positions (ghost cells included) are rescaled linearly, such that the first interior node ends up at ``new_min`` and the last one at ``new_max``. The data itself is left untouched.
+    /// 
+    /// This only makes sense if the remap is affine, i.e. the function you sampled really is ``f(a*x + b, y, z)`` on the new range. Any nonlinear change of coordinates needs a regeneration.  
+    /// The [`Self::config()`] along ``dir`` keeps a [`GridSpacing::Linear`] spacing with the new range. Any other spacing becomes [`GridSpacing::Custom`] with the remapped nodes, as e.g. [`GridSpacing::Logarithmic`] on the new range would describe a different grid (or none at all, if the range now crosses 0).
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet or if ``new_min >= new_max``.
+    pub fn remap_domain(&mut self, dir: Dir, new_min: f64, new_max: f64) {
+        assert!(new_min < new_max, "new_min needs to be smaller than new_max");
+
+        let conf = self.conf.as_mut();
+        let (v, conf) = match dir {
+            Dir::X => (&mut self.x, conf.map(|c| &mut c.x)),
+            Dir::Y => (&mut self.y, conf.map(|c| &mut c.y)),
+            Dir::Z => (&mut self.z, conf.map(|c| &mut c.z))
+        };
+        assert!(!v.is_empty(), "Interpolator has not been set up");
+
+        let old_min = v[GHOST_LO];
+        let old_max = v[v.len()-1-GHOST_HI];
+        let scale = (new_max - new_min)/(old_max - old_min);
+        for p in Arc::make_mut(v).iter_mut() {
+            *p = new_min + (*p - old_min)*scale;
+        }
+        // every derivative along dir shrinks with the stretched axis
+        for node in self.hermite.iter_mut().flat_map(Arc::make_mut) {
+            for (d, order) in node.iter_mut().zip(hermite::ORDERS) {
+                *d /= scale.powi(order[dir as usize] as i32);
+            }
+        }
+
+        if let Some(conf) = conf {
+            if conf.spacing == GridSpacing::Linear {
+                conf.min = new_min;
+                conf.max = new_max;
+            } else {
+                let mut nodes = v[GHOST_LO..v.len()-GHOST_HI].to_vec();
+                // keep the config consistent despite rounding
+                nodes[0] = new_min;
+                *nodes.last_mut().unwrap() = new_max;
+                *conf = DataGenConfSingle::custom(nodes);
+            }
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The padded sample at ``(i, j, k)``, ghost cells included
+    fn padded(ip: &Interp3D, i: usize, j: usize, k: usize) -> f64 {
+        ip.data.get(ip.index(i, j, k))
+    }
+
+    #[test]
+    fn minimal_grid_ghosts_copy_the_nearest_interior_node() {
+        // the cubic grid of the request and non-cubic ones, whose clamps along Y once used nx
+        for (nx, ny, nz) in [(5, 5, 5), (5, 7, 6), (7, 5, 5), (2, 2, 2), (2, 5, 3)] {
+            let single = |n| DataGenConfSingle { n, min: 0.0, max: 1.0, spacing: GridSpacing::Linear };
+            let conf = DataGenConf { x: single(nx), y: single(ny), z: single(nz) };
+            let f = |x: f64, y: f64, z: f64| x + 10.0*y + 100.0*z;
+            let ip = Interp3D::from_config(f, &conf);
+
+            assert_eq!((ip.nx, ip.ny, ip.nz), (nx + GHOST, ny + GHOST, nz + GHOST));
+            assert_eq!(ip.data.len(), ip.nx*ip.ny*ip.nz);
+            let clamp = |i: usize, n: usize| i.clamp(GHOST_LO, n - 1 - GHOST_HI);
+            for i in 0..ip.nx {
+                for j in 0..ip.ny {
+                    for k in 0..ip.nz {
+                        let (x, y, z) = ip.physical(clamp(i, ip.nx), clamp(j, ip.ny), clamp(k, ip.nz));
+                        assert_eq!(padded(&ip, i, j, k), f(x, y, z), "{:?} at {:?}", (nx, ny, nz), (i, j, k));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn default_ghost_width_extends_the_axes_linearly() {
+        assert_eq!((GHOST_LO, GHOST_HI, GHOST), (1, 2, 3));
+
+        let spacings = [GridSpacing::Linear, GridSpacing::Exponential(8.0), GridSpacing::Logarithmic, GridSpacing::Chebyshev];
+        for (spacing, n) in spacings.into_iter().flat_map(|s| [2, 3, 7].map(move |n| (s.clone(), n))) {
+            let single = DataGenConfSingle { n, min: 1.0, max: 4.0, spacing };
+            let conf = DataGenConf { x: single.clone(), y: DataGenConfSingle::custom(vec![-2.0, 0.5, 1.0, 3.0]), z: single.clone() };
+            let ip = Interp3D::from_config(|x, y, z| x + y + z, &conf);
+
+            for (v, m) in [(&ip.x, n), (&ip.y, 4), (&ip.z, n)] {
+                assert_eq!(v.len(), m + GHOST, "{:?}", single);
+                // the ghost nodes continue the spacing of the outermost interior cell
+                let (first, last) = (GHOST_LO, v.len()-1-GHOST_HI);
+                for g in 0..first {
+                    assert_eq!(v[g], 2.0*v[g+1] - v[g+2], "{:?}", single);
+                }
+                for g in last+1..v.len() {
+                    assert_eq!(v[g], 2.0*v[g-1] - v[g-2], "{:?}", single);
+                }
+                assert!(v.windows(2).all(|w| w[0] < w[1]), "{:?}", single);
+            }
+            assert_eq!(ip.grid_x(), &ip.x[GHOST_LO..ip.nx-GHOST_HI]);
+            assert_eq!((ip.x[GHOST_LO], ip.x[ip.nx-1-GHOST_HI]), (1.0, 4.0));
+            assert_eq!(&ip.y[GHOST_LO..ip.ny-GHOST_HI], &[-2.0, 0.5, 1.0, 3.0]);
+        }
+    }
+
+    #[test]
+    fn default_ghost_width_extrapolates_linear_data() {
+        let conf = DataGenConf {
+            x: DataGenConfSingle { n: 5, min: 0.0, max: 2.0, spacing: GridSpacing::Exponential(3.0) },
+            y: DataGenConfSingle::custom(vec![-1.0, 0.0, 0.25, 2.0]),
+            z: DataGenConfSingle { n: 2, min: 1.0, max: 3.0, spacing: GridSpacing::Linear }
+        };
+        let f = |x: f64, y: f64, z: f64| 1.0 + 2.0*x - 3.0*y + 0.5*z;
+        let mut ip = Interp3D::default();
+        ip.set_boundary_condition(BoundaryCondition::LinearExtrapolate);
+        ip.generate_data(f, &conf);
+
+        // every ghost sample lies on the plane through the interior samples, also in the edges and corners
+        for i in 0..ip.nx {
+            for j in 0..ip.ny {
+                for k in 0..ip.nz {
+                    let expected = f(ip.x[i], ip.y[j], ip.z[k]);
+                    assert!((padded(&ip, i, j, k) - expected).abs() < 1e-12, "{:?}: {} vs {}", (i, j, k), padded(&ip, i, j, k), expected);
+                }
+            }
+        }
+    }
+}
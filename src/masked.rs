@@ -0,0 +1,158 @@
+//! Contains the data generation from a closure that has no value at some of the nodes, and the filling of those holes.
+
+use crate::{Interp3D, Interp3DError, DataGenConf, FillStrategy};
+
+use std::collections::VecDeque;
+
+/// Mean of the values, ``NaN`` without any
+fn mean<I: Iterator<Item = f64>>(values: I) -> f64 {
+    let (sum, count) = values.fold((0.0, 0usize), |(sum, count), v| (sum + v, count + 1));
+    sum/count as f64
+}
+
+impl Interp3D {
+    /// Generates the data with a closure that returns ``None`` where it has no value, e.g. in an unphysical part of the parameter space. Those nodes are filled according to ``fill`` (see [`FillStrategy`]), such that the interpolation works across the whole grid.  
+    /// The interpolator remembers which nodes were missing, see [`Self::is_valid_region()`] and [`Self::missing_nodes()`]. This is only kept until the grid is set up again (it isn't written to files), changing the samples afterwards keeps it.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+    /// let mut ip = Interp3D::default();
+    /// ip.generate_data_masked(|x, y, z| if x + y < 1.5 { Some(x + y + z) } else { None }, &config, FillStrategy::NearestValid);
+    /// 
+    /// assert!(ip.is_valid_region(0.2, 0.3, 0.5));
+    /// assert!(!ip.is_valid_region(0.9, 0.9, 0.5));
+    /// assert!(ip.interpolate(0.9, 0.9, 0.5).is_finite());
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the config is invalid, see [`Self::try_generate_data_masked()`] for a version that returns the error instead.
+    pub fn generate_data_masked<F>(&mut self, f: F, conf: &DataGenConf, fill: FillStrategy)
+    where F: FnMut(f64, f64, f64) -> Option<f64> {
+        if let Err(e) = self.try_generate_data_masked(f, conf, fill) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Same as [`Self::generate_data_masked()`], but an invalid config is returned as an error instead of panicking. In that case the closure is never called and the interpolator is left unchanged.  
+    /// If the closure has no value anywhere, [`FillStrategy::NearestValid`] and [`FillStrategy::IterativeAverage`] have nothing to fill the holes with and leave ``NaN`` everywhere.
+    pub fn try_generate_data_masked<F>(&mut self, mut f: F, conf: &DataGenConf, fill: FillStrategy) -> Result<(), Interp3DError>
+    where F: FnMut(f64, f64, f64) -> Option<f64> {
+        self.setup(conf)?;
+
+        let mut valid = Vec::with_capacity(self.iter().len());
+        let mut samples = Vec::with_capacity(self.iter().len());
+        for (i, j, k) in self.interior() {
+            let (x, y, z) = self.physical(i, j, k);
+            let value = f(x, y, z);
+            valid.push(value.is_some());
+            samples.push(value.unwrap_or(f64::NAN));
+        }
+        if valid.contains(&false) {
+            self.fill_missing(&mut samples, &valid, fill);
+            self.mask = Some(valid.into());
+        }
+
+        let mut data = std::mem::take(&mut self.data);
+        let mut writer = data.writer();
+        for ((i, j, k), v) in self.interior().zip(samples) {
+            writer.set(self.index(i, j, k), v);
+        }
+        self.data = data;
+        self.set_data_outermost();
+
+        Ok(())
+    }
+
+    /// The neighbours of the ``n``-th interior node along the grid lines, up to 6
+    fn neighbours(&self, n: usize) -> impl Iterator<Item = usize> {
+        let (nx, ny, nz) = self.dims();
+        let (i, j, k) = (n/(ny*nz), n/nz % ny, n % nz);
+        [
+            (i > 0).then(|| n - ny*nz),
+            (i+1 < nx).then(|| n + ny*nz),
+            (j > 0).then(|| n - nz),
+            (j+1 < ny).then(|| n + nz),
+            (k > 0).then(|| n - 1),
+            (k+1 < nz).then(|| n + 1)
+        ].into_iter().flatten()
+    }
+
+    /// Fills the ``samples`` of the interior nodes (in storage order) that aren't ``valid``, see [`FillStrategy`]
+    fn fill_missing(&self, samples: &mut [f64], valid: &[bool], fill: FillStrategy) {
+        match fill {
+            FillStrategy::Nan => (),
+            FillStrategy::Constant(c) => {
+                for (s, _) in samples.iter_mut().zip(valid).filter(|(_, &v)| !v) {
+                    *s = c;
+                }
+            },
+            FillStrategy::NearestValid => {
+                // breadth first from all valid nodes at once, so every node is reached first from one of the closest
+                let mut known = valid.to_vec();
+                let mut queue: VecDeque<usize> = (0..valid.len()).filter(|&n| valid[n]).collect();
+                while let Some(n) = queue.pop_front() {
+                    for m in self.neighbours(n) {
+                        if !known[m] {
+                            known[m] = true;
+                            samples[m] = samples[n];
+                            queue.push_back(m);
+                        }
+                    }
+                }
+            },
+            FillStrategy::IterativeAverage { passes } => {
+                let mut known = valid.to_vec();
+                let mut rim: Vec<usize> = (0..valid.len()).filter(|&n| !known[n] && self.neighbours(n).any(|m| known[m])).collect();
+                while !rim.is_empty() {
+                    let means: Vec<f64> = rim.iter().map(|&n| mean(self.neighbours(n).filter(|&m| known[m]).map(|m| samples[m]))).collect();
+                    for (&n, mean) in rim.iter().zip(means) {
+                        samples[n] = mean;
+                        known[n] = true;
+                    }
+                    rim = rim.iter().flat_map(|&n| self.neighbours(n)).filter(|&m| !known[m]).collect();
+                    rim.sort_unstable();
+                    rim.dedup();
+                }
+
+                let filled: Vec<usize> = (0..valid.len()).filter(|&n| !valid[n] && known[n]).collect();
+                for _ in 0..passes {
+                    let means: Vec<f64> = filled.iter().map(|&n| mean(self.neighbours(n).map(|m| samples[m]))).collect();
+                    for (&n, mean) in filled.iter().zip(means) {
+                        samples[n] = mean;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns whether the interpolated value at ``(x, y, z)`` only depends on nodes where the closure of [`Self::generate_data_masked()`] had a value, i.e. none of the nodes of its stencil (see [`Self::stencil_nodes()`]) was filled.  
+    /// Interpolators generated otherwise are valid everywhere. Points that give ``NaN`` or an error because they are out of bounds (see [`OutOfBounds`](crate::OutOfBounds)) are never valid.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet.
+    pub fn is_valid_region(&self, x: f64, y: f64, z: f64) -> bool {
+        assert!(!self.data.is_empty(), "Interpolator has not been set up");
+        let (x, y, z) = self.to_grid(x, y, z);
+        let Ok(Some((x, y, z))) = self.apply_bounds(x, y, z, false) else {
+            return false;
+        };
+        let Some(mask) = &self.mask else {
+            return true;
+        };
+
+        let (_, ny, nz) = self.dims();
+        let [xs, ys, zs] = self.stencil_sources(self.locate(x, y, z));
+        xs.iter().all(|&i| ys.iter().all(|&j| zs.iter().all(|&k| mask[i*ny*nz + j*nz + k])))
+    }
+
+    /// Returns the number of nodes where the closure of [`Self::generate_data_masked()`] had no value, 0 for interpolators generated otherwise.
+    pub fn missing_nodes(&self) -> usize {
+        self.mask.as_ref().map_or(0, |mask| mask.iter().filter(|&&v| !v).count())
+    }
+}
@@ -0,0 +1,391 @@
+//! Contains the iteration over the grid nodes together with their indices, see [`GridNode`].
+
+use crate::{Interp3D, Interp3DError, DataGenConf, Dir, GHOST_LO};
+use crate::kernel::{self, Kernel};
+
+/// One interior node of the grid, as yielded by [`Interp3D::iter()`].  
+/// The indices are the ones of [`Interp3D::value_at()`], i.e. ``x == ip.grid_x()[i]`` and ``value == ip.value_at(i, j, k)``.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GridNode {
+    pub i: usize,
+    pub j: usize,
+    pub k: usize,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub value: f64
+}
+
+/// What [`Interp3D::interpolate_detailed()`] found out about a query
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryDetail {
+    /// the interpolated value, the same as the one of [`Interp3D::interpolate()`]
+    pub value: f64,
+    /// the node at the lower corner of the cell the point was found in, as for [`Interp3D::value_at()`]
+    pub cell: (usize, usize, usize),
+    /// the position inside of that cell, from 0 at the lower to 1 at the upper corner along each direction (in the coordinates of the grid, see [`Interp3D::set_axis_transform()`]). Extrapolated points are below 0 or above 1.
+    pub local: (f64, f64, f64),
+    /// the nodes ``(i, j, k)`` that enter the value together with their weights, in storage order, such that ``value`` is the sum of ``weight*ip.value_at(i, j, k)``
+    pub weights: Vec<(usize, usize, usize, f64)>
+}
+
+/// Iterator over the interior nodes in storage order, see [`Interp3D::iter()`]
+struct Nodes<'a> {
+    ip: &'a Interp3D,
+    /// position of the next node among the interior nodes
+    next: usize,
+    len: usize
+}
+
+impl Iterator for Nodes<'_> {
+    type Item = GridNode;
+
+    fn next(&mut self) -> Option<GridNode> {
+        if self.next == self.len {
+            return None;
+        }
+
+        let node = self.ip.node(self.next);
+        self.next += 1;
+        Some(node)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.next;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Nodes<'_> {}
+
+impl Interp3D {
+    /// The interior node at the position ``n`` of the storage order
+    pub(crate) fn node(&self, n: usize) -> GridNode {
+        let (_, ny, nz) = self.dims();
+        self.grid_node(n/(ny*nz), n/nz % ny, n % nz)
+    }
+
+    /// The interior node ``(i, j, k)``
+    fn grid_node(&self, i: usize, j: usize, k: usize) -> GridNode {
+        let value = self.data.get(self.index(i+GHOST_LO, j+GHOST_LO, k+GHOST_LO));
+        GridNode { i, j, k, x: self.grid_x()[i], y: self.grid_y()[j], z: self.grid_z()[k], value }
+    }
+
+    /// Iterates over all interior nodes (the ghost cells are skipped) together with their indices, e.g. to compute derived quantities or to write a custom format.  
+    /// The nodes come in storage order, i.e. ``k`` changes fastest and ``i`` slowest. The iterator knows its length, which is ``nx*ny*nz`` (see [`Self::dims()`]) and 0 if the interpolator has not been set up.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 3).build();
+    /// let ip = Interp3D::from_config(|x, y, z| x + 10.0*y + 100.0*z, &config);
+    /// 
+    /// let nodes = ip.iter();
+    /// assert_eq!(nodes.len(), 27);
+    /// 
+    /// let node = nodes.skip(5).next().unwrap();
+    /// assert_eq!((node.i, node.j, node.k), (0, 1, 2));
+    /// assert_eq!(node.value, 5.0 + 100.0);
+    /// ```
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = GridNode> + '_ {
+        let (nx, ny, nz) = self.dims();
+        Nodes { ip: self, next: 0, len: nx*ny*nz }
+    }
+
+    /// Calls ``f`` with every interior node and a mutable reference to its sample, in the order of [`Self::iter()`]. The ghost cells are refreshed afterwards.  
+    /// Unlike [`Self::update_data()`], ``f`` also gets the indices of the node and may leave the sample untouched.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 3).build();
+    /// let mut ip = Interp3D::from_config(|x, y, z| x*y*z, &config);
+    /// 
+    /// // zero the face at z = 0
+    /// ip.for_each_mut(|node, value| if node.k == 0 { *value = 0.0 });
+    /// assert_eq!(ip.interpolate(1.0, 1.0, 0.0), 0.0);
+    /// ```
+    pub fn for_each_mut<F>(&mut self, mut f: F)
+    where F: FnMut(GridNode, &mut f64) {
+        let (nx, ny, nz) = self.dims();
+        for n in 0..nx*ny*nz {
+            let node = self.node(n);
+            let mut value = node.value;
+            f(node, &mut value);
+            self.data.set(self.index(node.i+GHOST_LO, node.j+GHOST_LO, node.k+GHOST_LO), value);
+        }
+        self.set_data_outermost();
+        self.hermite = None;
+    }
+
+    /// Sets up the grid described by ``conf`` without generating any data, the first of the two steps of [`Self::generate_data()`] when the function can't be called point by point, e.g. because it evaluates thousands of points at once on a GPU, through MPI or in another process.  
+    /// Evaluate the function at the nodes of [`Self::grid_points()`] however you like and hand the results over to [`Self::fill_from_slice()`]. Until then all samples are 0. The settings are kept, and on error the interpolator is left unchanged.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+    /// let mut ip = Interp3D::default();
+    /// ip.prepare_grid(&config).unwrap();
+    /// 
+    /// // e.g. sent off as one batch
+    /// let points: Vec<(f64, f64, f64)> = ip.grid_points().map(|(_, _, _, x, y, z)| (x, y, z)).collect();
+    /// let values: Vec<f64> = points.iter().map(|&(x, y, z)| x*y + z).collect();
+    /// ip.fill_from_slice(&values).unwrap();
+    /// 
+    /// assert!((ip.interpolate(0.25, 0.5, 0.75) - 0.875).abs() < 1e-12);
+    /// ```
+    pub fn prepare_grid(&mut self, conf: &DataGenConf) -> Result<(), Interp3DError> {
+        self.setup(conf)
+    }
+
+    /// Returns the interior nodes of the grid as ``(i, j, k, x, y, z)`` in the order [`Self::fill_from_slice()`] expects the values in: storage order like [`Self::iter()`], i.e. ``k`` changes fastest and ``i`` slowest, so the node ``(i, j, k)`` is at position ``(i*ny + j)*nz + k`` with ``(nx, ny, nz) = self.dims()``.  
+    /// The coordinates are the physical ones (see [`Self::set_axis_transform()`]), exactly those [`Self::generate_data()`] calls its closure with. Without a grid there are no nodes.
+    pub fn grid_points(&self) -> impl ExactSizeIterator<Item = (usize, usize, usize, f64, f64, f64)> + '_ {
+        self.iter().map(|node| (node.i, node.j, node.k, node.x, node.y, node.z))
+    }
+
+    /// Takes over the values at the interior nodes, in the order of [`Self::grid_points()`], and builds the ghost cells, the second step after [`Self::prepare_grid()`]. This also works on any other interpolator to replace all of its samples at once.  
+    /// Like [`Self::regenerate_data()`] this drops supplied derivatives and the record of missing nodes. A wrong number of values is [`Interp3DError::DataLength`], without a grid this is [`Interp3DError::NotSetUp`], in both cases nothing is changed.
+    pub fn fill_from_slice(&mut self, values: &[f64]) -> Result<(), Interp3DError> {
+        if self.data.is_empty() {
+            return Err(Interp3DError::NotSetUp);
+        }
+        let expected = self.iter().len();
+        if values.len() != expected {
+            return Err(Interp3DError::DataLength { expected, found: values.len() });
+        }
+
+        let mut data = std::mem::take(&mut self.data);
+        let mut samples = data.writer();
+        for ((i, j, k), &v) in self.interior().zip(values) {
+            samples.set(self.index(i, j, k), v);
+        }
+        self.data = data;
+        self.set_data_outermost();
+        self.hermite = None;
+        self.mask = None;
+
+        Ok(())
+    }
+
+    /// Returns the indices of the node closest to ``(x, y, z)``, nearest along each direction on its own. Points outside of the domain are clamped onto it first (see [`Self::clamp_to_domain()`]), whatever the out of bounds policy is. Ties go to the lower node like for [`Type::Nearest`](crate::Type::Nearest).
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 5).build();
+    /// let ip = Interp3D::from_config(|x, y, z| x + y + z, &config);
+    /// 
+    /// assert_eq!(ip.nearest_grid_node(0.3, 0.6, 1.0), (1, 2, 4));
+    /// assert_eq!(ip.nearest_grid_node(-5.0, 0.55, 7.0), (0, 2, 4));
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet.
+    pub fn nearest_grid_node(&self, x: f64, y: f64, z: f64) -> (usize, usize, usize) {
+        let (x, y, z) = self.clamp_to_domain(x, y, z);
+        let ((i, tx), (j, ty), (k, tz)) = self.locate(x, y, z);
+        let nearest = |i: usize, t: f64| if t <= 0.5 { i } else { i+1 } - GHOST_LO;
+
+        (nearest(i, tx), nearest(j, ty), nearest(k, tz))
+    }
+
+    /// Returns the nodes whose samples enter the interpolation at ``(x, y, z)`` with the current [`Type`](crate::Type), in storage order, e.g. to find out which table entries are to blame for an odd value.  
+    /// That's the 4x4x4 stencil around the cell for the tricubic schemes, the 8 corners of the cell for [`Type::Trilinear`](crate::Type::Trilinear) and the nearest node for [`Type::Nearest`](crate::Type::Nearest). Close to the boundary the stencil reaches into the ghost cells, which are filled from interior nodes (see [`BoundaryCondition`](crate::BoundaryCondition)), so those nodes are returned instead and there can be less of them.
+    /// 
+    /// A point outside of [`Self::domain()`] gives [`Interp3DError::OutOfBounds`], whatever the out of bounds policy is, and [`Interp3DError::NotSetUp`] is returned if there is no data yet.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+    /// let mut ip = Interp3D::from_config(|x, y, z| x + y + z, &config);
+    /// 
+    /// assert_eq!(ip.stencil_nodes(0.45, 0.45, 0.45).unwrap().len(), 64);
+    /// ip.set_type(Type::Trilinear);
+    /// let corners = ip.stencil_nodes(0.45, 0.45, 0.45).unwrap();
+    /// assert_eq!(corners.len(), 8);
+    /// assert_eq!((corners[7].i, corners[7].j, corners[7].k), (5, 5, 5));
+    /// assert!(ip.stencil_nodes(0.45, 0.45, 1.5).is_err());
+    /// ```
+    pub fn stencil_nodes(&self, x: f64, y: f64, z: f64) -> Result<Vec<GridNode>, Interp3DError> {
+        if self.data.is_empty() {
+            return Err(Interp3DError::NotSetUp);
+        }
+        let (rx, ry, rz) = self.domain();
+        for (axis, value, range) in [(Dir::X, x, rx), (Dir::Y, y, ry), (Dir::Z, z, rz)] {
+            if !(range.0..=range.1).contains(&value) {
+                return Err(Interp3DError::OutOfBounds { axis, value, range });
+            }
+        }
+
+        let [xs, ys, zs] = self.stencil_sources(self.locate(x, y, z));
+
+        let mut nodes = Vec::with_capacity(xs.len()*ys.len()*zs.len());
+        for &i in &xs {
+            for &j in &ys {
+                for &k in &zs {
+                    nodes.push(self.grid_node(i, j, k));
+                }
+            }
+        }
+
+        Ok(nodes)
+    }
+
+    /// Interpolates at ``(x, y, z)`` like [`Self::interpolate()`] and also returns how: the cell the point was found in, the position inside of it and the weight of every node that enters the value, see [`QueryDetail`]. E.g. to debug odd values close to the boundary or to set up the interpolation as a sparse matrix.  
+    /// The value is computed by the very same code as the one of [`Self::interpolate()`], so they are always identical. The weights are the ones of the scheme with the ghost cells traced back to the interior nodes they are filled from (see [`BoundaryCondition`](crate::BoundaryCondition)), nodes that appear several times are merged and zero weights are left out. They sum up to 1 but with [`BoundaryCondition::Reflect`](crate::BoundaryCondition::Reflect) ``{ even: false }`` close to the boundary. As [`Type::TricubicMonotone`](crate::Type::TricubicMonotone) limits its slopes according to the samples, its weights are the ones chosen for this point and only hold for the current data.  
+    /// Points that are ``NaN`` with [`OutOfBounds::ReturnNan`](crate::OutOfBounds::ReturnNan) have no weights.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+    /// let mut ip = Interp3D::from_config(|x, y, z| x + y*z, &config);
+    /// ip.set_type(Type::Trilinear);
+    /// 
+    /// let detail = ip.interpolate_detailed(0.25, 0.5, 0.95);
+    /// assert_eq!(detail.value, ip.interpolate(0.25, 0.5, 0.95));
+    /// assert_eq!(detail.cell, (2, 5, 9));
+    /// assert_eq!(detail.weights.len(), 4);
+    /// let sum: f64 = detail.weights.iter().map(|&(i, j, k, w)| w*ip.value_at(i, j, k)).sum();
+    /// assert!((sum - detail.value).abs() < 1e-14);
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet, if the point is out of bounds with [`OutOfBounds::Error`](crate::OutOfBounds::Error) or if there are supplied derivatives, see [`Self::try_interpolate_detailed()`] for a version that returns the error instead.
+    pub fn interpolate_detailed(&self, x: f64, y: f64, z: f64) -> QueryDetail {
+        match self.try_interpolate_detailed(x, y, z) {
+            Ok(detail) => detail,
+            Err(e) => panic!("{}", e)
+        }
+    }
+
+    /// Same as [`Self::interpolate_detailed()`], but returns an error instead of panicking.  
+    /// With supplied derivatives (see [`Self::generate_data_hermite()`]) the value doesn't only depend on the samples, which gives [`Interp3DError::UnsupportedScheme`].
+    pub fn try_interpolate_detailed(&self, x: f64, y: f64, z: f64) -> Result<QueryDetail, Interp3DError> {
+        if self.data.is_empty() {
+            return Err(Interp3DError::NotSetUp);
+        }
+        if self.hermite().is_some() {
+            return Err(Interp3DError::UnsupportedScheme { scheme: self.scheme });
+        }
+
+        let (x, y, z) = self.to_grid(x, y, z);
+        let Some((x, y, z)) = self.try_bounded(x, y, z)? else {
+            let ((i, tx), (j, ty), (k, tz)) = self.locate(x, y, z);
+            return Ok(QueryDetail { value: f64::NAN, cell: (i - GHOST_LO, j - GHOST_LO, k - GHOST_LO), local: (tx, ty, tz), weights: Vec::new() });
+        };
+        let located = self.locate(x, y, z);
+        let ((i, tx), (j, ty), (k, tz)) = located;
+        let stencil = self.stencil_weights(located);
+
+        // the stencil spans the nodes i-1..=i+2 (ghost cells included) along each direction
+        let mut weights = Vec::with_capacity(64);
+        for (a, plane) in stencil.iter().enumerate() {
+            for (b, line) in plane.iter().enumerate() {
+                for (c, &w) in line.iter().enumerate().filter(|(_, &w)| w != 0.0) {
+                    for (si, wi) in self.ghost_sources(0, i-1+a) {
+                        for (sj, wj) in self.ghost_sources(1, j-1+b) {
+                            for (sk, wk) in self.ghost_sources(2, k-1+c) {
+                                weights.push((si - GHOST_LO, sj - GHOST_LO, sk - GHOST_LO, w*wi*wj*wk));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        weights.sort_by_key(|&(i, j, k, _)| (i, j, k));
+        weights.dedup_by(|next, kept| {
+            let same = (next.0, next.1, next.2) == (kept.0, kept.1, kept.2);
+            if same {
+                kept.3 += next.3;
+            }
+            same
+        });
+        weights.retain(|w| w.3 != 0.0);
+
+        Ok(QueryDetail {
+            value: self.evaluate(located),
+            cell: (i - GHOST_LO, j - GHOST_LO, k - GHOST_LO),
+            local: (tx, ty, tz),
+            weights
+        })
+    }
+
+    /// Weights of the 4x4x4 stencil around the cells found by [`Self::locate()`], the nodes ``i-1..=i+2`` along each direction (ghost cells included).  
+    /// For all schemes but [`Type::TricubicMonotone`](crate::Type::TricubicMonotone) that's the product of the 1d weights, for [`Type::Blend`](crate::Type::Blend) the blend of the cubic and the trilinear products. The monotone one limits the slopes of every column along Z, then of the resulting line along Y and finally along X (see [`Self::monotone()`]), so its weights are put together in the same order.
+    fn stencil_weights(&self, ((i, tx), (j, ty), (k, tz)): ((usize, f64), (usize, f64), (usize, f64))) -> [[[f64; 4]; 4]; 4] {
+        if self.scheme != crate::Type::TricubicMonotone {
+            let (wx, wy, wz) = (self.weights(Dir::X, i, tx), self.weights(Dir::Y, j, ty), self.weights(Dir::Z, k, tz));
+            let product = |wx: [f64; 4], wy: [f64; 4], wz: [f64; 4]| wx.map(|wx| wy.map(|wy| wz.map(|wz| wx*wy*wz)));
+            let mut stencil = product(wx, wy, wz);
+            if let Some((share, [[lx, _], [ly, _], [lz, _]])) = self.blend_linear((i, j, k), tx, ty, tz) {
+                for (w, linear) in stencil.iter_mut().flatten().flatten().zip(product(lx, ly, lz).iter().flatten().flatten()) {
+                    *w = (1.0 - share)*(*w) + share*linear;
+                }
+            }
+            return stencil;
+        }
+
+        let dot = |w: &[f64; 4], f: &[f64; 4]| w.iter().zip(f).map(|(w, f)| w*f).sum::<f64>();
+        let mut stencil = [[[0.0; 4]; 4]; 4];
+        let mut lines = [0.0; 4];
+        for (a, line) in lines.iter_mut().enumerate() {
+            let mut values = [0.0; 4];
+            for (b, value) in values.iter_mut().enumerate() {
+                let f = self.column(&self.data, self.index(i-1+a, j-1+b, k-1));
+                stencil[a][b] = kernel::monotone(&self.z, k, tz, &f);
+                *value = dot(&stencil[a][b], &f);
+            }
+            let wy = kernel::monotone(&self.y, j, ty, &values);
+            *line = dot(&wy, &values);
+            for (column, wy) in stencil[a].iter_mut().zip(wy) {
+                column.iter_mut().for_each(|w| *w *= wy);
+            }
+        }
+        let wx = kernel::monotone(&self.x, i, tx, &lines);
+        for (plane, wx) in stencil.iter_mut().zip(wx) {
+            plane.iter_mut().flatten().for_each(|w| *w *= wx);
+        }
+
+        stencil
+    }
+
+    /// The interior nodes along X, Y and Z (as for [`Self::value_at()`]) that the stencil of the cells found by [`Self::locate()`] is filled from, see [`Self::stencil_nodes()`]
+    pub(crate) fn stencil_sources(&self, located: ((usize, f64), (usize, f64), (usize, f64))) -> [Vec<usize>; 3] {
+        let sources = |dir: Dir, (i, t): (usize, f64)| -> Vec<usize> {
+            let stencil = match self.kernel(dir) {
+                Kernel::Cubic => i-1..i+3,
+                Kernel::Linear => i..i+2,
+                Kernel::Nearest if t <= 0.5 => i..i+1,
+                Kernel::Nearest => i+1..i+2
+            };
+            let mut nodes: Vec<usize> = stencil
+                .flat_map(|g| self.ghost_sources(dir as usize, g))
+                .filter(|s| s.1 != 0.0)
+                .map(|s| s.0 - GHOST_LO)
+                .collect();
+            nodes.sort_unstable();
+            nodes.dedup();
+            nodes
+        };
+
+        [sources(Dir::X, located.0), sources(Dir::Y, located.1), sources(Dir::Z, located.2)]
+    }
+}
@@ -0,0 +1,297 @@
+//! Contains reading and writing of NumPy ``.npz`` archives, i.e. uncompressed zip files of ``.npy`` arrays as written by ``np.savez``.
+
+use crate::{Interp3D, Interp3DError, AxisTransform};
+use crate::io::{TRANSFORMS, crc32};
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// Start of every ``.npy`` array
+const NPY_MAGIC: &[u8; 6] = b"\x93NUMPY";
+/// Signatures of the zip records used here
+const LOCAL_HEADER: u32 = 0x04034b50;
+const CENTRAL_HEADER: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x06054b50;
+
+fn error(message: String) -> Interp3DError {
+    Interp3DError::Parse { line: 0, message }
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> Result<u16, Interp3DError> {
+    bytes.get(at..at+2)
+        .map(|b| u16::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| error("npz archive is truncated".to_string()))
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Result<u32, Interp3DError> {
+    bytes.get(at..at+4)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| error("npz archive is truncated".to_string()))
+}
+
+fn u64_at(bytes: &[u8], at: usize) -> Result<u64, Interp3DError> {
+    bytes.get(at..at+8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| error("npz archive is truncated".to_string()))
+}
+
+/// Encodes a C ordered little-endian ``f64`` array with the given shape as ``.npy`` (format version 1.0)
+fn npy(shape: &[usize], values: &[f64]) -> Vec<u8> {
+    let dims: Vec<String> = shape.iter().map(|n| n.to_string()).collect();
+    let shape = if dims.len() == 1 { format!("({},)", dims[0]) } else { format!("({})", dims.join(", ")) };
+    let mut header = format!("{{'descr': '<f8', 'fortran_order': False, 'shape': {}, }}", shape);
+    // like numpy, pad the header with spaces such that the data starts 64 byte aligned
+    let unpadded = NPY_MAGIC.len() + 2 + 2 + header.len() + 1;
+    header.extend(std::iter::repeat_n(' ', (64 - unpadded%64)%64));
+    header.push('\n');
+
+    let mut bytes = Vec::with_capacity(NPY_MAGIC.len() + 4 + header.len() + 8*values.len());
+    bytes.extend_from_slice(NPY_MAGIC);
+    bytes.extend_from_slice(&[1, 0]);
+    bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(header.as_bytes());
+    for v in values {
+        bytes.extend_from_slice(&v.to_le_bytes());
+    }
+
+    bytes
+}
+
+/// Returns the text after ``'key':`` in a ``.npy`` header
+fn header_value<'a>(header: &'a str, key: &str) -> Result<&'a str, Interp3DError> {
+    let pattern = format!("'{}':", key);
+    header.find(&pattern)
+        .map(|start| header[start+pattern.len()..].trim_start())
+        .ok_or_else(|| error(format!("npy header lacks '{}'", key)))
+}
+
+/// Decodes a ``.npy`` array, which needs to hold C ordered little-endian ``f64``. Returns the shape and the values.
+fn parse_npy(name: &str, bytes: &[u8]) -> Result<(Vec<usize>, Vec<f64>), Interp3DError> {
+    if !bytes.starts_with(NPY_MAGIC) || bytes.len() < 10 {
+        return Err(error(format!("{} is not a npy array", name)));
+    }
+    let (header_len, start) = match bytes[6] {
+        1 => (u16_at(bytes, 8)? as usize, 10),
+        2 | 3 => (u32_at(bytes, 8)? as usize, 12),
+        version => return Err(error(format!("{} has the unsupported npy version {}", name, version)))
+    };
+    let header = bytes.get(start..start+header_len)
+        .and_then(|h| std::str::from_utf8(h).ok())
+        .ok_or_else(|| error(format!("{} has a malformed npy header", name)))?;
+
+    if !header_value(header, "descr")?.starts_with("'<f8'") {
+        return Err(error(format!("{} needs to be of dtype '<f8'", name)));
+    }
+    if !header_value(header, "fortran_order")?.starts_with("False") {
+        return Err(error(format!("{} needs to be in C order", name)));
+    }
+    let shape = header_value(header, "shape")?;
+    let shape = shape.strip_prefix('(')
+        .and_then(|s| s.split(')').next())
+        .ok_or_else(|| error(format!("{} has a malformed shape", name)))?;
+    let shape = shape.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().map_err(|_| error(format!("{} has a malformed shape", name))))
+        .collect::<Result<Vec<usize>, _>>()?;
+
+    let data = &bytes[start+header_len..];
+    let bytes_needed = shape.iter().try_fold(8usize, |n, &d| n.checked_mul(d));
+    if bytes_needed != Some(data.len()) {
+        return Err(error(format!("{} has {} bytes of data, which doesn't match its shape {:?}", name, data.len(), shape)));
+    }
+
+    Ok((shape, data.chunks_exact(8).map(|b| f64::from_le_bytes(b.try_into().unwrap())).collect()))
+}
+
+/// Returns the (uncompressed) content of the archive member ``name``
+fn zip_member<'a>(zip: &'a [u8], name: &str) -> Result<&'a [u8], Interp3DError> {
+    find_member(zip, name)?.ok_or_else(|| error(format!("npz archive has no array '{}'", name.trim_end_matches(".npy"))))
+}
+
+/// Same as [`zip_member()`], but ``None`` if the archive has no member ``name``
+fn find_member<'a>(zip: &'a [u8], name: &str) -> Result<Option<&'a [u8]>, Interp3DError> {
+    // the end of central directory record sits at the very end, followed by a comment of at most 64k
+    let eocd = (0..zip.len().saturating_sub(21)).rev()
+        .take(0x10000 + 22)
+        .find(|&at| u32_at(zip, at).ok() == Some(END_OF_CENTRAL_DIRECTORY))
+        .ok_or_else(|| error("not a zip archive".to_string()))?;
+    let entries = u16_at(zip, eocd+10)?;
+    let mut at = u32_at(zip, eocd+16)? as usize;
+
+    for _ in 0..entries {
+        if u32_at(zip, at)? != CENTRAL_HEADER {
+            return Err(error("malformed zip central directory".to_string()));
+        }
+        let method = u16_at(zip, at+10)?;
+        let crc = u32_at(zip, at+16)?;
+        let mut size = u32_at(zip, at+20)? as u64;
+        let name_len = u16_at(zip, at+28)? as usize;
+        let extra_len = u16_at(zip, at+30)? as usize;
+        let comment_len = u16_at(zip, at+32)? as usize;
+        let mut offset = u32_at(zip, at+42)? as u64;
+        let member = zip.get(at+46..at+46+name_len).ok_or_else(|| error("npz archive is truncated".to_string()))?;
+
+        if member == name.as_bytes() {
+            // sizes and offsets that don't fit are moved into the zip64 extra field, in this order
+            let mut extra = at + 46 + name_len;
+            let extra_end = extra + extra_len;
+            while extra + 4 <= extra_end {
+                let (id, len) = (u16_at(zip, extra)?, u16_at(zip, extra+2)? as usize);
+                if id == 0x0001 {
+                    let mut field = extra + 4;
+                    if u32_at(zip, at+24)? == u32::MAX {
+                        // the uncompressed size comes first
+                        field += 8;
+                    }
+                    if size == u32::MAX as u64 {
+                        size = u64_at(zip, field)?;
+                        field += 8;
+                    }
+                    if offset == u32::MAX as u64 {
+                        offset = u64_at(zip, field)?;
+                    }
+                }
+                extra += 4 + len;
+            }
+
+            if method != 0 {
+                return Err(error(format!("{} is compressed, only uncompressed archives (np.savez) are supported", name)));
+            }
+            let local = offset as usize;
+            if u32_at(zip, local)? != LOCAL_HEADER {
+                return Err(error(format!("malformed zip header of {}", name)));
+            }
+            let start = local + 30 + u16_at(zip, local+26)? as usize + u16_at(zip, local+28)? as usize;
+            let content = zip.get(start..start + size as usize).ok_or_else(|| error("npz archive is truncated".to_string()))?;
+            if crc32(content) != crc {
+                return Err(error(format!("checksum mismatch in {}", name)));
+            }
+
+            return Ok(Some(content));
+        }
+
+        at += 46 + name_len + extra_len + comment_len;
+    }
+
+    Ok(None)
+}
+
+impl Interp3D {
+    /// Exports the data set to a NumPy ``.npz`` archive (uncompressed, like ``np.savez``) with the arrays ``x``, ``y`` and ``z`` holding the node positions and ``data`` the samples with shape ``(nx, ny, nz)``.
+    /// All arrays are ``float64`` in C order, so ``np.load(file)["data"][i, j, k]`` is [`Self::value_at(i, j, k)`](Self::value_at()). The ghost cells are not exported.
+    /// The node positions are in the coordinates of the grid (see [`Self::set_axis_transform()`]). If an axis has a transform, the archive holds the additional array ``transforms`` with one code per axis (0 for [`AxisTransform::Identity`], 1 for [`AxisTransform::Log10`], 2 for [`AxisTransform::Ln`]), which [`Self::from_npz()`] restores.
+    ///
+    /// Returns [`Interp3DError::NotSetUp`] if there is no data to export yet and [`Interp3DError::UnsupportedTransform`] for an [`AxisTransform::Custom`], and doesn't touch the file in both cases.
+    pub fn export_npz(&self, file: &str) -> Result<(), Interp3DError> {
+        if self.data.is_empty() {
+            return Err(Interp3DError::NotSetUp);
+        }
+        let transforms = self.transform_codes()?;
+
+        let (nx, ny, nz) = self.dims();
+        let data: Vec<f64> = self.iter_points().map(|p| p.3).collect();
+        let mut arrays = vec![
+            ("x.npy", npy(&[nx], self.grid_x())),
+            ("y.npy", npy(&[ny], self.grid_y())),
+            ("z.npy", npy(&[nz], self.grid_z())),
+            ("data.npy", npy(&[nx, ny, nz], &data))
+        ];
+        if let Some(codes) = transforms {
+            arrays.push(("transforms.npy", npy(&[3], &codes.map(f64::from))));
+        }
+        if arrays.iter().map(|(_, a)| a.len() as u64 + 100).sum::<u64>() > u32::MAX as u64 {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "data set too large for a npz archive without zip64").into());
+        }
+
+        let mut w = BufWriter::new(File::create(file)?);
+        let mut central = Vec::new();
+        let mut offset = 0u32;
+        for (name, array) in &arrays {
+            // version 2.0, no flags, stored, 1980-01-01 00:00
+            let mut header = Vec::new();
+            header.extend_from_slice(&20u16.to_le_bytes());
+            header.extend_from_slice(&0u16.to_le_bytes());
+            header.extend_from_slice(&0u16.to_le_bytes());
+            header.extend_from_slice(&0u16.to_le_bytes());
+            header.extend_from_slice(&0x21u16.to_le_bytes());
+            header.extend_from_slice(&crc32(array).to_le_bytes());
+            header.extend_from_slice(&(array.len() as u32).to_le_bytes());
+            header.extend_from_slice(&(array.len() as u32).to_le_bytes());
+            header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            header.extend_from_slice(&0u16.to_le_bytes());
+
+            w.write_all(&LOCAL_HEADER.to_le_bytes())?;
+            w.write_all(&header)?;
+            w.write_all(name.as_bytes())?;
+            w.write_all(array)?;
+
+            // made by version 2.0, the shared fields, no comment, disk 0, no attributes
+            central.extend_from_slice(&CENTRAL_HEADER.to_le_bytes());
+            central.extend_from_slice(&20u16.to_le_bytes());
+            central.extend_from_slice(&header);
+            central.extend_from_slice(&[0; 10]);
+            central.extend_from_slice(&offset.to_le_bytes());
+            central.extend_from_slice(name.as_bytes());
+
+            offset += (4 + header.len() + name.len() + array.len()) as u32;
+        }
+
+        w.write_all(&central)?;
+        w.write_all(&END_OF_CENTRAL_DIRECTORY.to_le_bytes())?;
+        w.write_all(&[0; 4])?;
+        w.write_all(&(arrays.len() as u16).to_le_bytes())?;
+        w.write_all(&(arrays.len() as u16).to_le_bytes())?;
+        w.write_all(&(central.len() as u32).to_le_bytes())?;
+        w.write_all(&offset.to_le_bytes())?;
+        w.write_all(&0u16.to_le_bytes())?;
+        w.flush()?;
+
+        Ok(())
+    }
+
+    /// Constructs the interpolator from a NumPy ``.npz`` archive in the layout of [`Self::export_npz()`], e.g. written in Python with
+    /// ```text
+    /// np.savez("file.npz", x=x, y=y, z=z, data=data)
+    /// ```
+    /// where ``data.shape == (len(x), len(y), len(z))``. The arrays need to be ``float64`` (``'<f8'``) in C order and the archive uncompressed (not ``np.savez_compressed``). The ghost cells are rebuilt like for [`Self::from_grid_and_data()`].  
+    /// The axis transforms are taken from the optional array ``transforms`` (see [`Self::export_npz()`]), without it every axis is [`AxisTransform::Identity`].
+    pub fn from_npz(file: &str) -> Result<Self, Interp3DError> {
+        Self::read_npz(&std::fs::read(file)?, &Interp3D::default())
+    }
+
+    /// Reads the archive ``zip`` in the layout of [`Self::export_npz()`], with the settings of ``template`` (see [`Self::settings_only()`]) but the axis transforms of the archive.
+    pub(crate) fn read_npz(zip: &[u8], template: &Interp3D) -> Result<Self, Interp3DError> {
+        let mut axes = Vec::with_capacity(3);
+        for name in ["x.npy", "y.npy", "z.npy"] {
+            let (shape, values) = parse_npy(name, zip_member(zip, name)?)?;
+            if shape.len() != 1 {
+                return Err(error(format!("{} needs to be 1-dimensional, but has shape {:?}", name, shape)));
+            }
+            axes.push(values);
+        }
+
+        let (shape, data) = parse_npy("data.npy", zip_member(zip, "data.npy")?)?;
+        let expected = [axes[0].len(), axes[1].len(), axes[2].len()];
+        if shape != expected {
+            return Err(error(format!("data.npy has shape {:?}, but the axes need {:?}", shape, expected)));
+        }
+
+        let mut template = template.settings_only();
+        template.transform = [AxisTransform::Identity; 3];
+        if let Some(member) = find_member(zip, "transforms.npy")? {
+            let (shape, codes) = parse_npy("transforms.npy", member)?;
+            if shape != [3] {
+                return Err(error(format!("transforms.npy needs to have shape [3], but has {:?}", shape)));
+            }
+            for (transform, code) in template.transform.iter_mut().zip(codes) {
+                *transform = match TRANSFORMS.get(code as usize) {
+                    Some(&(_, t)) if code.fract() == 0.0 && code >= 0.0 => t,
+                    _ => return Err(error(format!("unknown axis transform {} in transforms.npy", code)))
+                };
+            }
+        }
+        let mut data = data.into_iter();
+        Self::from_parts(&axes[0], &axes[1], &axes[2], &template, || Ok(data.next().unwrap()))
+    }
+}
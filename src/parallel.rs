@@ -0,0 +1,202 @@
+//! Contains the multithreaded variants of the data generation and of the batch queries, available with the ``parallel`` feature.
+
+use crate::{Interp3D, DataGenConf, Interp3DError, GridNode, QueryCache, GHOST_LO, GHOST_HI};
+use crate::samples::SamplesMut;
+
+use std::ops::Range;
+use std::sync::Mutex;
+use std::thread;
+
+/// Number of points the threads of [`Interp3D::par_interpolate_many_into()`] take from the queue at a time
+const QUERY_CHUNK: usize = 4096;
+
+impl Interp3D {
+    /// Multithreaded version of [`Self::generate_data()`] for expensive closures. Requires the ``parallel`` feature.  
+    /// The interior is handed out to the threads slab by slab (one slab being all nodes with the same ``x``), so uneven costs across the grid balance out. One thread per available core is used.
+    /// 
+    /// Unlike [`Self::generate_data()`] the closure needs to be ``Fn + Sync`` since it is shared between the threads. As every node is computed independently, the result is bit-identical to the sequential version, no matter the number of threads.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the config is invalid (like [`Self::generate_data()`]) or if the closure panics.
+    pub fn generate_data_par<F>(&mut self, f: F, conf: &DataGenConf)
+    where F: Fn(f64, f64, f64) -> f64 + Sync {
+        if let Err(e) = self.try_generate_data_par(f, conf) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Same as [`Self::generate_data_par()`], but an invalid config is returned as an error instead of panicking.
+    pub fn try_generate_data_par<F>(&mut self, f: F, conf: &DataGenConf) -> Result<(), Interp3DError>
+    where F: Fn(f64, f64, f64) -> f64 + Sync {
+        self.setup(conf)?;
+
+        let (ny, nz) = (self.ny, self.nz);
+        let interior = [GHOST_LO..self.nx-GHOST_HI, GHOST_LO..ny-GHOST_HI, GHOST_LO..nz-GHOST_HI];
+        // the physical node positions, see set_axis_transform()
+        let axis = |d: usize, v: &[f64]| -> Vec<f64> { v.iter().map(|&u| self.transform[d].inverse(u)).collect() };
+        let axes = (axis(0, &self.x), axis(1, &self.y), axis(2, &self.z));
+        match self.data.writer() {
+            SamplesMut::F64(data) => fill_slabs(data, (ny, nz), interior, |i, j, k| f(axes.0[i], axes.1[j], axes.2[k])),
+            SamplesMut::F32(data) => fill_slabs(data, (ny, nz), interior, |i, j, k| f(axes.0[i], axes.1[j], axes.2[k]) as f32)
+        }
+
+        self.set_data_outermost();
+        Ok(())
+    }
+
+    /// Multithreaded version of [`Self::regenerate_region()`] for expensive closures. Requires the ``parallel`` feature.  
+    /// The slabs of the box along X are handed out to the threads like in [`Self::generate_data_par()`], and the result is bit-identical to the sequential version.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet or if the closure panics.
+    pub fn regenerate_region_par<F>(&mut self, f: F, xr: (f64, f64), yr: (f64, f64), zr: (f64, f64))
+    where F: Fn(f64, f64, f64) -> f64 + Sync {
+        assert!(!self.data.is_empty(), "Interpolator has not been set up");
+        let region = self.region([xr, yr, zr]);
+
+        let (ny, nz) = (self.ny, self.nz);
+        let axis = |d: usize, v: &[f64]| -> Vec<f64> { v.iter().map(|&u| self.transform[d].inverse(u)).collect() };
+        let axes = (axis(0, &self.x), axis(1, &self.y), axis(2, &self.z));
+        match self.data.writer() {
+            SamplesMut::F64(data) => fill_slabs(data, (ny, nz), region.clone(), |i, j, k| f(axes.0[i], axes.1[j], axes.2[k])),
+            SamplesMut::F32(data) => fill_slabs(data, (ny, nz), region.clone(), |i, j, k| f(axes.0[i], axes.1[j], axes.2[k]) as f32)
+        }
+
+        self.region_regenerated(region);
+    }
+
+    /// Multithreaded version of iterating over [`Self::iter()`], calls ``f`` with every interior node. Requires the ``parallel`` feature.  
+    /// The slabs along X are handed out to the threads like in [`Self::generate_data_par()`], so the nodes are visited in no particular order. Use this for expensive per-node work, e.g. collecting derived quantities into a ``Mutex`` or atomics.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the closure panics.
+    pub fn par_for_each<F>(&self, f: F)
+    where F: Fn(GridNode) + Sync {
+        let (nx, ny, nz) = self.dims();
+        let queue = Mutex::new(0..nx);
+
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        thread::scope(|s| {
+            for _ in 0..threads {
+                s.spawn(|| loop {
+                    let next = queue.lock().unwrap().next();
+                    let Some(i) = next else { break };
+                    for n in i*ny*nz..(i+1)*ny*nz {
+                        f(self.node(n));
+                    }
+                });
+            }
+        });
+    }
+
+    /// Multithreaded version of [`Self::interpolate_many()`] for large batches of points. Requires the ``parallel`` feature.  
+    /// The points are handed out to one thread per available core in chunks of a few thousand, and every thread keeps its own [`QueryCache`] across its chunks, so nothing is shared but the queue. The results come in the order of ``points`` and are bit-identical to the ones of [`Self::interpolate_many()`].
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+    /// let ip = Interp3D::from_config(|x, y, z| x + y*z, &config);
+    /// 
+    /// let points: Vec<_> = (0..10000).map(|i| (1e-4*i as f64, 0.5, 0.25)).collect();
+    /// assert_eq!(ip.par_interpolate_many(&points), ip.interpolate_many(&points));
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet or if a point is out of bounds with [`OutOfBounds::Error`](crate::OutOfBounds::Error).
+    pub fn par_interpolate_many(&self, points: &[(f64, f64, f64)]) -> Vec<f64> {
+        let mut values = vec![0.0; points.len()];
+        self.par_interpolate_many_into(points, &mut values);
+        values
+    }
+
+    /// Same as [`Self::par_interpolate_many()`], but writes the results into ``values`` instead of allocating a new ``Vec``, e.g. to reuse the buffer for every time step.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if ``values`` and ``points`` differ in length, and like [`Self::par_interpolate_many()`].
+    pub fn par_interpolate_many_into(&self, points: &[(f64, f64, f64)], values: &mut [f64]) {
+        assert!(!self.data.is_empty(), "Interpolator has not been set up");
+        assert_eq!(values.len(), points.len(), "one value per point is needed");
+
+        let queue = Mutex::new(points.chunks(QUERY_CHUNK).zip(values.chunks_mut(QUERY_CHUNK)));
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        thread::scope(|s| {
+            for _ in 0..threads {
+                s.spawn(|| {
+                    let mut cache = QueryCache::default();
+                    loop {
+                        let next = queue.lock().unwrap().next();
+                        let Some((points, values)) = next else { break };
+                        for (&(x, y, z), value) in points.iter().zip(values) {
+                            *value = self.interpolate_cached(&mut cache, x, y, z);
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    /// Multithreaded version of [`Self::interpolate_grid()`] for large lattices. Requires the ``parallel`` feature.  
+    /// The query vectors are located once like in [`Self::interpolate_grid()`], then the slabs of all points with the same ``x`` are handed out to one thread per available core. The results are bit-identical to the ones of [`Self::interpolate_grid()`].
+    /// 
+    /// # Panics
+    /// 
+    /// Panics like [`Self::interpolate_grid()`].
+    pub fn par_interpolate_grid(&self, xq: &[f64], yq: &[f64], zq: &[f64]) -> Vec<f64> {
+        let queries = match self.locate_grid(xq, yq, zq) {
+            Ok(queries) => queries,
+            Err(e) => panic!("{}", e)
+        };
+
+        let mut values = vec![0.0; xq.len()*yq.len()*zq.len()];
+        if values.is_empty() {
+            return values;
+        }
+        let queue = Mutex::new(values.chunks_mut(yq.len()*zq.len()).enumerate());
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        thread::scope(|s| {
+            for _ in 0..threads {
+                s.spawn(|| loop {
+                    let next = queue.lock().unwrap().next();
+                    let Some((a, slab)) = next else { break };
+                    self.fill_grid_slab(&queries, a, slab);
+                });
+            }
+        });
+
+        values
+    }
+}
+
+/// Fills the nodes ``[is, js, ks]`` of ``data`` with ``f(i, j, k)``, handing out the slabs along X to one thread per available core.
+fn fill_slabs<T, F>(data: &mut [T], (ny, nz): (usize, usize), [is, js, ks]: [Range<usize>; 3], f: F)
+where T: Send, F: Fn(usize, usize, usize) -> T + Sync {
+    let slabs: Vec<(usize, &mut [T])> = data
+        .chunks_mut(ny*nz)
+        .enumerate()
+        .filter(|(i, _)| is.contains(i))
+        .collect();
+    let queue = Mutex::new(slabs.into_iter());
+
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    thread::scope(|s| {
+        for _ in 0..threads {
+            s.spawn(|| loop {
+                let next = queue.lock().unwrap().next();
+                let Some((i, slab)) = next else { break };
+                for j in js.clone() {
+                    for k in ks.clone() {
+                        slab[j*nz + k] = f(i, j, k);
+                    }
+                }
+            });
+        }
+    });
+}
@@ -0,0 +1,173 @@
+//! Contains the storage of the samples, either at full or reduced precision (see [`Storage`]) or quantized to ``u16`` (see [`CompactInterp3D`](crate::CompactInterp3D)).
+
+use crate::Storage;
+
+use std::sync::Arc;
+
+/// The samples of an interpolator including the ghost cells, in storage order.
+/// Values are always handed in and out as ``f64``, with ``F32`` they are rounded once on store.  
+/// The samples are shared between clones, the first write to a shared block copies it (see [`Arc::make_mut()`]).
+#[derive(Clone, Debug)]
+pub(crate) enum Samples {
+    F64(Arc<[f64]>),
+    F32(Arc<[f32]>),
+    /// only held by a [`CompactInterp3D`](crate::CompactInterp3D), which never writes to its samples
+    U16(Quantized)
+}
+
+/// Samples quantized to ``u16`` with a linear map per slab of ``slab_len`` consecutive samples: the sample is ``offset + step*value``
+#[derive(Clone, Debug)]
+pub(crate) struct Quantized {
+    pub(crate) values: Arc<[u16]>,
+    pub(crate) slab_len: usize,
+    /// ``(offset, step)`` of every slab
+    pub(crate) scales: Arc<[(f64, f64)]>
+}
+
+impl Quantized {
+    /// Quantizes finite ``samples`` in slabs of ``slab_len``, the range of every slab is divided into ``u16::MAX`` steps.
+    pub(crate) fn new(samples: &[f64], slab_len: usize) -> Self {
+        let scales: Arc<[(f64, f64)]> = samples.chunks(slab_len).map(|slab| {
+            let (min, max) = slab.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &v| (min.min(v), max.max(v)));
+            (min, (max - min)/u16::MAX as f64)
+        }).collect();
+        let values = samples.iter().enumerate().map(|(index, &v)| {
+            let (offset, step) = scales[index/slab_len];
+            if step > 0.0 { ((v - offset)/step).round() as u16 } else { 0 }
+        }).collect();
+
+        Quantized { values, slab_len, scales }
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, index: usize) -> f64 {
+        let (offset, step) = self.scales[index/self.slab_len];
+        offset + step*self.values[index] as f64
+    }
+}
+
+/// Write access to the samples, see [`Samples::writer()`]
+pub(crate) enum SamplesMut<'a> {
+    F64(&'a mut [f64]),
+    F32(&'a mut [f32])
+}
+
+impl SamplesMut<'_> {
+    #[inline]
+    pub(crate) fn get(&self, index: usize) -> f64 {
+        match self {
+            SamplesMut::F64(v) => v[index],
+            SamplesMut::F32(v) => v[index] as f64
+        }
+    }
+
+    #[inline]
+    pub(crate) fn set(&mut self, index: usize, value: f64) {
+        match self {
+            SamplesMut::F64(v) => v[index] = value,
+            SamplesMut::F32(v) => v[index] = value as f32
+        }
+    }
+}
+
+impl Default for Samples {
+    fn default() -> Self {
+        Samples::F64(Arc::new([]))
+    }
+}
+
+impl Samples {
+    /// ``len`` zeros with the given storage type
+    pub(crate) fn zeros(storage: Storage, len: usize) -> Self {
+        match storage {
+            Storage::F64 => Samples::F64(std::iter::repeat_n(0.0, len).collect()),
+            Storage::F32 => Samples::F32(std::iter::repeat_n(0.0, len).collect())
+        }
+    }
+
+    pub(crate) fn storage(&self) -> Storage {
+        match self {
+            Samples::F64(_) => Storage::F64,
+            Samples::F32(_) => Storage::F32,
+            Samples::U16(_) => unreachable!("quantized samples have no storage type")
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Samples::F64(v) => v.len(),
+            Samples::F32(v) => v.len(),
+            Samples::U16(q) => q.values.len()
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        match self {
+            Samples::F64(v) => v.is_empty(),
+            Samples::F32(v) => v.is_empty(),
+            Samples::U16(q) => q.values.is_empty()
+        }
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, index: usize) -> f64 {
+        match self {
+            Samples::F64(v) => v[index],
+            Samples::F32(v) => v[index] as f64,
+            Samples::U16(q) => q.get(index)
+        }
+    }
+
+    /// Overwrites a single sample, see [`Self::writer()`] for many writes in a row
+    #[inline]
+    pub(crate) fn set(&mut self, index: usize, value: f64) {
+        self.writer().set(index, value);
+    }
+
+    /// Write access to all samples, which copies them first if they are shared with a clone.  
+    /// That check is only done once here, so loops over many samples should write through this instead of calling [`Self::set()`] for every one of them.
+    pub(crate) fn writer(&mut self) -> SamplesMut<'_> {
+        match self {
+            Samples::F64(v) => SamplesMut::F64(Arc::make_mut(v)),
+            Samples::F32(v) => SamplesMut::F32(Arc::make_mut(v)),
+            Samples::U16(_) => unreachable!("quantized samples are read-only")
+        }
+    }
+
+    /// Reference to the sample at ``index``, ``None`` for ``F32`` and ``U16`` which don't store ``f64``s
+    pub(crate) fn get_ref(&self, index: usize) -> Option<&f64> {
+        match self {
+            Samples::F64(v) => Some(&v[index]),
+            _ => None
+        }
+    }
+
+    /// Mutable reference to the sample at ``index``, see [`Self::get_ref()`]
+    pub(crate) fn get_mut(&mut self, index: usize) -> Option<&mut f64> {
+        match self {
+            Samples::F64(v) => Some(&mut Arc::make_mut(v)[index]),
+            _ => None
+        }
+    }
+
+    /// The 4 consecutive samples starting at ``index``, i.e. a column of the stencil along Z
+    #[inline]
+    pub(crate) fn column(&self, index: usize) -> [f64; 4] {
+        match self {
+            Samples::F64(v) => v[index..index+4].try_into().unwrap(),
+            Samples::F32(v) => std::array::from_fn(|n| v[index+n] as f64),
+            Samples::U16(q) => std::array::from_fn(|n| q.get(index+n))
+        }
+    }
+
+    /// Converts the samples to another storage type, rounding them if that is ``F32``. Quantized samples are expanded again.
+    pub(crate) fn converted(self, storage: Storage) -> Self {
+        match (self, storage) {
+            (Samples::U16(q), Storage::F64) => Samples::F64((0..q.values.len()).map(|index| q.get(index)).collect()),
+            (Samples::U16(q), Storage::F32) => Samples::F32((0..q.values.len()).map(|index| q.get(index) as f32).collect()),
+            (Samples::F64(v), Storage::F32) => Samples::F32(v.iter().map(|&v| v as f32).collect()),
+            (Samples::F32(v), Storage::F64) => Samples::F64(v.iter().map(|&v| f64::from(v)).collect()),
+            (samples, _) => samples
+        }
+    }
+}
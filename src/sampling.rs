@@ -0,0 +1,144 @@
+//! Contains the drawing of random points that are distributed like the interpolated field.
+
+use crate::{Interp3D, Interp3DError, Dir, Type, GHOST_LO, GHOST_HI};
+use crate::kernel::Kernel;
+use crate::analysis::splitmix64;
+
+/// Number of intervals the cell is divided into to find the largest sum of the absolute weights, see [`Interp3D::lebesgue_bounds()`]
+const LEBESGUE_STEPS: usize = 64;
+
+impl Interp3D {
+    /// Draws ``n`` random points from the domain that are distributed like the interpolated field, i.e. the field is taken as an unnormalised probability density (with respect to the physical coordinates, see [`Self::set_axis_transform()`]). The same ``seed`` gives the same points.
+    /// See [`Self::sample_points_with()`] for how the points are drawn and for a version that takes the random numbers from elsewhere.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+    /// let ip = Interp3D::from_config(|x, _, _| x, &config);
+    /// 
+    /// let points = ip.sample_points(1000, 42).unwrap();
+    /// assert_eq!(points, ip.sample_points(1000, 42).unwrap());
+    /// // the density grows along x, so the points crowd at the upper end
+    /// assert!(points.iter().filter(|p| p.0 > 0.5).count() > 700);
+    /// ```
+    pub fn sample_points(&self, n: usize, seed: u64) -> Result<Vec<(f64, f64, f64)>, Interp3DError> {
+        let mut state = seed;
+        self.sample_points_with(n, || (splitmix64(&mut state) >> 11) as f64/(1u64 << 53) as f64)
+    }
+
+    /// Same as [`Self::sample_points()`], but with the uniformly distributed random numbers in ``[0, 1)`` from ``uniform``, e.g. ``|| rng.gen::<f64>()`` with the ``rand`` crate.  
+    /// Each point is drawn by rejection: a cell is picked with a probability proportional to its volume times an upper bound of the field inside of it, then a uniformly distributed point of the cell is kept with the probability of the field over that bound. So the points follow the field exactly, including where an overshooting scheme like [`Type::Tricubic`] dips below 0 between non-negative samples (the density is 0 there). Setting up the table of cells takes about as long as a few queries per cell.  
+    /// Every sample needs to be non-negative and finite, otherwise this is [`Interp3DError::InvalidDensity`] with the first offending node, as it is if all samples are 0. Without data it's [`Interp3DError::NotSetUp`], and with supplied derivatives (see [`Self::generate_data_hermite()`]) [`Interp3DError::UnsupportedScheme`], as the field can't be bounded by the samples then.
+    pub fn sample_points_with<R>(&self, n: usize, mut uniform: R) -> Result<Vec<(f64, f64, f64)>, Interp3DError>
+    where R: FnMut() -> f64 {
+        if self.data.is_empty() {
+            return Err(Interp3DError::NotSetUp);
+        }
+        if self.hermite().is_some() {
+            return Err(Interp3DError::UnsupportedScheme { scheme: self.scheme });
+        }
+        let (nx, ny, nz) = self.dims();
+        for ((i, j, k), n) in self.interior().zip(0..) {
+            let v = self.data.get(self.index(i, j, k));
+            if !(v >= 0.0 && v.is_finite()) {
+                return Err(Interp3DError::InvalidDensity { node: Some((n/(ny*nz), n/nz % ny, n % nz)) });
+            }
+        }
+
+        // node positions in physical coordinates, for the volumes and the points
+        let physical: [Vec<f64>; 3] = [(Dir::X, &self.x), (Dir::Y, &self.y), (Dir::Z, &self.z)].map(|(dir, v)| {
+            v[GHOST_LO..v.len()-GHOST_HI].iter().map(|&u| self.transform[dir as usize].inverse(u)).collect()
+        });
+
+        // upper bound of the field in every cell and the cumulative masses of the envelope
+        let stencil_max = self.stencil_max();
+        let [bx, by, bz] = [Dir::X, Dir::Y, Dir::Z].map(|dir| self.lebesgue_bounds(dir));
+        // a blend is at most the sum of its parts, and the bound of the trilinear part is below the one of the cubic part
+        let parts = match self.scheme {
+            Type::Blend(alpha) => alpha.abs() + (1.0 - alpha).abs(),
+            _ => 1.0
+        };
+        let mut bounds = Vec::with_capacity((nx-1)*(ny-1)*(nz-1));
+        let mut cumulative = Vec::with_capacity(bounds.capacity());
+        let mut total = 0.0;
+        for i in 0..nx-1 {
+            for j in 0..ny-1 {
+                for k in 0..nz-1 {
+                    let bound = parts*bx[i]*by[j]*bz[k]*stencil_max[i*(ny-1)*(nz-1) + j*(nz-1) + k];
+                    let volume = (physical[0][i+1] - physical[0][i])*(physical[1][j+1] - physical[1][j])*(physical[2][k+1] - physical[2][k]);
+                    total += bound*volume.abs();
+                    bounds.push(bound);
+                    cumulative.push(total);
+                }
+            }
+        }
+        if total == 0.0 {
+            return Err(Interp3DError::InvalidDensity { node: None });
+        }
+
+        let mut points = Vec::with_capacity(n);
+        while points.len() < n {
+            let target = uniform()*total;
+            let cell = cumulative.partition_point(|&mass| mass <= target).min(cumulative.len()-1);
+            let (i, j, k) = (cell/((ny-1)*(nz-1)), cell/(nz-1) % (ny-1), cell % (nz-1));
+
+            let p = [(0, i), (1, j), (2, k)].map(|(d, c)| physical[d][c] + uniform()*(physical[d][c+1] - physical[d][c]));
+            let (x, y, z) = self.to_grid(p[0], p[1], p[2]);
+            let t = |v: &[f64], c: usize, u: f64| (c + GHOST_LO, ((u - v[c+GHOST_LO])/(v[c+GHOST_LO+1] - v[c+GHOST_LO])).clamp(0.0, 1.0));
+            let value = self.evaluate((t(&self.x, i, x), t(&self.y, j, y), t(&self.z, k, z)));
+            debug_assert!(value <= bounds[cell]*(1.0 + 1e-9) + 1e-300, "the bound of the cell ({}, {}, {}) is too low", i, j, k);
+
+            if uniform()*bounds[cell] < value {
+                points.push((p[0], p[1], p[2]));
+            }
+        }
+
+        Ok(points)
+    }
+
+    /// The largest absolute sample of the stencil of every interior cell, with the cells in storage order.  
+    /// The stencil of the cubic schemes is the largest one, its maximum is found separably along Z, Y and X (a sliding window of 4 nodes each).
+    fn stencil_max(&self) -> Vec<f64> {
+        let (nx, ny, nz) = (self.nx, self.ny, self.nz);
+        let (cx, cy, cz) = (nx - GHOST_LO - GHOST_HI - 1, ny - GHOST_LO - GHOST_HI - 1, nz - GHOST_LO - GHOST_HI - 1);
+        let window = |a: usize, get: &dyn Fn(usize) -> f64| (a+GHOST_LO-1..=a+GHOST_LO+2).map(get).fold(0.0, f64::max);
+
+        // the cells along Z for every node of the other directions, then along Y and X
+        let along_z: Vec<f64> = (0..nx*ny*cz).map(|n| {
+            let (ij, k) = (n/cz, n % cz);
+            window(k, &|c| self.sample(&self.data, ij*nz + c).abs())
+        }).collect();
+        let along_y: Vec<f64> = (0..nx*cy*cz).map(|n| {
+            let (i, j, k) = (n/(cy*cz), n/cz % cy, n % cz);
+            window(j, &|c| along_z[(i*ny + c)*cz + k])
+        }).collect();
+        (0..cx*cy*cz).map(|n| {
+            let (i, jk) = (n/(cy*cz), n % (cy*cz));
+            window(i, &|c| along_y[c*cy*cz + jk])
+        }).collect()
+    }
+
+    /// An upper bound of the sum of the absolute stencil weights along ``dir`` (the Lebesgue constant) for every interior cell, so that the field in the cell is at most that product times [`Self::stencil_max()`].  
+    /// The linear and nearest weights are non-negative and the monotone scheme stays within the range of the samples, which gives 1. The cubic weights are checked at [`LEBESGUE_STEPS`] points along the cell, plus what the sum can grow in between according to its derivative (doubled, to be on the safe side).
+    fn lebesgue_bounds(&self, dir: Dir) -> Vec<f64> {
+        let v = self.axis(dir);
+        let cells = GHOST_LO..v.len()-1-GHOST_HI;
+        if self.kernel(dir) != Kernel::Cubic || self.scheme == Type::TricubicMonotone {
+            return vec![1.0; cells.len()];
+        }
+
+        let abs_sum = |w: [f64; 4]| w.iter().map(|w| w.abs()).sum::<f64>();
+        cells.map(|i| {
+            let (mut sum, mut slope) = (0.0f64, 0.0f64);
+            for s in 0..=LEBESGUE_STEPS {
+                let t = s as f64/LEBESGUE_STEPS as f64;
+                sum = sum.max(abs_sum(self.weights(dir, i, t)));
+                slope = slope.max(abs_sum(self.weights_derivative(dir, i, t))*(v[i+1] - v[i]));
+            }
+            sum + slope/LEBESGUE_STEPS as f64
+        }).collect()
+    }
+}
@@ -0,0 +1,102 @@
+//! Contains the cleanup of hand-made axes for [`Interp3D::from_grid_and_data_lenient()`]: sorting, and merging of nodes that are (nearly) the same.
+
+use crate::{Interp3D, Interp3DError, Interp3DBuilder};
+
+/// What [`Interp3D::from_grid_and_data_lenient()`] had to change about the axes, per direction
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct SanitizeReport {
+    /// whether the nodes along the axis had to be sorted
+    pub reordered: [bool; 3],
+    /// number of nodes along the axis that were merged into a neighbour
+    pub merged: [usize; 3]
+}
+
+/// A sanitized axis: the new node positions and, for each of them, the indices of the given nodes it was merged from
+struct Axis {
+    nodes: Vec<f64>,
+    sources: Vec<Vec<usize>>
+}
+
+/// Sorts ``v`` ascending (stable, so equal nodes keep their order) and merges every node that is at most ``merge_tol`` above the first node of its group into that group, at the mean position
+fn sanitize_axis(v: &[f64], merge_tol: f64) -> Axis {
+    let mut order: Vec<usize> = (0..v.len()).collect();
+    order.sort_by(|&a, &b| v[a].total_cmp(&v[b]));
+
+    let mut sources: Vec<Vec<usize>> = Vec::with_capacity(v.len());
+    for index in order {
+        match sources.last_mut() {
+            Some(group) if v[index] - v[group[0]] <= merge_tol => group.push(index),
+            _ => sources.push(vec![index])
+        }
+    }
+    let nodes = sources.iter().map(|group| group.iter().map(|&n| v[n]).sum::<f64>()/group.len() as f64).collect();
+
+    Axis { nodes, sources }
+}
+
+impl Interp3D {
+    /// Same as [`Self::from_grid_and_data()`], but for axes that aren't strictly increasing, like tables in descending order or merged from several files.  
+    /// Each axis is sorted ascending, with the data reordered along with it, and nodes that are at most ``merge_tol`` apart (counted from the lowest one of a group) are merged into one node at their mean position, whose samples are the mean of the merged planes. Exact duplicates are always merged. The returned [`SanitizeReport`] tells what was done.  
+    /// An axis with less than 2 distinct nodes is still an error, as are non-finite coordinates, a wrong data length (with respect to the axes as given) and a ``merge_tol`` that is negative or not finite ([`Interp3DError::InvalidTolerance`]). [`Self::from_grid_and_data()`] keeps rejecting any axis that isn't strictly increasing.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let data = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0];
+    /// let (ip, report) = Interp3D::from_grid_and_data_lenient(&[3.0, 1.0, 0.0], &[0.0, 0.5], &[-1.0, 1.0], &data, 1e-9).unwrap();
+    /// 
+    /// assert_eq!(ip.grid_x(), &[0.0, 1.0, 3.0]);
+    /// assert_eq!(ip.interpolate(0.0, 0.0, -1.0), 9.0);
+    /// assert_eq!(report, SanitizeReport { reordered: [true, false, false], merged: [0, 0, 0] });
+    /// ```
+    pub fn from_grid_and_data_lenient(x: &[f64], y: &[f64], z: &[f64], data: &[f64], merge_tol: f64) -> Result<(Self, SanitizeReport), Interp3DError> {
+        Interp3DBuilder::default().build_from_grid_and_data_lenient(x, y, z, data, merge_tol)
+    }
+}
+
+/// The axes and the data after [`sanitize()`], ready for [`Interp3D::from_grid_and_data()`]
+pub(crate) struct Sanitized {
+    pub(crate) axes: [Vec<f64>; 3],
+    pub(crate) data: Vec<f64>,
+    pub(crate) report: SanitizeReport
+}
+
+/// Sorts and merges the axes and the data, see [`Interp3D::from_grid_and_data_lenient()`]
+pub(crate) fn sanitize(x: &[f64], y: &[f64], z: &[f64], data: &[f64], merge_tol: f64) -> Result<Sanitized, Interp3DError> {
+    if !(merge_tol >= 0.0 && merge_tol.is_finite()) {
+        return Err(Interp3DError::InvalidTolerance { tolerance: merge_tol });
+    }
+    let expected = x.len()*y.len()*z.len();
+    if data.len() != expected {
+        return Err(Interp3DError::DataLength { expected, found: data.len() });
+    }
+
+    let axes = [x, y, z].map(|v| sanitize_axis(v, merge_tol));
+    let mut report = SanitizeReport::default();
+    for (d, (axis, v)) in axes.iter().zip([x, y, z]).enumerate() {
+        report.reordered[d] = axis.sources.iter().flatten().enumerate().any(|(n, &index)| n != index);
+        report.merged[d] = v.len() - axis.nodes.len();
+    }
+
+    let (ny, nz) = (y.len(), z.len());
+    let mut samples = Vec::with_capacity(axes.iter().map(|axis| axis.nodes.len()).product());
+    for is in &axes[0].sources {
+        for js in &axes[1].sources {
+            for ks in &axes[2].sources {
+                let mut sum = 0.0;
+                for &i in is {
+                    for &j in js {
+                        for &k in ks {
+                            sum += data[i*ny*nz + j*nz + k];
+                        }
+                    }
+                }
+                samples.push(sum/(is.len()*js.len()*ks.len()) as f64);
+            }
+        }
+    }
+
+    Ok(Sanitized { axes: axes.map(|axis| axis.nodes), data: samples, report })
+}
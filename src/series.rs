@@ -0,0 +1,146 @@
+//! Contains the interpolation in time across a series of snapshots of a field.
+
+use crate::{Interp3D, Interp3DError, TimeBounds};
+
+/// The same field tabulated at a series of times ``t_0 < t_1 < ...``, interpolated linearly in time between the spatial interpolations of the two snapshots around ``t``:  
+/// ``(1 - w)*a + w*b`` with ``w = (t - t_n)/(t_{n+1} - t_n)``. At the times of the snapshots the result is the one of that snapshot. Outside of ``[t_0, t_last]`` the series is clamped by default, see [`Self::set_time_bounds()`].
+/// 
+/// All snapshots need the same grid, but keep their own settings (scheme, out of bounds policy, ...), which are used for their spatial interpolation. Compared to [`Interp3D::blend()`] nothing is allocated per query.
+/// 
+/// # Example
+/// 
+/// ```
+/// use crate::interp3d::*;
+/// 
+/// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+/// let series = Interp3DSeries::new(vec![
+///     (0.0, Interp3D::from_config(|x, y, z| x + y + z, &config)),
+///     (2.0, Interp3D::from_config(|x, y, z| x + y + z + 4.0, &config))
+/// ]);
+/// 
+/// assert!((series.interpolate(0.5, 0.1, 0.2, 0.3) - 1.6).abs() < 1e-14);
+/// assert_eq!(series.interpolate(5.0, 0.1, 0.2, 0.3), series.interpolate(2.0, 0.1, 0.2, 0.3));
+/// ```
+#[derive(Clone)]
+pub struct Interp3DSeries {
+    pub(crate) times: Vec<f64>,
+    pub(crate) snapshots: Vec<Interp3D>,
+    time_bounds: TimeBounds
+}
+
+impl Interp3DSeries {
+    /// Sets up the series from ``(t, snapshot)`` pairs, see [`Self::try_new()`].
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the snapshots don't make up a valid series, see [`Self::try_new()`] for a version that returns the error instead.
+    pub fn new(snapshots: Vec<(f64, Interp3D)>) -> Self {
+        match Self::try_new(snapshots) {
+            Ok(series) => series,
+            Err(e) => panic!("{}", e)
+        }
+    }
+
+    /// Same as [`Self::new()`], but returns an error instead of panicking.  
+    /// The times need to be finite and strictly increasing ([`Interp3DError::NonMonotonicTimes`]), and all snapshots need to be set up with exactly the same grid as the first one (see [`Interp3D::same_grid()`], [`Interp3DError::GridMismatch`]). Without any snapshot this is [`Interp3DError::NotSetUp`]. A single snapshot is fine, the series is then constant in time.
+    pub fn try_new(snapshots: Vec<(f64, Interp3D)>) -> Result<Self, Interp3DError> {
+        let Some((_, first)) = snapshots.first() else {
+            return Err(Interp3DError::NotSetUp);
+        };
+        for (index, (t, snapshot)) in snapshots.iter().enumerate() {
+            if !t.is_finite() || (index > 0 && *t <= snapshots[index-1].0) {
+                return Err(Interp3DError::NonMonotonicTimes { index });
+            }
+            first.check_same_grid(snapshot)?;
+        }
+
+        let (times, snapshots) = snapshots.into_iter().unzip();
+        Ok(Interp3DSeries { times, snapshots, time_bounds: TimeBounds::default() })
+    }
+
+    /// Sets what happens to times outside of the covered interval, see [`TimeBounds`]
+    pub fn set_time_bounds(&mut self, policy: TimeBounds) {
+        self.time_bounds = policy;
+    }
+
+    /// Returns the policy for times outside of the covered interval, see [`Self::set_time_bounds()`]
+    pub fn time_bounds(&self) -> TimeBounds {
+        self.time_bounds
+    }
+
+    /// Returns the times of the snapshots
+    pub fn times(&self) -> &[f64] {
+        &self.times
+    }
+
+    /// Returns the snapshots, in the order of [`Self::times()`]
+    pub fn snapshots(&self) -> &[Interp3D] {
+        &self.snapshots
+    }
+
+    /// Returns the ``(t, snapshot)`` pairs, e.g. to change the settings of the snapshots and set the series up again
+    pub fn into_snapshots(self) -> Vec<(f64, Interp3D)> {
+        self.times.into_iter().zip(self.snapshots).collect()
+    }
+
+    /// The snapshot ``n`` and the weight ``w`` of the next one at ``t``. ``w`` is 0 at the times of the snapshots, so a single snapshot is enough there.
+    fn bracket(&self, t: f64) -> Result<(usize, f64), Interp3DError> {
+        let range = (self.times[0], self.times[self.times.len()-1]);
+        if self.time_bounds == TimeBounds::Error && !(range.0..=range.1).contains(&t) {
+            return Err(Interp3DError::TimeOutOfBounds { t, range });
+        }
+
+        let t = t.clamp(range.0, range.1);
+        let n = self.times.partition_point(|&time| time <= t).saturating_sub(1);
+        if n+1 == self.times.len() || t == self.times[n] {
+            return Ok((n, 0.0));
+        }
+
+        Ok((n, (t - self.times[n])/(self.times[n+1] - self.times[n])))
+    }
+
+    /// Interpolates the field at time ``t`` and position ``(x, y, z)``.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if ``t`` is out of range with [`TimeBounds::Error`] or if the point is out of bounds with [`OutOfBounds::Error`](crate::OutOfBounds::Error).
+    pub fn interpolate(&self, t: f64, x: f64, y: f64, z: f64) -> f64 {
+        match self.try_interpolate(t, x, y, z) {
+            Ok(value) => value,
+            Err(e) => panic!("{}", e)
+        }
+    }
+
+    /// Same as [`Self::interpolate()`], but returns an error instead of panicking.
+    pub fn try_interpolate(&self, t: f64, x: f64, y: f64, z: f64) -> Result<f64, Interp3DError> {
+        let (n, w) = self.bracket(t)?;
+        let a = self.snapshots[n].try_interpolate(x, y, z)?;
+        if w == 0.0 {
+            return Ok(a);
+        }
+
+        let b = self.snapshots[n+1].try_interpolate(x, y, z)?;
+        Ok((1.0 - w)*a + w*b)
+    }
+
+    /// Interpolates the field at time ``t`` at all ``points``, see [`Interp3D::interpolate_many()`].  
+    /// The snapshots around ``t`` are only looked up once, and each of them is evaluated at all points in one go. The results are identical to calling [`Self::interpolate()`] for every point.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics like [`Self::interpolate()`].
+    pub fn interpolate_many(&self, t: f64, points: &[(f64, f64, f64)]) -> Vec<f64> {
+        let (n, w) = match self.bracket(t) {
+            Ok(bracket) => bracket,
+            Err(e) => panic!("{}", e)
+        };
+        let mut values = self.snapshots[n].interpolate_many(points);
+        if w != 0.0 {
+            for (a, b) in values.iter_mut().zip(self.snapshots[n+1].interpolate_many(points)) {
+                *a = (1.0 - w)**a + w*b;
+            }
+        }
+
+        values
+    }
+}
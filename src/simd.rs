@@ -0,0 +1,71 @@
+//! Contains the vectorized stencil sum of the ``simd`` feature, for ``x86_64`` CPUs with AVX (checked at runtime).
+//! Everywhere else the scalar sum of [`Interp3D::stencil_sum()`] is used.
+
+use crate::Interp3D;
+
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+impl Interp3D {
+    /// Same as [`Self::stencil_sum()`] for ``F64`` samples without stale ghost cells, ``None`` if the CPU lacks AVX.
+    #[inline]
+    pub(crate) fn stencil_sum_simd(&self, data: &[f64], (i, j, k): (usize, usize, usize), wx: &[f64; 4], wy: &[f64; 4], wz: &[f64; 4]) -> Option<f64> {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx") {
+            let start = self.index(i-1, j-1, k-1);
+            let strides = (self.ny*self.nz, self.nz);
+            // the stencil lies inside of the samples, so all loads below are in bounds
+            assert!(start + 3*strides.0 + 3*strides.1 + 4 <= data.len());
+            // SAFETY: AVX is available, which was checked above
+            return Some(unsafe { stencil_sum_avx(data, start, strides, wx, wy, wz) });
+        }
+
+        #[cfg(not(target_arch = "x86_64"))]
+        let _ = (data, (i, j, k), wx, wy, wz);
+        None
+    }
+}
+
+/// Transposes the 4x4 matrix with the given rows
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+fn transpose([r0, r1, r2, r3]: [__m256d; 4]) -> [__m256d; 4] {
+    let (t0, t1) = (_mm256_unpacklo_pd(r0, r1), _mm256_unpackhi_pd(r0, r1));
+    let (t2, t3) = (_mm256_unpacklo_pd(r2, r3), _mm256_unpackhi_pd(r2, r3));
+    [
+        _mm256_permute2f128_pd(t0, t2, 0x20),
+        _mm256_permute2f128_pd(t1, t3, 0x20),
+        _mm256_permute2f128_pd(t0, t2, 0x31),
+        _mm256_permute2f128_pd(t1, t3, 0x31)
+    ]
+}
+
+/// The stencil sum starting at ``data[start]`` with the given strides along X and Y.  
+/// The 4 columns along Z with the same ``a`` are summed up at once, and then the 4 sums along Y. Every lane adds up its terms in the same order as the scalar sum, so the result is identical to it.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn stencil_sum_avx(data: &[f64], start: usize, (sx, sy): (usize, usize), wx: &[f64; 4], wy: &[f64; 4], wz: &[f64; 4]) -> f64 {
+    let wz = wz.map(|w| _mm256_set1_pd(w));
+    // SAFETY: the weights are 4 consecutive f64
+    let (wx, wy) = unsafe { (_mm256_loadu_pd(wx.as_ptr()), _mm256_loadu_pd(wy.as_ptr())) };
+
+    // wy[b]*(wz[0]*f[a][b][0] + ... + wz[3]*f[a][b][3]) with b along the lanes, one register per a
+    let lines: [__m256d; 4] = std::array::from_fn(|a| {
+        // SAFETY: the caller checked that the whole stencil is in bounds
+        let columns = std::array::from_fn(|b| unsafe { _mm256_loadu_pd(data.as_ptr().add(start + a*sx + b*sy)) });
+        let [c0, c1, c2, c3] = transpose(columns);
+        let sum = _mm256_add_pd(_mm256_add_pd(_mm256_add_pd(_mm256_mul_pd(wz[0], c0), _mm256_mul_pd(wz[1], c1)), _mm256_mul_pd(wz[2], c2)), _mm256_mul_pd(wz[3], c3));
+        _mm256_mul_pd(wy, sum)
+    });
+
+    // the sums along Y with a along the lanes
+    let mut sum_y = _mm256_setzero_pd();
+    for terms in transpose(lines) {
+        sum_y = _mm256_add_pd(sum_y, terms);
+    }
+
+    let mut terms = [0.0; 4];
+    // SAFETY: the array holds 4 f64
+    unsafe { _mm256_storeu_pd(terms.as_mut_ptr(), _mm256_mul_pd(wx, sum_y)) };
+    terms.iter().fold(0.0, |sum, t| sum + t)
+}
@@ -0,0 +1,160 @@
+//! Contains the extraction of lower dimensional data, i.e. 2d slices and 1d lines through the interpolated data.
+
+use crate::{Interp3D, Interp3DError, Dir, OutOfBounds};
+
+/// The interpolated data on a plane normal to one of the axes, see [`Interp3D::slice()`].  
+/// The two remaining directions are called ``u`` and ``v`` in their usual order, i.e. ``(u, v)`` is ``(y, z)`` for a slice normal to X, ``(x, z)`` normal to Y and ``(x, y)`` normal to Z.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Slice2D {
+    /// direction the plane is normal to
+    pub normal: Dir,
+    /// position of the plane along ``normal``
+    pub position: f64,
+    /// node positions along the first remaining direction
+    pub u: Vec<f64>,
+    /// node positions along the second remaining direction
+    pub v: Vec<f64>,
+    /// ``u.len()*v.len()`` values, the one at ``(u[a], v[b])`` is number ``a*v.len() + b`` (i.e. ``v`` changes fastest, like in the 3d storage order)
+    pub values: Vec<f64>
+}
+
+impl Slice2D {
+    /// Returns the number of nodes ``(u.len(), v.len())``
+    pub fn dims(&self) -> (usize, usize) {
+        (self.u.len(), self.v.len())
+    }
+
+    /// Returns the value at ``(u[a], v[b])``.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if ``a`` or ``b`` is out of range.
+    pub fn get(&self, a: usize, b: usize) -> f64 {
+        assert!(a < self.u.len() && b < self.v.len(), "({}, {}) out of range for a {}x{} slice", a, b, self.u.len(), self.v.len());
+        self.values[a*self.v.len() + b]
+    }
+}
+
+impl Interp3D {
+    /// The point in 3d of the in-plane coordinates ``(u, v)`` on the plane normal to ``dir`` at ``position``
+    fn in_plane(dir: Dir, position: f64, (u, v): (f64, f64)) -> (f64, f64, f64) {
+        match dir {
+            Dir::X => (position, u, v),
+            Dir::Y => (u, position, v),
+            Dir::Z => (u, v, position)
+        }
+    }
+
+    /// Interpolates the data on the plane normal to ``dir`` at ``position``, at the nodes of the two remaining axes.  
+    /// Every value comes from the full 3d interpolation (not just from the nearest plane of nodes), so ``position`` can lie anywhere between the nodes. If it lies outside of the domain, the out of bounds policy applies as for every query.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default()
+    ///     .x(0.0, 1.0, 3, GridSpacing::Linear)
+    ///     .y(0.0, 2.0, 5, GridSpacing::Linear)
+    ///     .build();
+    /// let ip = Interp3D::from_config(|x, y, z| x + 10.0*y + z, &config);
+    /// 
+    /// let slice = ip.slice(Dir::Z, 0.25);
+    /// assert_eq!(slice.dims(), (3, 5));
+    /// assert!((slice.get(1, 4) - (0.5 + 20.0 + 0.25)).abs() < 1e-12);
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet or if ``position`` is out of bounds with [`OutOfBounds::Error`](crate::OutOfBounds::Error).
+    pub fn slice(&self, dir: Dir, position: f64) -> Slice2D {
+        let (u, v) = match dir {
+            Dir::X => (self.grid_y(), self.grid_z()),
+            Dir::Y => (self.grid_x(), self.grid_z()),
+            Dir::Z => (self.grid_x(), self.grid_y())
+        };
+
+        let points: Vec<(f64, f64)> = u.iter().flat_map(|&a| v.iter().map(move |&b| (a, b))).collect();
+        Slice2D {
+            normal: dir,
+            position,
+            u: u.to_vec(),
+            v: v.to_vec(),
+            values: self.slice_values_at(dir, position, &points)
+        }
+    }
+
+    /// Interpolates the data at arbitrary in-plane points ``(u, v)`` on the plane normal to ``dir`` at ``position``, see [`Self::slice()`] and [`Slice2D`] for the meaning of ``u`` and ``v``.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet or if a point is out of bounds with [`OutOfBounds::Error`](crate::OutOfBounds::Error).
+    pub fn slice_values_at(&self, dir: Dir, position: f64, points: &[(f64, f64)]) -> Vec<f64> {
+        let points: Vec<(f64, f64, f64)> = points.iter().map(|&p| Self::in_plane(dir, position, p)).collect();
+        self.interpolate_many_grid(&points)
+    }
+
+    /// Range of the parameter ``t`` of the segment ``p0 + t*(p1 - p0)`` (``0 <= t <= 1``) that lies inside of the domain, ``None`` if it misses the domain. Periodic axes never clip.
+    fn clip_segment(&self, p0: [f64; 3], p1: [f64; 3]) -> Option<(f64, f64)> {
+        let (mut t0, mut t1) = (0.0f64, 1.0f64);
+        for (d, v) in [&self.x, &self.y, &self.z].into_iter().enumerate() {
+            if self.periodic[d] {
+                continue;
+            }
+
+            let (lo, hi) = Self::range(v);
+            let delta = p1[d] - p0[d];
+            if delta == 0.0 {
+                if !(lo..=hi).contains(&p0[d]) {
+                    return None;
+                }
+                continue;
+            }
+            let (a, b) = ((lo - p0[d])/delta, (hi - p0[d])/delta);
+            t0 = t0.max(a.min(b));
+            t1 = t1.min(a.max(b));
+        }
+
+        if t0 <= t1 { Some((t0, t1)) } else { None }
+    }
+
+    /// Samples the data at ``n`` equally spaced points along the straight line from ``p0`` to ``p1`` (both included) and returns ``(s, value)`` for each of them, with ``s`` the distance from ``p0``.  
+    /// As consecutive points are close to each other, this runs through [`Self::interpolate_many()`] and mostly saves the search for the cell. Points outside of the domain follow the out of bounds policy, with [`OutOfBounds::Error`] a segment that isn't completely inside is rejected with [`Interp3DError::SegmentOutOfBounds`], which tells the part that is inside (e.g. to clip the segment and try again).
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+    /// let ip = Interp3D::from_config(|x, y, z| x + y + z, &config);
+    /// 
+    /// let line = ip.sample_line((0.0, 0.0, 0.0), (1.0, 1.0, 1.0), 5).unwrap();
+    /// assert_eq!(line.len(), 5);
+    /// assert!((line[4].0 - 3f64.sqrt()).abs() < 1e-15);
+    /// assert!((line[2].1 - 1.5).abs() < 1e-12);
+    /// ```
+    pub fn sample_line(&self, p0: (f64, f64, f64), p1: (f64, f64, f64), n: usize) -> Result<Vec<(f64, f64)>, Interp3DError> {
+        if self.data.is_empty() {
+            return Err(Interp3DError::NotSetUp);
+        }
+
+        let (a, b) = ([p0.0, p0.1, p0.2], [p1.0, p1.1, p1.2]);
+        if self.out_of_bounds == OutOfBounds::Error {
+            match self.clip_segment(a, b) {
+                Some((0.0, 1.0)) => (),
+                inside => return Err(Interp3DError::SegmentOutOfBounds { inside })
+            }
+        }
+
+        let length = a.iter().zip(&b).map(|(a, b)| (b - a)*(b - a)).sum::<f64>().sqrt();
+        let ts: Vec<f64> = (0..n).map(|i| if n > 1 { i as f64/(n - 1) as f64 } else { 0.0 }).collect();
+        let points: Vec<(f64, f64, f64)> = ts.iter().map(|&t| {
+            // p0 + t*(p1 - p0) hits the nodes of linearly spaced grids exactly, the clamp keeps rounding from leaving the segment
+            let p: [f64; 3] = std::array::from_fn(|d| (a[d] + t*(b[d] - a[d])).clamp(a[d].min(b[d]), a[d].max(b[d])));
+            (p[0], p[1], p[2])
+        }).collect();
+
+        Ok(ts.iter().map(|t| t*length).zip(self.interpolate_many_grid(&points)).collect())
+    }
+}
@@ -0,0 +1,115 @@
+//! Contains the smoothing of the stored samples with a separable filter.
+
+use crate::{Interp3D, SmoothingKernel, BoundaryCondition, Dir, GHOST_LO};
+
+impl Interp3D {
+    /// Maps the interior node ``m`` along the axis ``d`` (counted from the first interior node, also beyond both ends) onto the interior nodes its value is computed from, as ``(node, weight)`` pairs.  
+    /// Beyond the ends this continues the ghost cells (see [`Self::ghost_sources()`]) as far as the filter reaches, i.e. the ghost layers serve as the halo of the filter.
+    fn halo_sources(&self, d: usize, m: isize, n: usize) -> [(usize, f64); 2] {
+        let last = n as isize - 1;
+        let single = |node: isize, factor: f64| [(node.clamp(0, last) as usize, factor), (0, 0.0)];
+        if (0..=last).contains(&m) {
+            return single(m, 1.0);
+        }
+        if self.periodic[d] {
+            // the last node is the first one shifted by one period
+            return single(m.rem_euclid(last), 1.0);
+        }
+
+        match self.boundary[d] {
+            BoundaryCondition::Copy => single(m, 1.0),
+            BoundaryCondition::Reflect { even } => single(if m < 0 { -m } else { 2*last - m }, if even { 1.0 } else { -1.0 }),
+            BoundaryCondition::LinearExtrapolate => {
+                // the ghost cells continue the spacing of the outermost interval, so the line is linear in the index as well
+                let (near, other, s) = if m < 0 { (0, 1, m) } else { (last, last-1, last - m) };
+                [(near as usize, 1.0 - s as f64), (other as usize, s as f64)]
+            }
+        }
+    }
+
+    /// Smooths the stored samples with ``kernel`` along all three directions, e.g. to get rid of the node-to-node noise of Monte Carlo estimates before the cubic interpolation turns it into ringing. Like with any filter, real features narrower than the kernel are flattened as well.  
+    /// The filter is separable, so this is the same as [`Self::smooth_along()`] along X, Y and Z one after the other. See there for the details.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+    /// let mut ip = Interp3D::from_config(|x, y, z| if (x, y, z) == (0.5, 0.5, 0.5) { 27.0 } else { 0.0 }, &config);
+    /// 
+    /// // the spike is spread over the 3x3x3 nodes around it
+    /// ip.smooth(SmoothingKernel::Box(1));
+    /// assert!((ip.value_at(5, 5, 5) - 1.0).abs() < 1e-12);
+    /// assert!((ip.value_at(4, 6, 5) - 1.0).abs() < 1e-12);
+    /// assert_eq!(ip.value_at(3, 5, 5), 0.0);
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet or if ``sigma_cells`` of a Gaussian isn't positive and finite.
+    pub fn smooth(&mut self, kernel: SmoothingKernel) {
+        for dir in [Dir::X, Dir::Y, Dir::Z] {
+            self.smooth_along(dir, kernel);
+        }
+    }
+
+    /// Smooths the stored samples with ``kernel`` along ``dir`` only, e.g. for noise that is much stronger along one direction. Call it once per direction with different kernels to smooth each direction differently.  
+    /// Every interior sample is replaced by the weighted sum of the nodes around it along ``dir`` (see [`SmoothingKernel`]). Near the ends, the nodes beyond the domain continue the ghost cells: they follow the boundary condition (see [`BoundaryCondition`]) or wrap around for a periodic axis. So e.g. [`BoundaryCondition::LinearExtrapolate`] keeps linear functions unchanged all the way to the edges, while [`BoundaryCondition::Copy`] flattens them there.  
+    /// The ghost cells are refreshed afterwards. Derivatives supplied by [`Self::generate_data_hermite()`] are dropped, since they don't fit the smoothed samples anymore.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+    /// let mut ip = Interp3D::from_config(|x, y, z| x + y + z, &config);
+    /// ip.set_boundary_condition(BoundaryCondition::LinearExtrapolate);
+    /// 
+    /// // a linear function isn't changed by a symmetric kernel
+    /// ip.smooth_along(Dir::Z, SmoothingKernel::Gaussian { sigma_cells: 1.5, radius: 4 });
+    /// assert!((ip.value_at(3, 4, 0) - 0.7).abs() < 1e-12);
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet or if ``sigma_cells`` of a Gaussian isn't positive and finite.
+    pub fn smooth_along(&mut self, dir: Dir, kernel: SmoothingKernel) {
+        assert!(!self.data.is_empty(), "Interpolator has not been set up");
+
+        let weights = kernel.weights();
+        let radius = (weights.len()/2) as isize;
+        let d = dir as usize;
+        let (nx, ny, nz) = self.dims();
+        let n = [nx, ny, nz][d];
+
+        // the weights of the interior nodes along the line for every node, with the halo folded in
+        let sources: Vec<Vec<(usize, f64)>> = (0..n as isize).map(|m| {
+            let mut combined = Vec::with_capacity(2*weights.len());
+            for (o, &w) in (-radius..=radius).zip(&weights) {
+                combined.extend(self.halo_sources(d, m + o, n).iter().filter(|s| s.1 != 0.0).map(|&(node, factor)| (node, w*factor)));
+            }
+            combined
+        }).collect();
+
+        let stride = [self.ny*self.nz, self.nz, 1][d];
+        let mut line = vec![0.0; n];
+        let starts: Vec<usize> = self.interior()
+            .filter(|&(i, j, k)| [i, j, k][d] == GHOST_LO)
+            .map(|(i, j, k)| self.index(i, j, k))
+            .collect();
+        let mut samples = self.data.writer();
+        for start in starts {
+            for (m, value) in line.iter_mut().enumerate() {
+                *value = samples.get(start + m*stride);
+            }
+            for (m, sources) in sources.iter().enumerate() {
+                samples.set(start + m*stride, sources.iter().map(|&(node, w)| w*line[node]).sum());
+            }
+        }
+
+        self.set_data_outermost();
+        self.hermite = None;
+    }
+}
@@ -1,8 +1,11 @@
 //! Contains some utils for the implementation of the interpolator.
 
+use crate::{Interp3D, Interp3DError};
+
 use std::f64::consts::PI as PI;
 
 /// Used for specification of the direction
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Dir {
     X,
     Y,
@@ -10,10 +13,18 @@ pub enum Dir {
 }
 
 /// Defines the grid spacing for data generation
-#[derive(Copy, Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum GridSpacing {
     Linear,
-    Exponential(f64)
+    Exponential(f64),
+    /// Exponential spacing mirrored about the middle of the range, i.e. dense towards both ends for ``k > 0``
+    TwoSidedExponential(f64),
+    /// Equally spaced in ``log(x)``, i.e. every node is the previous one times a constant factor. ``min`` and ``max`` need to have the same sign.
+    Logarithmic,
+    /// Chebyshev–Gauss–Lobatto nodes, i.e. dense towards both ends of the range
+    Chebyshev,
+    /// Uses the given node positions as they are. They need to be finite and strictly increasing, see [`DataGenConfSingle::custom()`].
+    Custom(Vec<f64>)
 }
 
 /// Configure how to set the data point positions in 1d (for example along X)
@@ -25,8 +36,18 @@ pub enum GridSpacing {
 ///   
 /// As an example, for ``k = 8.0``, half of all points lie within the first ~7/8 of the specified range. Analogously, for ``k = -8.0``, half of all points will be in the last ~7/8 of the range.  
 ///   
-/// I found that ``k = 8.0`` gives very good low-end precision but also has enough high-end precision to strike a good balance. The best choice will starkly depend on the specific use case, however.
-#[derive(Copy, Clone)]
+/// I found that ``k = 8.0`` gives very good low-end precision but also has enough high-end precision to strike a good balance. The best choice will starkly depend on the specific use case, however.  
+///   
+/// ``GridSpacing::TwoSidedExponential(k)`` is meant for functions with features at both ends of the range, like boundary layers. The lower half of the points is distributed on ``[min, (min+max)/2]`` like ``Exponential(k)``, the upper half like its mirror image on ``[(min+max)/2, max]``.  
+/// ``k`` > 0 concentrates the points at both ends, ``k`` < 0 in the middle and ``k = 0.0`` is again the linear case.  
+/// Applying the rule from above to each half, for ``k = 8.0`` a quarter of all points lies within the first ~1/34 of the range and another quarter within the last ~1/34. In general that fraction of the range is about ``2^(-k/2)/2``, so every increase of ``k`` by 2 halves it.  
+///   
+/// ``GridSpacing::Logarithmic`` spaces the points evenly on a log scale, which is what you want for ranges spanning several orders of magnitude (e.g. frequencies from ``1e-3`` to ``1e3``). Every decade gets the same number of points. This only works if ``min`` and ``max`` have the same sign (and neither is 0).  
+///   
+/// ``GridSpacing::Chebyshev`` places the points at the Chebyshev–Gauss–Lobatto nodes ``(min+max)/2 - (max-min)/2*cos(pi*i/(n-1))``, which are denser towards both ends of the range. This counters the loss of accuracy of the cubic interpolation in the outermost cells, where the slopes can't be estimated from both sides.  
+///   
+/// ``GridSpacing::Custom(nodes)`` places the points exactly at ``nodes``, e.g. to concentrate them around a known feature. ``n``, ``min`` and ``max`` then have to agree with the number of nodes and the first and last one, [`Self::custom()`] takes care of that.
+#[derive(Clone, Debug, PartialEq)]
 pub struct DataGenConfSingle {
     /// number of points
     pub n: usize,
@@ -50,14 +71,78 @@ impl Default for DataGenConfSingle {
     }
 }
 
+impl DataGenConfSingle {
+    /// Creates a config that places the points exactly at ``nodes``, with ``n``, ``min`` and ``max`` taken from them.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let single = DataGenConfSingle::custom(vec![0.0, 3.0, 3.1, 3.2, 3.3, 10.0]);
+    /// assert_eq!((single.n, single.min, single.max), (6, 0.0, 10.0));
+    /// ```
+    pub fn custom(nodes: Vec<f64>) -> Self {
+        DataGenConfSingle {
+            n: nodes.len(),
+            min: nodes.first().copied().unwrap_or(f64::NAN),
+            max: nodes.last().copied().unwrap_or(f64::NAN),
+            spacing: GridSpacing::Custom(nodes)
+        }
+    }
+
+    /// Checks that the config describes a valid axis, ``dir`` is only used to tell the axis in the error. This is what [`Interp3D::try_generate_data()`](crate::Interp3D::try_generate_data()) checks before the closure is called.  
+    /// Rejected are ``n < 2``, a range that is not finite or empty or reversed (or doesn't have one sign with [`GridSpacing::Logarithmic`]), a parameter of [`GridSpacing::Exponential`] or [`GridSpacing::TwoSidedExponential`] that is not finite and [`GridSpacing::Custom`] nodes that are not finite and strictly increasing or don't agree with ``n``, ``min`` and ``max``.  
+    /// Some configs pass this but still fail to generate, because the nodes collapse numerically (e.g. ``Exponential(5000.0)`` squeezes all but the last node onto ``min``).
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let single = DataGenConfSingle { n: 11, min: 0.0, max: 1.0, spacing: GridSpacing::Exponential(f64::NAN) };
+    /// assert!(matches!(single.validate(Dir::Y), Err(Interp3DError::InvalidSpacing { dir: Dir::Y, .. })));
+    /// ```
+    pub fn validate(&self, dir: Dir) -> Result<(), Interp3DError> {
+        if let GridSpacing::Custom(nodes) = &self.spacing {
+            Interp3D::check_axis(dir, nodes)?;
+            if self.n != nodes.len() || self.min != nodes[0] || self.max != nodes[nodes.len()-1] {
+                return Err(Interp3DError::CustomAxisMismatch { dir });
+            }
+            return Ok(());
+        }
+
+        if self.n < 2 {
+            return Err(Interp3DError::TooFewPoints { dir, n: self.n });
+        }
+        let crosses_zero = self.spacing == GridSpacing::Logarithmic && self.min*self.max <= 0.0;
+        if !self.min.is_finite() || !self.max.is_finite() || self.min >= self.max || crosses_zero {
+            return Err(Interp3DError::InvalidRange { dir, min: self.min, max: self.max });
+        }
+        match self.spacing {
+            GridSpacing::Exponential(k) | GridSpacing::TwoSidedExponential(k) if !k.is_finite() => Err(Interp3DError::InvalidSpacing { dir, parameter: k }),
+            _ => Ok(())
+        }
+    }
+}
+
 /// Combines 3 single direction configs
-#[derive(Copy, Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DataGenConf {
     pub x: DataGenConfSingle,
     pub y: DataGenConfSingle,
     pub z: DataGenConfSingle
 }
 
+impl DataGenConf {
+    /// Checks all 3 directions, see [`DataGenConfSingle::validate()`]. The error is the one of the first invalid direction.
+    pub fn validate(&self) -> Result<(), Interp3DError> {
+        self.x.validate(Dir::X)?;
+        self.y.validate(Dir::Y)?;
+        self.z.validate(Dir::Z)
+    }
+}
+
 impl Default for DataGenConf {
     fn default() -> DataGenConf {
         DataGenConf {
@@ -73,8 +158,315 @@ impl Default for DataGenConf {
     }
 }
 
-/// Used to define whether to use bicubic-unilinear or tricubic interpolation
+/// Chained construction of a [`DataGenConf`], starting out from [`DataGenConf::default()`].
+/// 
+/// # Example
+/// 
+/// ```
+/// use crate::interp3d::*;
+/// 
+/// let config = DataGenConfBuilder::default()
+///     .uniform_cube(0.0, 10.0, 11)
+///     .z(0.0, 1.0, 5, GridSpacing::Exponential(2.0))
+///     .build();
+/// 
+/// assert_eq!(config.x, config.y);
+/// assert_eq!(config.z.n, 5);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct DataGenConfBuilder {
+    conf: DataGenConf
+}
+
+impl DataGenConfBuilder {
+    /// Sets the config along X
+    pub fn x(mut self, min: f64, max: f64, n: usize, spacing: GridSpacing) -> Self {
+        self.conf.x = DataGenConfSingle { n, min, max, spacing };
+        self
+    }
+
+    /// Sets the config along Y
+    pub fn y(mut self, min: f64, max: f64, n: usize, spacing: GridSpacing) -> Self {
+        self.conf.y = DataGenConfSingle { n, min, max, spacing };
+        self
+    }
+
+    /// Sets the config along Z
+    pub fn z(mut self, min: f64, max: f64, n: usize, spacing: GridSpacing) -> Self {
+        self.conf.z = DataGenConfSingle { n, min, max, spacing };
+        self
+    }
+
+    /// Places the points along X exactly at ``nodes``, see [`DataGenConfSingle::custom()`]
+    pub fn x_nodes(mut self, nodes: Vec<f64>) -> Self {
+        self.conf.x = DataGenConfSingle::custom(nodes);
+        self
+    }
+
+    /// Places the points along Y exactly at ``nodes``
+    pub fn y_nodes(mut self, nodes: Vec<f64>) -> Self {
+        self.conf.y = DataGenConfSingle::custom(nodes);
+        self
+    }
+
+    /// Places the points along Z exactly at ``nodes``
+    pub fn z_nodes(mut self, nodes: Vec<f64>) -> Self {
+        self.conf.z = DataGenConfSingle::custom(nodes);
+        self
+    }
+
+    /// Sets all 3 directions to the same linearly spaced range.
+    pub fn uniform_cube(self, min: f64, max: f64, n: usize) -> Self {
+        self.x(min, max, n, GridSpacing::Linear)
+            .y(min, max, n, GridSpacing::Linear)
+            .z(min, max, n, GridSpacing::Linear)
+    }
+
+    /// Returns the finished config
+    pub fn build(self) -> DataGenConf {
+        self.conf
+    }
+}
+
+/// Defines how the ghost cells around the sampled region are filled, see [`Interp3D::set_boundary_condition()`](crate::Interp3D::set_boundary_condition()).  
+/// The tricubic stencil reaches one node beyond the sampled region, so the choice affects the interpolation in the outermost cells. It can be set per direction, see [`Interp3D::set_boundary_condition_along()`](crate::Interp3D::set_boundary_condition_along()).
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub enum BoundaryCondition {
+    /// Copies the value of the nearest interior node. Simple but flattens the interpolant towards the boundary.
+    #[default]
+    Copy,
+    /// Mirrors the interior samples at the outermost node, e.g. the ghost cell in front of ``min`` gets the value of the second node.  
+    /// Use ``even: true`` for functions that are symmetric about the boundary and ``even: false`` for antisymmetric ones (the mirrored values are negated).  
+    /// This applies to both faces of a direction, so it's mostly useful for symmetric setups.
+    Reflect { even: bool },
+    /// Continues the line through the outermost node and its neighbour into the ghost cells, i.e. the slope at the edge is kept. The cubic schemes then reproduce linear functions also in the outermost cells, which usually makes them much more accurate at the edges of the domain.
+    LinearExtrapolate
+}
+
+/// Defines what happens to query points outside of the sampled domain ``[min, max]`` along any direction, see [`Interp3D::set_out_of_bounds()`](crate::Interp3D::set_out_of_bounds()).  
+/// Points within the tolerance of [`Interp3D::set_out_of_bounds_tolerance()`](crate::Interp3D::set_out_of_bounds_tolerance()) count as on the boundary, whatever the policy.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum OutOfBounds {
+    /// Moves the point onto the nearest point of the domain boundary, i.e. the interpolant is continued constantly
+    ClampToEdge,
+    /// Continues the polynomial of the outermost cell. This is the default and fine slightly outside of the domain, but diverges quickly further out.
+    #[default]
+    LinearExtrapolate,
+    /// Returns ``NaN``
+    ReturnNan,
+    /// Treats the query as an error: [`Interp3D::try_interpolate()`](crate::Interp3D::try_interpolate()) returns [`Interp3DError::OutOfBounds`](crate::Interp3DError::OutOfBounds), the other query methods panic
+    Error
+}
+
+/// Defines what happens to times outside of the covered interval ``[t_first, t_last]`` of an [`Interp3DSeries`](crate::Interp3DSeries), see [`Interp3DSeries::set_time_bounds()`](crate::Interp3DSeries::set_time_bounds()).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum TimeBounds {
+    /// Takes the first or the last snapshot, i.e. the series is continued constantly in time (the default)
+    #[default]
+    Clamp,
+    /// Treats the query as an error: [`Interp3DSeries::try_interpolate()`](crate::Interp3DSeries::try_interpolate()) returns [`Interp3DError::TimeOutOfBounds`](crate::Interp3DError::TimeOutOfBounds), the other query methods panic
+    Error
+}
+
+/// Maps the coordinate along an axis from the physical units of the queries onto the coordinate the grid is laid out in, see [`Interp3D::set_axis_transform()`](crate::Interp3D::set_axis_transform()).  
+/// E.g. with ``Log10`` along X the grid (the config, [`Interp3D::grid_x()`](crate::Interp3D::grid_x()) and so on) is in ``log10(x)``, while the queries and the closures that generate the data work with ``x`` itself.
+#[derive(Copy, Clone, Debug, Default)]
+pub enum AxisTransform {
+    /// The grid is in the physical coordinate
+    #[default]
+    Identity,
+    /// The grid is in ``log10(x)``, for physical coordinates ``x > 0`` that span several orders of magnitude
+    Log10,
+    /// The grid is in ``ln(x)``
+    Ln,
+    /// The grid is in ``forward(x)``, with the inverse ``inverse(u)`` mapping back. Both need to be strictly increasing. Custom transforms can't be written to a file.
+    Custom(fn(f64) -> f64, fn(f64) -> f64)
+}
+
+/// ``Custom`` transforms are equal if they hold the same functions (see [`std::ptr::fn_addr_eq()`])
+impl PartialEq for AxisTransform {
+    fn eq(&self, other: &AxisTransform) -> bool {
+        match (self, other) {
+            (AxisTransform::Custom(f1, i1), AxisTransform::Custom(f2, i2)) => std::ptr::fn_addr_eq(*f1, *f2) && std::ptr::fn_addr_eq(*i1, *i2),
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other)
+        }
+    }
+}
+
+impl AxisTransform {
+    /// Maps a physical coordinate onto the grid
+    pub fn forward(&self, x: f64) -> f64 {
+        match self {
+            AxisTransform::Identity => x,
+            AxisTransform::Log10 => x.log10(),
+            AxisTransform::Ln => x.ln(),
+            AxisTransform::Custom(forward, _) => forward(x)
+        }
+    }
+
+    /// Maps a coordinate of the grid back onto the physical one
+    pub fn inverse(&self, u: f64) -> f64 {
+        match self {
+            AxisTransform::Identity => u,
+            AxisTransform::Log10 => 10f64.powf(u),
+            AxisTransform::Ln => u.exp(),
+            AxisTransform::Custom(_, inverse) => inverse(u)
+        }
+    }
+
+    /// The first and the second derivative of [`Self::forward()`] at ``x``, for the chain rule of the derivative queries. Those of ``Custom`` are estimated by central differences.
+    pub(crate) fn derivatives(&self, x: f64) -> (f64, f64) {
+        match self {
+            AxisTransform::Identity => (1.0, 0.0),
+            AxisTransform::Log10 => (1.0/(x*std::f64::consts::LN_10), -1.0/(x*x*std::f64::consts::LN_10)),
+            AxisTransform::Ln => (1.0/x, -1.0/(x*x)),
+            AxisTransform::Custom(forward, _) => {
+                // about the optimal steps for the first and the second difference
+                let (h1, h2) = (6e-6*x.abs().max(1.0), 1e-4*x.abs().max(1.0));
+                ((forward(x + h1) - forward(x - h1))/(2.0*h1), (forward(x + h2) - 2.0*forward(x) + forward(x - h2))/(h2*h2))
+            }
+        }
+    }
+}
+
+/// The filter of [`Interp3D::smooth()`](crate::Interp3D::smooth()), applied along one direction at a time. The sizes are in cells (nodes), not in the units of the axis.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SmoothingKernel {
+    /// Replaces every sample by the mean of the ``2*radius + 1`` nodes centered on it (a moving average)
+    Box(usize),
+    /// Weights the nodes up to ``radius`` away with a Gaussian of the standard deviation ``sigma_cells``, normalized to a sum of 1. A ``radius`` of about ``3*sigma_cells`` keeps practically all of the Gaussian.
+    Gaussian { sigma_cells: f64, radius: usize }
+}
+
+impl SmoothingKernel {
+    /// The weights of the nodes ``-radius..=radius`` around the smoothed one
+    pub(crate) fn weights(&self) -> Vec<f64> {
+        match *self {
+            SmoothingKernel::Box(radius) => vec![1.0/(2*radius + 1) as f64; 2*radius + 1],
+            SmoothingKernel::Gaussian { sigma_cells, radius } => {
+                assert!(sigma_cells > 0.0 && sigma_cells.is_finite(), "sigma_cells of the Gaussian smoothing must be positive and finite, got {}", sigma_cells);
+                let r = radius as isize;
+                let weights: Vec<f64> = (-r..=r).map(|o| (-0.5*(o as f64/sigma_cells).powi(2)).exp()).collect();
+                let sum: f64 = weights.iter().sum();
+                weights.iter().map(|w| w/sum).collect()
+            }
+        }
+    }
+}
+
+/// What goes into the nodes where the closure of [`Interp3D::generate_data_masked()`](crate::Interp3D::generate_data_masked()) has no value. Distances are counted in nodes, not in the units of the axes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FillStrategy {
+    /// Leaves ``NaN`` in the missing nodes, so every query whose stencil touches one of them gives ``NaN``
+    Nan,
+    /// Puts the same value into all missing nodes
+    Constant(f64),
+    /// Copies the sample of the closest node with a value, closest meaning the fewest steps along the grid lines (the Manhattan distance of the indices). Ties go to the node that comes first in storage order.
+    NearestValid,
+    /// Fills the holes from their rim inwards, every node with the mean of its neighbours (along the grid lines) that already have a value. Then ``passes`` sweeps replace every filled node by the mean of all its neighbours, with the valid nodes kept fixed, which smooths the fill towards the solution of the Laplace equation.
+    IterativeAverage { passes: usize }
+}
+
+/// Used to define the interpolation scheme, see [`Interp3D::set_type()`](crate::Interp3D::set_type())
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
 pub enum Type {
-    BicubicUnilinear,
-    Tricubic
-}
\ No newline at end of file
+    /// Linear along the given direction and cubic along the other two, e.g. for data that is smooth in X and Y but only piecewise linear in Z (``BicubicUnilinear(Dir::Z)``)
+    BicubicUnilinear(Dir),
+    /// Cubic along all 3 directions (the default)
+    #[default]
+    Tricubic,
+    /// Linear along all 3 directions. Only reads the 8 corners of the cell, so this is the cheapest scheme, but the result is merely continuous.
+    Trilinear,
+    /// Cubic along all 3 directions like ``Tricubic``, but with the slopes at the nodes limited (Fritsch–Carlson style) such that the interpolant doesn't overshoot.  
+    /// Every value inside of the domain lies within the range of the 8 samples around it, so e.g. non-negative data never gives negative values. Data that is monotone along a direction stays monotone along every grid line in that direction.  
+    /// The price is accuracy: linear functions are still reproduced exactly, but the slopes at local extrema are set to 0, which flattens smooth peaks (the error there drops to second order). The interpolant also depends nonlinearly on the data and is a bit slower to evaluate.
+    TricubicMonotone,
+    /// Takes the sample of the nearest node, e.g. for categorical data where any blending would be wrong. "Nearest" is meant along each direction in physical coordinates, a query right in the middle between two nodes gets the lower one.  
+    /// Queries outside of the domain get the nearest boundary node, regardless of the out of bounds policy (except for ``ReturnNan`` and ``Error``). The gradient is 0 everywhere.
+    Nearest,
+    /// ``alpha`` times ``Tricubic`` plus ``1 - alpha`` times ``Trilinear``, both evaluated in the same cell. ``Blend(1.0)`` is purely cubic and ``Blend(0.0)`` purely trilinear, in between the overshoots of the cubic are damped at the price of its accuracy.  
+    /// Supplied derivatives (see [`Interp3D::generate_data_hermite()`](crate::Interp3D::generate_data_hermite())) are not used, the cubic part estimates the slopes from the samples. ``alpha`` has to be finite, values outside of ``0..=1`` extrapolate from one scheme past the other.
+    Blend(f64)
+}
+/// Precision at which the samples are stored, see [`Interp3D::set_storage()`](crate::Interp3D::set_storage()).  
+/// The node positions and all computations are always ``f64``, this only affects the memory taken up by the samples.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum Storage {
+    /// Full precision (the default)
+    #[default]
+    F64,
+    /// Rounds every sample to ``f32`` when it is stored, which halves the memory. The interpolated values then carry a relative error of about ``f32::EPSILON`` (~1e-7) with respect to the magnitude of the data.
+    F32
+}
+
+/// How [`Interp3D::quantize()`](crate::Interp3D::quantize()) maps the samples onto ``u16``, which takes a quarter of the memory of ``f64``.  
+/// The range of the samples is divided into ``u16::MAX`` equal steps, so every sample is off by at most half a step: about ``8e-6`` times the range it is quantized in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum QuantizeSpec {
+    /// One range for the whole table
+    #[default]
+    U16,
+    /// A range of its own for every slab of constant ``x``, at the cost of 16 bytes per slab. That's more accurate for tables whose values vary a lot along X.
+    U16PerSlab
+}
+
+/// How the numbers of a text file are written, see [`ExportOptions`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum FloatFormat {
+    /// The fewest digits in scientific notation that parse back to exactly the same number (the default), e.g. ``1.5e-1``
+    #[default]
+    Shortest,
+    /// Scientific notation with ``digits`` digits after the decimal point, right aligned to the same width for every number, e.g. `` 1.500e-1`` with 3 digits. This rounds the numbers, only ``digits >= 16`` is lossless.
+    Scientific { digits: usize },
+    /// Scientific notation with 17 significant digits, which is always enough to get the same ``f64`` back, e.g. ``1.4999999999999999e-1``
+    RoundTrip17
+}
+
+/// The line break of a text file, see [`ExportOptions`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// ``\n`` (the default)
+    #[default]
+    Lf,
+    /// ``\r\n``
+    CrLf
+}
+
+/// Formatting of the text files written by [`Interp3D::export_data_with()`](crate::Interp3D::export_data_with()). Whatever the options, [`Interp3D::import_data()`](crate::Interp3D::import_data()) reads the files again.  
+/// The default is what [`Interp3D::export_data()`](crate::Interp3D::export_data()) writes: space separated numbers in [`FloatFormat::Shortest`], with the header comments and ``\n`` line breaks.
+/// 
+/// # Example
+/// 
+/// ```
+/// use crate::interp3d::*;
+/// 
+/// // csv without comments
+/// let options = ExportOptions { delimiter: ',', header: false, ..ExportOptions::default() };
+/// assert_eq!(options.float_format, FloatFormat::Shortest);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ExportOptions {
+    /// Separates the numbers of a line, one of ``' '``, ``'\t'``, ``','`` and ``';'``
+    pub delimiter: char,
+    pub float_format: FloatFormat,
+    /// Whether the comments describing the layout of the file are written
+    pub header: bool,
+    pub line_ending: LineEnding
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        ExportOptions { delimiter: ' ', float_format: FloatFormat::default(), header: true, line_ending: LineEnding::default() }
+    }
+}
+
+/// The file formats [`Interp3D::import_data()`](crate::Interp3D::import_data()) reads. It recognizes them by their content, [`Interp3D::from_file_format()`](crate::Interp3D::from_file_format()) skips that.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FileFormat {
+    /// The text format of [`Interp3D::export_data()`](crate::Interp3D::export_data()), also for hand-written files
+    Text,
+    /// The binary format of [`Interp3D::export_data_binary()`](crate::Interp3D::export_data_binary())
+    Binary,
+    /// A NumPy ``.npz`` archive, see [`Interp3D::from_npz()`](crate::Interp3D::from_npz())
+    Npz
+}
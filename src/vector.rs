@@ -0,0 +1,293 @@
+//! Contains the interpolator for vector valued data, i.e. several components sampled on one shared grid.
+
+use crate::{Interp3D, Interp3DError, DataGenConf, BoundaryCondition, OutOfBounds, QueryStats, AxisTransform, Type, Storage, Dir, GHOST_LO};
+use crate::samples::Samples;
+
+/// Interpolates ``N`` components (e.g. density, temperature and a velocity) that are sampled on the same grid.  
+/// Compared to ``N`` separate [`Interp3D`] the grid is only stored once and every query only locates its cell once, the stencil is then evaluated for every component with the same weights. The results are identical to the ones of ``N`` separate interpolators, see [`Self::component()`].
+/// 
+/// The grid, the settings and all their semantics are the same as for [`Interp3D`], the query methods just return one result per component.
+/// 
+/// # Example
+/// 
+/// ```
+/// use crate::interp3d::*;
+/// 
+/// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+/// let ip = Interp3DVec::from_config(|x, y, z| [x + y, y*z, 1.0], &config);
+/// 
+/// let [sum, product, one] = ip.interpolate(0.25, 0.5, 0.5);
+/// assert!((sum - 0.75).abs() < 1e-14);
+/// assert!((product - 0.25).abs() < 1e-14);
+/// assert!((one - 1.0).abs() < 1e-14);
+/// ```
+#[derive(Default, Clone)]
+pub struct Interp3DVec<const N: usize> {
+    /// the grid and the settings, its samples are the first component
+    pub(crate) grid: Interp3D,
+    /// the other components, with the same layout as the samples of ``grid``
+    pub(crate) rest: Vec<Samples>
+}
+
+impl<const N: usize> Interp3DVec<N> {
+    /// The samples of all components
+    pub(crate) fn components(&self) -> [&Samples; N] {
+        assert!(!self.grid.data.is_empty(), "Interpolator has not been set up");
+        std::array::from_fn(|c| if c == 0 { &self.grid.data } else { &self.rest[c-1] })
+    }
+
+    /// Fills the samples of a grid that has been set up (but holds no data yet) one node after the other (in storage order) with ``next`` and takes it over, together with its settings.
+    fn fill<F>(&mut self, mut grid: Interp3D, mut next: F) -> Result<(), Interp3DError>
+    where F: FnMut(f64, f64, f64) -> Result<[f64; N], Interp3DError> {
+        const { assert!(N > 0, "Interp3DVec needs at least one component") };
+
+        let mut data = std::mem::take(&mut grid.data);
+        let mut rest = vec![data.clone(); N-1];
+        let mut first = data.writer();
+        let mut others: Vec<_> = rest.iter_mut().map(|samples| samples.writer()).collect();
+        for (i, j, k) in grid.interior() {
+            let index = grid.index(i, j, k);
+            let (x, y, z) = grid.physical(i, j, k);
+            let values = next(x, y, z)?;
+            first.set(index, values[0]);
+            for (samples, &v) in others.iter_mut().zip(&values[1..]) {
+                samples.set(index, v);
+            }
+        }
+        drop(others);
+        grid.data = data;
+
+        grid.set_data_outermost();
+        for samples in rest.iter_mut() {
+            grid.fill_ghosts(samples);
+        }
+        *self = Interp3DVec { grid, rest };
+
+        Ok(())
+    }
+
+    /// Refills the ghost cells of the other components after a setting they depend on changed, the ones of ``grid`` take care of themselves
+    fn refill_rest(&mut self) {
+        for samples in self.rest.iter_mut() {
+            self.grid.fill_ghosts(samples);
+        }
+    }
+
+    /// Generates the data of all components, calling ``f`` once per node. See [`Interp3D::generate_data()`].
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the config is invalid, see [`Self::try_generate_data()`] for a version that returns the error instead.
+    pub fn generate_data<F>(&mut self, f: F, conf: &DataGenConf)
+    where F: FnMut(f64, f64, f64) -> [f64; N] {
+        if let Err(e) = self.try_generate_data(f, conf) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Same as [`Self::generate_data()`], but an invalid config is returned as an error instead of panicking. In that case the closure is never called and the interpolator is left unchanged.
+    pub fn try_generate_data<F>(&mut self, mut f: F, conf: &DataGenConf) -> Result<(), Interp3DError>
+    where F: FnMut(f64, f64, f64) -> [f64; N] {
+        let mut grid = self.grid.settings_only();
+        grid.setup(conf)?;
+
+        self.fill(grid, |x, y, z| Ok(f(x, y, z)))
+    }
+
+    /// Use this to construct the interpolator and generate the data at once, see [`Self::generate_data()`].
+    pub fn from_config<F>(f: F, conf: &DataGenConf) -> Self
+    where F: FnMut(f64, f64, f64) -> [f64; N] {
+        let mut ip = Self::default();
+        ip.generate_data(f, conf);
+        ip
+    }
+
+    /// Constructs the interpolator from existing data on a rectilinear grid, with ``data`` holding all components of a node together. See [`Interp3D::from_grid_and_data()`] for the layout and the requirements.
+    pub fn from_grid_and_data(x: &[f64], y: &[f64], z: &[f64], data: &[[f64; N]]) -> Result<Self, Interp3DError> {
+        let expected = x.len()*y.len()*z.len();
+        if data.len() != expected {
+            return Err(Interp3DError::DataLength { expected, found: data.len() });
+        }
+
+        let mut ip = Self::default();
+        ip.fill_from_parts(x, y, z, data.iter().map(|&v| Ok(v)))?;
+        Ok(ip)
+    }
+
+    /// Sets up the axes (keeping the settings) and fills the samples in storage order from ``values``
+    pub(crate) fn fill_from_parts<I>(&mut self, x: &[f64], y: &[f64], z: &[f64], mut values: I) -> Result<(), Interp3DError>
+    where I: Iterator<Item = Result<[f64; N], Interp3DError>> {
+        let mut grid = self.grid.settings_only();
+        grid.setup_axes(x, y, z)?;
+
+        self.fill(grid, |_, _, _| values.next().expect("one value per node"))
+    }
+
+    /// Interpolates all components at ``(x, y, z)``, see [`Interp3D::interpolate()`].
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the interpolator has not been set up yet or if the point is out of bounds with [`OutOfBounds::Error`].
+    pub fn interpolate(&self, x: f64, y: f64, z: f64) -> [f64; N] {
+        let (x, y, z) = self.grid.to_grid(x, y, z);
+        match self.grid.bounded(x, y, z) {
+            Some((x, y, z)) => self.grid.evaluate_components(self.components(), self.grid.locate(x, y, z)),
+            None => [f64::NAN; N]
+        }
+    }
+
+    /// Same as [`Self::interpolate()`], but returns an error instead of panicking.
+    pub fn try_interpolate(&self, x: f64, y: f64, z: f64) -> Result<[f64; N], Interp3DError> {
+        if self.grid.data.is_empty() {
+            return Err(Interp3DError::NotSetUp);
+        }
+
+        let (x, y, z) = self.grid.to_grid(x, y, z);
+        match self.grid.try_bounded(x, y, z)? {
+            Some((x, y, z)) => Ok(self.grid.evaluate_components(self.components(), self.grid.locate(x, y, z))),
+            None => Ok([f64::NAN; N])
+        }
+    }
+
+    /// Interpolates all components together with their gradients, see [`Interp3D::interpolate_gradient()`].
+    pub fn interpolate_gradient(&self, x: f64, y: f64, z: f64) -> [(f64, [f64; 3]); N] {
+        self.grid.gradient_components(self.components(), x, y, z)
+    }
+
+    /// Returns a scalar interpolator for the component ``c``, with the same grid and settings.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if ``c >= N``.
+    pub fn component(&self, c: usize) -> Interp3D {
+        assert!(c < N, "component {} out of range for {} components", c, N);
+
+        self.grid.with_samples(self.components()[c].clone())
+    }
+
+    /// Splits the interpolator up into one scalar interpolator per component, with the same grid and settings.
+    pub fn into_components(self) -> [Interp3D; N] {
+        let mut grid = self.grid;
+        let mut samples = std::iter::once(std::mem::take(&mut grid.data)).chain(self.rest);
+        std::array::from_fn(|_| grid.with_samples(samples.next().unwrap_or_default()))
+    }
+
+    /// Sets the interpolation scheme for all components, see [`Interp3D::set_type()`]
+    pub fn set_type(&mut self, scheme: Type) {
+        self.grid.set_type(scheme);
+    }
+
+    /// Sets the out of bounds policy for all components, see [`Interp3D::set_out_of_bounds()`]
+    pub fn set_out_of_bounds(&mut self, policy: OutOfBounds) {
+        self.grid.set_out_of_bounds(policy);
+    }
+
+    /// Sets the tolerance for points just outside of the domain for all components, see [`Interp3D::set_out_of_bounds_tolerance()`]
+    pub fn set_out_of_bounds_tolerance(&mut self, rel: f64, abs: f64) {
+        self.grid.set_out_of_bounds_tolerance(rel, abs);
+    }
+
+    /// Returns how many queries were outside of the domain, see [`Interp3D::query_stats()`]. A query of all components counts once.
+    pub fn query_stats(&self) -> QueryStats {
+        self.grid.query_stats()
+    }
+
+    /// Sets the boundary condition for all components, see [`Interp3D::set_boundary_condition()`]
+    pub fn set_boundary_condition(&mut self, boundary: BoundaryCondition) {
+        self.grid.set_boundary_condition(boundary);
+        self.refill_rest();
+    }
+
+    /// Sets the boundary condition along ``dir`` for all components, see [`Interp3D::set_boundary_condition_along()`]
+    pub fn set_boundary_condition_along(&mut self, dir: Dir, boundary: BoundaryCondition) {
+        self.grid.set_boundary_condition_along(dir, boundary);
+        self.refill_rest();
+    }
+
+    /// Makes an axis periodic for all components, see [`Interp3D::set_periodic()`]
+    pub fn set_periodic(&mut self, dir: Dir, periodic: bool) {
+        self.grid.set_periodic(dir, periodic);
+        self.refill_rest();
+    }
+
+    /// Sets the transform along ``dir`` for all components, see [`Interp3D::set_axis_transform()`]
+    pub fn set_axis_transform(&mut self, dir: Dir, transform: AxisTransform) {
+        self.grid.set_axis_transform(dir, transform);
+    }
+
+    /// Sets the precision at which the samples of all components are stored, see [`Interp3D::set_storage()`]
+    pub fn set_storage(&mut self, storage: Storage) {
+        self.grid.set_storage(storage);
+        for samples in self.rest.iter_mut() {
+            *samples = std::mem::take(samples).converted(storage);
+        }
+    }
+
+    /// Returns the precision at which the samples are stored
+    pub fn storage(&self) -> Storage {
+        self.grid.storage()
+    }
+
+    /// Returns the number of nodes along each direction, see [`Interp3D::dims()`]
+    pub fn dims(&self) -> (usize, usize, usize) {
+        self.grid.dims()
+    }
+
+    /// Returns the node positions along X, without the ghost cells
+    pub fn grid_x(&self) -> &[f64] {
+        self.grid.grid_x()
+    }
+
+    /// Returns the node positions along Y, without the ghost cells
+    pub fn grid_y(&self) -> &[f64] {
+        self.grid.grid_y()
+    }
+
+    /// Returns the node positions along Z, without the ghost cells
+    pub fn grid_z(&self) -> &[f64] {
+        self.grid.grid_z()
+    }
+
+    /// Returns the config the grid was generated with, see [`Interp3D::config()`]
+    pub fn config(&self) -> Option<&DataGenConf> {
+        self.grid.config()
+    }
+
+    /// Returns the samples of all components at the node ``(grid_x()[i], grid_y()[j], grid_z()[k])``.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if one of the indices is out of range (see [`Self::dims()`]).
+    pub fn value_at(&self, i: usize, j: usize, k: usize) -> [f64; N] {
+        // checks the indices
+        self.grid.value_at(i, j, k);
+
+        let index = self.grid.index(i+GHOST_LO, j+GHOST_LO, k+GHOST_LO);
+        self.components().map(|samples| samples.get(index))
+    }
+}
+
+impl Interp3D {
+    /// Generates ``N`` interpolators on the same grid at once, with a closure that computes all of their values in one call (e.g. several outputs of one expensive solver run).  
+    /// The grid is iterated exactly once, the ``c``-th value of ``f`` ends up in the ``c``-th interpolator. All of them have identical axes and are independent afterwards, e.g. they can be exported separately. Use [`Interp3DVec`] instead if you want to keep them together, which stores the grid only once and locates every query only once.
+    /// 
+    /// # Example
+    /// 
+    /// ```
+    /// use crate::interp3d::*;
+    /// 
+    /// let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 11).build();
+    /// let [pressure, sound_speed] = Interp3D::generate_data_multi(|x, y, z| [x + y + z, (x*y*z).sqrt()], &config);
+    /// 
+    /// assert!((pressure.interpolate(0.1, 0.2, 0.3) - 0.6).abs() < 1e-14);
+    /// assert_eq!(sound_speed.grid_x(), pressure.grid_x());
+    /// ```
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the config is invalid, like [`Self::generate_data()`].
+    pub fn generate_data_multi<const N: usize, F>(f: F, conf: &DataGenConf) -> [Interp3D; N]
+    where F: FnMut(f64, f64, f64) -> [f64; N] {
+        Interp3DVec::from_config(f, conf).into_components()
+    }
+}
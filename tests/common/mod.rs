@@ -0,0 +1,44 @@
+//! Fixtures shared by the integration tests, each of which includes them with ``mod common;`` (the benchmarks with a ``#[path]`` attribute)
+
+// every test file is a crate of its own and only uses some of them
+#![allow(dead_code)]
+
+use interp3d::*;
+
+/// Path of the file ``name`` in the temporary directory, with the process id in it such that concurrent test runs don't collide
+pub fn temp_file(name: &str) -> String {
+    std::env::temp_dir()
+        .join(format!("interp3d-{}-{}", std::process::id(), name))
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+/// Config on ``[0, 2] x [-1, 3] x [0.5, 1.5]`` with ``(nx, ny, nz)`` nodes, spaced by ``y_spacing`` along Y and linearly along X and Z
+pub fn config_with((nx, ny, nz): (usize, usize, usize), y_spacing: GridSpacing) -> DataGenConf {
+    DataGenConfBuilder::default()
+        .x(0.0, 2.0, nx, GridSpacing::Linear)
+        .y(-1.0, 3.0, ny, y_spacing)
+        .z(0.5, 1.5, nz, GridSpacing::Linear)
+        .build()
+}
+
+/// Smooth test function for the tables on [`config_with()`]
+pub fn f(x: f64, y: f64, z: f64) -> f64 {
+    (x*y).sin() + z*z
+}
+
+/// Uniformly distributed pseudo random numbers in ``[0, 1)`` from a 64 bit LCG, the same ones for the same ``seed``
+pub fn uniform(seed: u64) -> impl FnMut() -> f64 {
+    let mut state = seed;
+    move || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (state >> 11) as f64/(1u64 << 53) as f64
+    }
+}
+
+/// ``n`` pseudo random points in the domain of [`config_with()`]
+pub fn random_points(n: usize, seed: u64) -> Vec<(f64, f64, f64)> {
+    let mut next = uniform(seed);
+    (0..n).map(|_| (2.0*next(), -1.0 + 4.0*next(), 0.5 + next())).collect()
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use common::{f, temp_file};
+use interp3d::*;
+
+fn config() -> DataGenConf {
+    common::config_with((21, 25, 11), GridSpacing::Linear)
+}
+
+/// Pseudo random points in the domain of ``config()``
+fn random_points(n: usize) -> Vec<(f64, f64, f64)> {
+    common::random_points(n, 4242)
+}
+
+#[test]
+fn quantization_error_bounds_hold_on_random_queries() {
+    let mut ip = Interp3D::from_config(f, &config());
+    let points = random_points(5000);
+
+    for spec in [QuantizeSpec::U16, QuantizeSpec::U16PerSlab] {
+        for (scheme, lebesgue) in [(Type::Tricubic, 2.0), (Type::Trilinear, 1.0), (Type::Nearest, 1.0)] {
+            ip.set_type(scheme);
+            let small = ip.quantize(spec);
+            let report = small.error_report();
+            assert_eq!(small.spec(), spec);
+            // half a step of the range of the samples, which is below 4 here
+            assert!(report.max_abs > 0.0 && report.max_abs < 4.0/65535.0, "{:?}", report);
+            assert!(report.rms <= report.max_abs);
+            let (nx, ny, nz) = ip.dims();
+            assert_eq!(report.samples, (nx + 3)*(ny + 3)*(nz + 3));
+
+            let bound = lebesgue*report.max_abs + 1e-12;
+            for &(x, y, z) in &points {
+                let error = (small.interpolate(x, y, z) - ip.interpolate(x, y, z)).abs();
+                assert!(error <= bound, "{:?} {:?} at ({}, {}, {}): {} > {}", spec, scheme, x, y, z, error, bound);
+            }
+            assert_eq!(small.interpolate_many(&points[..10]), points[..10].iter().map(|&(x, y, z)| small.interpolate(x, y, z)).collect::<Vec<_>>());
+        }
+    }
+}
+
+#[test]
+fn slabs_quantize_tables_that_vary_along_x_more_accurately() {
+    let ip = Interp3D::from_config(|x, y, z| 1e3*x.powi(6) + y + z, &config());
+    let whole = ip.quantize(QuantizeSpec::U16).error_report();
+    let slabs = ip.quantize(QuantizeSpec::U16PerSlab).error_report();
+    assert!(slabs.max_abs < 0.1*whole.max_abs, "{:?} vs {:?}", slabs, whole);
+}
+
+#[test]
+fn compact_interpolator_takes_a_quarter_of_the_memory() {
+    let ip = Interp3D::from_config(f, &DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 60).build());
+    let small = ip.quantize(QuantizeSpec::U16PerSlab);
+    let ratio = small.memory() as f64/ip.stats().memory as f64;
+    assert!(ratio > 0.24 && ratio < 0.27, "{}", ratio);
+
+    let expanded = small.dequantize();
+    assert_eq!(expanded.storage(), Storage::F64);
+    for (x, y, z) in [(0.1, 0.2, 0.3), (0.9, 0.5, 0.05)] {
+        assert_eq!(expanded.interpolate(x, y, z), small.interpolate(x, y, z));
+    }
+}
+
+#[test]
+fn non_finite_samples_are_rejected() {
+    let mut ip = Interp3D::from_config(f, &config());
+    ip.set_value(4, 7, 2, f64::NAN);
+    match ip.try_quantize(QuantizeSpec::U16) {
+        Err(Interp3DError::NonFiniteSample { i, j, k }) => assert_eq!((i, j, k), (4, 7, 2)),
+        other => panic!("expected NonFiniteSample, got {:?}", other)
+    }
+    ip.set_value(4, 7, 2, 1.0);
+    ip.set_value(20, 0, 10, f64::INFINITY);
+    assert!(matches!(ip.try_quantize(QuantizeSpec::U16PerSlab), Err(Interp3DError::NonFiniteSample { .. })));
+    assert!(ip.try_quantize(QuantizeSpec::U16PerSlab).unwrap_err().to_string().contains("not finite"));
+
+    assert!(matches!(Interp3D::default().try_quantize(QuantizeSpec::U16), Err(Interp3DError::NotSetUp)));
+}
+
+#[test]
+fn compact_files_round_trip() {
+    let mut ip = Interp3D::from_config(f, &config());
+    ip.set_periodic(Dir::Y, true);
+    ip.set_axis_transform(Dir::Z, AxisTransform::Ln);
+    ip.generate_data(f, &config());
+    let small = ip.quantize(QuantizeSpec::U16PerSlab);
+
+    let file = temp_file("compact.q16");
+    small.export_data(&file).unwrap();
+    let read = CompactInterp3D::try_from_file(&file).unwrap();
+    assert_eq!(read.spec(), QuantizeSpec::U16PerSlab);
+    assert_eq!(read.error_report(), small.error_report());
+    assert_eq!(read.domain(), small.domain());
+    for &(x, y, z) in &random_points(500) {
+        assert_eq!(read.interpolate(x, y, z).to_bits(), small.interpolate(x, y, z).to_bits());
+    }
+
+    // the samples take 2 bytes on disk as well
+    let (nx, ny, nz) = small.dims();
+    let size = std::fs::metadata(&file).unwrap().len() as usize;
+    assert!(size < 2*(nx + 3)*(ny + 3)*(nz + 3) + 2000, "{}", size);
+
+    // the other readers point to the right one
+    let message = Interp3D::try_from_file(&file).err().unwrap().to_string();
+    assert!(message.contains("CompactInterp3D::from_file()"), "{}", message);
+
+    let mut bytes = std::fs::read(&file).unwrap();
+    let last = bytes.len() - 10;
+    bytes[last] ^= 1;
+    std::fs::write(&file, &bytes).unwrap();
+    assert!(CompactInterp3D::try_from_file(&file).unwrap_err().to_string().contains("checksum"));
+    std::fs::write(&file, &bytes[..bytes.len()-1]).unwrap();
+    assert!(matches!(CompactInterp3D::try_from_file(&file), Err(Interp3DError::Parse { .. })));
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn huge_dimensions_in_the_header_are_rejected() {
+    let ip = Interp3D::from_config(f, &config());
+    let file = temp_file("huge.q16");
+
+    for spec in [QuantizeSpec::U16, QuantizeSpec::U16PerSlab] {
+        ip.quantize(spec).export_data(&file).unwrap();
+        let bytes = std::fs::read(&file).unwrap();
+        // nx, ny and nz follow each other as u64
+        let dims: Vec<u8> = [21u64, 25, 11].iter().flat_map(|n| n.to_le_bytes()).collect();
+        let at = bytes.windows(dims.len()).position(|w| w == &dims[..]).unwrap();
+
+        for (d, huge) in [(0, u64::MAX), (0, u64::MAX - 2), (1, u64::MAX), (2, u64::MAX/2), (0, 1 << 62)] {
+            let mut corrupted = bytes.clone();
+            corrupted[at+8*d..at+8*d+8].copy_from_slice(&huge.to_le_bytes());
+            std::fs::write(&file, &corrupted).unwrap();
+            assert!(matches!(CompactInterp3D::try_from_file(&file), Err(Interp3DError::Parse { .. })), "{:?} {} {}", spec, d, huge);
+        }
+    }
+    std::fs::remove_file(&file).unwrap();
+}
@@ -0,0 +1,180 @@
+use interp3d::*;
+
+#[test]
+fn builder_matches_struct_literals() {
+    let single = DataGenConfSingle {
+        n: 11,
+        min: 0.0,
+        max: 10.0,
+        spacing: GridSpacing::Exponential(1.0)
+    };
+    let by_hand = DataGenConf {
+        x: single.clone(),
+        y: single,
+        z: DataGenConfSingle { n: 5, min: -1.0, max: 1.0, spacing: GridSpacing::Linear }
+    };
+
+    let built = DataGenConfBuilder::default()
+        .x(0.0, 10.0, 11, GridSpacing::Exponential(1.0))
+        .y(0.0, 10.0, 11, GridSpacing::Exponential(1.0))
+        .z(-1.0, 1.0, 5, GridSpacing::Linear)
+        .build();
+    assert_eq!(built, by_hand);
+
+    assert_eq!(DataGenConfBuilder::default().build(), DataGenConf::default());
+}
+
+#[test]
+fn builder_uniform_cube() {
+    let single = DataGenConfSingle { n: 7, min: -2.0, max: 2.0, spacing: GridSpacing::Linear };
+    let conf = DataGenConfBuilder::default().uniform_cube(-2.0, 2.0, 7).build();
+
+    assert_eq!(conf, DataGenConf { x: single.clone(), y: single.clone(), z: single });
+}
+
+#[test]
+fn generated_interpolator_remembers_its_config() {
+    let conf = DataGenConfBuilder::default()
+        .uniform_cube(0.0, 1.0, 6)
+        .x(0.0, 10.0, 12, GridSpacing::Exponential(3.5))
+        .build();
+
+    let ip = Interp3D::from_config(|x, y, z| x*y*z, &conf);
+    assert_eq!(ip.config(), Some(&conf));
+    assert_eq!(ip.config().unwrap().x.spacing, GridSpacing::Exponential(3.5));
+
+    assert_eq!(Interp3D::default().config(), None);
+}
+
+#[test]
+fn invalid_configs_are_reported_per_axis() {
+    let conf = DataGenConfBuilder::default()
+        .uniform_cube(0.0, 1.0, 5)
+        .y(0.0, 1.0, 1, GridSpacing::Linear)
+        .build();
+    let mut calls = 0;
+    match Interp3D::try_from_config(|_, _, _| { calls += 1; 0.0 }, &conf) {
+        Err(Interp3DError::TooFewPoints { dir: Dir::Y, n: 1 }) => (),
+        other => panic!("unexpected {:?}", other.map(|_| ()))
+    }
+    assert_eq!(calls, 0);
+
+    let conf = DataGenConfBuilder::default()
+        .uniform_cube(0.0, 1.0, 5)
+        .z(2.0, 1.0, 5, GridSpacing::Linear)
+        .build();
+    match Interp3D::try_from_config(|_, _, _| 0.0, &conf) {
+        Err(Interp3DError::InvalidRange { dir: Dir::Z, min, max }) => assert_eq!((min, max), (2.0, 1.0)),
+        other => panic!("unexpected {:?}", other.map(|_| ()))
+    }
+
+    let conf = DataGenConfBuilder::default()
+        .uniform_cube(0.0, 1.0, 5)
+        .x(0.0, f64::INFINITY, 5, GridSpacing::Linear)
+        .build();
+    assert!(matches!(Interp3D::try_from_config(|_, _, _| 0.0, &conf), Err(Interp3DError::InvalidRange { dir: Dir::X, .. })));
+}
+
+#[test]
+fn failed_generation_leaves_the_interpolator_alone() {
+    let good = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 5).build();
+    let bad = DataGenConfBuilder::default().uniform_cube(1.0, 0.0, 5).build();
+
+    let mut ip = Interp3D::from_config(|x, y, z| x + y + z, &good);
+    assert!(ip.try_generate_data(|_, _, _| 0.0, &bad).is_err());
+    assert_eq!(ip.config(), Some(&good));
+    assert_eq!(ip.interpolate(0.5, 0.5, 0.5), 1.5);
+}
+
+#[test]
+#[should_panic(expected = "Number of points along X too low")]
+fn generate_data_panics_on_invalid_config() {
+    let conf = DataGenConfBuilder::default()
+        .uniform_cube(0.0, 1.0, 5)
+        .x(0.0, 1.0, 0, GridSpacing::Linear)
+        .build();
+    Interp3D::default().generate_data(|_, _, _| 0.0, &conf);
+}
+
+#[test]
+fn failing_closure_reports_the_point_and_keeps_the_old_state() {
+    let good = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 5).build();
+    let other = DataGenConfBuilder::default().uniform_cube(0.0, 2.0, 5).build();
+    let mut ip = Interp3D::from_config(|x, y, z| x + y + z, &good);
+
+    let mut calls = 0;
+    let result = ip.generate_data_fallible(|x, y, z| {
+        calls += 1;
+        if x > 1.0 && y > 1.0 { Err(format!("failed at x = {}", x)) } else { Ok(x*y*z) }
+    }, &other);
+    match result {
+        Err(GenerateError::Closure { x, y, z, error }) => {
+            assert_eq!((x, y, z), (1.5, 1.5, 0.0));
+            assert_eq!(error, "failed at x = 1.5");
+        },
+        other => panic!("unexpected {:?}", other)
+    }
+    // stopped right at the first failure
+    assert_eq!(calls, 3*5*5 + 3*5 + 1);
+
+    assert_eq!(ip.config(), Some(&good));
+    assert_eq!(ip.interpolate(0.5, 0.5, 0.5), 1.5);
+
+    let ip = Interp3D::from_config_fallible(|x, y, z| Ok::<f64, String>(x*y*z), &other).unwrap();
+    assert_eq!(ip.interpolate(1.0, 1.5, 2.0), 3.0);
+}
+
+#[test]
+fn validate_names_the_axis_and_the_problem() {
+    let good = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 5).build();
+    assert!(good.validate().is_ok());
+
+    let with_y = |y: DataGenConfSingle| DataGenConf { y, ..good.clone() };
+    let single = |n: usize, min: f64, max: f64, spacing: GridSpacing| DataGenConfSingle { n, min, max, spacing };
+    assert!(matches!(with_y(single(0, 0.0, 1.0, GridSpacing::Linear)).validate(), Err(Interp3DError::TooFewPoints { dir: Dir::Y, n: 0 })));
+    assert!(matches!(with_y(single(1, 0.0, 1.0, GridSpacing::Linear)).validate(), Err(Interp3DError::TooFewPoints { dir: Dir::Y, n: 1 })));
+    assert!(matches!(with_y(single(5, 1.0, 1.0, GridSpacing::Linear)).validate(), Err(Interp3DError::InvalidRange { dir: Dir::Y, .. })));
+    assert!(matches!(with_y(single(5, f64::NAN, 1.0, GridSpacing::Linear)).validate(), Err(Interp3DError::InvalidRange { dir: Dir::Y, .. })));
+    assert!(matches!(with_y(single(5, 0.0, 1.0, GridSpacing::Logarithmic)).validate(), Err(Interp3DError::InvalidRange { dir: Dir::Y, .. })));
+    assert!(matches!(with_y(single(5, 0.0, 1.0, GridSpacing::Exponential(f64::NAN))).validate(), Err(Interp3DError::InvalidSpacing { dir: Dir::Y, .. })));
+    assert!(matches!(with_y(single(5, 0.0, 1.0, GridSpacing::TwoSidedExponential(f64::INFINITY))).validate(), Err(Interp3DError::InvalidSpacing { dir: Dir::Y, .. })));
+    assert!(matches!(with_y(DataGenConfSingle::custom(vec![0.0, 2.0, 1.0])).validate(), Err(Interp3DError::NonMonotonicAxis { dir: Dir::Y, index: 1 })));
+    assert!(matches!(with_y(single(4, 0.0, 2.0, GridSpacing::Custom(vec![0.0, 1.0, 2.0]))).validate(), Err(Interp3DError::CustomAxisMismatch { dir: Dir::Y })));
+
+    // generation checks the same, before calling the closure
+    let conf = with_y(single(5, 0.0, 1.0, GridSpacing::Exponential(f64::NAN)));
+    assert!(matches!(Interp3D::try_from_config(|_, _, _| panic!("called"), &conf).map(|_| ()), Err(Interp3DError::InvalidSpacing { dir: Dir::Y, .. })));
+}
+
+#[test]
+fn builder_sets_up_grid_and_settings() {
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 2.0, 9, GridSpacing::Linear)
+        .y(-1.0, 1.0, 5, GridSpacing::Chebyshev)
+        .build();
+    let f = |x: f64, y: f64, z: f64| x*y - z;
+
+    let built = Interp3DBuilder::default()
+        .config(conf.clone())
+        .boundary_along(Dir::Y, BoundaryCondition::Reflect { even: false })
+        .periodic(Dir::Z)
+        .generate(f)
+        .unwrap();
+    let mut by_hand = Interp3D::default();
+    by_hand.set_boundary_condition_along(Dir::Y, BoundaryCondition::Reflect { even: false });
+    by_hand.set_periodic(Dir::Z, true);
+    by_hand.generate_data(f, &conf);
+
+    assert_eq!(built.config(), Some(&conf));
+    assert_eq!(built.boundary_condition(Dir::Y), BoundaryCondition::Reflect { even: false });
+    for &(x, y, z) in &[(0.1, -0.95, 0.2), (1.9, 0.99, 3.0), (1.0, 0.0, -0.5)] {
+        assert_eq!(built.interpolate(x, y, z), by_hand.interpolate(x, y, z));
+    }
+
+    // the default grid and invalid ones
+    assert_eq!(Interp3DBuilder::default().generate(|_, _, _| 0.0).unwrap().config(), Some(&DataGenConf::default()));
+    let invalid = Interp3DBuilder::default().x_axis(1.0, 0.0, 5, GridSpacing::Linear).generate(|_, _, _| panic!("called"));
+    assert!(matches!(invalid.map(|_| ()), Err(Interp3DError::InvalidRange { dir: Dir::X, .. })));
+    let short = Interp3DBuilder::default().build_from_grid_and_data(&[0.0, 1.0], &[0.0, 1.0], &[0.0, 1.0], &[0.0; 7]);
+    assert!(matches!(short.map(|_| ()), Err(Interp3DError::DataLength { expected: 8, found: 7 })));
+}
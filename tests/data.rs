@@ -0,0 +1,871 @@
+mod common;
+
+use interp3d::*;
+
+fn linear(n: usize, min: f64, max: f64) -> DataGenConfSingle {
+    DataGenConfSingle {
+        n,
+        min,
+        max,
+        spacing: GridSpacing::Linear
+    }
+}
+
+fn gaussian(x: f64, y: f64, z: f64) -> f64 {
+    (-((x - 0.2).powi(2) + (y - 0.1).powi(2) + (z - 0.3).powi(2))/4.0).exp()
+}
+
+#[test]
+fn extrema_and_argmax_of_single_peak() {
+    let conf = DataGenConf {
+        x: linear(11, 0.0, 10.0),
+        y: linear(11, 0.0, 10.0),
+        z: linear(11, 0.0, 10.0)
+    };
+    // off-centre inside the grid, so the nearest node is neither a corner nor the first one visited
+    let peak = |x: f64, y: f64, z: f64| (-((x - 4.3).powi(2) + (y - 6.6).powi(2) + (z - 2.2).powi(2))/4.0).exp();
+    let mut ip = Interp3D::from_config(peak, &conf);
+
+    assert_eq!(ip.argmax(), (4.0, 7.0, 2.0));
+    let (min, max) = ip.data_extrema();
+    assert_eq!(max, peak(4.0, 7.0, 2.0));
+    assert!(min > 0.0 && min < 1e-6);
+    assert_eq!((min, max), (ip.data_min().0, ip.data_max().0));
+
+    // a planted NaN at the peak is skipped, the next highest node (one below along y) takes over
+    ip.set_value(4, 7, 2, f64::NAN);
+    assert_eq!(ip.argmax(), (4.0, 6.0, 2.0));
+    assert_eq!(ip.data_extrema().1, peak(4.0, 6.0, 2.0));
+
+    // so are infinite samples, like for data_max() and stats()
+    ip.set_value(9, 0, 9, f64::INFINITY);
+    ip.set_value(0, 0, 0, f64::NEG_INFINITY);
+    assert_eq!(ip.argmax(), (4.0, 6.0, 2.0));
+    assert_eq!(ip.data_extrema(), (ip.data_min().0, ip.data_max().0));
+    assert_eq!(ip.data_max().1, (4, 6, 2));
+    assert!(ip.data_extrema().0.is_finite());
+}
+
+#[test]
+#[should_panic(expected = "Interpolator has no finite samples")]
+fn argmax_of_only_nan_samples_panics() {
+    let conf = DataGenConf { x: linear(3, 0.0, 1.0), y: linear(3, 0.0, 1.0), z: linear(3, 0.0, 1.0) };
+    let ip = Interp3D::from_config(|_, _, _| f64::NAN, &conf);
+    let (min, max) = ip.data_extrema();
+    assert!(min.is_nan() && max.is_nan());
+    ip.argmax();
+}
+
+#[test]
+#[should_panic(expected = "Interpolator has not been set up")]
+fn extrema_without_data_panic() {
+    Interp3D::default().data_extrema();
+}
+
+#[test]
+fn remap_domain_moves_the_nodes_only() {
+    let conf = DataGenConf {
+        x: linear(6, 0.0, 10.0),
+        y: linear(7, 0.0, 10.0),
+        z: linear(8, 0.0, 10.0)
+    };
+    let mut ip = Interp3D::from_config(|x, y, z| x + y + z, &conf);
+    let before = ip.data_extrema();
+    let (_, y, z) = ip.argmax();
+
+    ip.remap_domain(Dir::X, -3.0, 5.0);
+
+    let (x_new, y_new, z_new) = ip.argmax();
+    assert!((x_new - 5.0).abs() < 1e-12);
+    assert_eq!((y_new, z_new), (y, z));
+    assert_eq!(ip.data_extrema(), before);
+}
+
+#[test]
+fn remap_domain_keeps_the_interpolated_values() {
+    let conf = DataGenConf {
+        x: DataGenConfSingle { n: 9, min: 1.0, max: 10.0, spacing: GridSpacing::Logarithmic },
+        y: DataGenConfSingle { n: 8, min: 0.0, max: 2.0, spacing: GridSpacing::Exponential(3.0) },
+        z: linear(7, -1.0, 1.0)
+    };
+    let mut ip = Interp3D::from_config(|x, y, z| (x*y).sin() + z*z*x, &conf);
+    let original = ip.clone();
+
+    // an affine map along every axis, the one along X makes the logarithmic range cross 0
+    let maps = [(-3.0, 5.0), (10.0, 11.0), (0.0, 4.0)];
+    for (dir, &(min, max)) in [Dir::X, Dir::Y, Dir::Z].iter().zip(&maps) {
+        ip.remap_domain(*dir, min, max);
+    }
+    let remap = |p: f64, (a, b): (f64, f64), (min, max): (f64, f64)| min + (p - a)*(max - min)/(b - a);
+
+    for &x in &[1.0, 1.3, 4.7, 9.99] {
+        for &y in &[0.0, 0.05, 1.1, 2.0] {
+            for &z in &[-1.0, -0.2, 0.65] {
+                let moved = (remap(x, (1.0, 10.0), maps[0]), remap(y, (0.0, 2.0), maps[1]), remap(z, (-1.0, 1.0), maps[2]));
+                let (expected, value) = (original.interpolate(x, y, z), ip.interpolate(moved.0, moved.1, moved.2));
+                assert!((value - expected).abs() < 1e-10, "{:?}: {} vs {}", (x, y, z), value, expected);
+            }
+        }
+    }
+
+    // the non-linear spacings can't describe the new ranges, so the config lists the nodes
+    let remapped = ip.config().unwrap();
+    assert_eq!(remapped.x.spacing, GridSpacing::Custom(ip.grid_x().to_vec()));
+    assert_eq!((remapped.x.n, remapped.x.min, remapped.x.max), (9, -3.0, 5.0));
+    assert!(matches!(remapped.y.spacing, GridSpacing::Custom(_)));
+    assert_eq!((remapped.z.spacing.clone(), remapped.z.min, remapped.z.max), (GridSpacing::Linear, 0.0, 4.0));
+    for (dir, single) in [(Dir::X, &remapped.x), (Dir::Y, &remapped.y), (Dir::Z, &remapped.z)] {
+        single.validate(dir).unwrap();
+    }
+}
+
+#[test]
+fn iter_points_visits_every_interior_node() {
+    let conf = DataGenConf {
+        x: linear(5, 0.0, 1.0),
+        y: linear(6, 0.0, 1.0),
+        z: linear(7, 0.0, 1.0)
+    };
+    let f = |x: f64, y: f64, z: f64| x - 2.0*y + 3.0*z;
+    let ip = Interp3D::from_config(f, &conf);
+
+    let points: Vec<_> = ip.iter_points().collect();
+    assert_eq!(points.len(), 5*6*7);
+    for &(x, y, z, v) in &points {
+        assert_eq!(v, f(x, y, z));
+    }
+
+    // z changes fastest, x slowest
+    assert_eq!(points[0].0, points[1].0);
+    assert_eq!(points[0].1, points[1].1);
+    assert!(points[1].2 > points[0].2);
+    assert!(points[6*7].0 > points[0].0);
+}
+
+#[test]
+fn map_data_normalizes_by_max() {
+    let conf = DataGenConf {
+        x: linear(11, 0.0, 10.0),
+        y: linear(11, 0.0, 10.0),
+        z: linear(11, 0.0, 10.0)
+    };
+    let mut ip = Interp3D::from_config(|x, y, z| 3.0*gaussian(x, y, z), &conf);
+
+    let (_, max) = ip.data_extrema();
+    ip.map_data(|v| v/max);
+
+    assert_eq!(ip.data_extrema().1, 1.0);
+    for (x, y, z, v) in ip.iter_points() {
+        assert!((v - gaussian(x, y, z)/gaussian(0.0, 0.0, 0.0)).abs() < 1e-15);
+    }
+}
+
+#[test]
+fn accessors_report_the_interior_grid() {
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 2.0, 5, GridSpacing::Linear)
+        .y(-1.0, 1.0, 3, GridSpacing::Linear)
+        .z_nodes(vec![0.0, 0.1, 0.5, 2.0])
+        .build();
+    let f = |x: f64, y: f64, z: f64| x + 10.0*y + 100.0*z;
+    let ip = Interp3D::from_config(f, &conf);
+
+    assert_eq!(ip.dims(), (5, 3, 4));
+    assert_eq!(ip.grid_x(), &[0.0, 0.5, 1.0, 1.5, 2.0]);
+    assert_eq!(ip.grid_y(), &[-1.0, 0.0, 1.0]);
+    assert_eq!(ip.grid_z(), &[0.0, 0.1, 0.5, 2.0]);
+    assert_eq!(ip.domain(), ((0.0, 2.0), (-1.0, 1.0), (0.0, 2.0)));
+
+    for i in 0..5 {
+        for j in 0..3 {
+            for k in 0..4 {
+                assert_eq!(ip.value_at(i, j, k), f(ip.grid_x()[i], ip.grid_y()[j], ip.grid_z()[k]));
+            }
+        }
+    }
+
+    let empty = Interp3D::default();
+    assert_eq!(empty.dims(), (0, 0, 0));
+    assert!(empty.grid_x().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "out of range")]
+fn value_at_checks_the_interior_bounds() {
+    let conf = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 4).build();
+    Interp3D::from_config(|x, _, _| x, &conf).value_at(0, 4, 0);
+}
+
+#[test]
+fn set_value_refreshes_the_ghost_cells() {
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 1.0, 4, GridSpacing::Linear)
+        .y(0.0, 2.0, 5, GridSpacing::Exponential(2.0))
+        .z(-1.0, 1.0, 3, GridSpacing::Linear)
+        .build();
+    let f = |x: f64, y: f64, z: f64| x + y*y - z;
+
+    for boundary in [BoundaryCondition::Copy, BoundaryCondition::Reflect { even: true }, BoundaryCondition::Reflect { even: false }, BoundaryCondition::LinearExtrapolate] {
+        let mut ip = Interp3D::default();
+        ip.set_boundary_condition(boundary);
+        ip.generate_data(f, &conf);
+        let before = ip.interpolate(0.01, 0.01, -0.99);
+
+        // patch the corner and the node next to it and compare with an interpolator built from the patched data
+        ip.set_value(0, 0, 0, 10.0);
+        ip.set_value(1, 0, 0, -3.0);
+        let data: Vec<f64> = ip.iter_points().map(|p| p.3).collect();
+        assert_eq!((data[0], data[5*3]), (10.0, -3.0));
+        let mut fresh = Interp3D::from_grid_and_data(ip.grid_x(), ip.grid_y(), ip.grid_z(), &data).unwrap();
+        fresh.set_boundary_condition(boundary);
+
+        for &(x, y, z) in &[(0.01, 0.01, -0.99), (0.0, 0.0, -1.0), (-0.05, 0.3, -0.9), (0.2, 0.05, -0.5)] {
+            assert_eq!(ip.interpolate(x, y, z), fresh.interpolate(x, y, z));
+        }
+        assert_ne!(ip.interpolate(0.01, 0.01, -0.99), before);
+    }
+}
+
+#[test]
+fn index_mut_is_seen_by_the_next_query() {
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 1.0, 4, GridSpacing::Linear)
+        .y(0.0, 2.0, 5, GridSpacing::Exponential(2.0))
+        .z(-1.0, 1.0, 3, GridSpacing::Linear)
+        .build();
+    let f = |x: f64, y: f64, z: f64| x + y*y - z;
+
+    for boundary in [BoundaryCondition::Copy, BoundaryCondition::Reflect { even: false }, BoundaryCondition::LinearExtrapolate] {
+        for scheme in [Type::Tricubic, Type::TricubicMonotone, Type::Trilinear, Type::Nearest] {
+            let mut ip = Interp3D::default();
+            ip.set_boundary_condition(boundary);
+            ip.set_type(scheme);
+            ip.generate_data(f, &conf);
+
+            // write the corner and query right inside of it, the ghost cells around it are stale by now
+            ip[(0, 0, 0)] = 10.0;
+            ip[(1, 0, 0)] -= 3.0;
+            assert_eq!((ip[(0, 0, 0)], ip.value_at(1, 0, 0)), (10.0, f(1.0/3.0, 0.0, -1.0) - 3.0));
+
+            let data: Vec<f64> = ip.iter_points().map(|p| p.3).collect();
+            let mut fresh = Interp3D::from_grid_and_data(ip.grid_x(), ip.grid_y(), ip.grid_z(), &data).unwrap();
+            fresh.set_boundary_condition(boundary);
+            fresh.set_type(scheme);
+
+            let points = [(0.01, 0.01, -0.99), (0.0, 0.0, -1.0), (0.2, 0.05, -0.5), (1.0, 2.0, 1.0)];
+            for &(x, y, z) in &points {
+                assert_eq!(ip.interpolate(x, y, z), fresh.interpolate(x, y, z));
+                assert_eq!(ip.interpolate_gradient(x, y, z), fresh.interpolate_gradient(x, y, z));
+            }
+
+            // refreshing the ghost cells doesn't change anything
+            ip.set_boundary_condition(boundary);
+            for &(x, y, z) in &points {
+                assert_eq!(ip.interpolate(x, y, z), fresh.interpolate(x, y, z));
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "Node (4, 0, 0) is out of range, the grid has 4x5x3 nodes")]
+fn index_out_of_range() {
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 1.0, 4, GridSpacing::Linear)
+        .y(0.0, 1.0, 5, GridSpacing::Linear)
+        .z(0.0, 1.0, 3, GridSpacing::Linear)
+        .build();
+    let ip = Interp3D::from_config(|x, _, _| x, &conf);
+    let _ = ip[(4, 0, 0)];
+}
+
+#[test]
+fn update_data_sees_the_coordinates() {
+    let conf = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 4).build();
+    let mut ip = Interp3D::from_config(|x, y, z| x + y + z, &conf);
+    ip.update_data(|x, y, z, v| v - x - y - z + 1.0);
+
+    assert!(ip.iter_points().all(|p| (p.3 - 1.0).abs() < 1e-15));
+    assert!((ip.interpolate(0.0, 0.0, 0.0) - 1.0).abs() < 1e-15);
+}
+
+#[test]
+fn arithmetic_on_the_same_grid() {
+    let conf = DataGenConf {
+        x: linear(7, 0.0, 3.0),
+        y: DataGenConfSingle { n: 8, min: -1.0, max: 1.0, spacing: GridSpacing::Exponential(2.0) },
+        z: linear(5, 0.0, 1.0)
+    };
+    let g = |x: f64, y: f64, z: f64| (x - y).sin()*z;
+    let sum = Interp3D::from_config(|x, y, z| gaussian(x, y, z) + g(x, y, z), &conf);
+    let other = Interp3D::from_config(g, &conf);
+
+    let mut ip = Interp3D::from_config(gaussian, &conf);
+    ip.add_assign_field(&other).unwrap();
+    for &(x, y, z) in &[(0.0, -1.0, 0.0), (1.3, 0.25, 0.6), (3.0, 1.0, 1.0), (2.9, -0.99, 0.05)] {
+        assert!((ip.interpolate(x, y, z) - sum.interpolate(x, y, z)).abs() < 1e-14);
+    }
+
+    ip.sub_assign_field(&other).unwrap();
+    ip.scale(3.0);
+    let blended = ip.blend(&other, 0.25).unwrap();
+    for &(x, y, z) in &[(1.3, 0.25, 0.6), (2.9, -0.99, 0.05)] {
+        let expected = 0.75*3.0*gaussian(x, y, z) + 0.25*g(x, y, z);
+        assert!((blended.interpolate(x, y, z) - expected).abs() < 1e-2);
+        let separately = 0.75*ip.interpolate(x, y, z) + 0.25*other.interpolate(x, y, z);
+        assert!((blended.interpolate(x, y, z) - separately).abs() < 1e-14);
+    }
+}
+
+#[test]
+fn arithmetic_needs_matching_grids() {
+    let conf = DataGenConf { x: linear(5, 0.0, 1.0), y: linear(5, 0.0, 1.0), z: linear(5, 0.0, 1.0) };
+    let mut ip = Interp3D::from_config(gaussian, &conf);
+    let shifted = Interp3D::from_config(gaussian, &DataGenConf { y: linear(5, 0.0, 1.1), ..conf.clone() });
+    let before: Vec<_> = ip.iter_points().collect();
+
+    assert!(matches!(ip.add_assign_field(&shifted), Err(Interp3DError::GridMismatch { dir: Dir::Y })));
+    assert!(matches!(ip.blend(&shifted, 0.5).map(|_| ()), Err(Interp3DError::GridMismatch { dir: Dir::Y })));
+    assert!(matches!(ip.sub_assign_field(&Interp3D::default()), Err(Interp3DError::NotSetUp)));
+    assert_eq!(ip.iter_points().collect::<Vec<_>>(), before);
+}
+
+#[test]
+fn diff_of_identical_tables_is_zero() {
+    let conf = DataGenConf { x: linear(7, 0.0, 3.0), y: linear(6, -1.0, 1.0), z: linear(5, 0.0, 1.0) };
+    let ip = Interp3D::from_config(gaussian, &conf);
+    let again = Interp3D::from_config(gaussian, &conf);
+
+    let diff = ip.diff(&again).unwrap();
+    assert!(diff.iter().all(|node| node.value == 0.0));
+    assert_eq!(diff.interpolate(1.3, 0.25, 0.6), 0.0);
+    let report = ip.compare(&again, 0.0).unwrap();
+    assert_eq!((report.max_abs, report.max_rel, report.rms), (0.0, 0.0, 0.0));
+
+    let shifted = Interp3D::from_config(gaussian, &DataGenConf { z: linear(5, 0.0, 1.1), ..conf.clone() });
+    assert!(matches!(ip.diff(&shifted), Err(Interp3DError::GridMismatch { dir: Dir::Z })));
+    assert!(matches!(ip.compare(&shifted, 0.0), Err(Interp3DError::GridMismatch { dir: Dir::Z })));
+    assert!(matches!(ip.compare(&Interp3D::default(), 0.0), Err(Interp3DError::NotSetUp)));
+}
+
+#[test]
+fn diff_localises_a_perturbed_node() {
+    let conf = DataGenConf { x: linear(7, 0.0, 3.0), y: linear(6, -1.0, 1.0), z: linear(5, 0.0, 1.0) };
+    let reference = Interp3D::from_config(|x, y, z| gaussian(x, y, z) + 1.0, &conf);
+    let mut ip = reference.clone();
+    ip.set_type(Type::Trilinear);
+    ip.set_value(4, 1, 3, ip.value_at(4, 1, 3) + 0.5);
+    // tiny noise elsewhere, which is below the absolute tolerance
+    ip.set_value(0, 0, 0, ip.value_at(0, 0, 0) + 1e-10);
+
+    let report = ip.compare(&reference, 1e-9).unwrap();
+    assert_eq!(report.worst_node, (4, 1, 3));
+    assert_eq!(report.worst_coords, (2.0, -0.6, 0.75));
+    assert!((report.max_abs - 0.5).abs() < 1e-12);
+    assert!((report.max_rel - (0.5 - 1e-9)/ip.value_at(4, 1, 3)).abs() < 1e-12);
+    assert!((report.rms - 0.5/((7*6*5) as f64).sqrt()).abs() < 1e-9);
+    assert!(ip.approx_eq(&reference, report.max_rel*(1.0 + 1e-12), 1e-9));
+    assert!(!ip.approx_eq(&reference, report.max_rel*0.99, 1e-9));
+
+    // the difference field interpolates trilinearly like ip and is zero away from the perturbed node
+    let diff = ip.diff(&reference).unwrap();
+    assert!((diff.interpolate(2.0, -0.6, 0.75) - 0.5).abs() < 1e-12);
+    assert!((diff.interpolate(2.25, -0.6, 0.75) - 0.25).abs() < 1e-12);
+    assert_eq!(diff.interpolate(0.75, 0.5, 0.5), 0.0);
+    assert_eq!(diff.compare(&ip.diff(&reference).unwrap(), 0.0).unwrap().max_abs, 0.0);
+
+    let file = common::temp_file("diff.vtk");
+    diff.export_vtk(&file).unwrap();
+    let text = std::fs::read_to_string(&file).unwrap();
+    let values: Vec<f64> = text.split("LOOKUP_TABLE default").nth(1).unwrap().split_whitespace().map(|v| v.parse().unwrap()).collect();
+    assert_eq!(values.len(), 7*6*5);
+    assert_eq!(values.iter().filter(|v| v.abs() > 1e-9).count(), 1);
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn compare_reports_an_infinite_sample_as_the_worst_regression() {
+    let conf = DataGenConf { x: linear(5, 0.0, 1.0), y: linear(5, 0.0, 1.0), z: linear(5, 0.0, 1.0) };
+    let reference = Interp3D::from_config(|x, y, z| gaussian(x, y, z) + 1.0, &conf);
+    let mut ip = reference.clone();
+    ip.set_value(0, 0, 0, ip.value_at(0, 0, 0)*1.5);
+    ip.set_value(3, 1, 2, f64::INFINITY);
+
+    for abs_tol in [0.0, 1e-9, 1e300, f64::INFINITY] {
+        let report = ip.compare(&reference, abs_tol).unwrap();
+        assert_eq!(report.max_rel, f64::INFINITY, "{}", abs_tol);
+        assert_eq!(report.max_abs, f64::INFINITY);
+        assert_eq!(report.worst_node, (3, 1, 2));
+        assert!(!ip.approx_eq(&reference, report.max_rel, abs_tol));
+    }
+    // the same the other way round and for infinities of opposite sign
+    assert_eq!(reference.compare(&ip, 0.0).unwrap().max_rel, f64::INFINITY);
+    let mut negative = reference.clone();
+    negative.set_value(3, 1, 2, f64::NEG_INFINITY);
+    assert_eq!(ip.compare(&negative, 0.0).unwrap().max_rel, f64::INFINITY);
+
+    // equal infinities don't differ
+    let mut same = reference.clone();
+    same.set_value(0, 0, 0, ip.value_at(0, 0, 0));
+    same.set_value(3, 1, 2, f64::INFINITY);
+    let report = ip.compare(&same, 0.0).unwrap();
+    assert_eq!((report.max_abs, report.max_rel), (0.0, 0.0));
+}
+
+#[test]
+fn resample_onto_the_same_config_reproduces_the_data() {
+    let conf = DataGenConf {
+        x: linear(7, 0.0, 3.0),
+        y: DataGenConfSingle { n: 9, min: -1.0, max: 1.0, spacing: GridSpacing::Chebyshev },
+        z: DataGenConfSingle { n: 6, min: 0.5, max: 2.0, spacing: GridSpacing::Exponential(3.0) }
+    };
+    let ip = Interp3D::from_config(gaussian, &conf);
+
+    let resampled = ip.resample(&conf).unwrap();
+    assert_eq!(resampled.config(), Some(&conf));
+    for (a, b) in resampled.iter_points().zip(ip.iter_points()) {
+        assert_eq!(a, b);
+    }
+}
+
+#[test]
+fn resample_beyond_the_domain_follows_the_out_of_bounds_policy() {
+    let conf = DataGenConf { x: linear(5, 0.0, 1.0), y: linear(5, 0.0, 1.0), z: linear(5, 0.0, 1.0) };
+    let wider = DataGenConf { z: linear(5, 0.0, 2.0), ..conf.clone() };
+    let mut ip = Interp3D::from_config(|x, y, z| x + y + z, &conf);
+    ip.set_type(Type::Trilinear);
+
+    // extrapolated by default, which is exact for linear data with the trilinear scheme
+    let resampled = ip.resample(&wider).unwrap();
+    assert!((resampled.value_at(4, 4, 4) - 4.0).abs() < 1e-12);
+
+    ip.set_out_of_bounds(OutOfBounds::ClampToEdge);
+    assert_eq!(ip.resample(&wider).unwrap().value_at(4, 4, 4), 3.0);
+
+    ip.set_out_of_bounds(OutOfBounds::Error);
+    assert!(matches!(ip.resample(&wider).map(|_| ()), Err(Interp3DError::OutOfBounds { axis: Dir::Z, .. })));
+    assert!(matches!(Interp3D::default().resample(&conf).map(|_| ()), Err(Interp3DError::NotSetUp)));
+}
+
+#[test]
+fn crop_gives_the_same_values_inside_the_box() {
+    let conf = DataGenConf {
+        x: linear(17, 0.0, 4.0),
+        y: DataGenConfSingle { n: 13, min: -1.0, max: 1.0, spacing: GridSpacing::Chebyshev },
+        z: DataGenConfSingle { n: 11, min: 0.5, max: 2.0, spacing: GridSpacing::Exponential(3.0) }
+    };
+    let mut ip = Interp3D::from_config(gaussian, &conf);
+    ip.set_boundary_condition(BoundaryCondition::LinearExtrapolate);
+
+    let mut next = common::uniform(3);
+    // one box in the middle and one at the corner of the domain
+    for (xr, yr, zr) in [((1.1, 2.3), (-0.4, 0.3), (0.9, 1.4)), ((0.0, 0.6), (0.7, 1.0), (1.8, 2.0))] {
+        let points: Vec<_> = (0..300)
+            .map(|_| (xr.0 + (xr.1 - xr.0)*next(), yr.0 + (yr.1 - yr.0)*next(), zr.0 + (zr.1 - zr.0)*next()))
+            .chain([(xr.0, yr.0, zr.0), (xr.1, yr.1, zr.1)])
+            .collect();
+        for scheme in [Type::Tricubic, Type::TricubicMonotone, Type::Trilinear, Type::Nearest] {
+            ip.set_type(scheme);
+            let cropped = ip.crop(xr, yr, zr).unwrap();
+            assert!(cropped.iter().len() < ip.iter().len() / 4);
+            for &(x, y, z) in &points {
+                assert_eq!(cropped.interpolate(x, y, z), ip.interpolate(x, y, z), "{:?} at {:?}", scheme, (x, y, z));
+            }
+        }
+    }
+
+    // the block snaps onto the nodes and takes one more on each side
+    let cropped = ip.crop((1.1, 2.0), (-1.0, 1.0), (0.5, 2.0)).unwrap();
+    assert_eq!(cropped.grid_x(), &ip.grid_x()[3..=9]);
+    assert_eq!((cropped.grid_y(), cropped.grid_z()), (ip.grid_y(), ip.grid_z()));
+    assert_eq!(cropped.value_at(0, 5, 7), ip.value_at(3, 5, 7));
+
+    assert!(matches!(ip.crop((1.0, 4.5), (0.0, 0.5), (1.0, 1.5)).map(|_| ()), Err(Interp3DError::OutOfBounds { axis: Dir::X, .. })));
+    assert!(matches!(ip.crop((1.0, 2.0), (0.5, 0.0), (1.0, 1.5)).map(|_| ()), Err(Interp3DError::InvalidRange { dir: Dir::Y, .. })));
+    assert!(matches!(Interp3D::default().crop((0.0, 1.0), (0.0, 1.0), (0.0, 1.0)).map(|_| ()), Err(Interp3DError::NotSetUp)));
+}
+
+#[test]
+fn adaptive_grid_calls_the_closure_once_per_point() {
+    let initial = DataGenConf { x: linear(5, -2.0, 2.0), y: linear(4, 0.0, 1.0), z: linear(4, 0.0, 1.0) };
+    let f = |x: f64, y: f64, _z: f64| (-4.0*x*x).exp()*(1.0 + y*y);
+
+    let mut seen = std::collections::HashSet::new();
+    let ip = Interp3D::generate_adaptive(|x, y, z| {
+        assert!(seen.insert([x.to_bits(), y.to_bits(), z.to_bits()]), "called twice at ({}, {}, {})", x, y, z);
+        f(x, y, z)
+    }, &initial, 1e-4, 200);
+
+    let (nx, ny, nz) = ip.dims();
+    assert!(nx > 5 && ny > 4 && nz == 4);
+    assert!(matches!(ip.config().unwrap().x.spacing, GridSpacing::Custom(_)));
+    // every node was evaluated, the few extra calls are the checked midpoints
+    for (x, y, z, _) in ip.iter_points() {
+        assert!(seen.contains(&[x.to_bits(), y.to_bits(), z.to_bits()]));
+    }
+    assert!(seen.len() < 4*nx*ny*nz);
+    assert!(ip.estimate_error_on_grid(f).max_abs < 1e-3);
+}
+
+#[test]
+fn adaptive_grid_stops_at_the_maximum_number_of_points() {
+    let initial = DataGenConf { x: linear(5, -2.0, 2.0), y: linear(4, 0.0, 1.0), z: linear(4, 0.0, 1.0) };
+    let f = |x: f64, _y: f64, _z: f64| (-4.0*x*x).exp();
+
+    let ip = Interp3D::generate_adaptive(f, &initial, 1e-12, 12);
+    assert_eq!(ip.dims(), (12, 4, 4));
+
+    // the initial grid is kept if it is already fine enough
+    let ip = Interp3D::generate_adaptive(|_, _, _| 1.0, &initial, 1e-6, 100);
+    assert_eq!(ip.dims(), (5, 4, 4));
+    assert!(Interp3D::try_generate_adaptive(f, &DataGenConf { x: linear(1, 0.0, 1.0), ..initial.clone() }, 1e-6, 100).is_err());
+}
+
+#[test]
+fn ghost_cells_match_a_reference_on_an_anisotropic_grid() {
+    // uniform along y, so the cubic is Catmull-Rom there, and a different number of nodes along each direction
+    let conf = DataGenConf { x: linear(4, 0.0, 1.0), y: linear(9, -2.0, 2.0), z: linear(3, 0.0, 0.5) };
+    let g = |y: f64| (1.3*y).sin() + 0.2*y*y;
+    let nodes: Vec<f64> = (0..9).map(|j| g(-2.0 + 0.5*j as f64)).collect();
+    let catmull_rom = |p: [f64; 4], t: f64| {
+        0.5*(2.0*p[1] + (p[2] - p[0])*t + (2.0*p[0] - 5.0*p[1] + 4.0*p[2] - p[3])*t*t + (3.0*(p[1] - p[2]) + p[3] - p[0])*t*t*t)
+    };
+
+    // ghost value in front of the first node and behind the last one (outer, next to it)
+    let references: [(BoundaryCondition, f64, f64); 4] = [
+        (BoundaryCondition::Copy, nodes[0], nodes[8]),
+        (BoundaryCondition::Reflect { even: true }, nodes[1], nodes[7]),
+        (BoundaryCondition::Reflect { even: false }, -nodes[1], -nodes[7]),
+        (BoundaryCondition::LinearExtrapolate, 2.0*nodes[0] - nodes[1], 2.0*nodes[8] - nodes[7])
+    ];
+    for (boundary, lower, upper) in references {
+        let mut ip = Interp3D::default();
+        ip.set_boundary_condition_along(Dir::Y, boundary);
+        ip.generate_data(|_, y, _| g(y), &conf);
+
+        for &t in &[0.1, 0.5, 0.8] {
+            for &(x, z) in &[(0.0, 0.0), (0.3, 0.45), (1.0, 0.5)] {
+                let first = catmull_rom([lower, nodes[0], nodes[1], nodes[2]], t);
+                assert!((ip.interpolate(x, -2.0 + 0.5*t, z) - first).abs() < 1e-12, "{:?}", boundary);
+                let last = catmull_rom([nodes[6], nodes[7], nodes[8], upper], t);
+                assert!((ip.interpolate(x, 1.5 + 0.5*t, z) - last).abs() < 1e-12, "{:?}", boundary);
+            }
+        }
+    }
+}
+
+#[test]
+fn linear_extrapolation_reproduces_linear_data_up_to_the_edges() {
+    let conf = DataGenConf {
+        x: linear(5, 0.0, 1.0),
+        y: DataGenConfSingle { n: 9, min: 0.1, max: 3.0, spacing: GridSpacing::Exponential(2.0) },
+        z: DataGenConfSingle { n: 4, min: -1.0, max: 1.0, spacing: GridSpacing::Chebyshev }
+    };
+    let f = |x: f64, y: f64, z: f64| 1.0 + 2.0*x - 3.0*y + 0.5*z;
+    let mut ip = Interp3D::default();
+    ip.set_boundary_condition(BoundaryCondition::LinearExtrapolate);
+    ip.generate_data(f, &conf);
+
+    // the outermost cells and slightly beyond
+    for &x in &[-0.05, 0.02, 0.5, 0.97, 1.05] {
+        for &y in &[0.1, 0.12, 1.0, 2.95, 3.1] {
+            for &z in &[-1.02, -0.9, 0.0, 0.99] {
+                assert!((ip.interpolate(x, y, z) - f(x, y, z)).abs() < 1e-12, "at ({}, {}, {})", x, y, z);
+            }
+        }
+    }
+
+    // only along x with the default elsewhere
+    ip.set_boundary_condition(BoundaryCondition::Copy);
+    ip.set_boundary_condition_along(Dir::X, BoundaryCondition::LinearExtrapolate);
+    assert_eq!(ip.boundary_condition(Dir::Z), BoundaryCondition::Copy);
+    let (y, z) = (ip.grid_y()[4], ip.grid_z()[1]);
+    assert!((ip.interpolate(0.02, y, z) - f(0.02, y, z)).abs() < 1e-12);
+    assert!((ip.interpolate(0.5, 0.12, z) - f(0.5, 0.12, z)).abs() > 1e-6);
+}
+
+#[test]
+fn stats_count_non_finite_samples() {
+    let conf = DataGenConf { x: linear(4, 0.0, 3.0), y: linear(3, 0.0, 2.0), z: linear(2, 0.0, 1.0) };
+    let mut ip = Interp3D::from_config(|x, y, z| x + 10.0*y + 100.0*z, &conf);
+
+    let stats = ip.stats();
+    assert_eq!(stats.dims, (4, 3, 2));
+    assert_eq!((stats.min, stats.max, stats.mean), (0.0, 123.0, 1.5 + 10.0 + 50.0));
+    assert_eq!((stats.nan_count, stats.inf_count), (0, 0));
+    assert!(stats.memory >= 24*8);
+
+    ip.set_value(0, 0, 0, f64::NAN);
+    ip.set_value(3, 2, 1, f64::NEG_INFINITY);
+    let stats = ip.stats();
+    assert_eq!((stats.nan_count, stats.inf_count), (1, 1));
+    assert_eq!((stats.min, stats.max), (1.0, 122.0));
+    assert!(ip.to_string().contains("1 NaN, 1 inf"));
+
+    // the summaries never print the samples
+    assert!(format!("{:?}", ip).contains("dims: (4, 3, 2)"));
+    let empty = Interp3D::default();
+    assert!(empty.stats().mean.is_nan());
+    assert_eq!(empty.to_string(), "Interp3D (not set up)");
+
+    // f32 halves the memory of the samples
+    ip.set_storage(Storage::F32);
+    assert!(ip.stats().memory < stats.memory);
+}
+
+#[test]
+fn extrema_skip_non_finite_samples() {
+    let conf = DataGenConf { x: linear(4, 0.0, 3.0), y: linear(3, 0.0, 2.0), z: linear(3, 0.0, 2.0) };
+    let mut ip = Interp3D::from_config(|x, y, z| x - y + 0.5*z, &conf);
+    assert_eq!(ip.data_min(), (-2.0, (0, 2, 0)));
+    assert_eq!(ip.data_max(), (4.0, (3, 0, 2)));
+    assert_eq!(ip.data_mean(), 1.5 - 1.0 + 0.5);
+
+    // plant NaN at the corners of both extrema and a new extremum inside
+    ip.set_value(0, 2, 0, f64::NAN);
+    ip.set_value(3, 0, 2, f64::NAN);
+    ip.set_value(1, 1, 1, f64::INFINITY);
+    ip.set_value(2, 1, 1, -7.0);
+    let stats = ip.stats();
+    assert_eq!((stats.nan_count, stats.inf_count), (2, 1));
+    assert_eq!((stats.min, stats.min_at), (-7.0, (2, 1, 1)));
+    assert_eq!((stats.max, stats.max_at), (3.5, (3, 0, 1)));
+    assert_eq!(ip.data_min(), (stats.min, stats.min_at));
+    assert_eq!(ip.data_max(), (stats.max, stats.max_at));
+    assert!(ip.data_mean().is_finite());
+
+    // ties go to the first node in storage order
+    let flat = Interp3D::from_config(|_, _, _| 1.0, &conf);
+    assert_eq!((flat.data_min(), flat.data_max()), ((1.0, (0, 0, 0)), (1.0, (0, 0, 0))));
+    let empty = Interp3D::default();
+    assert!(empty.data_min().0.is_nan() && empty.data_max().0.is_nan() && empty.data_mean().is_nan());
+}
+
+#[test]
+fn smoothing_keeps_a_constant_field() {
+    let conf = DataGenConf { x: linear(6, 0.0, 1.0), y: linear(3, 0.0, 1.0), z: linear(9, -1.0, 1.0) };
+    let conditions = [BoundaryCondition::Copy, BoundaryCondition::Reflect { even: true }, BoundaryCondition::LinearExtrapolate];
+    for (condition, periodic) in conditions.iter().map(|&c| (c, false)).chain([(BoundaryCondition::Copy, true)]) {
+        let mut ip = Interp3D::default();
+        ip.set_boundary_condition(condition);
+        ip.set_periodic(Dir::Y, periodic);
+        ip.generate_data(|_, _, _| 2.5, &conf);
+
+        // the kernels reach beyond the 3 nodes along y
+        ip.smooth(SmoothingKernel::Box(2));
+        ip.smooth(SmoothingKernel::Gaussian { sigma_cells: 1.2, radius: 4 });
+        for node in ip.iter() {
+            assert!((node.value - 2.5).abs() < 1e-14, "{:?}", condition);
+        }
+        assert!((ip.interpolate(0.01, 0.99, -0.98) - 2.5).abs() < 1e-14);
+    }
+}
+
+#[test]
+fn smoothing_spreads_a_spike_with_the_kernel_weights() {
+    let conf = DataGenConf { x: linear(11, 0.0, 1.0), y: linear(11, 0.0, 1.0), z: linear(11, 0.0, 1.0) };
+    let spike = |x: f64, y: f64, z: f64| if (x, y, z) == (0.5, 0.5, 0.5) { 1.0 } else { 0.0 };
+    let (sigma, radius) = (0.8, 2);
+    let gauss: Vec<f64> = (-2..=2).map(|o: i32| (-0.5*(o as f64/sigma).powi(2)).exp()).collect();
+    let sum: f64 = gauss.iter().sum();
+    let weight = |o: isize| if o.abs() <= radius { gauss[(o + radius) as usize]/sum } else { 0.0 };
+
+    let mut ip = Interp3D::from_config(spike, &conf);
+    ip.generate_data_hermite(|x, y, z| (spike(x, y, z), [0.0; 3]), &conf);
+    ip.smooth(SmoothingKernel::Gaussian { sigma_cells: sigma, radius: radius as usize });
+    assert!(!ip.has_hermite_data());
+    for GridNode { i, j, k, value, .. } in ip.iter() {
+        let expected = weight(i as isize - 5)*weight(j as isize - 5)*weight(k as isize - 5);
+        assert!((value - expected).abs() < 1e-15, "at ({}, {}, {}): {} vs {}", i, j, k, value, expected);
+    }
+
+    // only along z
+    let mut ip = Interp3D::from_config(spike, &conf);
+    ip.smooth_along(Dir::Z, SmoothingKernel::Box(1));
+    assert!((ip.value_at(5, 5, 4) - 1.0/3.0).abs() < 1e-15);
+    assert_eq!(ip.value_at(4, 5, 5), 0.0);
+    assert_eq!(ip.value_at(5, 5, 3), 0.0);
+    // the ghost cells were refreshed
+    assert!((ip.interpolate(0.5, 0.5, 0.4) - 1.0/3.0).abs() < 1e-15);
+}
+
+#[test]
+fn derive_leaves_the_original_unchanged() {
+    let conf = DataGenConf { x: linear(4, 0.5, 2.0), y: linear(5, -1.0, 1.0), z: linear(3, 0.0, 1.0) };
+    let mut ip = Interp3D::default();
+    ip.set_boundary_condition(BoundaryCondition::LinearExtrapolate);
+    ip.generate_data(gaussian, &conf);
+
+    let r2 = |x: f64, y: f64, z: f64| x*x + y*y + z*z;
+    let scaled = ip.derive(|x, y, z, v| v*r2(x, y, z));
+    assert!(scaled.same_grid(&ip, 0.0));
+    assert_eq!(scaled.grid_y(), ip.grid_y());
+    assert_eq!(scaled.boundary_condition(Dir::Z), BoundaryCondition::LinearExtrapolate);
+    for node in scaled.iter() {
+        assert_eq!(node.value, gaussian(node.x, node.y, node.z)*r2(node.x, node.y, node.z));
+        assert_eq!(ip.value_at(node.i, node.j, node.k), gaussian(node.x, node.y, node.z));
+    }
+
+    // non-finite results are kept and counted
+    let inverse = ip.derive(|_, y, _, v| if y == 0.0 { f64::NAN } else { 1.0/v });
+    let stats = inverse.stats();
+    assert_eq!(stats.nan_count, 4*3);
+    assert!(inverse.to_string().contains("12 NaN"));
+}
+
+#[test]
+fn clones_share_their_storage_until_one_changes() {
+    let conf = DataGenConf { x: linear(6, 0.0, 1.0), y: linear(5, -1.0, 1.0), z: linear(7, 0.0, 2.0) };
+    let mut ip = Interp3D::from_config(gaussian, &conf);
+    let points = [(0.13, -0.4, 1.7), (0.5, 0.0, 0.0), (0.99, 0.77, 0.31)];
+    let before: Vec<f64> = points.iter().map(|&(x, y, z)| ip.interpolate(x, y, z)).collect();
+
+    let clone = ip.clone();
+    assert!(std::ptr::eq(&ip[(0, 0, 0)], &clone[(0, 0, 0)]));
+    assert_eq!(ip.grid_x().as_ptr(), clone.grid_x().as_ptr());
+
+    ip.set_value(2, 2, 2, 5.0);
+    assert!(!std::ptr::eq(&ip[(0, 0, 0)], &clone[(0, 0, 0)]));
+    assert_eq!(ip.grid_x().as_ptr(), clone.grid_x().as_ptr());
+    assert_eq!(clone.value_at(2, 2, 2), gaussian(0.4, 0.0, 2.0/3.0));
+
+    // the clone still interpolates the original samples, and the original its own
+    for (&(x, y, z), &value) in points.iter().zip(&before) {
+        assert_eq!(clone.interpolate(x, y, z).to_bits(), value.to_bits());
+    }
+    assert_ne!(ip.interpolate(0.4, 0.0, 2.0/3.0), clone.interpolate(0.4, 0.0, 2.0/3.0));
+
+    ip.remap_domain(Dir::X, 10.0, 20.0);
+    assert_ne!(ip.grid_x().as_ptr(), clone.grid_x().as_ptr());
+    assert_eq!(clone.grid_x()[5], 1.0);
+}
+
+#[test]
+fn regenerate_data_keeps_the_imported_grid() {
+    let mut ip = Interp3D::from_file("tests/fixtures/tiny.npz");
+    let axes = (ip.grid_x().to_vec(), ip.grid_y().to_vec(), ip.grid_z().to_vec());
+
+    let g = |x: f64, y: f64, z: f64| 3.0*x - y + 0.5*z;
+    ip.regenerate_data(g);
+    assert_eq!((ip.grid_x().to_vec(), ip.grid_y().to_vec(), ip.grid_z().to_vec()), axes);
+    assert_eq!(ip.dims(), (2, 3, 4));
+    for node in ip.iter() {
+        assert_eq!(node.value, g(node.x, node.y, node.z));
+    }
+    // the ghost cells were refreshed, so it interpolates like a table built from the new values
+    let values: Vec<f64> = ip.iter().map(|node| node.value).collect();
+    let built = Interp3D::from_grid_and_data(&axes.0, &axes.1, &axes.2, &values).unwrap();
+    assert_eq!(ip.interpolate(0.9, 0.95, 1.9), built.interpolate(0.9, 0.95, 1.9));
+
+    // the closure gets the physical coordinates of transformed axes
+    let conf = DataGenConf { x: linear(4, 0.0, 2.0), y: linear(3, 0.0, 1.0), z: linear(3, 0.0, 1.0) };
+    let mut ip = Interp3D::default();
+    ip.set_axis_transform(Dir::X, AxisTransform::Log10);
+    ip.generate_data_hermite(|x, _, _| (x, [1.0, 0.0, 0.0]), &conf);
+    ip.regenerate_data(|x, _, _| x.log10());
+    assert!(!ip.has_hermite_data());
+    assert!((ip.value_at(3, 0, 0) - 2.0).abs() < 1e-15);
+    assert_eq!(ip.config(), Some(&conf));
+}
+
+#[test]
+fn regenerate_region_only_touches_the_box() {
+    let conf = DataGenConf { x: linear(9, 0.0, 4.0), y: linear(7, -1.0, 2.0), z: linear(8, 0.0, 7.0) };
+    let mut ip = Interp3D::default();
+    ip.set_boundary_condition(BoundaryCondition::LinearExtrapolate);
+    ip.generate_data(gaussian, &conf);
+    let before = ip.clone();
+
+    // the box reaches beyond the domain along Y, and its boundaries along X and Z lie exactly on nodes
+    let g = |x: f64, y: f64, z: f64| x*y - z;
+    let mut calls = 0;
+    ip.regenerate_region(|x, y, z| { calls += 1; g(x, y, z) }, (1.0, 2.5), (-3.0, 0.0), (2.0, 3.0));
+    assert_eq!(calls, 4*3*2);
+    let inside = |x: f64, y: f64, z: f64| (1.0..=2.5).contains(&x) && y <= 0.0 && (2.0..=3.0).contains(&z);
+    for (node, old) in ip.iter().zip(before.iter()) {
+        if inside(node.x, node.y, node.z) {
+            assert_eq!(node.value, g(node.x, node.y, node.z));
+        } else {
+            assert_eq!(node.value.to_bits(), old.value.to_bits(), "at ({}, {}, {})", node.x, node.y, node.z);
+        }
+    }
+
+    // the ghost cells follow, so the result interpolates like a fresh table with the same samples
+    let values: Vec<f64> = ip.iter().map(|node| node.value).collect();
+    let mut built = Interp3D::from_grid_and_data(ip.grid_x(), ip.grid_y(), ip.grid_z(), &values).unwrap();
+    built.set_boundary_condition(BoundaryCondition::LinearExtrapolate);
+    for &(x, y, z) in &[(1.7, -0.9, 2.5), (0.1, -1.0, 0.2), (3.9, 1.9, 6.9), (2.0, -0.99, 2.1)] {
+        assert_eq!(ip.interpolate(x, y, z).to_bits(), built.interpolate(x, y, z).to_bits());
+    }
+
+    // a box between the nodes changes nothing
+    ip.regenerate_region(|_, _, _| panic!("no node is inside"), (1.1, 1.2), (-1.0, 2.0), (0.0, 7.0));
+}
+
+#[test]
+fn regenerate_region_takes_the_box_in_grid_coordinates() {
+    // x from 0.1 to 1000, one node per decade
+    let conf = DataGenConf { x: linear(5, -1.0, 3.0), y: linear(3, 0.0, 1.0), z: linear(3, 0.0, 1.0) };
+    let mut ip = Interp3D::default();
+    ip.set_axis_transform(Dir::X, AxisTransform::Log10);
+    ip.generate_data(|_, _, _| 0.0, &conf);
+
+    // like the box of crop(), while the closure gets physical coordinates
+    ip.regenerate_region(|x, _, _| x, (0.0, 2.0), (0.0, 1.0), (0.0, 0.0));
+    let regenerated: Vec<usize> = (0..5).filter(|&i| ip.value_at(i, 1, 0) != 0.0).collect();
+    assert_eq!(regenerated, [1, 2, 3]);
+    for i in regenerated {
+        let x = 10f64.powi(i as i32 - 1);
+        assert!((ip.value_at(i, 1, 0) - x).abs() < 1e-12*x);
+    }
+}
+
+#[test]
+fn prepared_grid_filled_from_a_slice_matches_generate_data() {
+    let config = DataGenConf {
+        x: linear(5, -1.0, 1.0),
+        y: DataGenConfSingle { n: 7, min: 0.5, max: 4.0, spacing: GridSpacing::Exponential(2.0) },
+        z: linear(4, 0.0, 3.0)
+    };
+    let expected = Interp3D::from_config(gaussian, &config);
+
+    let mut ip = Interp3D::default();
+    ip.prepare_grid(&config).unwrap();
+    let (nx, ny, nz) = ip.dims();
+    assert_eq!(ip.grid_points().len(), nx*ny*nz);
+    for (n, (i, j, k, x, y, z)) in ip.grid_points().enumerate() {
+        assert_eq!(n, (i*ny + j)*nz + k);
+        assert_eq!((x, y, z), (ip.grid_x()[i], ip.grid_y()[j], ip.grid_z()[k]));
+    }
+
+    // evaluated in one batch, like on another device
+    let points: Vec<(f64, f64, f64)> = ip.grid_points().map(|(_, _, _, x, y, z)| (x, y, z)).collect();
+    let values: Vec<f64> = points.iter().map(|&(x, y, z)| gaussian(x, y, z)).collect();
+    ip.fill_from_slice(&values).unwrap();
+
+    assert_eq!(ip.config(), expected.config());
+    for (a, b) in ip.iter().zip(expected.iter()) {
+        assert_eq!(a, b);
+    }
+    for &(x, y, z) in &[(-1.2, 0.4, 0.1), (0.3, 2.2, 1.7), (0.99, 3.9, 2.9)] {
+        assert_eq!(ip.interpolate(x, y, z).to_bits(), expected.interpolate(x, y, z).to_bits());
+    }
+}
+
+#[test]
+fn fill_from_slice_checks_the_number_of_values() {
+    let mut ip = Interp3D::default();
+    assert!(matches!(ip.fill_from_slice(&[1.0; 8]), Err(Interp3DError::NotSetUp)));
+
+    let config = DataGenConf { x: linear(2, 0.0, 1.0), y: linear(2, 0.0, 1.0), z: linear(3, 0.0, 1.0) };
+    ip.prepare_grid(&config).unwrap();
+    ip.fill_from_slice(&[1.0; 12]).unwrap();
+    match ip.fill_from_slice(&[2.0; 11]) {
+        Err(Interp3DError::DataLength { expected, found }) => assert_eq!((expected, found), (12, 11)),
+        other => panic!("expected DataLength, got {:?}", other)
+    }
+    assert_eq!(ip.interpolate(0.5, 0.5, 0.5), 1.0);
+
+    assert!(ip.prepare_grid(&DataGenConf { x: linear(1, 0.0, 1.0), ..config }).is_err());
+    assert_eq!(ip.dims(), (2, 2, 3));
+}
@@ -0,0 +1,315 @@
+use interp3d::*;
+
+fn single(n: usize) -> DataGenConfSingle {
+    DataGenConfSingle {
+        n,
+        min: 0.0,
+        max: 1.0,
+        spacing: GridSpacing::Linear
+    }
+}
+
+#[test]
+fn minimal_grid_padding_stays_in_range() {
+    // every combination of the minimal axis with a slightly larger one, so that the clamps of differently sized axes can't get mixed up
+    for &(nx, ny, nz) in &[(2, 2, 2), (2, 3, 4), (4, 2, 3), (3, 4, 2), (2, 2, 5), (5, 2, 2)] {
+        let conf = DataGenConf { x: single(nx), y: single(ny), z: single(nz) };
+
+        let mut calls = 0;
+        let _ip = Interp3D::from_config(|x, y, z| { calls += 1; x + 10.0*y + 100.0*z }, &conf);
+
+        // the ghost cells themselves are checked against a reference fill by the unit tests in src/lib.rs
+        assert_eq!(calls, nx*ny*nz);
+    }
+}
+
+#[test]
+fn nodes_run_from_min_to_max() {
+    // regression test of the node placement t = i/(n - 1), the former i/(n - 4) overshot max and broke n <= 4
+    for n in 2..=11 {
+        let axis = |spacing| DataGenConfSingle { n, min: -1.0, max: 3.0, spacing };
+        let conf = DataGenConf { x: axis(GridSpacing::Linear), y: axis(GridSpacing::Exponential(4.0)), z: axis(GridSpacing::Chebyshev) };
+        let ip = Interp3D::from_config(|x, y, z| x + y + z, &conf);
+
+        let expected: Vec<f64> = (0..n).map(|i| -1.0 + 4.0*i as f64/(n - 1) as f64).collect();
+        assert_eq!(ip.grid_x(), &expected[..]);
+        for v in [ip.grid_y(), ip.grid_z()] {
+            assert_eq!(v.len(), n);
+            assert_eq!((v[0], v[n-1]), (-1.0, 3.0));
+            assert!(v.windows(2).all(|w| w[0] < w[1]));
+        }
+        assert_eq!(ip.domain(), ((-1.0, 3.0), (-1.0, 3.0), (-1.0, 3.0)));
+    }
+}
+
+#[test]
+fn large_exponential_parameter_gives_increasing_nodes() {
+    // the dense end sits at 0 such that the tiny spacings there are representable
+    for &(k, min, max) in &[(700.0, 0.0, 15.0), (1000.0, 0.0, 15.0), (-700.0, -15.0, 0.0), (-1000.0, -15.0, 0.0)] {
+        let axis = DataGenConfSingle { n: 50, min, max, spacing: GridSpacing::Exponential(k) };
+        let conf = DataGenConf { x: axis, y: single(5), z: single(5) };
+        let ip = Interp3D::from_config(|x, _, _| x, &conf);
+
+        let mut xs: Vec<f64> = ip.iter_points().map(|p| p.0).collect();
+        xs.dedup();
+        assert_eq!(xs.len(), 50);
+        assert!(xs.iter().all(|x| x.is_finite()));
+        assert!(xs.windows(2).all(|w| w[0] < w[1]));
+    }
+}
+
+#[test]
+#[should_panic(expected = "not finite and strictly increasing")]
+fn collapsing_exponential_grid_is_rejected() {
+    let axis = DataGenConfSingle { n: 50, min: 1.0, max: 15.0, spacing: GridSpacing::Exponential(5000.0) };
+    let conf = DataGenConf { x: axis, y: single(5), z: single(5) };
+    Interp3D::from_config(|x, _, _| x, &conf);
+}
+
+#[test]
+fn progress_reports_every_column_up_to_the_total() {
+    let conf = DataGenConf { x: single(5), y: single(6), z: single(7) };
+    let mut reports = Vec::new();
+    let mut calls = 0;
+
+    Interp3D::default().generate_data_with_progress(|_, _, _| { calls += 1; 0.0 }, &conf, |done, total| reports.push((done, total)));
+
+    assert_eq!(reports.len(), 5*6);
+    assert!(reports.iter().all(|&(_, total)| total == 5*6*7));
+    assert!(reports.windows(2).all(|w| w[1].0 == w[0].0 + 7));
+    assert_eq!(reports.last(), Some(&(5*6*7, 5*6*7)));
+    assert_eq!(calls, 5*6*7);
+}
+
+#[test]
+fn custom_axis_is_used_as_is() {
+    // dense around a resonance at 3.2
+    let nodes = vec![0.0, 1.0, 2.0, 2.8, 3.0, 3.1, 3.15, 3.2, 3.25, 3.3, 3.4, 3.6, 4.5, 6.0];
+    let conf = DataGenConfBuilder::default()
+        .x_nodes(nodes.clone())
+        .y(0.0, 1.0, 4, GridSpacing::Linear)
+        .z(0.0, 1.0, 3, GridSpacing::Linear)
+        .build();
+    let f = |x: f64, y: f64, z: f64| 1.0/((x - 3.2).powi(2) + 0.01) + y + z;
+    let ip = Interp3D::from_config(f, &conf);
+
+    let mut xs: Vec<f64> = ip.iter_points().map(|p| p.0).collect();
+    xs.dedup();
+    assert_eq!(xs, nodes);
+    assert_eq!(ip.interpolate(3.2, 0.5, 0.5), f(3.2, 0.5, 0.5));
+    assert_eq!(ip.config(), Some(&conf));
+}
+
+#[test]
+fn invalid_custom_axes_are_rejected() {
+    let with_x = |x: DataGenConfSingle| DataGenConf { x, y: single(3), z: single(3) };
+    let try_x = |x| Interp3D::try_from_config(|_, _, _| 0.0, &with_x(x)).map(|_| ());
+
+    assert!(matches!(try_x(DataGenConfSingle::custom(vec![1.0])), Err(Interp3DError::TooFewPoints { dir: Dir::X, n: 1 })));
+    assert!(matches!(try_x(DataGenConfSingle::custom(vec![])), Err(Interp3DError::TooFewPoints { dir: Dir::X, n: 0 })));
+    assert!(matches!(try_x(DataGenConfSingle::custom(vec![0.0, 2.0, 1.0])), Err(Interp3DError::NonMonotonicAxis { dir: Dir::X, index: 1 })));
+    assert!(matches!(try_x(DataGenConfSingle::custom(vec![0.0, 1.0, 1.0, 2.0])), Err(Interp3DError::NonMonotonicAxis { dir: Dir::X, index: 1 })));
+
+    let mut inconsistent = DataGenConfSingle::custom(vec![0.0, 1.0, 2.0]);
+    inconsistent.max = 3.0;
+    assert!(matches!(try_x(inconsistent), Err(Interp3DError::CustomAxisMismatch { dir: Dir::X })));
+    let mut inconsistent = DataGenConfSingle::custom(vec![0.0, 1.0, 2.0]);
+    inconsistent.n = 5;
+    assert!(matches!(try_x(inconsistent), Err(Interp3DError::CustomAxisMismatch { dir: Dir::X })));
+}
+
+#[test]
+fn logarithmic_axis_has_constant_ratio() {
+    let conf = DataGenConf {
+        x: single(3),
+        y: single(3),
+        z: DataGenConfSingle { n: 121, min: 1e-3, max: 1e3, spacing: GridSpacing::Logarithmic }
+    };
+    let f = |_: f64, _: f64, z: f64| (2.0*z.ln()).sin();
+    let ip = Interp3D::from_config(f, &conf);
+
+    let mut zs: Vec<f64> = ip.iter_points().map(|p| p.2).collect();
+    zs.truncate(121);
+    assert!((zs[0] - 1e-3).abs() < 1e-15 && (zs[120] - 1e3).abs() < 1e-9);
+    for w in zs.windows(2) {
+        assert!((w[1]/w[0] - 10f64.powf(0.05)).abs() < 1e-12);
+    }
+
+    // every decade is resolved equally well, away from the boundary cells
+    let max_error = |lo: f64| (0..=100)
+        .map(|i| 2.0*lo*(4f64).powf(i as f64/100.0))
+        .map(|z| (ip.interpolate(0.5, 0.5, z) - f(0.5, 0.5, z)).abs())
+        .fold(0.0, f64::max);
+    let (first, last) = (max_error(1e-3), max_error(1e2));
+    assert!(first < 1e-3 && last < 1e-3);
+    assert!(first < 3.0*last && last < 3.0*first);
+}
+
+#[test]
+fn logarithmic_axis_needs_a_range_of_one_sign() {
+    for &(min, max) in &[(0.0, 1.0), (-1.0, 1.0), (-1.0, 0.0)] {
+        let conf = DataGenConf { x: DataGenConfSingle { n: 5, min, max, spacing: GridSpacing::Logarithmic }, y: single(3), z: single(3) };
+        assert!(matches!(Interp3D::try_from_config(|_, _, _| 0.0, &conf), Err(Interp3DError::InvalidRange { dir: Dir::X, .. })));
+    }
+
+    let conf = DataGenConf { x: DataGenConfSingle { n: 5, min: -100.0, max: -0.01, spacing: GridSpacing::Logarithmic }, y: single(3), z: single(3) };
+    assert!(Interp3D::try_from_config(|x, _, _| x, &conf).is_ok());
+}
+
+#[test]
+fn chebyshev_axis_beats_linear_for_runge() {
+    let f = |x: f64, _: f64, _: f64| 1.0/(1.0 + x*x);
+    let max_error = |spacing: GridSpacing| {
+        let conf = DataGenConf { x: DataGenConfSingle { n: 15, min: 0.0, max: 5.0, spacing }, y: single(3), z: single(3) };
+        let ip = Interp3D::from_config(f, &conf);
+
+        let mut xs: Vec<f64> = ip.iter_points().map(|p| p.0).collect();
+        xs.dedup();
+        assert_eq!(xs.len(), 15);
+        assert!(xs.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!((xs[0], xs[14]), (0.0, 5.0));
+
+        (0..=1000).map(|i| 0.005*i as f64)
+            .map(|x| (ip.interpolate(x, 0.5, 0.5) - f(x, 0.5, 0.5)).abs())
+            .fold(0.0, f64::max)
+    };
+
+    let (chebyshev, linear) = (max_error(GridSpacing::Chebyshev), max_error(GridSpacing::Linear));
+    assert!(chebyshev < linear);
+}
+
+#[test]
+fn two_sided_exponential_axis_is_symmetric_and_increasing() {
+    let nodes = |n: usize, spacing: GridSpacing| {
+        let conf = DataGenConf { x: DataGenConfSingle { n, min: -1.0, max: 1.0, spacing }, y: single(3), z: single(3) };
+        let ip = Interp3D::from_config(|x, _, _| x, &conf);
+        let mut xs: Vec<f64> = ip.iter_points().map(|p| p.0).collect();
+        xs.dedup();
+        xs
+    };
+
+    for &n in &[2, 3, 10, 51] {
+        for &k in &[0.5, 8.0, 40.0, -0.5, -8.0, -40.0] {
+            let xs = nodes(n, GridSpacing::TwoSidedExponential(k));
+            assert_eq!(xs.len(), n);
+            assert!((xs[0] + 1.0).abs() < 1e-15 && (xs[n-1] - 1.0).abs() < 1e-15);
+            assert!(xs.windows(2).all(|w| w[0] < w[1]));
+            for i in 0..n {
+                assert!((xs[i] + xs[n-1-i]).abs() < 1e-14);
+            }
+            if n > 3 {
+                // dense at the ends for k > 0, in the middle otherwise
+                let (outer, inner) = (xs[1] - xs[0], xs[n/2] - xs[n/2-1]);
+                assert_eq!(outer < inner, k > 0.0);
+            }
+        }
+
+        assert_eq!(nodes(n, GridSpacing::TwoSidedExponential(0.0)), nodes(n, GridSpacing::Linear));
+    }
+}
+
+#[test]
+fn node_positions_of_a_small_grid() {
+    let axis = |spacing: GridSpacing| {
+        let conf = DataGenConf { x: DataGenConfSingle { n: 5, min: 0.0, max: 1.0, spacing }, y: single(3), z: single(3) };
+        Interp3D::from_config(|_, _, _| 0.0, &conf).grid_x().to_vec()
+    };
+
+    // every node is at (i/(n - 1)) of the spacing function, both ends included
+    assert_eq!(axis(GridSpacing::Linear), vec![0.0, 0.25, 0.5, 0.75, 1.0]);
+    let sqrt2 = 2f64.sqrt();
+    let expected = [0.0, (sqrt2 - 1.0)/3.0, 1.0/3.0, (2.0*sqrt2 - 1.0)/3.0, 1.0];
+    for (p, e) in axis(GridSpacing::Exponential(2.0)).into_iter().zip(expected) {
+        assert!((p - e).abs() < 1e-15, "{} vs {}", p, e);
+    }
+    let s = (std::f64::consts::PI/8.0).sin().powi(2);
+    for (p, e) in axis(GridSpacing::Chebyshev).into_iter().zip([0.0, s, 0.5, 1.0 - s, 1.0]) {
+        assert!((p - e).abs() < 1e-15, "{} vs {}", p, e);
+    }
+}
+
+#[test]
+fn iter_yields_the_nodes_in_storage_order() {
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 1.0, 3, GridSpacing::Linear)
+        .y(-1.0, 1.0, 4, GridSpacing::Exponential(2.0))
+        .z(1.0, 10.0, 2, GridSpacing::Logarithmic)
+        .build();
+    let mut ip = Interp3D::from_config(|x, y, z| x + 10.0*y + 100.0*z, &conf);
+
+    let nodes = ip.iter();
+    assert_eq!(nodes.len(), 3*4*2);
+    let nodes: Vec<GridNode> = nodes.collect();
+    assert_eq!(nodes.len(), 24);
+
+    let mut expected = Vec::new();
+    for i in 0..3 {
+        for j in 0..4 {
+            for k in 0..2 {
+                expected.push((i, j, k));
+            }
+        }
+    }
+    assert_eq!(nodes.iter().map(|n| (n.i, n.j, n.k)).collect::<Vec<_>>(), expected);
+    for n in &nodes {
+        assert_eq!((n.x, n.y, n.z), (ip.grid_x()[n.i], ip.grid_y()[n.j], ip.grid_z()[n.k]));
+        assert_eq!(n.value, ip.value_at(n.i, n.j, n.k));
+    }
+
+    let mut rest = ip.iter().skip(20);
+    assert_eq!(rest.len(), 4);
+    rest.next();
+    assert_eq!(rest.len(), 3);
+    drop(rest);
+    assert_eq!(Interp3D::default().iter().len(), 0);
+
+    // for_each_mut sees the same nodes and refreshes the ghost cells
+    let mut seen = Vec::new();
+    ip.for_each_mut(|node, value| {
+        seen.push(node);
+        *value = node.i as f64;
+    });
+    assert_eq!(seen, nodes);
+    assert_eq!(ip.interpolate(1.0, 1.0, 10.0), 2.0);
+    assert!((ip.interpolate(0.5, 0.3, 5.0) - 1.0).abs() < 1e-14);
+}
+
+#[test]
+fn domain_is_the_interior_range() {
+    for spacing in [GridSpacing::Linear, GridSpacing::Exponential(3.0)] {
+        let conf = DataGenConfBuilder::default()
+            .x(-2.0, 5.0, 7, spacing.clone())
+            .y(0.0, 1.0, 2, spacing.clone())
+            .z(1.0, 4.0, 12, spacing)
+            .build();
+        let f = |x: f64, y: f64, z: f64| x*y + z;
+        let mut ip = Interp3D::from_config(f, &conf);
+        ip.set_out_of_bounds(OutOfBounds::Error);
+
+        let domain = ip.domain();
+        assert_eq!(domain, ((-2.0, 5.0), (0.0, 1.0), (1.0, 4.0)));
+        let (gx, gz) = (ip.grid_x(), ip.grid_z());
+        assert_eq!(domain.0, (gx[0], gx[gx.len()-1]));
+        assert_eq!(domain.2, (gz[0], gz[gz.len()-1]));
+
+        // the boundary is contained, the next representable number outside is not
+        for &(x, y, z) in &[(-2.0, 0.0, 1.0), (5.0, 1.0, 4.0), (5.0, 0.5, 1.0)] {
+            assert!(ip.contains(x, y, z));
+            assert!(ip.try_interpolate(x, y, z).is_ok());
+        }
+        let above = |v: f64| f64::from_bits(v.to_bits() + 1);
+        for &(x, y, z) in &[(above(5.0), 0.5, 2.0), (0.0, -f64::from_bits(1), 2.0), (0.0, 0.5, above(4.0)), (f64::NAN, 0.5, 2.0)] {
+            assert!(!ip.contains(x, y, z));
+            assert!(ip.try_interpolate(x, y, z).is_err());
+        }
+
+        // clamping by hand gives the same values as the clamping policy
+        let mut clamping = Interp3D::from_config(f, &conf);
+        clamping.set_out_of_bounds(OutOfBounds::ClampToEdge);
+        for &(x, y, z) in &[(-3.0, 0.5, 2.0), (6.0, 2.0, 0.0), (1.0, 0.25, 3.0)] {
+            let (cx, cy, cz) = ip.clamp_to_domain(x, y, z);
+            assert!(ip.contains(cx, cy, cz));
+            assert_eq!(ip.interpolate(cx, cy, cz), clamping.interpolate(x, y, z));
+        }
+    }
+}
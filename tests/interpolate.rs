@@ -0,0 +1,1459 @@
+mod common;
+
+use interp3d::*;
+
+fn config() -> DataGenConf {
+    DataGenConfBuilder::default()
+        .x(0.0, 2.0, 9, GridSpacing::Linear)
+        .y(-1.0, 3.0, 12, GridSpacing::Exponential(3.0))
+        .z(0.5, 1.5, 7, GridSpacing::Exponential(-2.0))
+        .build()
+}
+
+#[test]
+fn nodes_are_reproduced_exactly() {
+    let f = |x: f64, y: f64, z: f64| (x*y).sin() + z.exp();
+    let ip = Interp3D::from_config(f, &config());
+
+    for (x, y, z, v) in ip.iter_points() {
+        assert_eq!(ip.interpolate(x, y, z), v);
+    }
+}
+
+#[test]
+fn quadratics_are_reproduced_away_from_the_boundary() {
+    let f = |x: f64, y: f64, z: f64| x*x*y - 2.0*y*z + z*z + 3.0;
+    let ip = Interp3D::from_config(f, &config());
+
+    // stay out of the outermost cell along each direction, where the ghost cells enter the stencil
+    for &(x, y, z) in &[(0.61, 0.13, 0.97), (1.3, 1.9, 1.1), (0.8, -0.1, 0.8), (1.52, 2.2, 1.2)] {
+        assert!((ip.interpolate(x, y, z) - f(x, y, z)).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn smooth_function_off_grid() {
+    let f = |x: f64, y: f64, z: f64| ((-x*x - y*y - z*z)/5.0).exp();
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 5.0, 40, GridSpacing::Exponential(1.0))
+        .y(0.0, 5.0, 40, GridSpacing::Linear)
+        .z(0.0, 5.0, 40, GridSpacing::Linear)
+        .build();
+    let ip = Interp3D::from_config(f, &conf);
+
+    for i in 0..50 {
+        let t = i as f64/49.0;
+        let (x, y, z) = (5.0*t, 5.0*(1.0 - t), 2.5 + 2.5*(7.0*t).sin());
+        assert!((ip.interpolate(x, y, z) - f(x, y, z)).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn from_grid_and_data_on_a_hand_built_grid() {
+    let x = [0.0, 0.5, 1.5, 2.0, 3.5];
+    let y = [-2.0, -1.0, 0.0, 0.25, 1.0];
+    let z = [1.0, 2.0, 4.0, 8.0, 16.0];
+    let f = |x: f64, y: f64, z: f64| 2.0*x*x - x*y + 0.5*z + 1.0;
+
+    let mut data = Vec::new();
+    for &x in &x {
+        for &y in &y {
+            for &z in &z {
+                data.push(f(x, y, z));
+            }
+        }
+    }
+    let ip = Interp3D::from_grid_and_data(&x, &y, &z, &data).unwrap();
+
+    assert_eq!(ip.interpolate(1.5, 0.25, 4.0), f(1.5, 0.25, 4.0));
+    // the middle cells along each axis only use actual nodes, so the quadratic is reproduced there
+    for &(px, py, pz) in &[(0.7, -0.5, 3.0), (1.9, 0.1, 5.5), (1.0, -0.9, 2.1)] {
+        assert!((ip.interpolate(px, py, pz) - f(px, py, pz)).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn from_grid_and_data_rejects_bad_input() {
+    let axis = [0.0, 1.0, 2.0];
+    let data = vec![0.0; 27];
+
+    match Interp3D::from_grid_and_data(&axis, &axis, &axis, &data[1..]) {
+        Err(Interp3DError::DataLength { expected: 27, found: 26 }) => (),
+        other => panic!("unexpected {:?}", other.map(|_| ()))
+    }
+    match Interp3D::from_grid_and_data(&axis, &[0.0, 2.0, 1.0], &axis, &data) {
+        Err(Interp3DError::NonMonotonicAxis { dir: Dir::Y, index: 1 }) => (),
+        other => panic!("unexpected {:?}", other.map(|_| ()))
+    }
+    match Interp3D::from_grid_and_data(&axis, &axis, &[0.0, 1.0, 1.0], &data) {
+        Err(Interp3DError::NonMonotonicAxis { dir: Dir::Z, index: 1 }) => (),
+        other => panic!("unexpected {:?}", other.map(|_| ()))
+    }
+}
+
+/// Samples of ``f`` on the grid ``x`` x ``y`` x ``z`` in storage order
+fn tabulate(f: impl Fn(f64, f64, f64) -> f64, x: &[f64], y: &[f64], z: &[f64]) -> Vec<f64> {
+    x.iter().flat_map(|&x| y.iter().flat_map(move |&y| z.iter().map(move |&z| (x, y, z)))).map(|(x, y, z)| f(x, y, z)).collect()
+}
+
+#[test]
+fn lenient_grid_sorts_descending_axes() {
+    let f = |x: f64, y: f64, z: f64| (x*y).sin() + z*z;
+    let (x, y, z) = ([0.0, 0.5, 1.5, 2.0], [-1.0, 0.0, 2.0], [0.0, 1.0, 3.0]);
+    let reference = Interp3D::from_grid_and_data(&x, &y, &z, &tabulate(f, &x, &y, &z)).unwrap();
+
+    let (descending_x, descending_z) = ([2.0, 1.5, 0.5, 0.0], [3.0, 1.0, 0.0]);
+    let data = tabulate(f, &descending_x, &y, &descending_z);
+    assert!(matches!(Interp3D::from_grid_and_data(&descending_x, &y, &descending_z, &data), Err(Interp3DError::NonMonotonicAxis { dir: Dir::X, index: 0 })));
+
+    let (ip, report) = Interp3D::from_grid_and_data_lenient(&descending_x, &y, &descending_z, &data, 0.0).unwrap();
+    assert_eq!(report, SanitizeReport { reordered: [true, false, true], merged: [0; 3] });
+    assert_eq!(ip.grid_x(), &x);
+    assert_eq!(ip.grid_z(), &z);
+    for &(x, y, z) in &[(0.1, -0.7, 0.2), (1.2, 1.5, 2.5), (1.9, 0.1, 0.9)] {
+        assert_eq!(ip.interpolate(x, y, z), reference.interpolate(x, y, z));
+    }
+
+    // nothing to do for clean axes
+    let (_, report) = Interp3D::from_grid_and_data_lenient(&x, &y, &z, &tabulate(f, &x, &y, &z), 1e-12).unwrap();
+    assert_eq!(report, SanitizeReport::default());
+}
+
+#[test]
+fn lenient_grid_merges_duplicated_planes() {
+    let (x, y, z) = ([0.0, 1.0, 2.0], [0.0, 1.0], [0.0, 0.5, 1.0]);
+    // y = 1 appears twice with different data (+1 and +3, averaging to +2) and z = 0.5 once more a hair off
+    let (merged_y, merged_z) = ([1.0, 0.0, 1.0], [0.0, 0.5, 0.5 + 1e-12, 1.0]);
+    let f = |x: f64, y: f64, z: f64| x + 10.0*y + 100.0*z;
+    let mut data = Vec::new();
+    for &xi in &x {
+        for (j, &yj) in merged_y.iter().enumerate() {
+            for &zk in &merged_z {
+                let offset = if yj == 1.0 { if j == 0 { 1.0 } else { 3.0 } } else { 0.0 };
+                data.push(f(xi, yj, zk) + offset);
+            }
+        }
+    }
+    let reference = Interp3D::from_grid_and_data(&x, &y, &z, &tabulate(|x, y, z| f(x, y, z) + 2.0*y, &x, &y, &z)).unwrap();
+
+    let (ip, report) = Interp3D::from_grid_and_data_lenient(&x, &merged_y, &merged_z, &data, 1e-9).unwrap();
+    assert_eq!(report, SanitizeReport { reordered: [false, true, false], merged: [0, 1, 1] });
+    assert_eq!(ip.dims(), (3, 2, 3));
+    assert!((ip.grid_z()[1] - 0.5).abs() < 1e-12);
+    for &(x, y, z) in &[(0.1, 0.7, 0.2), (1.2, 0.5, 0.5), (1.9, 1.0, 0.9)] {
+        assert!((ip.interpolate(x, y, z) - reference.interpolate(x, y, z)).abs() < 1e-9);
+    }
+
+    // without a tolerance the near duplicate along Z stays a node of its own
+    let (ip, report) = Interp3D::from_grid_and_data_lenient(&x, &merged_y, &merged_z, &data, 0.0).unwrap();
+    assert_eq!(report.merged, [0, 1, 0]);
+    assert_eq!(ip.dims(), (3, 2, 4));
+}
+
+#[test]
+fn lenient_grid_still_rejects_unusable_axes() {
+    let axis = [0.0, 1.0, 2.0];
+    let data = vec![0.0; 27];
+
+    match Interp3D::from_grid_and_data_lenient(&axis, &[1.0, 1.0 + 1e-10, 1.0], &axis, &data, 1e-9) {
+        Err(Interp3DError::TooFewPoints { dir: Dir::Y, n: 1 }) => (),
+        other => panic!("unexpected {:?}", other.map(|_| ()))
+    }
+    assert!(matches!(Interp3D::from_grid_and_data_lenient(&axis, &axis, &[0.0, f64::NAN, 2.0], &data, 0.0), Err(Interp3DError::NonMonotonicAxis { dir: Dir::Z, .. })));
+    assert!(matches!(Interp3D::from_grid_and_data_lenient(&axis, &axis, &axis, &data[1..], 0.0), Err(Interp3DError::DataLength { expected: 27, found: 26 })));
+    assert!(matches!(Interp3D::from_grid_and_data_lenient(&axis, &axis, &axis, &data, -1.0), Err(Interp3DError::InvalidTolerance { .. })));
+}
+
+#[test]
+fn gradient_of_a_reproduced_polynomial_is_exact() {
+    let f = |x: f64, y: f64, z: f64| x*x*y + z;
+    let ip = Interp3D::from_config(f, &config());
+
+    for &(x, y, z) in &[(0.61, 0.13, 0.97), (1.3, 1.9, 1.1), (0.8, -0.1, 0.8), (1.52, 2.2, 1.2)] {
+        let (v, [dx, dy, dz]) = ip.interpolate_gradient(x, y, z);
+        assert_eq!(v, ip.interpolate(x, y, z));
+        assert!((dx - 2.0*x*y).abs() < 1e-12);
+        assert!((dy - x*x).abs() < 1e-12);
+        assert!((dz - 1.0).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn gradient_is_continuous_across_cell_faces() {
+    let f = |x: f64, y: f64, z: f64| (x*y).sin() + z.exp();
+    let ip = Interp3D::from_config(f, &config());
+
+    // x = 0.75 is a node of the linear x axis
+    let (_, below) = ip.interpolate_gradient(0.75 - 1e-9, 0.4, 1.0);
+    let (_, above) = ip.interpolate_gradient(0.75 + 1e-9, 0.4, 1.0);
+    for d in 0..3 {
+        assert!((below[d] - above[d]).abs() < 1e-6);
+    }
+}
+
+/// Clustered and scattered query points in and around ``config()``, the nodes of ``ip`` and some special cases
+fn query_points(ip: &Interp3D) -> Vec<(f64, f64, f64)> {
+    // clustered: a trajectory through the domain and out of it again
+    let mut points: Vec<_> = (0..2000).map(|i| {
+        let s = i as f64/2000.0;
+        (-0.3 + 2.6*s, -1.2 + 4.5*s*s, 1.0 + 0.7*(10.0*s).sin())
+    }).collect();
+    // scattered: pseudo random points, partly outside of the domain
+    let mut next = common::uniform(12345);
+    for _ in 0..2000 {
+        points.push((-0.5 + 3.0*next(), -2.0 + 6.0*next(), 0.2 + 1.6*next()));
+    }
+    // nodes and other special cases
+    points.extend(ip.iter_points().map(|(x, y, z, _)| (x, y, z)));
+    points.extend([(2.0, 3.0, 1.5), (0.0, -1.0, 0.5), (f64::NAN, 1.0, 1.0), (1.0, f64::INFINITY, 1.0)]);
+    points
+}
+
+#[test]
+fn interpolate_many_matches_single_queries() {
+    let f = |x: f64, y: f64, z: f64| (x*y).sin() + z.exp();
+    let ip = Interp3D::from_config(f, &config());
+    let points = query_points(&ip);
+
+    let many = ip.interpolate_many(&points);
+    assert_eq!(many.len(), points.len());
+    for (&(x, y, z), v) in points.iter().zip(many) {
+        assert_eq!(ip.interpolate(x, y, z).to_bits(), v.to_bits(), "at ({}, {}, {})", x, y, z);
+    }
+}
+
+#[test]
+fn interpolate_many_sorted_matches_interpolate_many() {
+    let f = |x: f64, y: f64, z: f64| (x*y).sin() + z.exp();
+    let mut ip = Interp3D::from_config(f, &config());
+    let points = query_points(&ip);
+
+    for scheme in [Type::Tricubic, Type::Trilinear, Type::Nearest, Type::TricubicMonotone] {
+        ip.set_type(scheme);
+        for policy in [OutOfBounds::LinearExtrapolate, OutOfBounds::ClampToEdge, OutOfBounds::ReturnNan] {
+            ip.set_out_of_bounds(policy);
+            let sorted = ip.interpolate_many_sorted(&points);
+            for (a, b) in sorted.iter().zip(ip.interpolate_many(&points)) {
+                assert_eq!(a.to_bits(), b.to_bits(), "{:?} {:?}", scheme, policy);
+            }
+        }
+    }
+
+    ip.set_axis_transform(Dir::Z, AxisTransform::Ln);
+    let sorted = ip.interpolate_many_sorted(&points);
+    for (a, b) in sorted.iter().zip(ip.interpolate_many(&points)) {
+        assert_eq!(a.to_bits(), b.to_bits());
+    }
+    assert!(ip.interpolate_many_sorted(&[]).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "outside of the sampled range")]
+fn interpolate_many_sorted_panics_out_of_bounds() {
+    let mut ip = Interp3D::from_config(|x, _, _| x, &config());
+    ip.set_out_of_bounds(OutOfBounds::Error);
+    ip.interpolate_many_sorted(&[(1.0, 1.0, 1.0), (1.0, 1.0, 2.0)]);
+}
+
+/// Query vectors along each direction of ``config()`` with a different length each: unsorted, partly outside of the domain, on the nodes and just outside of the faces
+fn lattice_axes(ip: &Interp3D) -> [Vec<f64>; 3] {
+    let ((x0, x1), (y0, _), (z0, z1)) = ip.domain();
+    [
+        vec![0.37, x0, -0.2, 1.0, x1, 2.3, 1.5, x1 + 1e-12, 0.01],
+        vec![y0 - 1e-12, 2.9, -1.0, 0.3, 3.5, -1.7],
+        vec![1.2, z0, 0.77, z1 + 0.2, z1, 0.5001, f64::NAN, 0.9]
+    ]
+}
+
+#[test]
+fn interpolate_grid_matches_single_queries() {
+    let f = |x: f64, y: f64, z: f64| (x*y).sin() + z.exp();
+    let mut ip = Interp3D::from_config(f, &config());
+    ip.set_out_of_bounds_tolerance(0.0, 1e-9);
+    let [xq, yq, zq] = lattice_axes(&ip);
+    let check = |ip: &Interp3D, label: &str| {
+        let values = ip.interpolate_grid(&xq, &yq, &zq);
+        assert_eq!(values.len(), xq.len()*yq.len()*zq.len());
+        for (a, &x) in xq.iter().enumerate() {
+            for (b, &y) in yq.iter().enumerate() {
+                for (c, &z) in zq.iter().enumerate() {
+                    let value = values[(a*yq.len() + b)*zq.len() + c];
+                    assert_eq!(value.to_bits(), ip.interpolate(x, y, z).to_bits(), "{} at ({}, {}, {})", label, x, y, z);
+                }
+            }
+        }
+    };
+
+    for scheme in [Type::Tricubic, Type::Trilinear, Type::BicubicUnilinear(Dir::Y), Type::Nearest, Type::TricubicMonotone, Type::Blend(0.3)] {
+        ip.set_type(scheme);
+        for policy in [OutOfBounds::LinearExtrapolate, OutOfBounds::ClampToEdge, OutOfBounds::ReturnNan] {
+            ip.set_out_of_bounds(policy);
+            check(&ip, &format!("{:?} {:?}", scheme, policy));
+        }
+    }
+
+    ip.set_type(Type::Tricubic);
+    ip.set_out_of_bounds(OutOfBounds::LinearExtrapolate);
+    ip.set_periodic(Dir::X, true);
+    ip.set_axis_transform(Dir::Y, AxisTransform::Custom(|y| y.sinh(), |u| u.asinh()));
+    ip.generate_data(f, &config());
+    check(&ip, "periodic and transformed");
+
+    assert!(ip.interpolate_grid(&xq, &[], &zq).is_empty());
+    assert!(ip.interpolate_grid(&[], &yq, &zq).is_empty());
+}
+
+#[test]
+fn interpolate_grid_counts_every_point_as_a_query() {
+    let mut ip = Interp3D::from_config(|x, y, z| x + y + z, &config());
+    ip.set_out_of_bounds_tolerance(0.0, 1e-9);
+    let [xq, yq, zq] = lattice_axes(&ip);
+
+    for &x in &xq {
+        for &y in &yq {
+            for &z in &zq {
+                ip.interpolate(x, y, z);
+            }
+        }
+    }
+    let pointwise = ip.query_stats();
+    ip.reset_query_stats();
+    ip.interpolate_grid(&xq, &yq, &zq);
+    assert_eq!(ip.query_stats(), pointwise);
+    assert!(pointwise.within_tolerance > 0 && pointwise.out_of_range > 0);
+}
+
+#[test]
+fn interpolate_grid_reports_the_first_point_out_of_bounds() {
+    let mut ip = Interp3D::from_config(|x, y, z| x + y + z, &config());
+    ip.set_out_of_bounds(OutOfBounds::Error);
+    let ((x0, x1), (y0, y1), (z0, z1)) = ip.domain();
+    let message = |xq: &[f64], yq: &[f64], zq: &[f64]| {
+        std::panic::catch_unwind(|| ip.interpolate_grid(xq, yq, zq)).unwrap_err().downcast::<String>().unwrap()
+    };
+    let pointwise = |x: f64, y: f64, z: f64| ip.try_interpolate(x, y, z).unwrap_err().to_string();
+
+    // x is slowest, so the first point out of bounds has the first entry along X and Y
+    assert_eq!(*message(&[x0, x1 + 1.0], &[y0, y1 + 2.0], &[z0, z1 + 3.0]), pointwise(x0, y0, z1 + 3.0));
+    assert_eq!(*message(&[x0, x1 + 1.0], &[y0, y1 + 2.0], &[z0, z1]), pointwise(x0, y1 + 2.0, z0));
+    assert_eq!(*message(&[x0, x1 + 1.0], &[y0, y1], &[z0, z1]), pointwise(x1 + 1.0, y0, z0));
+    assert_eq!(*message(&[x1 + 1.0], &[y1 + 2.0], &[z0]), pointwise(x1 + 1.0, y1 + 2.0, z0));
+    assert_eq!(ip.interpolate_grid(&[x0, x1], &[y0], &[z0, z1]).len(), 4);
+}
+
+/// Points just barely outside of each of the 6 faces of ``config()``, followed by a far away one
+fn outside_points() -> [(f64, f64, f64); 7] {
+    let eps = 1e-9;
+    [
+        (-eps, 1.0, 1.0), (2.0 + eps, 1.0, 1.0),
+        (1.0, -1.0 - eps, 1.0), (1.0, 3.0 + eps, 1.0),
+        (1.0, 1.0, 0.5 - eps), (1.0, 1.0, 1.5 + eps),
+        (100.0, -50.0, 7.0)
+    ]
+}
+
+#[test]
+fn out_of_bounds_extrapolates_by_default() {
+    let f = |x: f64, y: f64, z: f64| x*x*y + z;
+    let ip = Interp3D::from_config(f, &config());
+
+    // barely outside the extrapolation is continuous
+    for &(x, y, z) in &outside_points()[..6] {
+        assert!((ip.interpolate(x, y, z) - f(x, y, z)).abs() < 1e-6);
+    }
+    assert!(ip.interpolate(100.0, -50.0, 7.0).is_finite());
+}
+
+#[test]
+fn out_of_bounds_clamp_to_edge() {
+    let f = |x: f64, y: f64, z: f64| (x*y).sin() + z.exp();
+    let mut ip = Interp3D::from_config(f, &config());
+    ip.set_out_of_bounds(OutOfBounds::ClampToEdge);
+
+    for &(x, y, z) in &outside_points() {
+        let clamped = (x.clamp(0.0, 2.0), y.clamp(-1.0, 3.0), z.clamp(0.5, 1.5));
+        assert_eq!(ip.interpolate(x, y, z), ip.interpolate(clamped.0, clamped.1, clamped.2));
+    }
+    let (_, gradient) = ip.interpolate_gradient(-1.0, 1.0, 1.0);
+    assert_eq!(gradient[0], 0.0);
+    assert_ne!(gradient[1], 0.0);
+}
+
+#[test]
+fn out_of_bounds_return_nan() {
+    let f = |x: f64, y: f64, z: f64| (x*y).sin() + z.exp();
+    let mut ip = Interp3D::from_config(f, &config());
+    ip.set_out_of_bounds(OutOfBounds::ReturnNan);
+
+    for &(x, y, z) in &outside_points() {
+        assert!(ip.interpolate(x, y, z).is_nan());
+        assert!(ip.try_interpolate(x, y, z).unwrap().is_nan());
+    }
+    let many = ip.interpolate_many(&[(1.0, 1.0, 1.0), (3.0, 1.0, 1.0)]);
+    assert!(many[0].is_finite() && many[1].is_nan());
+    // the faces themselves are inside
+    assert_eq!(ip.interpolate(2.0, 3.0, 1.5), f(2.0, 3.0, 1.5));
+}
+
+#[test]
+fn out_of_bounds_error() {
+    let f = |x: f64, y: f64, z: f64| (x*y).sin() + z.exp();
+    let mut ip = Interp3D::from_config(f, &config());
+    ip.set_out_of_bounds(OutOfBounds::Error);
+
+    let expected = [
+        (Dir::X, (0.0, 2.0)), (Dir::X, (0.0, 2.0)),
+        (Dir::Y, (-1.0, 3.0)), (Dir::Y, (-1.0, 3.0)),
+        (Dir::Z, (0.5, 1.5)), (Dir::Z, (0.5, 1.5)),
+        (Dir::X, (0.0, 2.0))
+    ];
+    for (&(x, y, z), (dir, expected_range)) in outside_points().iter().zip(expected) {
+        match ip.try_interpolate(x, y, z) {
+            Err(Interp3DError::OutOfBounds { axis, range, .. }) => {
+                assert_eq!(axis, dir);
+                assert_eq!(range, expected_range);
+            }
+            other => panic!("expected OutOfBounds, got {:?}", other)
+        }
+    }
+    assert_eq!(ip.try_interpolate(1.0, 1.0, 1.0).unwrap(), ip.interpolate(1.0, 1.0, 1.0));
+}
+
+#[test]
+#[should_panic(expected = "outside of the sampled range")]
+fn out_of_bounds_error_panics_in_interpolate() {
+    let mut ip = Interp3D::from_config(|x, _, _| x, &config());
+    ip.set_out_of_bounds(OutOfBounds::Error);
+    ip.interpolate(1.0, 1.0, 2.0);
+}
+
+/// The points on the 6 faces of ``config()`` moved by ``offset`` outwards (inwards if negative)
+fn face_points(offset: f64) -> [(f64, f64, f64); 6] {
+    [
+        (-offset, 1.0, 1.0), (2.0 + offset, 1.0, 1.0),
+        (1.0, -1.0 - offset, 1.0), (1.0, 3.0 + offset, 1.0),
+        (1.0, 1.0, 0.5 - offset), (1.0, 1.0, 1.5 + offset)
+    ]
+}
+
+#[test]
+fn out_of_bounds_tolerance_moves_close_points_onto_the_faces() {
+    let f = |x: f64, y: f64, z: f64| (x*y).sin() + z.exp();
+    let mut ip = Interp3D::from_config(f, &config());
+    ip.set_out_of_bounds(OutOfBounds::Error);
+    ip.set_out_of_bounds_tolerance(0.0, 1e-8);
+    assert_eq!(ip.out_of_bounds_tolerance(), (0.0, 1e-8));
+
+    for ((inside, within), (beyond, face)) in face_points(-1e-12).into_iter().zip(face_points(1e-9)).zip(face_points(1e-6).into_iter().zip(face_points(0.0))) {
+        assert!(ip.try_interpolate(inside.0, inside.1, inside.2).is_ok());
+        assert_eq!(ip.try_interpolate(within.0, within.1, within.2).unwrap(), ip.interpolate(face.0, face.1, face.2));
+        assert!(matches!(ip.try_interpolate(beyond.0, beyond.1, beyond.2), Err(Interp3DError::OutOfBounds { .. })));
+    }
+    assert_eq!(ip.query_stats(), QueryStats { within_tolerance: 6, out_of_range: 6 });
+
+    // a point outside along two directions counts once, and is out of range if either is beyond the tolerance
+    assert!(ip.try_interpolate(2.0 + 1e-9, 3.0 + 1e-9, 1.0).is_ok());
+    assert!(ip.try_interpolate(2.0 + 1e-9, 3.0 + 1e-6, 1.0).is_err());
+    assert!(ip.try_interpolate(f64::NAN, 1.0, 1.0).is_err());
+    assert_eq!(ip.query_stats(), QueryStats { within_tolerance: 7, out_of_range: 8 });
+
+    ip.reset_query_stats();
+    assert_eq!(ip.query_stats(), QueryStats::default());
+}
+
+#[test]
+fn out_of_bounds_tolerance_applies_to_every_policy() {
+    let f = |x: f64, y: f64, z: f64| x*x*y + z;
+    let mut ip = Interp3D::from_config(f, &config());
+    // relative to the edge, so 3e-9 along Y at y = 3
+    ip.set_out_of_bounds_tolerance(1e-9, 0.0);
+
+    for policy in [OutOfBounds::LinearExtrapolate, OutOfBounds::ClampToEdge, OutOfBounds::ReturnNan] {
+        ip.set_out_of_bounds(policy);
+        assert_eq!(ip.interpolate(1.0, 3.0 + 2e-9, 1.0), ip.interpolate(1.0, 3.0, 1.0));
+        let beyond = ip.interpolate(1.0, 3.0 + 1e-7, 1.0);
+        match policy {
+            OutOfBounds::LinearExtrapolate => assert_ne!(beyond, ip.interpolate(1.0, 3.0, 1.0)),
+            OutOfBounds::ClampToEdge => assert_eq!(beyond, ip.interpolate(1.0, 3.0, 1.0)),
+            _ => assert!(beyond.is_nan())
+        }
+    }
+    assert_eq!(ip.query_stats(), QueryStats { within_tolerance: 3, out_of_range: 3 });
+
+    // without a tolerance nothing is moved
+    ip.set_out_of_bounds_tolerance(0.0, 0.0);
+    assert!(ip.interpolate(1.0, 3.0 + 2e-9, 1.0).is_nan());
+}
+
+#[test]
+#[should_panic(expected = "non-negative and finite")]
+fn negative_out_of_bounds_tolerance_panics() {
+    let mut ip = Interp3D::from_config(|x, _, _| x, &config());
+    ip.set_out_of_bounds_tolerance(-1.0, 0.0);
+}
+
+#[test]
+fn periodic_axis_is_smooth_across_the_seam() {
+    use std::f64::consts::PI;
+
+    let config = DataGenConfBuilder::default()
+        .uniform_cube(0.0, 1.0, 4)
+        .z(0.0, 2.0*PI, 25, GridSpacing::Linear)
+        .build();
+    let f = |_: f64, _: f64, z: f64| z.cos();
+    let mut ip = Interp3D::default();
+    ip.set_periodic(Dir::Z, true);
+    ip.generate_data(f, &config);
+
+    let bounded = Interp3D::from_config(f, &config);
+    let max_error = |ip: &Interp3D, zs: &mut dyn Iterator<Item = f64>| zs
+        .map(|z| (ip.interpolate(0.5, 0.5, z) - f(0.5, 0.5, z)).abs())
+        .fold(0.0, f64::max);
+    // the cells next to the seam are as accurate as the ones in the middle
+    let seam = max_error(&ip, &mut (0..=100).map(|i| -0.3 + 0.006*i as f64));
+    let middle = max_error(&ip, &mut (0..=100).map(|i| PI - 0.3 + 0.006*i as f64));
+    assert!(seam < 2.0*middle && seam < 1e-3);
+    assert!(max_error(&bounded, &mut (0..=100).map(|i| 0.003*i as f64)) > 10.0*seam);
+
+    // the value and slope on both sides of the seam agree, and whole periods don't matter
+    for &eps in &[1e-9, 0.05] {
+        let (below, [_, _, slope_below]) = ip.interpolate_gradient(0.5, 0.5, 2.0*PI - eps);
+        let (above, [_, _, slope_above]) = ip.interpolate_gradient(0.5, 0.5, -eps);
+        assert!((below - above).abs() < 1e-12 && (slope_below - slope_above).abs() < 1e-9);
+        assert!((ip.interpolate(0.5, 0.5, 6.0*PI - eps) - below).abs() < 1e-12);
+    }
+    let (_, [_, _, slope]) = ip.interpolate_gradient(0.5, 0.5, 0.0);
+    assert!(slope.abs() < 1e-3);
+
+    // switching it off again gives the bounded interpolator back
+    ip.set_periodic(Dir::Z, false);
+    assert_eq!(ip.interpolate(0.5, 0.5, 0.1), bounded.interpolate(0.5, 0.5, 0.1));
+}
+
+#[test]
+fn trilinear_reproduces_linear_functions() {
+    let f = |x: f64, y: f64, z: f64| 2.0*x - 3.0*y + z;
+    let mut ip = Interp3D::from_config(f, &config());
+    ip.set_type(Type::Trilinear);
+
+    let mut next = common::uniform(7);
+    for _ in 0..1000 {
+        let (x, y, z) = (2.0*next(), -1.0 + 4.0*next(), 0.5 + next());
+        assert!((ip.interpolate(x, y, z) - f(x, y, z)).abs() < 1e-13);
+        let (v, gradient) = ip.interpolate_gradient(x, y, z);
+        assert!((v - f(x, y, z)).abs() < 1e-13);
+        assert!((gradient[0] - 2.0).abs() < 1e-12 && (gradient[1] + 3.0).abs() < 1e-12 && (gradient[2] - 1.0).abs() < 1e-12);
+    }
+
+    // same domain semantics as the cubic scheme
+    ip.set_out_of_bounds(OutOfBounds::ReturnNan);
+    assert!(ip.interpolate(2.1, 0.0, 1.0).is_nan());
+    assert_eq!(ip.interpolate(2.0, 3.0, 1.5), f(2.0, 3.0, 1.5));
+}
+
+#[test]
+fn trilinear_blends_the_cell_corners() {
+    let f = |x: f64, y: f64, z: f64| x*y*z + (x*y).sin();
+    let mut ip = Interp3D::from_config(f, &config());
+    ip.set_type(Type::Trilinear);
+
+    // nodes are hit exactly, in between the result stays within the corner values
+    for (x, y, z, v) in ip.iter_points() {
+        assert_eq!(ip.interpolate(x, y, z), v);
+    }
+    let (x0, x1) = (0.75, 1.0);
+    let mid = ip.interpolate(0.5*(x0 + x1), 1.0, 1.0);
+    let (a, b) = (ip.interpolate(x0, 1.0, 1.0), ip.interpolate(x1, 1.0, 1.0));
+    assert!((mid - 0.5*(a + b)).abs() < 1e-14);
+}
+
+#[test]
+fn blend_averages_tricubic_and_trilinear() {
+    let f = |x: f64, y: f64, z: f64| x*y*z + (x*y).sin() + (2.0*z).exp();
+    let tricubic = Interp3D::from_config(f, &config());
+    let mut trilinear = Interp3D::from_config(f, &config());
+    trilinear.set_type(Type::Trilinear);
+    let mut ip = Interp3D::from_config(f, &config());
+
+    let mut next = common::uniform(277);
+    let points: Vec<_> = (0..200).map(|_| (-0.1 + 2.2*next(), -1.2 + 4.4*next(), 0.5 + next())).collect();
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-12*(1.0 + b.abs());
+
+    ip.set_type(Type::Blend(0.5));
+    for &(x, y, z) in &points {
+        let (c, l) = (tricubic.interpolate_hessian(x, y, z), trilinear.interpolate_hessian(x, y, z));
+        let (v, gradient, h) = ip.interpolate_hessian(x, y, z);
+        assert!(close(ip.interpolate(x, y, z), 0.5*(c.0 + l.0)), "at ({}, {}, {})", x, y, z);
+        assert!(close(v, 0.5*(c.0 + l.0)));
+        for (d, (c, l)) in gradient.iter().zip(c.1.iter().zip(&l.1)) {
+            assert!(close(*d, 0.5*(c + l)));
+        }
+        for (d, (c, l)) in h.iter().flatten().zip(c.2.iter().flatten().zip(l.2.iter().flatten())) {
+            assert!(close(*d, 0.5*(c + l)), "at ({}, {}, {})", x, y, z);
+        }
+        assert_eq!(ip.interpolate_gradient(x, y, z), (v, gradient));
+    }
+
+    // the ends are the pure schemes
+    for (alpha, pure) in [(1.0, &tricubic), (0.0, &trilinear)] {
+        ip.set_type(Type::Blend(alpha));
+        for &(x, y, z) in &points {
+            assert!(close(ip.interpolate(x, y, z), pure.interpolate(x, y, z)));
+        }
+    }
+
+    // the nodes are hit exactly whatever the blend
+    ip.set_type(Type::Blend(0.2));
+    for (x, y, z, v) in ip.iter_points().step_by(5) {
+        assert!(close(ip.interpolate(x, y, z), v));
+    }
+}
+
+#[test]
+#[should_panic(expected = "alpha of Type::Blend must be finite")]
+fn blend_with_non_finite_alpha_panics() {
+    Interp3D::default().set_type(Type::Blend(f64::NAN));
+}
+
+#[test]
+fn nearest_takes_the_closest_node() {
+    // region ids on the non-uniform y axis of config()
+    let mut ip = Interp3D::from_config(|x, y, z| (x*4.0).round() + 10.0*(y*3.0).floor() + 100.0*(z*8.0).round(), &config());
+    ip.set_type(Type::Nearest);
+
+    let nodes: Vec<(f64, f64, f64, f64)> = ip.iter_points().collect();
+    // exactly at a node
+    for &(x, y, z, v) in &nodes {
+        assert_eq!(ip.interpolate(x, y, z), v);
+    }
+
+    let ys: Vec<f64> = { let mut ys: Vec<f64> = nodes.iter().map(|p| p.1).collect(); ys.sort_by(f64::total_cmp); ys.dedup(); ys };
+    let z = nodes[3].2;
+    let value = |x: f64, y: f64, z: f64| nodes.iter().find(|p| (p.0, p.1, p.2) == (x, y, z)).unwrap().3;
+    for w in ys.windows(2) {
+        // physical distance decides, not the index
+        let (y0, y1) = (w[0], w[1]);
+        let h = y1 - y0;
+        assert_eq!(ip.interpolate(0.75, y0 + 0.49*h, z), value(0.75, y0, z));
+        assert_eq!(ip.interpolate(0.75, y0 + 0.51*h, z), value(0.75, y1, z));
+        // on the cell face x = 0.75 and in the middle between 0.75 and 1.0 (ties go to the lower node)
+        assert_eq!(ip.interpolate(0.75, y0 + 0.3*h, z), value(0.75, y0, z));
+        assert_eq!(ip.interpolate(0.875, y0 + 0.3*h, z), value(0.75, y0, z));
+    }
+
+    // never a ghost value, neither at the boundary nor outside
+    let (first, last) = (nodes[0], nodes[nodes.len()-1]);
+    assert_eq!(ip.interpolate(last.0, last.1, last.2), last.3);
+    assert_eq!(ip.interpolate(2.5, 4.0, 2.0), last.3);
+    assert_eq!(ip.interpolate(-0.5, -2.0, 0.0), first.3);
+    assert_eq!(ip.interpolate_gradient(1.1, 0.3, 0.9).1, [0.0; 3]);
+}
+
+#[test]
+fn monotone_scheme_does_not_overshoot_a_step() {
+    // opacity-like data: non-negative with a steep step along x and a smooth variation along y and z
+    let f = |x: f64, y: f64, z: f64| if x < 1.1 { 1e-3 } else { 5.0 } * (1.0 + 0.1*y*z);
+    let mut ip = Interp3D::from_config(f, &config());
+    let (_, max) = ip.data_extrema();
+
+    let grid = |n: usize, lo: f64, hi: f64| (0..=n).map(move |i| lo + (hi - lo)*i as f64/n as f64);
+    let range_over_domain = |ip: &Interp3D| grid(80, 0.0, 2.0)
+        .flat_map(|x| grid(10, -1.0, 3.0).map(move |y| (x, y)))
+        .flat_map(|(x, y)| grid(10, 0.5, 1.5).map(move |z| (x, y, z)))
+        .map(|(x, y, z)| ip.interpolate(x, y, z))
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+
+    // plain tricubic rings on both sides of the step
+    let (lo, hi) = range_over_domain(&ip);
+    assert!(lo < 0.0 && hi > max);
+
+    ip.set_type(Type::TricubicMonotone);
+    let (lo, hi) = range_over_domain(&ip);
+    // up to rounding
+    assert!(lo > 0.0 && hi <= max*(1.0 + 1e-14));
+}
+
+#[test]
+fn monotone_scheme_keeps_monotone_data_monotone() {
+    let f = |x: f64, y: f64, z: f64| 1.0/(1.0 + (-8.0*(x + 0.5*y - z)).exp());
+    let mut ip = Interp3D::from_config(f, &config());
+    ip.set_type(Type::TricubicMonotone);
+
+    let nodes: Vec<(f64, f64, f64, f64)> = ip.iter_points().collect();
+    // along every grid line in x
+    for &(_, y, z, _) in nodes.iter().filter(|p| p.0 == 0.0) {
+        let values: Vec<f64> = (0..=200).map(|i| ip.interpolate(0.01*i as f64, y, z)).collect();
+        assert!(values.windows(2).all(|w| w[0] <= w[1]), "not monotone at y = {}, z = {}", y, z);
+        assert!(values.iter().all(|v| (0.0..=1.0).contains(v)));
+    }
+    // the nodes are still reproduced and linear data exactly
+    for &(x, y, z, v) in &nodes {
+        assert_eq!(ip.interpolate(x, y, z), v);
+    }
+    let mut linear = Interp3D::from_config(|x, y, z| 2.0*x - 3.0*y + z, &config());
+    linear.set_type(Type::TricubicMonotone);
+    let (v, gradient) = linear.interpolate_gradient(1.3, 0.7, 0.9);
+    assert!((v - (2.6 - 2.1 + 0.9)).abs() < 1e-13);
+    assert!((gradient[0] - 2.0).abs() < 1e-12 && (gradient[1] + 3.0).abs() < 1e-12 && (gradient[2] - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn monotone_gradient_matches_finite_differences() {
+    let f = |x: f64, y: f64, z: f64| (x*y).sin() + (2.0*z).cos()*y;
+    let mut ip = Interp3D::from_config(f, &config());
+    ip.set_type(Type::TricubicMonotone);
+
+    let (x, y, z, eps) = (0.93, 1.37, 1.11, 1e-6);
+    let (_, gradient) = ip.interpolate_gradient(x, y, z);
+    let fd = [
+        (ip.interpolate(x + eps, y, z) - ip.interpolate(x - eps, y, z))/(2.0*eps),
+        (ip.interpolate(x, y + eps, z) - ip.interpolate(x, y - eps, z))/(2.0*eps),
+        (ip.interpolate(x, y, z + eps) - ip.interpolate(x, y, z - eps))/(2.0*eps)
+    ];
+    for d in 0..3 {
+        assert!((gradient[d] - fd[d]).abs() < 1e-6, "{:?} vs {:?}", gradient, fd);
+    }
+}
+
+#[test]
+fn bicubic_unilinear_along_each_direction() {
+    let pick = |dir: Dir, x: f64, y: f64, z: f64| match dir { Dir::X => x, Dir::Y => y, Dir::Z => z };
+    let mut next = common::uniform(3);
+
+    for dir in [Dir::X, Dir::Y, Dir::Z] {
+        // linear along dir, smooth along the other two
+        let f = move |x: f64, y: f64, z: f64| {
+            let (a, b) = match dir { Dir::X => (y, z), Dir::Y => (x, z), Dir::Z => (x, y) };
+            (1.0 + 2.0*pick(dir, x, y, z))*((a*b).sin() + a.cos() + b*b)
+        };
+        let mut tricubic = Interp3D::from_config(f, &config());
+        let mut ip = Interp3D::from_config(f, &config());
+        ip.set_type(Type::BicubicUnilinear(dir));
+        let nodes: Vec<(f64, f64, f64, f64)> = ip.iter_points().collect();
+
+        // away from the boundary cells the cubic along dir reproduces the linear data as well
+        for _ in 0..200 {
+            let (x, y, z) = (0.3 + 1.4*next(), -0.4 + 2.4*next(), 0.8 + 0.5*next());
+            assert!((ip.interpolate(x, y, z) - tricubic.interpolate(x, y, z)).abs() < 1e-12);
+        }
+
+        // along dir the interpolation is exactly linear between the nodes, also in the boundary cells
+        tricubic.set_type(Type::Trilinear);
+        for &(x, y, z, _) in nodes.iter().step_by(7) {
+            for t in [-0.1, 0.0, 0.02, 0.5, 0.97, 1.0] {
+                let s = |lo: f64, hi: f64| lo + t*(hi - lo);
+                let (x, y, z) = match dir {
+                    Dir::X => (s(0.0, 2.0), y, z),
+                    Dir::Y => (x, s(-1.0, 3.0), z),
+                    Dir::Z => (x, y, s(0.5, 1.5))
+                };
+                assert!((ip.interpolate(x, y, z) - tricubic.interpolate(x, y, z)).abs() < 1e-12);
+            }
+        }
+    }
+}
+
+#[test]
+fn f32_storage_is_accurate_to_f32_precision() {
+    let f = |x: f64, y: f64, z: f64| 100.0*(x*y).sin() + 50.0*z.exp();
+    let full = Interp3D::from_config(f, &config());
+    let mut reduced = Interp3D::default();
+    reduced.set_storage(Storage::F32);
+    reduced.generate_data(f, &config());
+
+    let (lo, hi) = full.data_extrema();
+    let magnitude = lo.abs().max(hi.abs());
+    for a in 0..20 {
+        let t = a as f64/19.0;
+        let (x, y, z) = (2.0*t, -1.0 + 4.0*(2.3*t).fract(), 0.5 + (3.7*t).fract());
+        let difference = (full.interpolate(x, y, z) - reduced.interpolate(x, y, z)).abs();
+        assert!(difference < 4.0*f32::EPSILON as f64*magnitude, "{} at ({}, {}, {})", difference, x, y, z);
+    }
+
+    // converting afterwards gives the same samples as rounding on store
+    let mut converted = Interp3D::from_config(f, &config());
+    converted.set_storage(Storage::F32);
+    assert_eq!(converted.iter_points().collect::<Vec<_>>(), reduced.iter_points().collect::<Vec<_>>());
+}
+
+#[test]
+fn slices_have_the_remaining_axes_in_order() {
+    // different sizes along every direction pin down the ordering
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 1.0, 3, GridSpacing::Linear)
+        .y(0.0, 2.0, 4, GridSpacing::Linear)
+        .z(0.0, 3.0, 5, GridSpacing::Linear)
+        .build();
+    let f = |x: f64, y: f64, z: f64| x + 10.0*y + 100.0*z;
+    let mut ip = Interp3D::from_config(f, &conf);
+    ip.set_type(Type::Trilinear);
+
+    for (dir, position, dims) in [(Dir::X, 0.3, (4, 5)), (Dir::Y, 1.1, (3, 5)), (Dir::Z, 2.45, (3, 4))] {
+        let slice = ip.slice(dir, position);
+        assert_eq!((slice.normal, slice.position, slice.dims()), (dir, position, dims));
+        assert_eq!(slice.values.len(), dims.0*dims.1);
+
+        for a in 0..dims.0 {
+            for b in 0..dims.1 {
+                let (u, v) = (slice.u[a], slice.v[b]);
+                let expected = match dir {
+                    Dir::X => f(position, u, v),
+                    Dir::Y => f(u, position, v),
+                    Dir::Z => f(u, v, position)
+                };
+                assert!((slice.get(a, b) - expected).abs() < 1e-12);
+                assert_eq!(slice.values[a*dims.1 + b], slice.get(a, b));
+            }
+        }
+    }
+
+    let values = ip.slice_values_at(Dir::Y, 0.5, &[(0.25, 0.75), (1.0, 3.0)]);
+    assert_eq!(values, vec![ip.interpolate(0.25, 0.5, 0.75), ip.interpolate(1.0, 0.5, 3.0)]);
+}
+
+#[test]
+fn slices_outside_of_the_domain_follow_the_out_of_bounds_policy() {
+    let mut ip = Interp3D::from_config(|x, y, z| x + y + z, &config());
+    ip.set_out_of_bounds(OutOfBounds::ReturnNan);
+    assert!(ip.slice(Dir::Z, 1.6).values.iter().all(|v| v.is_nan()));
+
+    ip.set_out_of_bounds(OutOfBounds::ClampToEdge);
+    assert_eq!(ip.slice(Dir::Z, 1.6), Slice2D { position: 1.6, ..ip.slice(Dir::Z, 1.5) });
+}
+
+#[test]
+fn line_along_an_axis_hits_the_nodes_exactly() {
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 2.0, 9, GridSpacing::Linear)
+        .y(-1.0, 3.0, 12, GridSpacing::Exponential(3.0))
+        .z(0.5, 1.5, 11, GridSpacing::Linear)
+        .build();
+    let ip = Interp3D::from_config(|x: f64, y: f64, z: f64| (x*y).sin() + z.exp(), &conf);
+    let (x, y) = (ip.grid_x()[3], ip.grid_y()[7]);
+
+    let line = ip.sample_line((x, y, 0.5), (x, y, 1.5), 11).unwrap();
+    assert_eq!(line.len(), 11);
+    for (k, &(s, v)) in line.iter().enumerate() {
+        assert!((s - (ip.grid_z()[k] - 0.5)).abs() < 1e-15);
+        assert_eq!(v, ip.value_at(3, 7, k));
+    }
+
+    // backwards and with a single point
+    let reversed = ip.sample_line((x, y, 1.5), (x, y, 0.5), 11).unwrap();
+    assert_eq!(reversed.last().unwrap().1, ip.value_at(3, 7, 0));
+    assert_eq!(ip.sample_line((x, y, 1.0), (0.0, 0.0, 0.5), 1).unwrap(), vec![(0.0, ip.interpolate(x, y, 1.0))]);
+}
+
+#[test]
+fn line_leaving_the_domain_reports_the_inside_part() {
+    let mut ip = Interp3D::from_config(|x, y, z| x + y + z, &config());
+    ip.set_out_of_bounds(OutOfBounds::Error);
+
+    // x from -1 to 3 is inside for t in [0.25, 0.75]
+    match ip.sample_line((-1.0, 0.0, 1.0), (3.0, 0.0, 1.0), 5) {
+        Err(Interp3DError::SegmentOutOfBounds { inside: Some((t0, t1)) }) => assert_eq!((t0, t1), (0.25, 0.75)),
+        other => panic!("unexpected {:?}", other)
+    }
+    assert!(matches!(ip.sample_line((0.0, 5.0, 1.0), (2.0, 5.0, 1.0), 5), Err(Interp3DError::SegmentOutOfBounds { inside: None })));
+    assert!(ip.sample_line((0.0, -1.0, 0.5), (2.0, 3.0, 1.5), 5).is_ok());
+
+    ip.set_out_of_bounds(OutOfBounds::ReturnNan);
+    let line = ip.sample_line((-1.0, 0.0, 1.0), (3.0, 0.0, 1.0), 5).unwrap();
+    let nan: Vec<bool> = line.iter().map(|(_, v)| v.is_nan()).collect();
+    assert_eq!(nan, vec![true, false, false, false, true]);
+}
+
+#[test]
+fn integral_of_a_constant_is_the_volume() {
+    let mut ip = Interp3D::from_config(|_, _, _| 2.5, &config());
+    for scheme in [Type::Tricubic, Type::Trilinear, Type::TricubicMonotone, Type::Nearest] {
+        ip.set_type(scheme);
+        let integral = ip.integrate((0.0, 2.0), (-1.0, 3.0), (0.5, 1.5)).unwrap();
+        assert!((integral - 2.5*8.0).abs() < 1e-13, "{:?}: {}", scheme, integral);
+    }
+}
+
+#[test]
+fn integral_of_x_over_partial_cells() {
+    let mut ip = Interp3D::from_config(|x, _, _| x, &config());
+    let exact = |(x0, x1): (f64, f64), (y0, y1): (f64, f64), (z0, z1): (f64, f64)| 0.5*(x1*x1 - x0*x0)*(y1 - y0)*(z1 - z0);
+
+    // limits right through cells along every direction
+    let boxes = [
+        ((0.3, 1.7), (-0.4, 2.2), (0.61, 1.03)),
+        ((0.9, 0.95), (1.0, 1.01), (0.7, 0.71)),
+        ((0.0, 2.0), (-1.0, 3.0), (0.5, 1.5))
+    ];
+    for scheme in [Type::Trilinear, Type::Tricubic, Type::TricubicMonotone] {
+        ip.set_type(scheme);
+        for &(xr, yr, zr) in &boxes {
+            // the cubic schemes only reproduce linear functions away from the outermost cells along X
+            if scheme != Type::Trilinear && xr.0 < 0.25 {
+                continue;
+            }
+            let integral = ip.integrate(xr, yr, zr).unwrap();
+            assert!((integral - exact(xr, yr, zr)).abs() < 1e-14*exact(xr, yr, zr).abs().max(1.0), "{:?}: {} vs {}", scheme, integral, exact(xr, yr, zr));
+        }
+    }
+
+    // nearest is piecewise constant, which is the integral of the midpoint rule on the cells
+    ip.set_type(Type::Nearest);
+    let integral = ip.integrate((0.0, 2.0), (-1.0, 3.0), (0.5, 1.5)).unwrap();
+    assert!((integral - exact((0.0, 2.0), (-1.0, 3.0), (0.5, 1.5))).abs() < 1e-13);
+}
+
+#[test]
+fn integral_outside_of_the_domain_follows_the_policy() {
+    let mut ip = Interp3D::from_config(|x, _, _| x, &config());
+    ip.set_type(Type::Trilinear);
+
+    // extrapolated linearly by default
+    let integral = ip.integrate((0.0, 3.0), (0.0, 1.0), (0.5, 1.5)).unwrap();
+    assert!((integral - 4.5).abs() < 1e-13);
+
+    ip.set_out_of_bounds(OutOfBounds::ClampToEdge);
+    let integral = ip.integrate((0.0, 3.0), (0.0, 1.0), (0.5, 1.5)).unwrap();
+    assert!((integral - (2.0 + 2.0)).abs() < 1e-13);
+
+    ip.set_out_of_bounds(OutOfBounds::Error);
+    assert!(matches!(ip.integrate((0.0, 3.0), (0.0, 1.0), (0.5, 1.5)), Err(Interp3DError::OutOfBounds { axis: Dir::X, .. })));
+    assert!(matches!(ip.integrate((0.0, 1.0), (1.0, 1.0), (0.5, 1.5)), Err(Interp3DError::InvalidRange { dir: Dir::Y, .. })));
+}
+
+#[test]
+fn error_estimate_is_zero_for_reproduced_functions() {
+    let f = |x: f64, y: f64, z: f64| 2.0*x - y + 3.0*z + x*y*z;
+    let mut ip = Interp3D::from_config(f, &config());
+    ip.set_type(Type::Trilinear);
+
+    let report = ip.estimate_error(f, 500, 7);
+    assert_eq!(report.samples, 500);
+    assert!(report.max_abs < 1e-13 && report.rms <= report.max_abs);
+    assert!(ip.estimate_error_on_grid(f).max_abs < 1e-13);
+    assert_eq!(ip.estimate_error_on_grid(f).samples, 8*11*6);
+}
+
+#[test]
+fn error_estimate_finds_the_worst_point() {
+    let f = |x: f64, y: f64, z: f64| (4.0*x).sin()*(y*z).cos();
+    let ip = Interp3D::from_config(f, &config());
+
+    let report = ip.estimate_error(f, 2000, 1);
+    assert!(report.max_abs > 1e-5 && report.rms > 0.0 && report.rms < report.max_abs);
+    let (x, y, z) = report.worst;
+    assert_eq!((ip.interpolate(x, y, z) - f(x, y, z)).abs(), report.max_abs);
+    let ((x0, x1), (y0, y1), (z0, z1)) = ip.domain();
+    assert!((x0..=x1).contains(&x) && (y0..=y1).contains(&y) && (z0..=z1).contains(&z));
+
+    // reproducible with the same seed, other points with another one
+    assert_eq!(ip.estimate_error(f, 2000, 1), report);
+    assert_ne!(ip.estimate_error(f, 2000, 2).worst, report.worst);
+
+    // the cell centres are about as bad as it gets
+    let on_grid = ip.estimate_error_on_grid(f);
+    assert!(on_grid.max_abs > 0.5*report.max_abs);
+}
+
+#[test]
+fn cached_lookup_matches_a_brute_force_search() {
+    let config = DataGenConfBuilder::default()
+        .x(0.0, 10.0, 300, GridSpacing::Exponential(8.0))
+        .y(-1.0, 1.0, 300, GridSpacing::TwoSidedExponential(5.0))
+        .z(0.0, 1.0, 40, GridSpacing::Linear)
+        .build();
+    // the value of a node tells its indices
+    let mut ip = Interp3D::default();
+    ip.generate_data(|_, _, _| 0.0, &config);
+    let (gx, gy, gz) = (ip.grid_x().to_vec(), ip.grid_y().to_vec(), ip.grid_z().to_vec());
+    let index = |v: &[f64], p: f64| v.iter().position(|&node| node == p).unwrap() as f64;
+    ip.update_data(|x, y, z, _| 1e6*index(&gx, x) + 1e3*index(&gy, y) + index(&gz, z));
+
+    // nearest node by scanning all of them
+    let nearest = |v: &[f64], p: f64| (0..v.len()).min_by(|&a, &b| (v[a] - p).abs().total_cmp(&(v[b] - p).abs())).unwrap() as f64;
+    let brute_force = |x: f64, y: f64, z: f64| 1e6*nearest(&gx, x) + 1e3*nearest(&gy, y) + nearest(&gz, z);
+
+    let mut next = common::uniform(987654321);
+    let mut points = Vec::new();
+    for round in 0..20 {
+        // a monotone sweep through many cells, back and forth
+        for i in 0..500 {
+            let s = if round % 2 == 0 { i as f64/500.0 } else { 1.0 - i as f64/500.0 };
+            points.push((10.0*s*s, -1.0 + 2.0*s, 0.5 + 0.5*(3.0*s).sin()*next()));
+        }
+        // followed by scattered points
+        for _ in 0..500 {
+            points.push((10.0*next(), -1.0 + 2.0*next(), next()));
+        }
+    }
+
+    let mut cache = QueryCache::default();
+    for (scheme, exact) in [(Type::Nearest, true), (Type::Tricubic, false)] {
+        ip.set_type(scheme);
+        for &(x, y, z) in &points {
+            let cached = ip.interpolate_cached(&mut cache, x, y, z);
+            assert_eq!(cached.to_bits(), ip.interpolate(x, y, z).to_bits(), "at ({}, {}, {})", x, y, z);
+            if exact {
+                assert_eq!(cached, brute_force(x, y, z), "at ({}, {}, {})", x, y, z);
+            }
+        }
+    }
+}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn interpolators_are_send_and_sync() {
+    assert_send_sync::<Interp3D>();
+    assert_send_sync::<Interp3DVec<3>>();
+    assert_send_sync::<QueryCache>();
+    assert_send_sync::<CompiledInterp3D>();
+}
+
+#[test]
+fn concurrent_queries_match_single_threaded_ones() {
+    let f = |x: f64, y: f64, z: f64| (x*y).sin() + z.exp();
+    let ip = Interp3D::from_config(f, &config());
+
+    let points: Vec<_> = (0..8000).map(|i| {
+        let s = i as f64/8000.0;
+        (2.0*s, -1.0 + 4.0*(7.0*s).fract(), 0.5 + (13.0*s).fract())
+    }).collect();
+    let expected: Vec<f64> = points.iter().map(|&(x, y, z)| ip.interpolate(x, y, z)).collect();
+
+    let results: Vec<Vec<f64>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = points.chunks(1000).map(|chunk| {
+            let ip = &ip;
+            scope.spawn(move || {
+                let mut cache = QueryCache::default();
+                chunk.iter().map(|&(x, y, z)| ip.interpolate_cached(&mut cache, x, y, z)).collect()
+            })
+        }).collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    let results: Vec<f64> = results.concat();
+    for (a, b) in results.iter().zip(&expected) {
+        assert_eq!(a.to_bits(), b.to_bits());
+    }
+}
+
+#[test]
+fn compiled_interpolator_matches_the_stencil_sum() {
+    // periodic along x, with the period 2 of config()
+    let f = |x: f64, y: f64, z: f64| (std::f64::consts::PI*x).sin()*y + (z - y).exp();
+    let mut ip = Interp3D::from_config(f, &config());
+    ip.set_periodic(Dir::X, true);
+
+    let mut next = common::uniform(24680);
+    // partly slightly outside of the domain, to cover the extrapolation as well
+    let points: Vec<_> = (0..5000).map(|_| (-0.5 + 3.0*next(), -1.2 + 4.4*next(), 0.45 + 1.1*next())).collect();
+    let scale = ip.iter_points().map(|(_, _, _, v)| v.abs()).fold(0.0, f64::max);
+
+    for scheme in [Type::Tricubic, Type::Trilinear, Type::BicubicUnilinear(Dir::Y), Type::Blend(0.3)] {
+        ip.set_type(scheme);
+        let fast = ip.compile();
+        let mut cache = QueryCache::default();
+        for &(x, y, z) in &points {
+            let expected = ip.interpolate(x, y, z);
+            assert!((fast.interpolate(x, y, z) - expected).abs() <= 1e-13*scale, "{:?} at ({}, {}, {})", scheme, x, y, z);
+            assert_eq!(fast.interpolate_cached(&mut cache, x, y, z), fast.interpolate(x, y, z));
+        }
+        for (x, y, z, v) in ip.iter_points() {
+            assert!((fast.interpolate(x, y, z) - v).abs() <= 1e-13*scale);
+        }
+    }
+
+    ip.set_out_of_bounds(OutOfBounds::Error);
+    assert!(ip.compile().try_interpolate(5.0, 0.0, 1.0).is_ok());
+    assert!(matches!(ip.compile().try_interpolate(1.0, 5.0, 1.0), Err(Interp3DError::OutOfBounds { axis: Dir::Y, .. })));
+    ip.set_type(Type::Nearest);
+    assert!(matches!(ip.try_compile().map(|_| ()), Err(Interp3DError::UnsupportedScheme { scheme: Type::Nearest })));
+    assert!(matches!(Interp3D::default().try_compile().map(|_| ()), Err(Interp3DError::NotSetUp)));
+}
+
+#[test]
+fn stencil_nodes_are_the_ones_the_value_depends_on() {
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 1.0, 5, GridSpacing::Linear)
+        .y(0.0, 2.0, 4, GridSpacing::Exponential(2.0))
+        .z(-1.0, 1.0, 6, GridSpacing::Linear)
+        .build();
+    let f = |x: f64, y: f64, z: f64| (x + y).sin() + z*z;
+    let points = [(0.43, 0.77, 0.11), (0.03, 0.1, -0.95), (0.99, 1.93, 0.87), (1.0, 0.0, -1.0)];
+
+    for boundary in [BoundaryCondition::Copy, BoundaryCondition::LinearExtrapolate] {
+        for scheme in [Type::Tricubic, Type::Trilinear, Type::BicubicUnilinear(Dir::Y), Type::Nearest] {
+            let mut ip = Interp3D::default();
+            ip.set_boundary_condition(boundary);
+            ip.set_type(scheme);
+            ip.generate_data(f, &conf);
+
+            for &(x, y, z) in &points {
+                let stencil = ip.stencil_nodes(x, y, z).unwrap();
+                let before = ip.interpolate(x, y, z);
+                if scheme == Type::Tricubic && (x, y, z) == points[0] {
+                    assert_eq!(stencil.len(), 64);
+                }
+
+                // bump every node on its own, the value changes exactly for the stencil nodes
+                for node in ip.iter().collect::<Vec<_>>() {
+                    let (i, j, k) = (node.i, node.j, node.k);
+                    ip[(i, j, k)] += 1.0;
+                    let changed = ip.interpolate(x, y, z) != before;
+                    ip[(i, j, k)] -= 1.0;
+                    if (x, y, z) != points[3] || scheme == Type::Nearest {
+                        assert_eq!(changed, stencil.contains(&node), "{:?} {:?} at {:?}: {:?}", boundary, scheme, (x, y, z), node);
+                    } else if changed {
+                        // on the corner node itself, most weights are 0
+                        assert!(stencil.contains(&node));
+                    }
+                }
+            }
+            assert_eq!(ip.stencil_nodes(0.5, 0.5, 0.5).unwrap().len(), match scheme {
+                Type::Tricubic => 64,
+                Type::Trilinear => 8,
+                Type::Nearest => 1,
+                _ => 32
+            });
+
+            let nearest = ip.nearest_grid_node(0.6, 0.2, 0.1);
+            if scheme == Type::Nearest {
+                let node = ip.stencil_nodes(0.6, 0.2, 0.1).unwrap()[0];
+                assert_eq!((node.i, node.j, node.k), nearest);
+            }
+            assert_eq!(ip.nearest_grid_node(7.0, -3.0, 0.1), (4, 0, nearest.2));
+            assert!(ip.stencil_nodes(1.1, 0.5, 0.5).is_err());
+            assert!(ip.stencil_nodes(0.5, f64::NAN, 0.5).is_err());
+        }
+    }
+    assert!(Interp3D::default().stencil_nodes(0.0, 0.0, 0.0).is_err());
+}
+
+#[test]
+fn detailed_weights_reproduce_the_value() {
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 1.0, 5, GridSpacing::Linear)
+        .y(0.0, 2.0, 4, GridSpacing::Exponential(2.0))
+        .z(-1.0, 1.0, 6, GridSpacing::Linear)
+        .build();
+    let f = |x: f64, y: f64, z: f64| (3.0*x + y).sin() + z*z*z;
+    let points = [(0.43, 0.77, 0.11), (0.03, 0.1, -0.95), (0.99, 1.93, 0.87), (1.0, 0.0, -1.0), (1.2, -0.3, 0.5)];
+
+    for boundary in [BoundaryCondition::Copy, BoundaryCondition::LinearExtrapolate, BoundaryCondition::Reflect { even: true }] {
+        for scheme in [Type::Tricubic, Type::TricubicMonotone, Type::Trilinear, Type::BicubicUnilinear(Dir::Z), Type::Nearest, Type::Blend(0.3)] {
+            let mut ip = Interp3D::default();
+            ip.set_boundary_condition(boundary);
+            ip.set_type(scheme);
+            ip.generate_data(f, &conf);
+
+            for &(x, y, z) in &points {
+                let detail = ip.interpolate_detailed(x, y, z);
+                assert_eq!(detail.value.to_bits(), ip.interpolate(x, y, z).to_bits());
+
+                // the schemes reproduce constants, so the weights sum up to 1
+                let sum: f64 = detail.weights.iter().map(|w| w.3).sum();
+                assert!((sum - 1.0).abs() < 1e-12, "{:?} {:?} at {:?}: {}", boundary, scheme, (x, y, z), sum);
+                let value: f64 = detail.weights.iter().map(|&(i, j, k, w)| w*ip.value_at(i, j, k)).sum();
+                assert!((value - detail.value).abs() < 1e-12, "{:?} {:?} at {:?}", boundary, scheme, (x, y, z));
+
+                // the nodes are the ones of the stencil, in storage order and without duplicates
+                assert!(detail.weights.windows(2).all(|w| (w[0].0, w[0].1, w[0].2) < (w[1].0, w[1].1, w[1].2)));
+                if let Ok(stencil) = ip.stencil_nodes(x, y, z) {
+                    assert!(detail.weights.iter().all(|&(i, j, k, _)| stencil.iter().any(|n| (n.i, n.j, n.k) == (i, j, k))));
+                }
+
+                let (cx, cy, cz) = detail.cell;
+                let (tx, ty, tz) = detail.local;
+                let (gx, gy, gz) = (ip.grid_x(), ip.grid_y(), ip.grid_z());
+                assert!((gx[cx] + tx*(gx[cx+1] - gx[cx]) - x).abs() < 1e-12);
+                assert!((gy[cy] + ty*(gy[cy+1] - gy[cy]) - y).abs() < 1e-12);
+                assert!((gz[cz] + tz*(gz[cz+1] - gz[cz]) - z).abs() < 1e-12);
+            }
+        }
+    }
+}
+
+#[test]
+fn detailed_query_follows_the_out_of_bounds_policy() {
+    let mut ip = Interp3D::from_config(|x, y, z| x + y + z, &config());
+    ip.set_type(Type::Trilinear);
+    let ((_, x1), _, _) = ip.domain();
+
+    ip.set_out_of_bounds(OutOfBounds::ClampToEdge);
+    let detail = ip.interpolate_detailed(x1 + 1.0, 0.5, 0.5);
+    assert_eq!(detail.local.0, 1.0);
+    assert_eq!(detail.value, ip.interpolate(x1, 0.5, 0.5));
+
+    ip.set_out_of_bounds(OutOfBounds::ReturnNan);
+    let detail = ip.interpolate_detailed(x1 + 1.0, 0.5, 0.5);
+    assert!(detail.value.is_nan() && detail.weights.is_empty());
+
+    ip.set_out_of_bounds(OutOfBounds::Error);
+    assert!(matches!(ip.try_interpolate_detailed(x1 + 1.0, 0.5, 0.5), Err(Interp3DError::OutOfBounds { axis: Dir::X, .. })));
+    assert!(matches!(Interp3D::default().try_interpolate_detailed(0.0, 0.0, 0.0), Err(Interp3DError::NotSetUp)));
+}
+
+#[test]
+fn hessian_of_a_quadratic_is_exact() {
+    let f = |x: f64, y: f64, z: f64| x*x*y + y*z*z;
+    let ip = Interp3D::from_config(f, &config());
+
+    // stay out of the outermost cells, where the ghost cells enter the stencil
+    let inner = |v: &[f64], s: f64| v[1] + s*(v[v.len()-2] - v[1]);
+    let mut next = common::uniform(11);
+    for _ in 0..500 {
+        let (x, y, z) = (inner(ip.grid_x(), next()), inner(ip.grid_y(), next()), inner(ip.grid_z(), next()));
+        let (v, gradient, h) = ip.interpolate_hessian(x, y, z);
+        assert_eq!((v, gradient), ip.interpolate_gradient(x, y, z));
+
+        let expected = [[2.0*y, 2.0*x, 0.0], [2.0*x, 0.0, 2.0*z], [0.0, 2.0*z, 2.0*y]];
+        for a in 0..3 {
+            for b in 0..3 {
+                assert!((h[a][b] - expected[a][b]).abs() < 1e-10, "{:?} at {:?}", h, (x, y, z));
+                assert_eq!(h[a][b], h[b][a]);
+            }
+        }
+    }
+}
+
+#[test]
+fn hessian_matches_finite_differences_of_the_gradient() {
+    let f = |x: f64, y: f64, z: f64| (x*y).sin() + (y - z).powi(3) + (-x*z).exp();
+    let mut next = common::uniform(5);
+
+    for scheme in [Type::Tricubic, Type::TricubicMonotone, Type::BicubicUnilinear(Dir::Z), Type::Trilinear] {
+        let mut ip = Interp3D::default();
+        ip.set_type(scheme);
+        ip.generate_data(f, &config());
+
+        for _ in 0..200 {
+            let p = [2.0*next(), -1.0 + 4.0*next(), 0.5 + next()];
+            let (v, gradient, h) = ip.interpolate_hessian(p[0], p[1], p[2]);
+            assert_eq!((v, gradient), ip.interpolate_gradient(p[0], p[1], p[2]));
+
+            let eps = 1e-6;
+            for b in 0..3 {
+                let (mut lo, mut hi) = (p, p);
+                lo[b] -= eps;
+                hi[b] += eps;
+                let (_, g_lo) = ip.interpolate_gradient(lo[0], lo[1], lo[2]);
+                let (_, g_hi) = ip.interpolate_gradient(hi[0], hi[1], hi[2]);
+                for a in 0..3 {
+                    let fd = (g_hi[a] - g_lo[a])/(2.0*eps);
+                    assert!((h[a][b] - fd).abs() < 1e-4*(1.0 + fd.abs()), "{:?}: h[{}][{}] = {} vs {} at {:?}", scheme, a, b, h[a][b], fd, p);
+                }
+            }
+        }
+    }
+
+    // clamped directions have no derivatives at all
+    let mut ip = Interp3D::from_config(f, &config());
+    ip.set_out_of_bounds(OutOfBounds::ClampToEdge);
+    let (_, gradient, h) = ip.interpolate_hessian(2.5, 1.0, 1.0);
+    assert_eq!(gradient[0], 0.0);
+    assert_eq!((h[0], [h[1][0], h[2][0]]), ([0.0; 3], [0.0; 2]));
+    assert_ne!(h[1][2], 0.0);
+}
+
+#[test]
+fn hermite_data_reproduces_cubics_everywhere() {
+    let f = |x: f64, y: f64, z: f64| x*x*x - 2.0*y*y*y + z*z*z + x*y - 3.0*z;
+    let gradient = |x: f64, y: f64, z: f64| [3.0*x*x + y, -6.0*y*y + x, 3.0*z*z - 3.0];
+    let mut ip = Interp3D::default();
+    ip.generate_data_hermite(|x, y, z| (f(x, y, z), gradient(x, y, z)), &config());
+    assert!(ip.has_hermite_data());
+
+    // the outermost cells and the extrapolation beyond them included
+    let mut next = common::uniform(13);
+    let compiled = ip.compile();
+    for _ in 0..500 {
+        let (x, y, z) = (-0.1 + 2.2*next(), -1.1 + 4.2*next(), 0.45 + 1.1*next());
+        let scale = 30.0;
+        assert!((ip.interpolate(x, y, z) - f(x, y, z)).abs() < 1e-12*scale, "{:?}", (x, y, z));
+        assert!((compiled.interpolate(x, y, z) - f(x, y, z)).abs() < 1e-12*scale);
+
+        let (_, g) = ip.interpolate_gradient(x, y, z);
+        let (_, g2, h) = ip.interpolate_hessian(x, y, z);
+        assert_eq!(g, g2);
+        for (g, expected) in g.iter().zip(gradient(x, y, z)) {
+            assert!((g - expected).abs() < 1e-10*scale);
+        }
+        assert!((h[0][0] - 6.0*x).abs() < 1e-9*scale && (h[0][1] - 1.0).abs() < 1e-9*scale && (h[2][2] - 6.0*z).abs() < 1e-9*scale);
+    }
+
+    // the other schemes and changed samples work without the derivatives
+    let plain = Interp3D::from_config(f, &config());
+    ip.set_type(Type::Trilinear);
+    let mut trilinear = Interp3D::from_config(f, &config());
+    trilinear.set_type(Type::Trilinear);
+    assert_eq!(ip.interpolate(0.3, 0.4, 0.7), trilinear.interpolate(0.3, 0.4, 0.7));
+    ip.set_type(Type::Tricubic);
+    ip.set_value(0, 0, 0, f(0.0, -1.0, 0.5));
+    assert!(!ip.has_hermite_data());
+    assert_eq!(ip.interpolate(0.3, 0.4, 0.7), plain.interpolate(0.3, 0.4, 0.7));
+}
+
+#[test]
+fn hermite_data_beats_the_estimated_slopes() {
+    let f = |x: f64, y: f64, z: f64| x.sin()*y.cos()*z;
+    let gradient = |x: f64, y: f64, z: f64| [x.cos()*y.cos()*z, -x.sin()*y.sin()*z, x.sin()*y.cos()];
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 3.0, 8, GridSpacing::Linear)
+        .y(-2.0, 2.0, 9, GridSpacing::Linear)
+        .z(0.0, 1.0, 4, GridSpacing::Linear)
+        .build();
+    let plain = Interp3D::from_config(f, &conf);
+    let mut hermite = Interp3D::default();
+    hermite.generate_data_hermite(|x, y, z| (f(x, y, z), gradient(x, y, z)), &conf);
+
+    // the largest error at the cell centers
+    let (xs, ys, zs) = (plain.grid_x().to_vec(), plain.grid_y().to_vec(), plain.grid_z().to_vec());
+    let max_error = |ip: &Interp3D| {
+        let mut max = 0.0f64;
+        for x in xs.windows(2).map(|w| 0.5*(w[0] + w[1])) {
+            for y in ys.windows(2).map(|w| 0.5*(w[0] + w[1])) {
+                for z in zs.windows(2).map(|w| 0.5*(w[0] + w[1])) {
+                    max = max.max((ip.interpolate(x, y, z) - f(x, y, z)).abs());
+                }
+            }
+        }
+        max
+    };
+    let (plain_error, hermite_error) = (max_error(&plain), max_error(&hermite));
+    assert!(hermite_error < 0.2*plain_error, "{} vs {}", hermite_error, plain_error);
+}
+
+#[test]
+fn inverse_lookup_solves_along_every_direction() {
+    let f = |x: f64, y: f64, z: f64| x.exp() + 2.0*y + z*z*z;
+    let ip = Interp3D::from_config(f, &config());
+    let tol = 1e-12;
+
+    let x = ip.invert_along(Dir::X, 4.0, (0.5, 1.0), None, tol).unwrap();
+    assert!((ip.interpolate(x, 0.5, 1.0) - 4.0).abs() <= tol);
+    let y = ip.invert_along(Dir::Y, 4.0, (1.0, 1.0), Some((2.0, -1.0)), tol).unwrap();
+    assert!((ip.interpolate(1.0, y, 1.0) - 4.0).abs() <= tol);
+    let z = ip.invert_along(Dir::Z, 4.0, (1.0, 0.5), None, tol).unwrap();
+    assert!((ip.interpolate(1.0, 0.5, z) - 4.0).abs() <= tol);
+    assert!((z - (4.0 - 1f64.exp() - 1.0).cbrt()).abs() < 1e-2, "{}", z);
+
+    // an end of the bracket that's already a solution
+    let v = ip.interpolate(1.0, 0.5, 0.5);
+    assert_eq!(ip.invert_along(Dir::Z, v, (1.0, 0.5), Some((0.5, 1.0)), tol).unwrap(), 0.5);
+}
+
+#[test]
+fn inverse_lookup_reports_missing_and_ambiguous_solutions() {
+    let config = DataGenConfBuilder::default().uniform_cube(-2.0, 2.0, 17).build();
+    let ip = Interp3D::from_config(|x, y, z| x*x + y*z, &config);
+    let tol = 1e-12;
+
+    let roots = ip.invert_along_all(Dir::X, 2.0, (1.0, 1.0), tol).unwrap();
+    assert_eq!(roots.len(), 2);
+    assert!(roots[0] < 0.0 && roots[1] > 0.0);
+    for &x in &roots {
+        assert!((ip.interpolate(x, 1.0, 1.0) - 2.0).abs() <= tol);
+    }
+    match ip.invert_along(Dir::X, 2.0, (1.0, 1.0), None, tol) {
+        Err(Interp3DError::MultipleSolutions { dir: Dir::X, roots: found }) => assert_eq!(found, roots),
+        other => panic!("expected multiple solutions, got {:?}", other)
+    }
+    let x = ip.invert_along(Dir::X, 2.0, (1.0, 1.0), Some((0.0, 2.0)), tol).unwrap();
+    assert!((x - roots[1]).abs() < 1e-10);
+
+    assert!(ip.invert_along_all(Dir::X, 10.0, (1.0, 1.0), tol).unwrap().is_empty());
+    assert!(matches!(ip.invert_along(Dir::X, 10.0, (1.0, 1.0), None, tol), Err(Interp3DError::NoSolution { .. })));
+    assert!(matches!(ip.invert_along(Dir::X, 2.0, (1.0, 1.0), Some((-0.5, 0.5)), tol), Err(Interp3DError::NoSolution { .. })));
+    assert!(matches!(Interp3D::default().invert_along_all(Dir::X, 0.0, (0.0, 0.0), tol), Err(Interp3DError::NotSetUp)));
+}
+
+#[test]
+fn lazy_interpolator_only_evaluates_the_visited_nodes() {
+    let f = |x: f64, y: f64, z: f64| (x*y).sin() + z.exp();
+    let calls = std::cell::Cell::new(0);
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 2.0, 40, GridSpacing::Linear)
+        .y(-1.0, 3.0, 50, GridSpacing::Exponential(3.0))
+        .z(0.5, 1.5, 30, GridSpacing::Linear)
+        .build();
+    let mut lazy = LazyInterp3D::new(|x, y, z| { calls.set(calls.get() + 1); f(x, y, z) }, &conf);
+    assert_eq!(lazy.evaluated_nodes(), 0);
+    let mut full = Interp3D::from_config(f, &conf);
+
+    // a cluster of queries in one corner, one right on the boundary and one extrapolated
+    let mut next = common::uniform(7);
+    let mut points: Vec<_> = (0..200).map(|_| (0.1*next(), -1.0 + 0.2*next(), 0.5 + 0.1*next())).collect();
+    points.extend([(0.0, -1.0, 0.5), (-0.05, -1.02, 0.45)]);
+    for scheme in [Type::Tricubic, Type::TricubicMonotone, Type::Trilinear, Type::Nearest] {
+        lazy.set_type(scheme);
+        full.set_type(scheme);
+        for &(x, y, z) in &points {
+            assert_eq!(lazy.interpolate(x, y, z), full.interpolate(x, y, z), "{:?} at {:?}", scheme, (x, y, z));
+        }
+    }
+    assert_eq!(calls.get(), lazy.evaluated_nodes());
+    assert!(calls.get() < 200, "{} evaluations", calls.get());
+
+    // the cached nodes aren't evaluated again
+    let before = calls.get();
+    lazy.interpolate(0.05, -0.9, 0.55);
+    assert_eq!(calls.get(), before);
+
+    lazy.set_out_of_bounds(OutOfBounds::Error);
+    assert!(lazy.try_interpolate(2.5, 0.0, 1.0).is_err());
+
+    let ip = lazy.materialize();
+    assert_eq!(calls.get(), 40*50*30);
+    assert!(ip.approx_eq(&full, 0.0, 0.0));
+    assert_eq!(ip.config(), Some(&conf));
+}
+
+#[test]
+fn log_transform_queries_in_physical_units() {
+    let f = |x: f64, y: f64, z: f64| x.sqrt()*y + z;
+    // the grid is laid out in log10(x) from 0.01 to 100
+    let conf = DataGenConfBuilder::default().x(-2.0, 2.0, 21, GridSpacing::Linear).y(0.0, 1.0, 5, GridSpacing::Linear).z(0.0, 1.0, 5, GridSpacing::Linear).build();
+    let mut ip = Interp3D::default();
+    ip.set_axis_transform(Dir::X, AxisTransform::Log10);
+    ip.generate_data(f, &conf);
+    let plain = Interp3D::from_config(|u, y, z| f(10f64.powf(u), y, z), &conf);
+
+    for &(x, y, z) in &[(0.013, 0.2, 0.9), (3.7, 0.5, 0.1), (99.0, 1.0, 0.0), (1.3, 0.3, 0.3)] {
+        assert_eq!(ip.interpolate(x, y, z), plain.interpolate(x.log10(), y, z));
+
+        // the chain rule turns the slopes into ones with respect to x
+        let (_, gradient) = ip.interpolate_gradient(x, y, z);
+        let h = 1e-6*x;
+        let difference = (ip.interpolate(x + h, y, z) - ip.interpolate(x - h, y, z))/(2.0*h);
+        assert!((gradient[0] - difference).abs() < 1e-6*difference.abs().max(1.0), "{} vs {}", gradient[0], difference);
+        let (_, hessian_gradient, hessian) = ip.interpolate_hessian(x, y, z);
+        assert_eq!(hessian_gradient, gradient);
+        let second = (ip.interpolate_gradient(x + h, y, z).1[0] - ip.interpolate_gradient(x - h, y, z).1[0])/(2.0*h);
+        assert!((hessian[0][0] - second).abs() < 1e-5*second.abs().max(1.0), "{} vs {}", hessian[0][0], second);
+    }
+
+    // the grid itself stays in log10(x)
+    assert_eq!(ip.domain().0, (-2.0, 2.0));
+}
+
+#[test]
+fn f64_and_f32_storage_of_the_same_samples_agree() {
+    // F32 samples are always summed up by the scalar stencil sum, F64 ones by the vectorized one with the simd feature
+    let f = |x: f64, y: f64, z: f64| 100.0*(x*y).sin() + 50.0*z.exp();
+    let mut reduced = Interp3D::from_config(f, &config());
+    reduced.set_storage(Storage::F32);
+    let mut full = Interp3D::from_config(f, &config());
+    full.set_storage(Storage::F32);
+    full.set_storage(Storage::F64);
+
+    let mut next = common::uniform(7);
+    for scheme in [Type::Tricubic, Type::Trilinear] {
+        full.set_type(scheme);
+        reduced.set_type(scheme);
+        for _ in 0..1000 {
+            let (x, y, z) = (2.0*next(), -1.0 + 4.0*next(), 0.5 + next());
+            assert_eq!(full.interpolate(x, y, z), reduced.interpolate(x, y, z), "{:?} at ({}, {}, {})", scheme, x, y, z);
+            assert_eq!(full.interpolate_hessian(x, y, z), reduced.interpolate_hessian(x, y, z));
+        }
+    }
+}
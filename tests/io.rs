@@ -0,0 +1,846 @@
+mod common;
+
+use common::temp_file;
+use interp3d::*;
+
+fn small_config() -> DataGenConf {
+    DataGenConfBuilder::default()
+        .x(0.0, 2.0, 5, GridSpacing::Linear)
+        .y(-1.0, 1.0, 6, GridSpacing::Exponential(2.0))
+        .z(0.0, 3.0, 7, GridSpacing::Linear)
+        .build()
+}
+
+fn f(x: f64, y: f64, z: f64) -> f64 {
+    (x*y).sin() + z*z/3.0
+}
+
+/// The same table as ``Interp3D::from_config(f, &small_config())``, but built from its nodes, so it has no config to write
+fn small_table_without_config() -> Interp3D {
+    let ip = Interp3D::from_config(f, &small_config());
+    let values: Vec<f64> = ip.iter().map(|node| node.value).collect();
+    Interp3D::from_grid_and_data(ip.grid_x(), ip.grid_y(), ip.grid_z(), &values).unwrap()
+}
+
+#[test]
+fn csv_export_has_one_row_per_node() {
+    let ip = Interp3D::from_config(f, &small_config());
+    let file = temp_file("rows.csv");
+    ip.export_csv(&file).unwrap();
+
+    let content = std::fs::read_to_string(&file).unwrap();
+    std::fs::remove_file(&file).unwrap();
+
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines[0], "x,y,z,value");
+    assert_eq!(lines.len(), 1 + 5*6*7);
+
+    let points: Vec<_> = ip.iter_points().collect();
+    for &row in &[0, 17, 5*6*7 - 1] {
+        let values: Vec<f64> = lines[row + 1].split(',').map(|s| s.parse().unwrap()).collect();
+        let (x, y, z, v) = points[row];
+        assert_eq!(values, vec![x, y, z, v]);
+    }
+
+    // like the other exports, nothing is written without data
+    assert!(matches!(Interp3D::default().export_csv(&file), Err(Interp3DError::NotSetUp)));
+    assert!(!std::path::Path::new(&file).exists());
+}
+
+/// Writes the documented text format by hand from the points of an interpolator.
+fn write_text_file(ip: &Interp3D, file: &str) {
+    let points: Vec<_> = ip.iter_points().collect();
+    let mut axes: [Vec<f64>; 3] = Default::default();
+    for p in &points {
+        for (axis, v) in axes.iter_mut().zip([p.0, p.1, p.2]) {
+            if !axis.contains(&v) {
+                axis.push(v);
+            }
+        }
+    }
+
+    let mut content = format!("# hand written\n{} {} {}\n", axes[0].len(), axes[1].len(), axes[2].len());
+    for axis in &axes {
+        let line: Vec<String> = axis.iter().map(|v| format!("{:e}", v)).collect();
+        content += &(line.join(" ") + "\n");
+    }
+    for p in &points {
+        content += &format!("{:e}\n", p.3);
+    }
+    std::fs::write(file, content).unwrap();
+}
+
+#[test]
+fn import_hand_written_file() {
+    let ip = Interp3D::from_config(f, &small_config());
+    let file = temp_file("hand.ip3d");
+    write_text_file(&ip, &file);
+
+    let imported = Interp3D::from_file(&file);
+    std::fs::remove_file(&file).unwrap();
+
+    assert_eq!(imported.iter_points().collect::<Vec<_>>(), ip.iter_points().collect::<Vec<_>>());
+    for &(x, y, z) in &[(0.3, 0.1, 2.9), (1.99, -0.8, 0.2), (1.0, 0.0, 1.5)] {
+        assert_eq!(imported.interpolate(x, y, z), ip.interpolate(x, y, z));
+    }
+}
+
+fn import_str(name: &str, content: &str) -> Result<(), Interp3DError> {
+    let file = temp_file(name);
+    std::fs::write(&file, content).unwrap();
+    let result = Interp3D::default().import_data(&file);
+    std::fs::remove_file(&file).unwrap();
+
+    result
+}
+
+#[test]
+fn malformed_files_are_reported() {
+    let valid = "2 2 2\n0 1\n0 1\n0 1\n1 2 3 4 5 6 7 8\n";
+    assert!(import_str("valid.ip3d", valid).is_ok());
+
+    match import_str("truncated.ip3d", "2 2 2\n0 1\n0 1\n0 1\n1 2 3 4 5 6 7\n") {
+        Err(Interp3DError::Parse { line: 5, .. }) => (),
+        other => panic!("unexpected {:?}", other)
+    }
+    match import_str("token.ip3d", "2 2 2\n0 1\n0 one\n0 1\n1 2 3 4 5 6 7 8\n") {
+        Err(Interp3DError::Parse { line: 3, message }) => assert!(message.contains("'one'")),
+        other => panic!("unexpected {:?}", other)
+    }
+    match import_str("trailing.ip3d", "2 2 2\n0 1\n0 1\n0 1\n1 2 3 4 5 6 7 8 9\n") {
+        Err(Interp3DError::Parse { line: 5, .. }) => (),
+        other => panic!("unexpected {:?}", other)
+    }
+    match import_str("few.ip3d", "2 1 2\n0 1\n0\n0 1\n1 2 3 4\n") {
+        Err(Interp3DError::TooFewPoints { dir: Dir::Y, n: 1 }) => (),
+        other => panic!("unexpected {:?}", other)
+    }
+    assert!(matches!(import_str("missing.ip3d", "").map_err(|e| e.to_string()), Err(msg) if msg.contains("end of file")));
+
+    // absurd counts run into the end of the file instead of allocating for them
+    for n in [u64::MAX, 1 << 62, 1 << 40] {
+        match import_str("oversized.ip3d", &format!("2 2 {}\n0 1\n0 1\n0 1\n", n)) {
+            Err(Interp3DError::Parse { line: 4, message }) => assert!(message.contains("end of file"), "{}", message),
+            other => panic!("unexpected {:?}", other)
+        }
+    }
+}
+
+fn table_str(name: &str, content: &str) -> Result<Interp3D, Interp3DError> {
+    let file = temp_file(name);
+    std::fs::write(&file, content).unwrap();
+    let result = Interp3D::from_table(&file);
+    std::fs::remove_file(&file).unwrap();
+
+    result
+}
+
+#[test]
+fn table_in_any_order_is_imported() {
+    let ip = Interp3D::from_config(f, &small_config());
+    let file = temp_file("table.csv");
+    ip.export_csv(&file).unwrap();
+    let content = std::fs::read_to_string(&file).unwrap();
+    std::fs::remove_file(&file).unwrap();
+
+    // header first, rows reversed and whitespace separated
+    let mut lines: Vec<String> = content.lines().skip(1).map(|l| l.replace(',', "  ")).collect();
+    lines.reverse();
+    let table = format!("x y z value\n# reversed\n{}\n", lines.join("\n"));
+
+    let imported = table_str("table.txt", &table).unwrap();
+    assert_eq!(imported.dims(), ip.dims());
+    let points: Vec<_> = imported.iter_points().collect();
+    assert_eq!(points, ip.iter_points().collect::<Vec<_>>());
+}
+
+#[test]
+fn table_with_rounded_coordinates_is_imported() {
+    let table = "0 0 0 1\n1 0 0 2\n0 1 0 3\n1 1.0000000000001 0 4\n0 0 1 5\n1 0 1 6\n0 1 1 7\n1 1 1, 8 # last\n";
+    let ip = table_str("rounded.txt", table).unwrap();
+    assert_eq!(ip.dims(), (2, 2, 2));
+    assert_eq!(ip.value_at(1, 1, 0), 4.0);
+}
+
+#[test]
+fn incomplete_tables_are_reported() {
+    match table_str("duplicate.txt", "0 0 0 1\n1 0 0 2\n0 1 0 3\n1 1 0 4\n0 0 1 5\n1 0 1 6\n0 1 1 7\n1 1 1 8\n0 1 0 9\n").map(|_| ()) {
+        Err(Interp3DError::Parse { line: 9, message }) => assert!(message.contains("line 3")),
+        other => panic!("unexpected {:?}", other)
+    }
+    match table_str("missing.txt", "0 0 0 1\n1 0 0 2\n0 1 0 3\n1 1 0 4\n0 0 1 5\n1 0 1 6\n0 1 1 7\n").map(|_| ()) {
+        Err(Interp3DError::Parse { message, .. }) => assert!(message.contains("(1, 1, 1)")),
+        other => panic!("unexpected {:?}", other)
+    }
+    match table_str("columns.txt", "0 0 0 1\n1 0 0\n").map(|_| ()) {
+        Err(Interp3DError::Parse { line: 2, .. }) => (),
+        other => panic!("unexpected {:?}", other)
+    }
+}
+
+#[test]
+fn swapped_axis_nodes_are_rejected() {
+    let content = "4 2 2\n0 2 1 3\n0 1\n0 1\n1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16\n";
+    match import_str("swapped.ip3d", content) {
+        Err(Interp3DError::NonMonotonicAxis { dir: Dir::X, index: 1 }) => (),
+        other => panic!("unexpected {:?}", other)
+    }
+}
+
+#[test]
+fn export_import_round_trip() {
+    let ip = Interp3D::from_config(f, &small_config());
+    let file = temp_file("round-trip.ip3d");
+    ip.export_data(&file).unwrap();
+
+    let imported = Interp3D::from_file(&file);
+    std::fs::remove_file(&file).unwrap();
+
+    // bitwise equal, not just close
+    assert_eq!(imported.iter_points().collect::<Vec<_>>(), ip.iter_points().collect::<Vec<_>>());
+    for &(x, y, z) in &[(0.3, 0.1, 2.9), (1.99, -0.8, 0.2), (1.0, 0.0, 1.5), (0.01, 0.99, 0.01)] {
+        assert_eq!(imported.interpolate(x, y, z), ip.interpolate(x, y, z));
+    }
+}
+
+#[test]
+fn export_without_data_is_an_error() {
+    let file = temp_file("empty.ip3d");
+    assert!(matches!(Interp3D::default().export_data(&file), Err(Interp3DError::NotSetUp)));
+    assert!(!std::path::Path::new(&file).exists());
+}
+
+#[test]
+fn binary_round_trip_is_bit_exact() {
+    let ip = Interp3D::from_config(f, &small_config());
+    let file = temp_file("round-trip.bin");
+    ip.export_data_binary(&file).unwrap();
+
+    let imported = Interp3D::from_file(&file);
+    std::fs::remove_file(&file).unwrap();
+
+    let bits = |ip: &Interp3D| -> Vec<[u64; 4]> {
+        ip.iter_points().map(|(x, y, z, v)| [x.to_bits(), y.to_bits(), z.to_bits(), v.to_bits()]).collect()
+    };
+    assert_eq!(bits(&imported), bits(&ip));
+    for &(x, y, z) in &[(0.3, 0.1, 2.9), (1.99, -0.8, 0.2), (1.0, 0.0, 1.5)] {
+        assert_eq!(imported.interpolate(x, y, z).to_bits(), ip.interpolate(x, y, z).to_bits());
+    }
+}
+
+#[test]
+fn binary_from_the_future_or_truncated_is_rejected() {
+    let ip = Interp3D::from_config(f, &small_config());
+    let file = temp_file("broken.bin");
+    ip.export_data_binary(&file).unwrap();
+    let bytes = std::fs::read(&file).unwrap();
+
+    let mut future = bytes.clone();
+    future[8] = 4;
+    std::fs::write(&file, &future).unwrap();
+    match Interp3D::default().import_data(&file) {
+        Err(Interp3DError::UnsupportedVersion { version: 4, supported: 3 }) => (),
+        other => panic!("unexpected {:?}", other)
+    }
+
+    std::fs::write(&file, &bytes[..bytes.len() - 8]).unwrap();
+    assert!(Interp3D::default().import_data(&file).is_err());
+
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn older_binary_versions_are_still_read() {
+    let ip = small_table_without_config();
+    let file = temp_file("v1.bin");
+    ip.export_data_binary(&file).unwrap();
+
+    // version 2 is version 3 without the flags byte and the checksum, version 1 also lacks the storage byte
+    let mut bytes = std::fs::read(&file).unwrap();
+    assert_eq!((bytes[8], bytes[9], bytes[10]), (3, 0, 8));
+    bytes[8] = 2;
+    bytes.remove(10);
+    bytes.truncate(bytes.len() - 4);
+    for version in [2, 1] {
+        if version == 1 {
+            bytes[8] = 1;
+            bytes.remove(9);
+        }
+        std::fs::write(&file, &bytes).unwrap();
+        let imported = Interp3D::from_file(&file);
+        assert_eq!(imported.iter_points().collect::<Vec<_>>(), ip.iter_points().collect::<Vec<_>>(), "version {}", version);
+    }
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn corrupted_binary_is_rejected_by_the_checksum() {
+    let ip = Interp3D::from_config(f, &small_config());
+    let file = temp_file("corrupted.bin");
+    ip.export_data_binary(&file).unwrap();
+    let bytes = std::fs::read(&file).unwrap();
+
+    // a single flipped bit in a sample and in the checksum itself
+    for at in [bytes.len() - 100, bytes.len() - 1] {
+        let mut corrupted = bytes.clone();
+        corrupted[at] ^= 0x10;
+        std::fs::write(&file, &corrupted).unwrap();
+        let error = Interp3D::default().import_data(&file).unwrap_err();
+        assert!(error.to_string().contains("checksum mismatch"), "{}", error);
+    }
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn binary_fixture_is_read_on_any_host() {
+    // written once and checked in, the format is little-endian regardless of the machine that reads or writes it
+    let ip = Interp3D::from_file("tests/fixtures/tiny.bin");
+    assert_eq!(ip.dims(), (2, 2, 3));
+    assert_eq!(ip.grid_z(), &[-1.0, 0.0, 1.0]);
+    let values: Vec<f64> = ip.iter().map(|node| node.value).collect();
+    assert_eq!(values, (1..=12).map(|v| v as f64 + 0.5).collect::<Vec<_>>());
+    assert_eq!(ip.config(), None);
+
+    // exporting it again gives the same bytes
+    let file = temp_file("tiny.bin");
+    ip.export_data_binary(&file).unwrap();
+    assert_eq!(std::fs::read(&file).unwrap(), std::fs::read("tests/fixtures/tiny.bin").unwrap());
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn f32_storage_survives_export_and_import() {
+    let mut ip = Interp3D::default();
+    ip.set_storage(Storage::F32);
+    ip.generate_data(f, &small_config());
+    let points: Vec<_> = ip.iter_points().collect();
+
+    for (name, binary) in [("f32.ip3d", false), ("f32.bin", true)] {
+        let file = temp_file(name);
+        if binary {
+            ip.export_data_binary(&file).unwrap();
+        } else {
+            ip.export_data(&file).unwrap();
+        }
+
+        // a default interpolator would keep f64 files at f64
+        let imported = Interp3D::from_file(&file);
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(imported.storage(), Storage::F32);
+        assert_eq!(imported.iter_points().collect::<Vec<_>>(), points);
+    }
+
+    // f32 samples take half the space of the f64 ones
+    let mut full = Interp3D::from_config(f, &small_config());
+    let (file, file_full) = (temp_file("half.bin"), temp_file("full.bin"));
+    ip.export_data_binary(&file).unwrap();
+    full.export_data_binary(&file_full).unwrap();
+    let samples = 4*5*6*7;
+    assert_eq!(std::fs::metadata(&file_full).unwrap().len() - std::fs::metadata(&file).unwrap().len(), samples as u64);
+
+    // the current storage is kept for f64 files
+    full.set_storage(Storage::F32);
+    full.import_data(&file_full).unwrap();
+    assert_eq!(full.storage(), Storage::F32);
+    std::fs::remove_file(&file).unwrap();
+    std::fs::remove_file(&file_full).unwrap();
+}
+
+#[test]
+fn missing_file_is_an_io_error() {
+    match Interp3D::try_from_file(&temp_file("does-not-exist.ip3d")) {
+        Err(Interp3DError::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::NotFound),
+        other => panic!("unexpected {:?}", other.map(|_| ()))
+    }
+}
+
+#[test]
+fn npz_fixture_has_the_numpy_ordering() {
+    // written like np.savez("tiny.npz", x=[0, 1], y=[0, 0.5, 1], z=[-1, 0, 1, 2], data=100*i + 10*j + k) with data.shape == (2, 3, 4)
+    let ip = Interp3D::from_npz("tests/fixtures/tiny.npz").unwrap();
+
+    assert_eq!(ip.dims(), (2, 3, 4));
+    assert_eq!(ip.grid_y(), &[0.0, 0.5, 1.0]);
+    assert_eq!(ip.grid_z(), &[-1.0, 0.0, 1.0, 2.0]);
+    for i in 0..2 {
+        for j in 0..3 {
+            for k in 0..4 {
+                assert_eq!(ip.value_at(i, j, k), (100*i + 10*j + k) as f64);
+            }
+        }
+    }
+}
+
+#[test]
+fn npz_round_trip_is_exact() {
+    let ip = Interp3D::from_config(f, &small_config());
+    let file = temp_file("round-trip.npz");
+    ip.export_npz(&file).unwrap();
+    let read = Interp3D::from_npz(&file);
+    std::fs::remove_file(&file).unwrap();
+    let read = read.unwrap();
+
+    assert_eq!(read.dims(), ip.dims());
+    assert_eq!((read.grid_x(), read.grid_y(), read.grid_z()), (ip.grid_x(), ip.grid_y(), ip.grid_z()));
+    assert!(ip.iter_points().zip(read.iter_points()).all(|(a, b)| a == b));
+    assert_eq!(read.interpolate(1.1, 0.2, 2.9), ip.interpolate(1.1, 0.2, 2.9));
+}
+
+#[test]
+fn npz_round_trip_keeps_the_axis_transforms() {
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 1.0, 11, GridSpacing::Linear)
+        .y(-1.0, 1.0, 6, GridSpacing::Linear)
+        .z(0.0, 2.0, 7, GridSpacing::Linear)
+        .build();
+    let mut ip = Interp3D::default();
+    ip.set_axis_transform(Dir::X, AxisTransform::Log10);
+    ip.set_axis_transform(Dir::Z, AxisTransform::Ln);
+    ip.generate_data(|x, y, z| x + y*z.ln(), &conf);
+
+    let file = temp_file("transforms.npz");
+    ip.export_npz(&file).unwrap();
+    let read = Interp3D::from_npz(&file);
+    std::fs::remove_file(&file).unwrap();
+    let read = read.unwrap();
+
+    assert_eq!(read.axis_transform(Dir::X), AxisTransform::Log10);
+    assert_eq!(read.axis_transform(Dir::Y), AxisTransform::Identity);
+    assert_eq!(read.axis_transform(Dir::Z), AxisTransform::Ln);
+    for &(x, y, z) in &[(5.0, 0.3, 2.0), (1.5, -0.7, 6.5), (9.9, 1.0, 1.1)] {
+        assert_eq!(read.interpolate(x, y, z), ip.interpolate(x, y, z));
+    }
+    // the nodes are read in log10(x), not as x
+    assert!((read.interpolate(5.0, 0.0, 1.0) - 5.0).abs() < 1e-3);
+
+    // custom transforms can't be stored, so nothing is written
+    let mut custom = ip.clone();
+    custom.set_axis_transform(Dir::Y, AxisTransform::Custom(f64::sqrt, |u| u*u));
+    assert!(matches!(custom.export_npz(&file), Err(Interp3DError::UnsupportedTransform { dir: Dir::Y })));
+    assert!(!std::path::Path::new(&file).exists());
+}
+
+#[test]
+fn npz_rejects_other_files() {
+    let file = temp_file("not.npz");
+    std::fs::write(&file, "2 2 2\n").unwrap();
+    let read = Interp3D::from_npz(&file);
+    std::fs::remove_file(&file).unwrap();
+    assert!(matches!(read, Err(Interp3DError::Parse { .. })));
+
+    // a corrupted byte in the data is caught by the checksum
+    let mut bytes = std::fs::read("tests/fixtures/tiny.npz").unwrap();
+    let at = bytes.windows(6).rposition(|w| w == b"\x93NUMPY").unwrap() + 200;
+    bytes[at] ^= 1;
+    let file = temp_file("corrupt.npz");
+    std::fs::write(&file, bytes).unwrap();
+    let read = Interp3D::from_npz(&file);
+    std::fs::remove_file(&file).unwrap();
+    match read {
+        Err(Interp3DError::Parse { message, .. }) => assert!(message.contains("checksum")),
+        other => panic!("expected a checksum error, got {:?}", other.map(|_| ()))
+    }
+}
+
+#[test]
+fn vtk_export_uses_x_fastest_ordering() {
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 2.0, 3, GridSpacing::Linear)
+        .y(-1.0, 1.0, 4, GridSpacing::Exponential(2.0))
+        .z_nodes(vec![0.0, 0.1, 0.3, 0.7, 1.5])
+        .build();
+    let ip = Interp3D::from_config(|x, y, z| x + 10.0*y + 100.0*z, &conf);
+    let file = temp_file("grid.vtk");
+    ip.export_vtk(&file).unwrap();
+    let content = std::fs::read_to_string(&file).unwrap();
+    std::fs::remove_file(&file).unwrap();
+
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines[0], "# vtk DataFile Version 3.0");
+    assert_eq!(&lines[2..5], &["ASCII", "DATASET RECTILINEAR_GRID", "DIMENSIONS 3 4 5"]);
+    let numbers = |line: &str| -> Vec<f64> { line.split_whitespace().map(|t| t.parse().unwrap()).collect() };
+    assert_eq!((lines[5], numbers(lines[6])), ("X_COORDINATES 3 double", ip.grid_x().to_vec()));
+    assert_eq!((lines[7], numbers(lines[8])), ("Y_COORDINATES 4 double", ip.grid_y().to_vec()));
+    assert_eq!((lines[9], numbers(lines[10])), ("Z_COORDINATES 5 double", ip.grid_z().to_vec()));
+    assert_eq!(&lines[11..14], &["POINT_DATA 60", "SCALARS value double 1", "LOOKUP_TABLE default"]);
+
+    let values: Vec<f64> = lines[14..].iter().flat_map(|l| numbers(l)).collect();
+    assert_eq!(values.len(), 60);
+    for (p, v) in values.into_iter().enumerate() {
+        let (i, j, k) = (p%3, (p/3)%4, p/12);
+        assert_eq!(v, ip.value_at(i, j, k));
+    }
+}
+
+#[test]
+fn builder_applies_its_settings_to_imported_data() {
+    let file = temp_file("builder.ip3d");
+    let conf = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 5).build();
+    Interp3D::from_config(|x, y, z| x + y + z, &conf).export_data(&file).unwrap();
+
+    let ip = Interp3DBuilder::default()
+        .interpolation(Type::Trilinear)
+        .out_of_bounds(OutOfBounds::ReturnNan)
+        .storage(Storage::F32)
+        .build_from_file(&file)
+        .unwrap();
+    assert_eq!(ip.storage(), Storage::F32);
+    assert!((ip.interpolate(0.1, 0.2, 0.3) - 0.6).abs() < 1e-6);
+    assert!(ip.interpolate(1.1, 0.2, 0.3).is_nan());
+
+    assert!(matches!(Interp3DBuilder::default().build_from_file(&temp_file("missing.ip3d")).map(|_| ()), Err(Interp3DError::Io(_))));
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn text_round_trip_compares_equal_with_a_tolerance() {
+    let file = temp_file("approx.ip3d");
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 2.0, 7, GridSpacing::Exponential(3.0))
+        .y(-1.0, 1.0, 4, GridSpacing::Chebyshev)
+        .build();
+    let mut ip = Interp3D::default();
+    ip.set_storage(Storage::F32);
+    ip.generate_data(|x, y, z| (x*y).exp() + z, &conf);
+    ip.export_data(&file).unwrap();
+
+    let mut reference = Interp3D::from_config(|x, y, z| (x*y).exp() + z, &conf);
+    let imported = Interp3D::try_from_file(&file).unwrap();
+    assert!(imported.same_grid(&reference, 1e-12));
+    assert!(imported.approx_eq(&reference, 1e-6, 0.0));
+    assert!(!imported.approx_eq(&reference, 1e-12, 0.0));
+    let (diff, _) = imported.max_abs_diff(&reference).unwrap();
+    assert!(diff > 0.0 && diff < 1e-5);
+
+    // the worst node is found, NaN only matches NaN
+    reference.set_value(3, 1, 2, 100.0);
+    let (diff, node) = imported.max_abs_diff(&reference).unwrap();
+    assert_eq!(node, (3, 1, 2));
+    assert!((diff - (100.0 - imported.value_at(3, 1, 2))).abs() < 1e-12);
+    reference.set_value(0, 0, 0, f64::NAN);
+    assert_eq!(imported.max_abs_diff(&reference).unwrap(), (f64::INFINITY, (0, 0, 0)));
+    assert!(!imported.approx_eq(&reference, 1.0, 1e9));
+
+    // other grids
+    let coarser = Interp3D::from_config(|_, _, _| 0.0, &DataGenConfBuilder::default().x(0.0, 2.0, 6, GridSpacing::Linear).build());
+    assert!(!coarser.same_grid(&reference, 1.0));
+    assert!(matches!(coarser.max_abs_diff(&reference), Err(Interp3DError::GridMismatch { dir: Dir::X })));
+    assert!(matches!(Interp3D::default().max_abs_diff(&reference), Err(Interp3DError::NotSetUp)));
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn hermite_data_survives_export_and_import() {
+    let gradient = |x: f64, y: f64, z: f64| [y*(x*y).cos(), x*(x*y).cos(), 2.0*z/3.0];
+    let mut ip = Interp3D::default();
+    ip.generate_data_hermite(|x, y, z| (f(x, y, z), gradient(x, y, z)), &small_config());
+
+    for (name, binary) in [("hermite.ip3d", false), ("hermite.bin", true)] {
+        let file = temp_file(name);
+        if binary {
+            ip.export_data_binary(&file).unwrap();
+            assert_eq!(std::fs::read(&file).unwrap()[8], 3);
+        } else {
+            ip.export_data(&file).unwrap();
+        }
+
+        let imported = Interp3D::from_file(&file);
+        assert!(imported.has_hermite_data());
+        for &(x, y, z) in &[(0.3, 0.1, 2.9), (1.99, -0.8, 0.2), (1.0, 0.0, 1.5)] {
+            assert_eq!(imported.interpolate(x, y, z).to_bits(), ip.interpolate(x, y, z).to_bits());
+        }
+
+        // the vector version doesn't take derivatives
+        assert!(Interp3DVec::<1>::default().import_data(&file).is_err());
+        std::fs::remove_file(&file).unwrap();
+    }
+
+    // without derivatives, the flags only announce the checksum
+    let plain = small_table_without_config();
+    let file = temp_file("plain.bin");
+    plain.export_data_binary(&file).unwrap();
+    assert_eq!(std::fs::read(&file).unwrap()[10], 8);
+    assert!(!Interp3D::from_file(&file).has_hermite_data());
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn streamed_generation_matches_the_binary_export() {
+    let conf = small_config();
+    let ip = Interp3D::from_config(f, &conf);
+    let exported = temp_file("exported.bin");
+    ip.export_data_binary(&exported).unwrap();
+
+    // 2 planes per slab doesn't divide the 5 planes along X
+    for chunk_planes in [0, 2, 5, 10] {
+        let streamed = temp_file(&format!("streamed-{}.bin", chunk_planes));
+        Interp3D::generate_data_to_file(f, &conf, &streamed, chunk_planes).unwrap();
+        assert_eq!(std::fs::read(&streamed).unwrap(), std::fs::read(&exported).unwrap());
+
+        let mut read = Interp3D::default();
+        read.import_data(&streamed).unwrap();
+        std::fs::remove_file(&streamed).unwrap();
+        assert!(read.approx_eq(&ip, 0.0, 0.0));
+        assert_eq!(read.value_at(4, 0, 6), f(2.0, -1.0, 3.0));
+    }
+    std::fs::remove_file(&exported).unwrap();
+}
+
+#[test]
+fn interrupted_streamed_generation_is_rejected() {
+    let file = temp_file("interrupted.bin");
+    let invalid = DataGenConfBuilder::default().x(1.0, 0.0, 5, GridSpacing::Linear).build();
+    assert!(matches!(Interp3D::generate_data_to_file(f, &invalid, &file, 1), Err(Interp3DError::InvalidRange { dir: Dir::X, .. })));
+    assert!(!std::path::Path::new(&file).exists());
+
+    let interrupted = std::panic::catch_unwind(|| {
+        Interp3D::generate_data_to_file(|x, y, z| if x > 1.0 { panic!("closure failed") } else { f(x, y, z) }, &small_config(), &file, 1)
+    });
+    assert!(interrupted.is_err());
+
+    let mut ip = Interp3D::default();
+    let error = ip.import_data(&file).unwrap_err();
+    assert!(error.to_string().contains("incomplete binary file"), "{}", error);
+    assert!(Interp3DVec::<1>::try_from_file(&file).is_err());
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn axis_transforms_survive_export_and_import() {
+    let conf = DataGenConfBuilder::default().x(-1.0, 1.0, 5, GridSpacing::Linear).y(0.5, 2.0, 4, GridSpacing::Linear).z(-1.0, 3.0, 6, GridSpacing::Linear).build();
+    let mut ip = Interp3D::default();
+    ip.set_axis_transform(Dir::X, AxisTransform::Log10);
+    ip.set_axis_transform(Dir::Y, AxisTransform::Ln);
+    ip.generate_data(f, &conf);
+
+    for (name, binary) in [("transform.ip3d", false), ("transform.bin", true)] {
+        let file = temp_file(name);
+        if binary {
+            ip.export_data_binary(&file).unwrap();
+            assert_eq!(std::fs::read(&file).unwrap()[8], 3);
+        } else {
+            ip.export_data(&file).unwrap();
+        }
+
+        // the transforms come from the file, not from the interpolator that reads it
+        let mut imported = Interp3D::default();
+        imported.set_axis_transform(Dir::Z, AxisTransform::Ln);
+        imported.import_data(&file).unwrap();
+        assert_eq!([Dir::X, Dir::Y, Dir::Z].map(|dir| imported.axis_transform(dir)), [AxisTransform::Log10, AxisTransform::Ln, AxisTransform::Identity]);
+        assert_eq!(imported.interpolate(2.5, 3.0, 0.7).to_bits(), ip.interpolate(2.5, 3.0, 0.7).to_bits());
+        std::fs::remove_file(&file).unwrap();
+    }
+
+    // a custom transform can't be written
+    let mut custom = Interp3D::from_config(f, &small_config());
+    custom.set_axis_transform(Dir::Z, AxisTransform::Custom(f64::sqrt, |u| u*u));
+    for binary in [false, true] {
+        let file = temp_file(if binary { "custom.bin" } else { "custom.ip3d" });
+        let result = if binary { custom.export_data_binary(&file) } else { custom.export_data(&file) };
+        assert!(matches!(result, Err(Interp3DError::UnsupportedTransform { dir: Dir::Z })));
+        assert!(!std::path::Path::new(&file).exists());
+    }
+}
+
+#[test]
+fn generation_config_survives_export_and_import() {
+    let configs = [
+        DataGenConf {
+            x: DataGenConfSingle { n: 5, min: 0.1, max: 2.0, spacing: GridSpacing::Exponential(8.0) },
+            y: DataGenConfSingle { n: 6, min: -1.0, max: 1.0, spacing: GridSpacing::TwoSidedExponential(-1.5) },
+            z: DataGenConfSingle::custom(vec![0.0, 0.25, 1.0, 3.0])
+        },
+        DataGenConf {
+            x: DataGenConfSingle { n: 4, min: 1e-3, max: 1e3, spacing: GridSpacing::Logarithmic },
+            y: DataGenConfSingle { n: 5, min: -1.0, max: 1.0, spacing: GridSpacing::Chebyshev },
+            z: DataGenConfSingle { n: 3, min: 0.0, max: 1.0, spacing: GridSpacing::Linear }
+        }
+    ];
+
+    for (c, conf) in configs.iter().enumerate() {
+        let ip = Interp3D::from_config(f, conf);
+        for binary in [false, true] {
+            let file = temp_file(&format!("config-{}.{}", c, if binary { "bin" } else { "ip3d" }));
+            if binary {
+                ip.export_data_binary(&file).unwrap();
+            } else {
+                ip.export_data(&file).unwrap();
+            }
+
+            let imported = Interp3D::from_file(&file);
+            std::fs::remove_file(&file).unwrap();
+            assert_eq!(imported.generation_config().as_ref(), imported.config());
+            let read = imported.config().expect("config written to the file");
+            for (read, original) in [(&read.x, &conf.x), (&read.y, &conf.y), (&read.z, &conf.z)] {
+                assert_eq!((read.n, read.min, read.max), (original.n, original.min, original.max));
+                assert_eq!(read.spacing, original.spacing);
+            }
+            assert!(imported.approx_eq(&ip, 0.0, 0.0));
+        }
+    }
+
+    // streamed tables know their config as well
+    let file = temp_file("config-streamed.bin");
+    Interp3D::generate_data_to_file(f, &configs[0], &file, 2).unwrap();
+    assert_eq!(Interp3D::from_file(&file).config(), Some(&configs[0]));
+    std::fs::remove_file(&file).unwrap();
+
+    // tables built from their nodes have none, in both formats
+    let plain = small_table_without_config();
+    for name in ["no-config.ip3d", "no-config.bin"] {
+        let file = temp_file(name);
+        if name.ends_with("bin") {
+            plain.export_data_binary(&file).unwrap();
+        } else {
+            plain.export_data(&file).unwrap();
+        }
+        assert_eq!(Interp3D::from_file(&file).generation_config(), None);
+        std::fs::remove_file(&file).unwrap();
+    }
+}
+
+#[test]
+fn text_export_options_round_trip() {
+    let gradient = |x: f64, y: f64, z: f64| [y*(x*y).cos(), x*(x*y).cos(), 2.0*z/3.0];
+    let mut hermite = Interp3D::default();
+    hermite.generate_data_hermite(|x, y, z| (f(x, y, z), gradient(x, y, z)), &small_config());
+    let mut reduced = Interp3D::default();
+    reduced.set_storage(Storage::F32);
+    reduced.set_axis_transform(Dir::Z, AxisTransform::Ln);
+    reduced.generate_data(f, &small_config());
+
+    let bits = |ip: &Interp3D| -> Vec<[u64; 4]> {
+        ip.iter_points().map(|(x, y, z, v)| [x.to_bits(), y.to_bits(), z.to_bits(), v.to_bits()]).collect()
+    };
+    let file = temp_file("options.ip3d");
+    for delimiter in [' ', '\t', ',', ';'] {
+        for float_format in [FloatFormat::Shortest, FloatFormat::Scientific { digits: 16 }, FloatFormat::RoundTrip17] {
+            for (header, line_ending) in [(true, LineEnding::Lf), (false, LineEnding::CrLf)] {
+                let options = ExportOptions { delimiter, float_format, header, line_ending };
+                for ip in [&hermite, &reduced] {
+                    ip.export_data_with(&file, &options).unwrap();
+                    let imported = Interp3D::from_file(&file);
+
+                    assert_eq!(bits(&imported), bits(ip), "{:?}", options);
+                    assert_eq!(imported.config(), ip.config());
+                    assert_eq!(imported.storage(), ip.storage());
+                    assert_eq!(imported.axis_transform(Dir::Z), ip.axis_transform(Dir::Z));
+                    assert_eq!(imported.has_hermite_data(), ip.has_hermite_data());
+                    for &(x, y, z) in &[(0.3, 0.1, 2.9), (1.99, -0.8, 0.2), (1.0, 0.0, 1.5)] {
+                        assert_eq!(imported.interpolate(x, y, z).to_bits(), ip.interpolate(x, y, z).to_bits());
+                    }
+                }
+
+                let text = std::fs::read_to_string(&file).unwrap();
+                assert_eq!(text.contains('#'), header);
+                assert_eq!(text.contains("\r\n"), line_ending == LineEnding::CrLf);
+            }
+        }
+    }
+
+    // the vector version uses the same format
+    let vector = Interp3DVec::<2>::from_config(|x, y, z| [f(x, y, z), x - y], &small_config());
+    vector.export_data_with(&file, &ExportOptions { delimiter: ';', float_format: FloatFormat::RoundTrip17, ..ExportOptions::default() }).unwrap();
+    let imported = Interp3DVec::<2>::from_file(&file);
+    assert_eq!(imported.value_at(3, 4, 5), vector.value_at(3, 4, 5));
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn rounded_text_export_has_fixed_width() {
+    let ip = small_table_without_config();
+    let file = temp_file("fixed-width.ip3d");
+    let options = ExportOptions { float_format: FloatFormat::Scientific { digits: 3 }, header: false, ..ExportOptions::default() };
+    ip.export_data_with(&file, &options).unwrap();
+
+    // the grid dims are integers, all other lines are numbers of 11 characters each
+    let text = std::fs::read_to_string(&file).unwrap();
+    let mut lines = text.lines();
+    assert_eq!(lines.next(), Some("5 6 7"));
+    for line in lines {
+        assert_eq!(line.len() % 12, 11, "{:?}", line);
+    }
+    assert!(text.contains("  2.000e0 "));
+
+    let imported = Interp3D::from_file(&file);
+    std::fs::remove_file(&file).unwrap();
+    for (a, b) in imported.iter_points().zip(ip.iter_points()) {
+        assert!((a.3 - b.3).abs() <= 5e-4*b.3.abs().max(1.0), "{:?} {:?}", a, b);
+    }
+}
+
+#[test]
+fn unreadable_delimiter_is_rejected() {
+    let ip = small_table_without_config();
+    let file = temp_file("pipe.ip3d");
+    let result = ip.export_data_with(&file, &ExportOptions { delimiter: '|', ..ExportOptions::default() });
+    assert!(matches!(result, Err(Interp3DError::UnsupportedDelimiter { delimiter: '|' })));
+    assert!(!std::path::Path::new(&file).exists());
+}
+
+#[test]
+fn format_is_detected_by_content() {
+    let binary = Interp3D::from_file("tests/fixtures/tiny.bin");
+    let text = Interp3D::from_file("tests/fixtures/tiny.txt");
+    assert_eq!(text.iter_points().collect::<Vec<_>>(), binary.iter_points().collect::<Vec<_>>());
+
+    let npz = Interp3D::from_file("tests/fixtures/tiny.npz");
+    assert_eq!(npz.value_at(1, 2, 3), 123.0);
+
+    // a binary file despite its name
+    let mislabeled = Interp3D::from_file("tests/fixtures/mislabeled.npz");
+    assert_eq!(mislabeled.iter_points().collect::<Vec<_>>(), binary.iter_points().collect::<Vec<_>>());
+
+    // import_data keeps the settings for every format
+    let mut ip = Interp3D::default();
+    ip.set_out_of_bounds(OutOfBounds::ReturnNan);
+    ip.import_data("tests/fixtures/tiny.npz").unwrap();
+    assert!(ip.interpolate(5.0, 0.0, 0.0).is_nan());
+}
+
+#[test]
+fn unreadable_formats_say_what_they_look_like() {
+    let error = Interp3D::try_from_file("tests/fixtures/tiny.txt.gz").unwrap_err().to_string();
+    assert!(error.contains("gzip") && error.contains("text, binary and npz"), "{}", error);
+    assert!(Interp3DVec::<1>::try_from_file("tests/fixtures/tiny.txt.gz").is_err());
+
+    let error = Interp3DVec::<2>::try_from_file("tests/fixtures/tiny.npz").err().unwrap().to_string();
+    assert!(error.contains("npz archive"), "{}", error);
+
+    let file = temp_file("series.ip3d");
+    Interp3DSeries::new(vec![(0.0, Interp3D::from_file("tests/fixtures/tiny.bin"))]).export_data(&file).unwrap();
+    let error = Interp3D::try_from_file(&file).unwrap_err().to_string();
+    std::fs::remove_file(&file).unwrap();
+    assert!(error.contains("Interp3DSeries::from_file()"), "{}", error);
+}
+
+#[test]
+fn explicit_format_overrides_the_detection() {
+    let binary = Interp3D::from_file("tests/fixtures/tiny.bin");
+    for (file, format) in [("tests/fixtures/tiny.txt", FileFormat::Text), ("tests/fixtures/mislabeled.npz", FileFormat::Binary)] {
+        let ip = Interp3D::from_file_format(file, format);
+        assert_eq!(ip.iter_points().collect::<Vec<_>>(), binary.iter_points().collect::<Vec<_>>());
+    }
+
+    // a text file that happens to start like a zip archive
+    let file = temp_file("pk.ip3d");
+    std::fs::write(&file, "PK\n2 2 3").unwrap();
+    let error = Interp3D::try_from_file(&file).unwrap_err().to_string();
+    assert!(error.contains("read as a npz archive by its content"), "{}", error);
+    let error = Interp3D::try_from_file_format(&file, FileFormat::Text).unwrap_err().to_string();
+    assert!(error.contains("'PK'") && error.contains("read as text, but it looks like a npz archive"), "{}", error);
+    std::fs::remove_file(&file).unwrap();
+
+    // the wrong format is reported together with what the file looks like
+    let error = Interp3D::try_from_file_format("tests/fixtures/tiny.txt", FileFormat::Npz).unwrap_err().to_string();
+    assert!(error.contains("read as a npz archive, but it looks like text"), "{}", error);
+    let error = Interp3D::try_from_file_format("tests/fixtures/tiny.npz", FileFormat::Binary).unwrap_err().to_string();
+    assert!(error.contains("not a binary data file") && error.contains("looks like a npz archive"), "{}", error);
+
+    // a text file named like an archive is read as text, the extension only shows up in errors
+    let file = temp_file("text.npz");
+    std::fs::write(&file, "2 2 3\n0 1\n0 1\n0 1 x").unwrap();
+    let error = Interp3D::try_from_file(&file).unwrap_err().to_string();
+    std::fs::remove_file(&file).unwrap();
+    assert!(error.contains("read as text by its content, although the extension suggests a npz archive"), "{}", error);
+}
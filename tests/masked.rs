@@ -0,0 +1,112 @@
+use interp3d::*;
+
+fn config() -> DataGenConf {
+    DataGenConfBuilder::default().uniform_cube(-1.0, 1.0, 21).build()
+}
+
+fn f(x: f64, y: f64, z: f64) -> f64 {
+    1.0 + x + 0.5*y*z
+}
+
+/// ``f`` outside of a sphere of radius 0.45 around ``(0.2, 0, 0)``, nothing inside
+fn masked(x: f64, y: f64, z: f64) -> Option<f64> {
+    let r2 = (x - 0.2).powi(2) + y*y + z*z;
+    (r2 > 0.45*0.45).then(|| f(x, y, z))
+}
+
+fn generate(fill: FillStrategy) -> Interp3D {
+    let mut ip = Interp3D::default();
+    ip.generate_data_masked(masked, &config(), fill);
+    ip
+}
+
+/// The samples of the nodes inside of the sphere
+fn hole(ip: &Interp3D) -> Vec<f64> {
+    ip.iter().filter(|node| masked(node.x, node.y, node.z).is_none()).map(|node| node.value).collect()
+}
+
+#[test]
+fn the_mask_marks_the_sphere() {
+    let ip = generate(FillStrategy::Nan);
+    assert_eq!(ip.missing_nodes(), hole(&ip).len());
+    assert!(ip.missing_nodes() > 0);
+    assert!(hole(&ip).iter().all(|v| v.is_nan()));
+
+    assert!(!ip.is_valid_region(0.2, 0.0, 0.0));
+    assert!(ip.interpolate(0.2, 0.0, 0.0).is_nan());
+    // far enough from the sphere that the stencil doesn't touch it
+    assert!(ip.is_valid_region(-0.8, 0.7, -0.6));
+    assert_eq!(ip.interpolate(-0.8, 0.7, -0.6), Interp3D::from_config(f, &config()).interpolate(-0.8, 0.7, -0.6));
+
+    // the valid nodes are the ones the closure had a value for
+    for node in ip.iter().filter(|node| masked(node.x, node.y, node.z).is_some()) {
+        assert_eq!(node.value, f(node.x, node.y, node.z));
+    }
+
+    // generating again without holes drops the mask
+    let mut ip = ip;
+    ip.generate_data(f, &config());
+    assert_eq!(ip.missing_nodes(), 0);
+    assert!(ip.is_valid_region(0.2, 0.0, 0.0));
+}
+
+#[test]
+fn constant_fill() {
+    let ip = generate(FillStrategy::Constant(-7.0));
+    assert!(hole(&ip).iter().all(|&v| v == -7.0));
+    assert!((ip.interpolate(0.2, 0.0, 0.0) + 7.0).abs() < 1e-12);
+}
+
+#[test]
+fn nearest_valid_fill_copies_the_closest_node() {
+    let ip = generate(FillStrategy::NearestValid);
+    let valid: Vec<GridNode> = ip.iter().filter(|node| masked(node.x, node.y, node.z).is_some()).collect();
+    for node in ip.iter().filter(|node| masked(node.x, node.y, node.z).is_none()) {
+        let distance = |other: &GridNode| other.i.abs_diff(node.i) + other.j.abs_diff(node.j) + other.k.abs_diff(node.k);
+        let closest = valid.iter().map(distance).min().unwrap();
+        assert!(valid.iter().any(|other| distance(other) == closest && other.value == node.value), "{:?}", node);
+    }
+}
+
+#[test]
+fn iterative_average_fill_is_smooth() {
+    let rough = generate(FillStrategy::IterativeAverage { passes: 0 });
+    let smooth = generate(FillStrategy::IterativeAverage { passes: 200 });
+    assert!(hole(&rough).iter().chain(&hole(&smooth)).all(|v| v.is_finite()));
+
+    // the filled values stay within the range of the valid ones around the hole
+    let (lo, hi) = (f(-0.25, 0.45, 0.45).min(f(-0.25, -0.45, 0.45)), f(0.65, 0.45, 0.45));
+    assert!(hole(&smooth).iter().all(|&v| (lo..=hi).contains(&v)));
+
+    // f is harmonic (its Laplacian vanishes), which the relaxed fill approaches
+    let error = |ip: &Interp3D| ip.iter()
+        .filter(|node| masked(node.x, node.y, node.z).is_none())
+        .map(|node| (node.value - f(node.x, node.y, node.z)).abs())
+        .fold(0.0, f64::max);
+    assert!(error(&smooth) < 0.2*error(&rough), "{} vs {}", error(&smooth), error(&rough));
+    assert!(error(&smooth) < 1e-3, "{}", error(&smooth));
+}
+
+#[test]
+fn nothing_to_fill_from_leaves_nan() {
+    let mut ip = Interp3D::default();
+    ip.generate_data_masked(|_, _, _| None, &config(), FillStrategy::IterativeAverage { passes: 3 });
+    assert!(ip.iter().all(|node| node.value.is_nan()));
+    assert!(!ip.is_valid_region(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn regenerated_nodes_count_as_valid() {
+    let mut ip = generate(FillStrategy::Nan);
+    let missing = ip.missing_nodes();
+
+    // half of the sphere
+    ip.regenerate_region(f, (0.2, 1.0), (-1.0, 1.0), (-1.0, 1.0));
+    assert!(ip.missing_nodes() > 0 && ip.missing_nodes() < missing);
+    assert!(ip.is_valid_region(0.6, 0.0, 0.0));
+
+    ip.regenerate_region(f, (-1.0, 1.0), (-0.5, 0.5), (-0.5, 0.5));
+    assert_eq!(ip.missing_nodes(), 0);
+    assert!(ip.is_valid_region(0.2, 0.0, 0.0));
+    assert!((ip.interpolate(0.2, 0.0, 0.0) - f(0.2, 0.0, 0.0)).abs() < 1e-14);
+}
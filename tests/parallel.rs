@@ -0,0 +1,115 @@
+#![cfg(feature = "parallel")]
+
+mod common;
+
+use interp3d::*;
+
+#[test]
+fn parallel_generation_is_bit_identical() {
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 4.0, 23, GridSpacing::Exponential(2.0))
+        .y(-1.0, 1.0, 17, GridSpacing::Linear)
+        .z(0.0, 3.0, 11, GridSpacing::Linear)
+        .build();
+    let f = |x: f64, y: f64, z: f64| (x*y).sin()*z.exp() + (x - z).powi(3);
+
+    let serial = Interp3D::from_config(f, &conf);
+    let mut parallel = Interp3D::default();
+    parallel.generate_data_par(f, &conf);
+
+    let bits = |ip: &Interp3D| -> Vec<u64> { ip.iter_points().map(|p| p.3.to_bits()).collect() };
+    assert_eq!(bits(&parallel), bits(&serial));
+
+    let mut serial_f32 = Interp3D::default();
+    serial_f32.set_storage(Storage::F32);
+    serial_f32.generate_data(f, &conf);
+    let mut parallel_f32 = Interp3D::default();
+    parallel_f32.set_storage(Storage::F32);
+    parallel_f32.generate_data_par(f, &conf);
+    assert_eq!(bits(&parallel_f32), bits(&serial_f32));
+    assert_eq!(parallel.config(), Some(&conf));
+    for &(x, y, z) in &[(0.0, -1.0, 0.0), (1.3, 0.2, 2.2), (4.0, 1.0, 3.0), (4.5, 1.2, -0.3)] {
+        assert_eq!(parallel.interpolate(x, y, z).to_bits(), serial.interpolate(x, y, z).to_bits());
+    }
+}
+
+#[test]
+fn parallel_iteration_visits_every_node_once() {
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 1.0, 9, GridSpacing::Linear)
+        .y(0.0, 2.0, 4, GridSpacing::Linear)
+        .z(0.0, 3.0, 6, GridSpacing::Linear)
+        .build();
+    let ip = Interp3D::from_config(|x, y, z| x + 10.0*y + 100.0*z, &conf);
+
+    let visited = std::sync::Mutex::new(Vec::new());
+    ip.par_for_each(|node| visited.lock().unwrap().push(node));
+    let mut visited = visited.into_inner().unwrap();
+    visited.sort_by_key(|node| (node.i, node.j, node.k));
+
+    assert_eq!(visited, ip.iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn parallel_queries_match_the_sequential_ones() {
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 4.0, 23, GridSpacing::Exponential(2.0))
+        .y(-1.0, 1.0, 17, GridSpacing::Linear)
+        .z(0.0, 3.0, 11, GridSpacing::Linear)
+        .build();
+    let ip = Interp3D::from_config(|x, y, z| (x*y).sin()*z.exp() + (x - z).powi(3), &conf);
+
+    let mut random = common::uniform(7);
+    // a little beyond the domain as well
+    let points: Vec<(f64, f64, f64)> = (0..300_000).map(|_| (4.2*random() - 0.1, 2.2*random() - 1.1, 3.2*random() - 0.1)).collect();
+
+    let bits = |values: &[f64]| -> Vec<u64> { values.iter().map(|v| v.to_bits()).collect() };
+    let sequential = ip.interpolate_many(&points);
+    assert_eq!(bits(&ip.par_interpolate_many(&points)), bits(&sequential));
+
+    let mut buffer = vec![f64::NAN; points.len()];
+    ip.par_interpolate_many_into(&points, &mut buffer);
+    assert_eq!(bits(&buffer), bits(&sequential));
+    assert!(ip.par_interpolate_many(&[]).is_empty());
+}
+
+#[test]
+fn parallel_lattice_matches_the_sequential_one() {
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 4.0, 23, GridSpacing::Exponential(2.0))
+        .y(-1.0, 1.0, 17, GridSpacing::Linear)
+        .z(0.0, 3.0, 11, GridSpacing::Linear)
+        .build();
+    let ip = Interp3D::from_config(|x, y, z| (x*y).sin()*z.exp() + (x - z).powi(3), &conf);
+
+    let lattice = |n: usize, min: f64, max: f64| -> Vec<f64> { (0..n).map(|i| min + (max - min)*i as f64/(n - 1) as f64).collect() };
+    let (xq, yq, zq) = (lattice(61, -0.1, 4.1), lattice(40, -1.1, 1.1), lattice(25, -0.1, 3.1));
+
+    let bits = |values: &[f64]| -> Vec<u64> { values.iter().map(|v| v.to_bits()).collect() };
+    assert_eq!(bits(&ip.par_interpolate_grid(&xq, &yq, &zq)), bits(&ip.interpolate_grid(&xq, &yq, &zq)));
+    assert!(ip.par_interpolate_grid(&xq, &yq, &[]).is_empty());
+}
+
+#[test]
+fn parallel_region_regeneration_is_bit_identical() {
+    let conf = DataGenConfBuilder::default()
+        .x(0.0, 4.0, 23, GridSpacing::Exponential(2.0))
+        .y(-1.0, 1.0, 17, GridSpacing::Linear)
+        .z(0.0, 3.0, 11, GridSpacing::Linear)
+        .build();
+    let f = |x: f64, y: f64, z: f64| (x*y).sin()*z.exp();
+    let g = |x: f64, y: f64, z: f64| (x - z).powi(3) + y;
+
+    for storage in [Storage::F64, Storage::F32] {
+        let mut serial = Interp3D::default();
+        serial.set_storage(storage);
+        serial.generate_data(f, &conf);
+        let mut parallel = serial.clone();
+        serial.regenerate_region(g, (0.5, 2.0), (-0.5, 0.5), (1.0, 10.0));
+        parallel.regenerate_region_par(g, (0.5, 2.0), (-0.5, 0.5), (1.0, 10.0));
+
+        let bits = |ip: &Interp3D| -> Vec<u64> { ip.iter_points().map(|p| p.3.to_bits()).collect() };
+        assert_eq!(bits(&parallel), bits(&serial));
+        assert!(parallel.iter().any(|node| node.value == g(node.x, node.y, node.z)));
+    }
+}
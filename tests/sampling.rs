@@ -0,0 +1,83 @@
+mod common;
+
+use interp3d::*;
+
+fn density(x: f64, y: f64, z: f64) -> f64 {
+    (1.0 + 3.0*x)*(0.5 + y*y)*(2.0 - z)
+}
+
+/// Chi-square of the number of points in every cell against the integral of the interpolant over it
+fn chi_square(ip: &Interp3D, points: &[(f64, f64, f64)]) -> (f64, usize) {
+    let (gx, gy, gz) = (ip.grid_x(), ip.grid_y(), ip.grid_z());
+    let cell = |v: &[f64], p: f64| v.partition_point(|&node| node <= p).clamp(1, v.len()-1) - 1;
+    let (cy, cz) = (gy.len()-1, gz.len()-1);
+
+    let mut counts = vec![0usize; (gx.len()-1)*cy*cz];
+    for &(x, y, z) in points {
+        counts[cell(gx, x)*cy*cz + cell(gy, y)*cz + cell(gz, z)] += 1;
+    }
+
+    let ((x0, x1), (y0, y1), (z0, z1)) = ip.domain();
+    let total = ip.integrate((x0, x1), (y0, y1), (z0, z1)).unwrap();
+    let mut chi2 = 0.0;
+    for (n, &count) in counts.iter().enumerate() {
+        let (i, j, k) = (n/(cy*cz), n/cz % cy, n % cz);
+        let mass = ip.integrate((gx[i], gx[i+1]), (gy[j], gy[j+1]), (gz[k], gz[k+1])).unwrap();
+        let expected = points.len() as f64*mass/total;
+        chi2 += (count as f64 - expected).powi(2)/expected;
+    }
+
+    (chi2, counts.len() - 1)
+}
+
+#[test]
+fn sampled_points_follow_the_cell_integrals() {
+    let config = DataGenConfBuilder::default()
+        .x(0.0, 1.0, 6, GridSpacing::Linear)
+        .y(-1.0, 1.0, 6, GridSpacing::Linear)
+        .z(0.0, 1.0, 5, GridSpacing::Linear)
+        .build();
+    let mut ip = Interp3D::from_config(density, &config);
+
+    // the monotone scheme is slow to evaluate without optimizations
+    for (scheme, n) in [(Type::Trilinear, 40000), (Type::Tricubic, 40000), (Type::Nearest, 40000), (Type::Blend(0.5), 40000), (Type::TricubicMonotone, 5000)] {
+        ip.set_type(scheme);
+        let points = ip.sample_points(n, 7).unwrap();
+        assert_eq!(points.len(), n);
+        assert!(points.iter().all(|&(x, y, z)| (0.0..=1.0).contains(&x) && (-1.0..=1.0).contains(&y) && (0.0..=1.0).contains(&z)));
+
+        // 99 degrees of freedom, whose chi-square stays below 160 but in one of 10^5 cases
+        let (chi2, dof) = chi_square(&ip, &points);
+        assert_eq!(dof, 99);
+        assert!(chi2 < 160.0, "{:?}: {}", scheme, chi2);
+    }
+}
+
+#[test]
+fn sampling_is_deterministic() {
+    let ip = Interp3D::from_config(density, &DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 9).build());
+    let points = ip.sample_points(500, 1).unwrap();
+    assert_eq!(points, ip.sample_points(500, 1).unwrap());
+    assert_ne!(points, ip.sample_points(500, 2).unwrap());
+    assert!(ip.sample_points(0, 1).unwrap().is_empty());
+
+    // any source of uniform random numbers works, e.g. a small LCG
+    assert_eq!(ip.sample_points_with(500, common::uniform(3)).unwrap(), ip.sample_points_with(500, common::uniform(3)).unwrap());
+}
+
+#[test]
+fn invalid_densities_are_rejected() {
+    let config = DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 5).build();
+    let mut ip = Interp3D::from_config(density, &config);
+    ip.set_value(1, 2, 3, -1e-3);
+    match ip.sample_points(10, 0) {
+        Err(Interp3DError::InvalidDensity { node }) => assert_eq!(node, Some((1, 2, 3))),
+        other => panic!("expected InvalidDensity, got {:?}", other)
+    }
+    ip.set_value(1, 2, 3, f64::NAN);
+    assert!(ip.sample_points(10, 0).unwrap_err().to_string().contains("negative or not finite"));
+
+    let zero = Interp3D::from_config(|_, _, _| 0.0, &config);
+    assert!(matches!(zero.sample_points(10, 0), Err(Interp3DError::InvalidDensity { node: None })));
+    assert!(matches!(Interp3D::default().sample_points(10, 0), Err(Interp3DError::NotSetUp)));
+}
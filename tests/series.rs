@@ -0,0 +1,86 @@
+mod common;
+
+use common::{f, temp_file};
+use interp3d::*;
+
+fn config() -> DataGenConf {
+    common::config_with((9, 12, 7), GridSpacing::Exponential(3.0))
+}
+
+/// Snapshots of ``f + 0.5*t`` at the given times
+fn ramp(times: &[f64]) -> Interp3DSeries {
+    Interp3DSeries::new(times.iter().map(|&t| (t, Interp3D::from_config(|x, y, z| f(x, y, z) + 0.5*t, &config()))).collect())
+}
+
+#[test]
+fn linear_ramp_in_time_is_recovered() {
+    let series = ramp(&[1.0, 3.0]);
+    let ip = Interp3D::from_config(f, &config());
+
+    let points: Vec<_> = (0..50).map(|i| {
+        let s = i as f64/49.0;
+        (2.0*s, -1.0 + 4.0*(3.1*s).fract(), 0.5 + (1.7*s).fract())
+    }).collect();
+    for t in [1.0, 1.25, 2.0, 2.9, 3.0] {
+        let many = series.interpolate_many(t, &points);
+        for (&(x, y, z), value) in points.iter().zip(many) {
+            let expected = ip.interpolate(x, y, z) + 0.5*t;
+            assert!((series.interpolate(t, x, y, z) - expected).abs() < 1e-13, "t = {} at ({}, {}, {})", t, x, y, z);
+            assert_eq!(value, series.interpolate(t, x, y, z));
+        }
+    }
+
+    // at the snapshots the result is exactly the one of the snapshot
+    assert_eq!(series.interpolate(3.0, 0.3, 0.4, 0.9), series.snapshots()[1].interpolate(0.3, 0.4, 0.9));
+}
+
+#[test]
+fn times_outside_of_the_series_are_clamped_or_rejected() {
+    let mut series = ramp(&[0.0, 1.0, 4.0]);
+    assert_eq!(series.time_bounds(), TimeBounds::Clamp);
+    assert_eq!(series.interpolate(-2.0, 0.5, 0.5, 1.0), series.interpolate(0.0, 0.5, 0.5, 1.0));
+    assert_eq!(series.interpolate(9.0, 0.5, 0.5, 1.0), series.interpolate(4.0, 0.5, 0.5, 1.0));
+
+    series.set_time_bounds(TimeBounds::Error);
+    assert!(matches!(series.try_interpolate(4.5, 0.5, 0.5, 1.0), Err(Interp3DError::TimeOutOfBounds { range: (0.0, 4.0), .. })));
+    assert!(series.try_interpolate(f64::NAN, 0.5, 0.5, 1.0).is_err());
+    assert!(series.try_interpolate(4.0, 0.5, 0.5, 1.0).is_ok());
+}
+
+#[test]
+fn invalid_series_are_rejected() {
+    let snapshot = || Interp3D::from_config(f, &config());
+    assert!(matches!(Interp3DSeries::try_new(Vec::new()), Err(Interp3DError::NotSetUp)));
+    assert!(matches!(Interp3DSeries::try_new(vec![(0.0, snapshot()), (0.0, snapshot())]), Err(Interp3DError::NonMonotonicTimes { index: 1 })));
+    assert!(matches!(Interp3DSeries::try_new(vec![(f64::NAN, snapshot())]), Err(Interp3DError::NonMonotonicTimes { index: 0 })));
+
+    let coarse = Interp3D::from_config(f, &DataGenConfBuilder::default().uniform_cube(0.0, 1.0, 4).build());
+    assert!(matches!(Interp3DSeries::try_new(vec![(0.0, snapshot()), (1.0, coarse)]), Err(Interp3DError::GridMismatch { .. })));
+
+    // a single snapshot is constant in time
+    let single = Interp3DSeries::new(vec![(2.0, snapshot())]);
+    assert_eq!(single.interpolate(-1.0, 0.5, 0.5, 1.0), snapshot().interpolate(0.5, 0.5, 1.0));
+}
+
+#[test]
+fn series_survives_export_and_import() {
+    let series = ramp(&[0.0, 0.5, 2.0]);
+    let file = temp_file("series.bin");
+    series.export_data(&file).unwrap();
+    let imported = Interp3DSeries::try_from_file(&file);
+
+    // a truncated file is rejected
+    let bytes = std::fs::read(&file).unwrap();
+    std::fs::write(&file, &bytes[..bytes.len()-3]).unwrap();
+    let truncated = Interp3DSeries::try_from_file(&file);
+    std::fs::remove_file(&file).unwrap();
+
+    let imported = imported.unwrap();
+    assert_eq!(imported.times(), series.times());
+    for (a, b) in imported.snapshots().iter().zip(series.snapshots()) {
+        assert!(a.approx_eq(b, 0.0, 0.0));
+        assert_eq!(a.config(), b.config());
+    }
+    assert_eq!(imported.interpolate(1.2, 0.3, 0.4, 0.9), series.interpolate(1.2, 0.3, 0.4, 0.9));
+    assert!(truncated.is_err());
+}
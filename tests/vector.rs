@@ -0,0 +1,131 @@
+mod common;
+
+use common::temp_file;
+use interp3d::*;
+
+fn config() -> DataGenConf {
+    DataGenConfBuilder::default()
+        .x(0.0, 2.0, 9, GridSpacing::Linear)
+        .y(-1.0, 3.0, 12, GridSpacing::Exponential(3.0))
+        .z(0.5, 1.5, 7, GridSpacing::Chebyshev)
+        .build()
+}
+
+fn f(x: f64, y: f64, z: f64) -> [f64; 3] {
+    [(x*y).sin() + z.exp(), x*x - y*z, (3.0*z).cos()*y]
+}
+
+#[test]
+fn components_match_separate_interpolators() {
+    let mut ip = Interp3DVec::from_config(f, &config());
+    ip.set_boundary_condition(BoundaryCondition::Reflect { even: true });
+    ip.set_out_of_bounds(OutOfBounds::ClampToEdge);
+
+    let points = [(0.3, 0.2, 0.7), (1.9, -0.95, 1.45), (1.0, 2.5, 1.0), (-0.5, 4.0, 1.2), (0.0, -1.0, 0.5)];
+    for scheme in [Type::Tricubic, Type::Trilinear, Type::TricubicMonotone, Type::Nearest, Type::BicubicUnilinear(Dir::Y)] {
+        ip.set_type(scheme);
+
+        for c in 0..3 {
+            let mut single = Interp3D::default();
+            single.set_boundary_condition(BoundaryCondition::Reflect { even: true });
+            single.set_out_of_bounds(OutOfBounds::ClampToEdge);
+            single.set_type(scheme);
+            single.generate_data(|x, y, z| f(x, y, z)[c], &config());
+
+            for &(x, y, z) in &points {
+                assert_eq!(ip.interpolate(x, y, z)[c].to_bits(), single.interpolate(x, y, z).to_bits(), "{:?} component {}", scheme, c);
+                assert_eq!(ip.interpolate_gradient(x, y, z)[c], single.interpolate_gradient(x, y, z));
+                assert_eq!(ip.component(c).interpolate(x, y, z).to_bits(), single.interpolate(x, y, z).to_bits());
+            }
+        }
+    }
+}
+
+#[test]
+fn settings_apply_to_all_components() {
+    let angle = DataGenConfBuilder::default()
+        .uniform_cube(0.0, 1.0, 5)
+        .z(0.0, 2.0*std::f64::consts::PI, 17, GridSpacing::Linear)
+        .build();
+    let mut ip = Interp3DVec::from_config(|x, _, z| [z.sin(), x*z.cos()], &angle);
+    ip.set_periodic(Dir::Z, true);
+
+    for c in 0..2 {
+        let component = ip.component(c);
+        for &z in &[-0.1, 0.05, 6.25, 7.0] {
+            assert_eq!(ip.interpolate(0.5, 0.5, z)[c], component.interpolate(0.5, 0.5, z));
+        }
+    }
+    let [sin, _] = ip.interpolate(0.5, 0.5, 2.0*std::f64::consts::PI + 0.3);
+    assert!((sin - 0.3f64.sin()).abs() < 1e-3);
+
+    ip.set_out_of_bounds(OutOfBounds::Error);
+    assert!(matches!(ip.try_interpolate(1.5, 0.5, 0.0), Err(Interp3DError::OutOfBounds { axis: Dir::X, .. })));
+}
+
+#[test]
+fn from_grid_and_data_keeps_the_components_together() {
+    let (x, y, z) = ([0.0, 1.0], [0.0, 1.0, 2.0], [0.0, 1.0]);
+    let data: Vec<[f64; 2]> = (0..12).map(|n| [n as f64, -(n as f64)]).collect();
+    let ip = Interp3DVec::from_grid_and_data(&x, &y, &z, &data).unwrap();
+
+    assert_eq!(ip.dims(), (2, 3, 2));
+    assert_eq!(ip.value_at(1, 2, 0), [10.0, -10.0]);
+    assert_eq!(ip.interpolate(0.0, 1.0, 1.0), [3.0, -3.0]);
+
+    match Interp3DVec::from_grid_and_data(&x, &y, &z, &data[1..]).map(|_| ()) {
+        Err(Interp3DError::DataLength { expected: 12, found: 11 }) => (),
+        other => panic!("unexpected {:?}", other)
+    }
+}
+
+#[test]
+fn export_import_round_trip_carries_the_components() {
+    let mut ip = Interp3DVec::from_config(f, &config());
+    ip.set_storage(Storage::F32);
+    let file = temp_file("vector.ip3d");
+    ip.export_data(&file).unwrap();
+
+    let imported = Interp3DVec::<3>::from_file(&file);
+    assert_eq!(imported.storage(), Storage::F32);
+    for &(x, y, z) in &[(0.3, 0.2, 0.7), (1.9, -0.95, 1.45)] {
+        assert_eq!(imported.interpolate(x, y, z), ip.interpolate(x, y, z));
+    }
+
+    match Interp3DVec::<2>::try_from_file(&file).map(|_| ()) {
+        Err(Interp3DError::Parse { message, .. }) => assert!(message.contains("3 components")),
+        other => panic!("unexpected {:?}", other)
+    }
+    assert!(Interp3D::try_from_file(&file).is_err());
+    std::fs::remove_file(&file).unwrap();
+
+    // single component files are the scalar format
+    let scalar = Interp3D::from_config(|x, y, z| f(x, y, z)[0], &config());
+    scalar.export_data(&file).unwrap();
+    let imported = Interp3DVec::<1>::from_file(&file);
+    std::fs::remove_file(&file).unwrap();
+    assert_eq!(imported.interpolate(0.3, 0.2, 0.7), [scalar.interpolate(0.3, 0.2, 0.7)]);
+}
+
+#[test]
+fn generate_data_multi_calls_the_closure_once_per_node() {
+    let mut calls = 0;
+    let [a, b, c] = Interp3D::generate_data_multi(|x, y, z| {
+        calls += 1;
+        f(x, y, z)
+    }, &config());
+    assert_eq!(calls, 9*12*7);
+
+    for (ip, component) in [a, b, c].iter().zip(0..) {
+        let single = Interp3D::from_config(|x, y, z| f(x, y, z)[component], &config());
+        assert_eq!((ip.grid_x(), ip.grid_y(), ip.grid_z()), (single.grid_x(), single.grid_y(), single.grid_z()));
+        assert_eq!(ip.config(), Some(&config()));
+        assert_eq!(ip.interpolate(1.1, 0.3, 0.9).to_bits(), single.interpolate(1.1, 0.3, 0.9).to_bits());
+
+        let file = temp_file(&format!("multi-{}.ip3d", component));
+        ip.export_data(&file).unwrap();
+        let imported = Interp3D::from_file(&file);
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(imported.iter_points().collect::<Vec<_>>(), single.iter_points().collect::<Vec<_>>());
+    }
+}